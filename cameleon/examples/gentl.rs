@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This example describes how to access cameras through a third-party `GenTL` producer.
+
+use cameleon::gentl::enumerate_cameras;
+
+fn main() {
+    // The path to the `GenTL` producer is passed as the first argument.
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            println!("usage: gentl <PATH_TO_CTI>");
+            return;
+        }
+    };
+
+    // Loads the producer and enumerates cameras reachable through it.
+    let mut cameras = enumerate_cameras(path).unwrap();
+
+    if cameras.is_empty() {
+        println!("no camera found!");
+        return;
+    }
+
+    let mut camera = cameras.pop().unwrap();
+    println!("{:?}", camera.info());
+
+    // Open the camera.
+    camera.open().unwrap();
+    // Load `GenApi` context.
+    camera.load_context().unwrap();
+
+    // Start streaming. Channel capacity is set to 3.
+    let payload_rx = camera.start_streaming(3).unwrap();
+
+    let mut payload_count = 0_usize;
+    while payload_count < 10 {
+        match payload_rx.try_recv() {
+            Ok(payload) => {
+                println!(
+                    "payload received! block_id: {:?}, timestamp: {:?}",
+                    payload.id(),
+                    payload.timestamp()
+                );
+                if let Some(image_info) = payload.image_info() {
+                    println!("{:?}\n", image_info);
+                }
+                payload_count += 1;
+
+                // Send back payload to streaming loop to reuse the buffer.
+                payload_rx.send_back(payload);
+            }
+            Err(_err) => {
+                continue;
+            }
+        }
+    }
+
+    camera.close().ok();
+}
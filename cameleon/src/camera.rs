@@ -57,13 +57,17 @@
 //! camera.close().unwrap();
 //! ```
 
+use std::time::Duration;
+
+use async_std::{future, task};
 use auto_impl::auto_impl;
 use tracing::info;
 
 use super::{
-    genapi::{DefaultGenApiCtxt, FromXml, GenApiCtxt, ParamsCtxt},
-    payload::{channel, PayloadReceiver, PayloadSender},
-    CameleonError, CameleonResult, ControlResult, StreamError, StreamResult,
+    genapi::{CommandNode, DefaultGenApiCtxt, EnumerationNode, FromXml, GenApiCtxt, ParamsCtxt},
+    payload::{channel, Payload, PayloadReceiver, PayloadSender},
+    recovery::{self, Reconnect, Recovery, RecoveryConfig, RecoveryEvent},
+    CameleonError, CameleonResult, ControlError, ControlResult, StreamError, StreamResult,
 };
 
 /// Provides easy-to-use access to a `GenICam` compatible camera.
@@ -127,6 +131,8 @@ pub struct Camera<Ctrl, Strm, Ctxt = DefaultGenApiCtxt> {
     pub ctxt: Option<Ctxt>,
     /// Information of the camera.
     info: CameraInfo,
+    /// Recovery state, `None` if recovery is disabled.
+    recovery: Option<Recovery>,
 }
 
 macro_rules! expect_node {
@@ -303,6 +309,23 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
             return Err(StreamError::InStreaming.into());
         }
 
+        let (sender, receiver) = channel(cap, DEFAULT_BUFFER_CAP);
+        self.start_streaming_with(sender.clone())?;
+        if let Some(recovery) = &mut self.recovery {
+            // Keep the sender to restart streaming with the same receiver on recovery.
+            recovery.sender = Some(sender);
+        }
+
+        info!("start streaming successfully");
+        Ok(receiver)
+    }
+
+    fn start_streaming_with(&mut self, sender: PayloadSender) -> CameleonResult<()>
+    where
+        Ctrl: DeviceControl,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+    {
         // Enable streaimng.
         self.ctrl.enable_streaming()?;
         let mut ctxt = self.params_ctxt()?;
@@ -310,11 +333,8 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
         expect_node!(&ctxt, "AcquisitionStart", as_command).execute(&mut ctxt)?;
 
         // Start streaming loop.
-        let (sender, receiver) = channel(cap, DEFAULT_BUFFER_CAP);
         self.strm.start_streaming_loop(sender, &mut self.ctrl)?;
-
-        info!("start streaming successfully");
-        Ok(receiver)
+        Ok(())
     }
 
     /// Stops the streaming.
@@ -352,6 +372,9 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
         Ctxt: GenApiCtxt,
     {
         info!("try stopping streaming");
+        if let Some(recovery) = &mut self.recovery {
+            recovery.sender = None;
+        }
         if !self.strm.is_loop_running() {
             return Ok(());
         }
@@ -369,6 +392,330 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
         Ok(())
     }
 
+    /// Grabs a single frame and returns it.
+    ///
+    /// This is a shorthand for [`grab_n`](Self::grab_n) with `n = 1`, see it for more details.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use cameleon::u3v;
+    /// # use std::time::Duration;
+    /// # let mut cameras = u3v::enumerate_cameras().unwrap();
+    /// # if cameras.is_empty() {
+    /// #     return;
+    /// # }
+    /// # let mut camera = cameras.pop().unwrap();
+    /// camera.open().unwrap();
+    /// camera.load_context().unwrap();
+    ///
+    /// let payload = camera.grab_one(Duration::from_secs(1)).unwrap();
+    /// if let Some(image_info) = payload.image_info() {
+    ///     println!("{:?}", image_info);
+    /// }
+    ///
+    /// camera.close().unwrap();
+    /// ```
+    pub fn grab_one(&mut self, timeout: Duration) -> CameleonResult<Payload>
+    where
+        Ctrl: DeviceControl,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+    {
+        let mut payloads = self.grab_n(1, timeout)?;
+        Ok(payloads.pop().unwrap())
+    }
+
+    /// Grabs `n` frames and returns them in the order they were received.
+    ///
+    /// If the camera supports software trigger, i.e. it has `TriggerMode`, `TriggerSource` and
+    /// `TriggerSoftware` nodes defined in `GenICam SFNC`, this method sets `AcquisitionMode` to
+    /// `Continuous`, `TriggerSelector` to `FrameStart`, `TriggerMode` to `On` and `TriggerSource`
+    /// to `Software`, then executes `TriggerSoftware` once per frame and waits for the resulting
+    /// frame. Otherwise, the camera is left free-running and the first `n` frames after
+    /// starting streaming are returned.
+    ///
+    /// Streaming is stopped and all the modified features are restored to their previous values
+    /// before this method returns, even if an error occurs.
+    ///
+    /// Make sure to load `GenApi` context before calling this method.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use cameleon::u3v;
+    /// # use std::time::Duration;
+    /// # let mut cameras = u3v::enumerate_cameras().unwrap();
+    /// # if cameras.is_empty() {
+    /// #     return;
+    /// # }
+    /// # let mut camera = cameras.pop().unwrap();
+    /// camera.open().unwrap();
+    /// camera.load_context().unwrap();
+    ///
+    /// // Grab 5 frames, waits up to 1 second for each frame.
+    /// let payloads = camera.grab_n(5, Duration::from_secs(1)).unwrap();
+    /// assert_eq!(payloads.len(), 5);
+    ///
+    /// camera.close().unwrap();
+    /// ```
+    ///
+    /// # Arguments
+    /// * `n` - The number of frames to grab.
+    /// * `timeout` - Maximum duration to wait for each frame. [`StreamError::Timeout`] is
+    /// returned if a frame doesn't arrive in time.
+    #[tracing::instrument(skip(self),
+                          level = "info",
+                          fields(camera = ?self.info()))]
+    pub fn grab_n(&mut self, n: usize, timeout: Duration) -> CameleonResult<Vec<Payload>>
+    where
+        Ctrl: DeviceControl,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+    {
+        if self.strm.is_loop_running() {
+            return Err(StreamError::InStreaming.into());
+        }
+        if n == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut settings = GrabSettings::default();
+        let grab_res = self
+            .params_ctxt()
+            .and_then(|mut ctxt| settings.configure(&mut ctxt))
+            .and_then(|_| self.grab_payloads(n, timeout, settings.trigger_software));
+
+        // Stop streaming and restore the settings regardless of the result of the grab.
+        let stop_res = self.stop_streaming();
+        let restore_res = self
+            .params_ctxt()
+            .and_then(|mut ctxt| settings.restore(&mut ctxt));
+
+        let payloads = grab_res?;
+        stop_res?;
+        restore_res?;
+        info!("grabbed {} frames successfully", n);
+        Ok(payloads)
+    }
+
+    fn grab_payloads(
+        &mut self,
+        n: usize,
+        timeout: Duration,
+        trigger_software: Option<CommandNode>,
+    ) -> CameleonResult<Vec<Payload>>
+    where
+        Ctrl: DeviceControl,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+    {
+        let payload_rx = self.start_streaming(n)?;
+
+        let mut payloads = Vec::with_capacity(n);
+        while payloads.len() < n {
+            if let Some(trigger_software) = trigger_software {
+                // Discard stale payloads so that the next payload is the one corresponding to the
+                // trigger.
+                while payload_rx.try_recv().is_ok() {}
+                trigger_software.execute(&mut self.params_ctxt()?)?;
+            }
+
+            let payload = task::block_on(future::timeout(timeout, payload_rx.recv()))
+                .map_err(|_| StreamError::Timeout)??;
+            payloads.push(payload);
+        }
+
+        Ok(payloads)
+    }
+
+    /// Enables recovery of the camera after its device is lost.
+    ///
+    /// Once enabled, writes to the device are recorded so that [`recover`](Self::recover) can
+    /// restore them, and `callback` is called with each step of the recovery.
+    /// Enable recovery before starting streaming, otherwise streaming isn't restarted on
+    /// recovery.
+    ///
+    /// See also [`with_recovery`](Self::with_recovery) which recovers the camera automatically.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use cameleon::u3v;
+    /// use cameleon::recovery::{RecoveryConfig, RecoveryEvent};
+    ///
+    /// # let mut cameras = u3v::enumerate_cameras().unwrap();
+    /// # if cameras.is_empty() {
+    /// #     return;
+    /// # }
+    /// # let mut camera = cameras.pop().unwrap();
+    /// camera.open().unwrap();
+    /// camera.load_context().unwrap();
+    ///
+    /// camera.enable_recovery(RecoveryConfig::default(), |event| {
+    ///     if let RecoveryEvent::AttemptFailed { attempt, error } = event {
+    ///         println!("attempt {} failed: {}", attempt, error);
+    ///     }
+    /// });
+    /// ```
+    pub fn enable_recovery(
+        &mut self,
+        config: RecoveryConfig,
+        callback: impl FnMut(RecoveryEvent<'_>) + Send + 'static,
+    ) where
+        Ctrl: Reconnect<Strm>,
+    {
+        self.ctrl.set_write_journal(true);
+        self.recovery = Some(Recovery::new(config, callback));
+    }
+
+    /// Disables recovery of the camera and discards the recorded writes.
+    pub fn disable_recovery(&mut self)
+    where
+        Ctrl: Reconnect<Strm>,
+    {
+        self.ctrl.set_write_journal(false);
+        self.recovery = None;
+    }
+
+    /// Returns `true` if recovery is enabled.
+    pub fn is_recovery_enabled(&self) -> bool {
+        self.recovery.is_some()
+    }
+
+    /// Recovers the camera after its device is lost.
+    ///
+    /// This method finds the same device again, reopens the control and stream channels, reuses
+    /// the loaded `GenApi` context, writes the features written before the device was lost, and
+    /// restarts streaming if it was active. The receiver returned from
+    /// [`start_streaming`](Self::start_streaming) keeps receiving payloads after recovery.
+    ///
+    /// Recovery is attempted up to [`RecoveryConfig::max_attempts`] times and the error of the
+    /// last attempt is returned if all attempts fail.
+    ///
+    /// Recovery must be enabled by [`enable_recovery`](Self::enable_recovery) beforehand,
+    /// otherwise [`CameleonError::RecoveryDisabled`] is returned.
+    #[tracing::instrument(skip(self),
+                          level = "info",
+                          fields(camera = ?self.info()))]
+    pub fn recover(&mut self) -> CameleonResult<()>
+    where
+        Ctrl: Reconnect<Strm>,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+    {
+        let recovery = self
+            .recovery
+            .clone()
+            .ok_or(CameleonError::RecoveryDisabled)?;
+        info!("try recovering the device");
+        recovery.report(RecoveryEvent::Started);
+
+        // The channels are already broken, so errors are ignored.
+        if self.strm.is_loop_running() {
+            self.strm.stop_streaming_loop().ok();
+        }
+        self.ctrl.close().ok();
+        self.strm.close().ok();
+
+        let config = recovery.config;
+        let mut last_err = None;
+        for attempt in 1..=config.max_attempts {
+            match self.try_recover(attempt, &recovery) {
+                Ok(()) => {
+                    recovery.report(RecoveryEvent::Recovered);
+                    info!("recovered the device successfully");
+                    return Ok(());
+                }
+                Err(err) => {
+                    recovery.report(RecoveryEvent::AttemptFailed {
+                        attempt,
+                        error: &err,
+                    });
+                    self.ctrl.close().ok();
+                    self.strm.close().ok();
+                    last_err = Some(err);
+                    if attempt < config.max_attempts {
+                        std::thread::sleep(config.retry_interval);
+                    }
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| ControlError::Disconnected.into()))
+    }
+
+    fn try_recover(&mut self, attempt: u32, recovery: &Recovery) -> CameleonResult<()>
+    where
+        Ctrl: Reconnect<Strm>,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+    {
+        if !self.ctrl.reconnect(&mut self.strm)? {
+            return Err(ControlError::Disconnected.into());
+        }
+        recovery.report(RecoveryEvent::DeviceFound { attempt });
+
+        self.ctrl.open()?;
+        self.strm.open()?;
+        recovery.report(RecoveryEvent::Reopened);
+
+        if let Some(ctxt) = &mut self.ctxt {
+            // Values cached before the device was lost may be stale.
+            ctxt.clear_cache();
+            recovery.report(RecoveryEvent::ContextRestored);
+
+            let journal = self.ctrl.write_journal().unwrap_or_default();
+            let count = recovery::restore_features(&mut self.ctrl, ctxt, &journal);
+            recovery.report(RecoveryEvent::FeaturesRestored { count });
+        }
+
+        if let Some(sender) = &recovery.sender {
+            self.start_streaming_with(sender.clone())?;
+            recovery.report(RecoveryEvent::StreamingRestarted);
+        }
+
+        Ok(())
+    }
+
+    /// Runs `f` with the camera, and if `f` fails because the device is disconnected, recovers
+    /// the camera by [`recover`](Self::recover) and runs `f` again.
+    ///
+    /// If recovery is disabled, the error of `f` is returned as is.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use cameleon::u3v;
+    /// use std::time::Duration;
+    ///
+    /// use cameleon::recovery::RecoveryConfig;
+    ///
+    /// # let mut cameras = u3v::enumerate_cameras().unwrap();
+    /// # if cameras.is_empty() {
+    /// #     return;
+    /// # }
+    /// # let mut camera = cameras.pop().unwrap();
+    /// camera.open().unwrap();
+    /// camera.load_context().unwrap();
+    /// camera.enable_recovery(RecoveryConfig::default(), |event| println!("{:?}", event));
+    ///
+    /// let payload = camera
+    ///     .with_recovery(|camera| camera.grab_one(Duration::from_secs(1)))
+    ///     .unwrap();
+    /// ```
+    pub fn with_recovery<F, T>(&mut self, mut f: F) -> CameleonResult<T>
+    where
+        Ctrl: Reconnect<Strm>,
+        Strm: PayloadStream,
+        Ctxt: GenApiCtxt,
+        F: FnMut(&mut Self) -> CameleonResult<T>,
+    {
+        match f(self) {
+            Err(err) if err.is_disconnected() && self.recovery.is_some() => {
+                self.recover()?;
+                f(self)
+            }
+            res => res,
+        }
+    }
+
     /// Returns the context of the camera params.
     ///
     /// Make sure to load `GenApi` context before calling this method.
@@ -447,6 +794,7 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
             strm,
             ctxt,
             info,
+            recovery: None,
         }
     }
 
@@ -460,12 +808,13 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
         Strm: From<Strm2>,
         Ctxt: From<Ctxt2>,
     {
-        Camera::new(
-            from.ctrl.into(),
-            from.strm.into(),
-            from.ctxt.map(|ctxt| ctxt.into()),
-            from.info,
-        )
+        Camera {
+            ctrl: from.ctrl.into(),
+            strm: from.strm.into(),
+            ctxt: from.ctxt.map(|ctxt| ctxt.into()),
+            info: from.info,
+            recovery: from.recovery,
+        }
     }
 
     /// Converts internal types. This method work same as `std::convert::Into`, just hack to avoid
@@ -492,12 +841,13 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
         Strm: Into<Strm2>,
         Ctxt: Into<Ctxt2>,
     {
-        Camera::new(
-            self.ctrl.into(),
-            self.strm.into(),
-            self.ctxt.map(|ctxt| ctxt.into()),
-            self.info,
-        )
+        Camera {
+            ctrl: self.ctrl.into(),
+            strm: self.strm.into(),
+            ctxt: self.ctxt.map(|ctxt| ctxt.into()),
+            info: self.info,
+            recovery: self.recovery,
+        }
     }
 
     /// Set a context to the camera. It's recommended to use [`Self::load_context`] instead if `Self::Ctxt`
@@ -508,10 +858,128 @@ impl<Ctrl, Strm, Ctxt> Camera<Ctrl, Strm, Ctxt> {
             strm: self.strm,
             ctxt: Some(ctxt),
             info: self.info,
+            recovery: self.recovery,
         }
     }
 }
 
+/// Holds the previous values of the features modified by [`Camera::grab_n`].
+#[derive(Default)]
+struct GrabSettings {
+    acquisition_mode: Option<(EnumerationNode, String)>,
+    trigger_selector: Option<(EnumerationNode, String)>,
+    trigger_mode: Option<(EnumerationNode, String)>,
+    trigger_source: Option<(EnumerationNode, String)>,
+    trigger_software: Option<CommandNode>,
+}
+
+impl GrabSettings {
+    /// Configures the camera to grab frames with software trigger if possible, while recording
+    /// the previous values of the modified features.
+    fn configure<Ctrl, Ctxt>(&mut self, ctxt: &mut ParamsCtxt<Ctrl, Ctxt>) -> CameleonResult<()>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        self.acquisition_mode = set_enum_entry(ctxt, "AcquisitionMode", "Continuous")?;
+
+        let trigger_software = ctxt
+            .node("TriggerSoftware")
+            .and_then(|n| n.as_command(ctxt));
+        let trigger_software = match trigger_software {
+            Some(trigger_software) if has_enum_entry(ctxt, "TriggerSource", "Software") => {
+                trigger_software
+            }
+            // Software trigger isn't available, let the camera run freely.
+            _ => return Ok(()),
+        };
+
+        if enum_node(ctxt, "TriggerSelector").is_some() {
+            if !has_enum_entry(ctxt, "TriggerSelector", "FrameStart") {
+                return Ok(());
+            }
+            self.trigger_selector = set_enum_entry(ctxt, "TriggerSelector", "FrameStart")?;
+        }
+        self.trigger_source = set_enum_entry(ctxt, "TriggerSource", "Software")?;
+        self.trigger_mode = set_enum_entry(ctxt, "TriggerMode", "On")?;
+        self.trigger_software = Some(trigger_software);
+
+        Ok(())
+    }
+
+    /// Restores the modified features to their previous values.
+    fn restore<Ctrl, Ctxt>(&self, ctxt: &mut ParamsCtxt<Ctrl, Ctxt>) -> CameleonResult<()>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        // `TriggerMode` and `TriggerSource` must be restored while `TriggerSelector` still points
+        // to `FrameStart`.
+        for (node, symbolic) in [
+            &self.trigger_mode,
+            &self.trigger_source,
+            &self.trigger_selector,
+            &self.acquisition_mode,
+        ]
+        .iter()
+        .copied()
+        .flatten()
+        {
+            node.set_entry_by_symbolic(ctxt, symbolic)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn enum_node<Ctrl, Ctxt>(ctxt: &ParamsCtxt<Ctrl, Ctxt>, name: &str) -> Option<EnumerationNode>
+where
+    Ctxt: GenApiCtxt,
+{
+    ctxt.node(name).and_then(|n| n.as_enumeration(ctxt))
+}
+
+fn has_enum_entry<Ctrl, Ctxt>(ctxt: &ParamsCtxt<Ctrl, Ctxt>, name: &str, symbolic: &str) -> bool
+where
+    Ctrl: DeviceControl,
+    Ctxt: GenApiCtxt,
+{
+    match enum_node(ctxt, name) {
+        Some(node) => node
+            .entries(ctxt)
+            .iter()
+            .any(|ent| ent.symbolic(ctxt) == symbolic),
+        None => false,
+    }
+}
+
+/// Sets `symbolic` to the enumeration node named `name` and returns the node with its previous
+/// entry.
+///
+/// Returns `None` if the node is missing, not writable, or already set to `symbolic`.
+fn set_enum_entry<Ctrl, Ctxt>(
+    ctxt: &mut ParamsCtxt<Ctrl, Ctxt>,
+    name: &str,
+    symbolic: &str,
+) -> CameleonResult<Option<(EnumerationNode, String)>>
+where
+    Ctrl: DeviceControl,
+    Ctxt: GenApiCtxt,
+{
+    let node = match enum_node(ctxt, name) {
+        Some(node) if node.is_writable(ctxt)? && has_enum_entry(ctxt, name, symbolic) => node,
+        _ => return Ok(None),
+    };
+
+    let prev = node.current_entry(ctxt)?.symbolic(ctxt).to_string();
+    if prev == symbolic {
+        return Ok(None);
+    }
+    node.set_entry_by_symbolic(ctxt, symbolic)?;
+
+    Ok(Some((node, prev)))
+}
+
 /// Information of the camera.
 #[derive(Clone, Debug, PartialEq, Hash)]
 pub struct CameraInfo {
@@ -543,6 +1011,30 @@ pub trait DeviceControl {
     /// Writes data to the device's memory.
     fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()>;
 
+    /// Reads multiple regions of the device's memory.
+    ///
+    /// Each entry is a pair of an address and a buffer, reads length is same as the buffer length.
+    /// Implementors may read all regions in fewer transactions than entries, the default
+    /// implementation reads each region in turn by [`DeviceControl::read`].
+    fn read_batch(&mut self, entries: &mut [(u64, &mut [u8])]) -> ControlResult<()> {
+        for (address, buf) in entries {
+            self.read(*address, buf)?;
+        }
+        Ok(())
+    }
+
+    /// Writes data to multiple regions of the device's memory.
+    ///
+    /// Each entry is a pair of an address and data, entries are written in order.
+    /// Implementors may write all regions in fewer transactions than entries, the default
+    /// implementation writes each region in turn by [`DeviceControl::write`].
+    fn write_batch(&mut self, entries: &[(u64, &[u8])]) -> ControlResult<()> {
+        for (address, data) in entries {
+            self.write(*address, data)?;
+        }
+        Ok(())
+    }
+
     /// Returns `GenICam` xml string.
     fn genapi(&mut self) -> ControlResult<String>;
 
@@ -575,3 +1067,325 @@ pub trait PayloadStream {
     /// Returns `true` if streaming loop is running.
     fn is_loop_running(&self) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        genapi::NoCacheGenApiCtxt,
+        payload::PayloadType,
+        test_utils::{params_ctxt, MemoryControl},
+    };
+
+    use super::*;
+
+    /// A camera without software trigger, whose `AcquisitionMode` register is at 0x0.
+    const FREE_RUN_FEATURES: &str = r#"
+    <Enumeration Name="AcquisitionMode">
+        <EnumEntry Name="Continuous"><Value>0</Value></EnumEntry>
+        <EnumEntry Name="SingleFrame"><Value>1</Value></EnumEntry>
+        <pValue>AcquisitionModeReg</pValue>
+    </Enumeration>
+    <IntReg Name="AcquisitionModeReg">
+        <Address>0x0</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntReg Name="TLParamsLocked">
+        <Address>0x4</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <Command Name="AcquisitionStart">
+        <pValue>AcquisitionCommandReg</pValue>
+        <CommandValue>1</CommandValue>
+    </Command>
+    <Command Name="AcquisitionStop">
+        <pValue>AcquisitionCommandReg</pValue>
+        <CommandValue>2</CommandValue>
+    </Command>
+    <IntReg Name="AcquisitionCommandReg">
+        <Address>0x8</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    "#;
+
+    /// [`PayloadStream`] which sends `num_payloads` payloads as soon as streaming starts.
+    struct FreeRunStream {
+        num_payloads: u64,
+        sender: Option<PayloadSender>,
+    }
+
+    impl PayloadStream for FreeRunStream {
+        fn open(&mut self) -> StreamResult<()> {
+            Ok(())
+        }
+
+        fn close(&mut self) -> StreamResult<()> {
+            Ok(())
+        }
+
+        fn start_streaming_loop(
+            &mut self,
+            sender: PayloadSender,
+            _ctrl: &mut dyn DeviceControl,
+        ) -> StreamResult<()> {
+            for id in 0..self.num_payloads {
+                let payload = Payload {
+                    id,
+                    payload_type: PayloadType::Image,
+                    image_info: None,
+                    payload: vec![],
+                    valid_payload_size: 0,
+                    timestamp: Duration::default(),
+                    chunk_layout_id: None,
+                };
+                // Payloads exceeding the channel capacity are dropped as a real stream does.
+                sender.try_send(Ok(payload)).ok();
+            }
+            // Keep the sender alive until streaming is stopped.
+            self.sender = Some(sender);
+            Ok(())
+        }
+
+        fn stop_streaming_loop(&mut self) -> StreamResult<()> {
+            self.sender = None;
+            Ok(())
+        }
+
+        fn is_loop_running(&self) -> bool {
+            self.sender.is_some()
+        }
+    }
+
+    fn free_run_camera(
+        num_payloads: u64,
+    ) -> Camera<MemoryControl, FreeRunStream, NoCacheGenApiCtxt> {
+        let ParamsCtxt { ctrl, ctxt } = params_ctxt(FREE_RUN_FEATURES, 0xc);
+        let strm = FreeRunStream {
+            num_payloads,
+            sender: None,
+        };
+        let info = CameraInfo {
+            vendor_name: "Cameleon".into(),
+            model_name: "Test".into(),
+            serial_number: "FREERUN".into(),
+        };
+        Camera::new(ctrl, strm, Some(ctxt), info)
+    }
+
+    fn read_u32(ctrl: &MemoryControl, address: usize) -> u32 {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(&ctrl.memory[address..address + 4]);
+        u32::from_le_bytes(buf)
+    }
+
+    #[test]
+    fn test_grab_n_free_run() {
+        let mut camera = free_run_camera(3);
+        // SingleFrame.
+        camera.ctrl.memory[0] = 1;
+
+        let payloads = camera.grab_n(2, Duration::from_secs(1)).unwrap();
+        assert_eq!(
+            payloads.iter().map(Payload::id).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        // Streaming is stopped, and `AcquisitionMode` is restored to `SingleFrame`.
+        assert!(!camera.strm.is_loop_running());
+        assert_eq!(read_u32(&camera.ctrl, 0x8), 2);
+        assert_eq!(read_u32(&camera.ctrl, 0x0), 1);
+    }
+
+    #[test]
+    fn test_grab_n_free_run_timeout() {
+        let mut camera = free_run_camera(1);
+        camera.ctrl.memory[0] = 1;
+
+        assert!(matches!(
+            camera.grab_n(2, Duration::from_millis(100)),
+            Err(CameleonError::StreamError(StreamError::Timeout))
+        ));
+        assert!(!camera.strm.is_loop_running());
+        assert_eq!(read_u32(&camera.ctrl, 0x0), 1);
+    }
+
+    #[test]
+    fn test_grab_n_without_context() {
+        let mut camera = free_run_camera(1);
+        camera.ctxt = None;
+
+        assert!(matches!(
+            camera.grab_n(1, Duration::from_millis(100)),
+            Err(CameleonError::GenApiContextMissing)
+        ));
+    }
+
+    #[cfg(feature = "emulator")]
+    mod emulator {
+        use cameleon_device::emulator::{EmulatorBuilder, FaultSchedule, StreamFault};
+
+        use crate::u3v::{open_emulated_by, CameraFilter, ControlHandle, StreamHandle};
+
+        use super::*;
+
+        /// [`ControlHandle`] recording the addresses of all writes.
+        struct WriteLog {
+            inner: ControlHandle,
+            addresses: Vec<u64>,
+        }
+
+        impl DeviceControl for WriteLog {
+            fn open(&mut self) -> ControlResult<()> {
+                self.inner.open()
+            }
+
+            fn close(&mut self) -> ControlResult<()> {
+                self.inner.close()
+            }
+
+            fn is_opened(&self) -> bool {
+                self.inner.is_opened()
+            }
+
+            fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+                self.inner.read(address, buf)
+            }
+
+            fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+                self.addresses.push(address);
+                self.inner.write(address, data)
+            }
+
+            fn genapi(&mut self) -> ControlResult<String> {
+                self.inner.genapi()
+            }
+
+            fn enable_streaming(&mut self) -> ControlResult<()> {
+                self.inner.enable_streaming()
+            }
+
+            fn disable_streaming(&mut self) -> ControlResult<()> {
+                self.inner.disable_streaming()
+            }
+        }
+
+        type TestCamera = Camera<WriteLog, StreamHandle>;
+
+        fn open(serial_number: &str, fault: Option<StreamFault>) -> TestCamera {
+            let mut builder = EmulatorBuilder::new().serial_number(serial_number).unwrap();
+            if let Some(fault) = fault {
+                builder = builder.stream_fault(fault, FaultSchedule::Always);
+            }
+            builder.build();
+
+            let filter = CameraFilter::new().serial_number(serial_number);
+            let mut camera = open_emulated_by(&filter).unwrap().unwrap();
+            camera.load_context().unwrap();
+            let ctrl = WriteLog {
+                inner: camera.ctrl,
+                addresses: vec![],
+            };
+            Camera::new(ctrl, camera.strm, camera.ctxt, camera.info)
+        }
+
+        fn entry(camera: &mut TestCamera, name: &str) -> String {
+            let mut ctxt = camera.params_ctxt().unwrap();
+            let node = enum_node(&ctxt, name).unwrap();
+            node.current_entry(&mut ctxt)
+                .unwrap()
+                .symbolic(&ctxt)
+                .to_string()
+        }
+
+        fn set_entry(camera: &mut TestCamera, name: &str, symbolic: &str) {
+            let mut ctxt = camera.params_ctxt().unwrap();
+            let node = enum_node(&ctxt, name).unwrap();
+            node.set_entry_by_symbolic(&mut ctxt, symbolic).unwrap();
+        }
+
+        /// Returns the address `TriggerSoftware` writes to.
+        fn trigger_software_address(camera: &mut TestCamera) -> u64 {
+            let mut ctxt = camera.params_ctxt().unwrap();
+            let node = ctxt.node("TriggerSoftware").unwrap();
+            node.as_command(&ctxt).unwrap().execute(&mut ctxt).unwrap();
+            camera.ctrl.addresses.pop().unwrap()
+        }
+
+        fn num_writes_to(camera: &TestCamera, address: u64) -> usize {
+            camera
+                .ctrl
+                .addresses
+                .iter()
+                .filter(|&&addr| addr == address)
+                .count()
+        }
+
+        #[test]
+        fn test_grab_n_with_software_trigger() {
+            let mut camera = open("GRAB001", None);
+            set_entry(&mut camera, "AcquisitionMode", "SingleFrame");
+            set_entry(&mut camera, "TriggerMode", "Off");
+            let trigger_address = trigger_software_address(&mut camera);
+            camera.ctrl.addresses.clear();
+
+            let payloads = camera.grab_n(3, Duration::from_secs(3)).unwrap();
+            // Each trigger yields exactly one frame, so no frame is skipped.
+            assert_eq!(
+                payloads.iter().map(Payload::id).collect::<Vec<_>>(),
+                vec![0, 1, 2]
+            );
+            assert_eq!(num_writes_to(&camera, trigger_address), 3);
+
+            // The features are restored after streaming is stopped.
+            assert!(!camera.strm.is_loop_running());
+            assert_eq!(entry(&mut camera, "AcquisitionMode"), "SingleFrame");
+            assert_eq!(entry(&mut camera, "TriggerMode"), "Off");
+            assert_eq!(entry(&mut camera, "TriggerSelector"), "FrameStart");
+            assert_eq!(entry(&mut camera, "TriggerSource"), "Software");
+
+            camera.close().unwrap();
+        }
+
+        #[test]
+        fn test_grab_n_keeps_trigger_mode() {
+            let mut camera = open("GRAB002", None);
+            set_entry(&mut camera, "TriggerMode", "On");
+
+            assert_eq!(camera.grab_n(2, Duration::from_secs(3)).unwrap().len(), 2);
+            assert_eq!(entry(&mut camera, "TriggerMode"), "On");
+            assert_eq!(entry(&mut camera, "AcquisitionMode"), "Continuous");
+
+            camera.close().unwrap();
+        }
+
+        #[test]
+        fn test_grab_n_timeout() {
+            // No frame is delivered since every packet is dropped.
+            let mut camera = open("GRAB003", Some(StreamFault::DropPacket));
+            set_entry(&mut camera, "AcquisitionMode", "SingleFrame");
+            let trigger_address = trigger_software_address(&mut camera);
+            camera.ctrl.addresses.clear();
+
+            assert!(matches!(
+                camera.grab_n(2, Duration::from_millis(300)),
+                Err(CameleonError::StreamError(StreamError::Timeout))
+            ));
+            // The grab gives up after the first frame.
+            assert_eq!(num_writes_to(&camera, trigger_address), 1);
+
+            // The features are restored even though the grab failed.
+            assert!(!camera.strm.is_loop_running());
+            assert_eq!(entry(&mut camera, "AcquisitionMode"), "SingleFrame");
+            assert_eq!(entry(&mut camera, "TriggerMode"), "Off");
+
+            camera.close().unwrap();
+        }
+    }
+}
@@ -0,0 +1,579 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides [`ClockSync`] which maps device timestamps to the host clock.
+//!
+//! [`ClockSync`] latches the device timestamp repeatedly, and estimates the offset and the drift
+//! of the device clock against the host clock from the latched timestamps. Then timestamps of
+//! payloads are converted to the host time with an error bound, so that payloads from multiple
+//! cameras and other sensors can be fused on the same time axis.
+//!
+//! # Examples
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use cameleon::{clock::ClockSync, u3v};
+//! use cameleon_device::emulator::EmulatorBuilder;
+//!
+//! EmulatorBuilder::new().build();
+//! let mut camera = u3v::enumerate_emulated_cameras().unwrap().pop().unwrap();
+//! camera.open().unwrap();
+//! camera.load_context().unwrap();
+//!
+//! let mut clock = ClockSync::new();
+//! for _ in 0..4 {
+//!     clock.sync(&mut camera.ctrl).unwrap();
+//!     std::thread::sleep(Duration::from_millis(10));
+//! }
+//!
+//! let payload = camera.grab_one(Duration::from_secs(1)).unwrap();
+//! let host_timestamp = clock.to_host_time(payload.timestamp()).unwrap();
+//! println!(
+//!     "{:?} ± {:?}",
+//!     host_timestamp.system_time, host_timestamp.error_bound
+//! );
+//! # camera.close().unwrap();
+//! ```
+
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use tracing::warn;
+
+use crate::ControlResult;
+
+/// The default number of samples used for the estimation.
+const DEFAULT_WINDOW: usize = 16;
+
+/// Provides access to the timestamp counter of the device.
+pub trait TimestampLatch {
+    /// Latches the current value of the timestamp counter.
+    fn latch_timestamp(&mut self) -> ControlResult<()>;
+
+    /// Returns the value latched by [`latch_timestamp`](Self::latch_timestamp) in ticks.
+    fn latched_timestamp(&mut self) -> ControlResult<u64>;
+
+    /// Returns the duration of a tick of the timestamp counter in ns.
+    fn timestamp_increment(&mut self) -> ControlResult<u64>;
+}
+
+/// A pair of a device timestamp and the host time when the timestamp is latched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockSample {
+    /// The latched device timestamp in ticks.
+    pub device_ticks: u64,
+    /// The host monotonic time at the middle of the latch.
+    pub instant: Instant,
+    /// The host wall-clock time at the middle of the latch.
+    pub system_time: SystemTime,
+    /// The time taken to latch the device timestamp. Half of it bounds the error of the sample.
+    pub round_trip: Duration,
+}
+
+/// The relation between the device clock and the host clock estimated by [`ClockSync`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClockEstimate {
+    /// The estimated duration of a device tick measured by the host clock in ns.
+    pub tick_period_ns: f64,
+    /// The drift of the device clock against the host clock in ppm. A positive value means the
+    /// device clock runs faster than the nominal rate.
+    pub drift_ppm: f64,
+    /// The error bound of a timestamp converted by the estimate.
+    pub error_bound: Duration,
+    /// The number of samples used for the estimation.
+    pub samples: usize,
+}
+
+/// A device timestamp converted to the host clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostTimestamp {
+    /// The host monotonic time corresponding to the device timestamp.
+    pub instant: Instant,
+    /// The host wall-clock time corresponding to the device timestamp.
+    pub system_time: SystemTime,
+    /// The converted time is within `± error_bound` of the true time, assuming the device
+    /// clock drifts linearly.
+    pub error_bound: Duration,
+}
+
+/// Estimates the offset and the drift of the device clock against the host clock.
+///
+/// Call [`sync`](Self::sync) periodically, or use [`ClockSync::spawn`] to sync in a background
+/// thread. The estimation uses the latest samples which fit in the window, so that slow changes
+/// of the drift, e.g. due to temperature, are followed.
+///
+/// See the [module level documentation](self) for an example.
+#[derive(Clone, Debug)]
+pub struct ClockSync {
+    samples: VecDeque<ClockSample>,
+    window: usize,
+    /// Nominal tick period of the device clock in ns.
+    nominal_period_ns: Option<f64>,
+    /// Linear fit of the samples, `host_ns = intercept + slope * (ticks - base_ticks)` where
+    /// `host_ns` is measured from `base_instant`.
+    fit: Option<Fit>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Fit {
+    base_ticks: u64,
+    base_instant: Instant,
+    base_system_time: SystemTime,
+    intercept: f64,
+    slope: f64,
+    error_bound: Duration,
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockSync {
+    /// Creates a [`ClockSync`] which estimates from the latest 16 samples.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_window(DEFAULT_WINDOW)
+    }
+
+    /// Creates a [`ClockSync`] which estimates from the latest `window` samples.
+    ///
+    /// # Panics
+    /// If `window` is zero, this method will panic.
+    #[must_use]
+    pub fn with_window(window: usize) -> Self {
+        assert!(window > 0, "window must not be zero");
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window,
+            nominal_period_ns: None,
+            fit: None,
+        }
+    }
+
+    /// Latches the device timestamp, adds it to the samples and updates the estimate.
+    pub fn sync<Ctrl>(&mut self, ctrl: &mut Ctrl) -> ControlResult<ClockSample>
+    where
+        Ctrl: TimestampLatch + ?Sized,
+    {
+        if self.nominal_period_ns.is_none() {
+            self.nominal_period_ns = Some(ctrl.timestamp_increment()? as f64);
+        }
+
+        let start = Instant::now();
+        let start_system_time = SystemTime::now();
+        ctrl.latch_timestamp()?;
+        let round_trip = start.elapsed();
+        let device_ticks = ctrl.latched_timestamp()?;
+
+        let sample = ClockSample {
+            device_ticks,
+            instant: start + round_trip / 2,
+            system_time: start_system_time + round_trip / 2,
+            round_trip,
+        };
+        self.add_sample(sample);
+        Ok(sample)
+    }
+
+    /// Adds a sample obtained without [`sync`](Self::sync) and updates the estimate.
+    ///
+    /// If the device timestamp goes back, e.g. the device is reset, all previous samples are
+    /// discarded.
+    pub fn add_sample(&mut self, sample: ClockSample) {
+        if let Some(last) = self.samples.back() {
+            if sample.device_ticks < last.device_ticks {
+                warn!("device timestamp goes back, discard previous clock samples");
+                self.samples.clear();
+            }
+        }
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.fit = self.estimate_fit();
+    }
+
+    /// Sets the nominal tick period of the device clock in ns.
+    ///
+    /// The nominal period is used while only one sample is available, and as the reference of
+    /// [`ClockEstimate::drift_ppm`]. [`sync`](Self::sync) reads it from the device if it's not set.
+    pub fn set_nominal_period(&mut self, period_ns: u64) {
+        self.nominal_period_ns = Some(period_ns as f64);
+        self.fit = self.estimate_fit();
+    }
+
+    /// Returns the samples used for the estimation, the oldest first.
+    pub fn samples(&self) -> impl Iterator<Item = &ClockSample> {
+        self.samples.iter()
+    }
+
+    /// Returns the current estimate, or `None` if no estimate is available yet.
+    #[must_use]
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        let fit = self.fit?;
+        let nominal = self.nominal_period_ns.unwrap_or(fit.slope);
+        Some(ClockEstimate {
+            tick_period_ns: fit.slope,
+            drift_ppm: (nominal / fit.slope - 1.0) * 1e6,
+            error_bound: fit.error_bound,
+            samples: self.samples.len(),
+        })
+    }
+
+    /// Converts a device timestamp in ticks to the host clock.
+    ///
+    /// Returns `None` if no estimate is available yet, or the converted time is out of the range
+    /// of the host clock.
+    #[must_use]
+    pub fn ticks_to_host_time(&self, device_ticks: u64) -> Option<HostTimestamp> {
+        let fit = self.fit?;
+        let delta_ticks = i128::from(device_ticks) - i128::from(fit.base_ticks);
+        let host_ns = fit.intercept + fit.slope * delta_ticks as f64;
+        if !host_ns.is_finite() {
+            return None;
+        }
+
+        let delta = Duration::from_nanos(u64::try_from(host_ns.abs().round() as u128).ok()?);
+        let (instant, system_time) = if host_ns >= 0.0 {
+            (
+                fit.base_instant.checked_add(delta)?,
+                fit.base_system_time.checked_add(delta)?,
+            )
+        } else {
+            (
+                fit.base_instant.checked_sub(delta)?,
+                fit.base_system_time.checked_sub(delta)?,
+            )
+        };
+
+        Some(HostTimestamp {
+            instant,
+            system_time,
+            error_bound: fit.error_bound,
+        })
+    }
+
+    /// Converts a timestamp of a payload to the host clock.
+    ///
+    /// `device_timestamp` is the value returned from
+    /// [`Payload::timestamp`](crate::payload::Payload::timestamp), i.e. the device ticks
+    /// represented as ns.
+    ///
+    /// Returns `None` if no estimate is available yet, or the converted time is out of the range
+    /// of the host clock.
+    #[must_use]
+    pub fn to_host_time(&self, device_timestamp: Duration) -> Option<HostTimestamp> {
+        let ticks = u64::try_from(device_timestamp.as_nanos()).ok()?;
+        self.ticks_to_host_time(ticks)
+    }
+
+    /// Spawns a thread which syncs every `interval`, and returns the handle to the shared
+    /// [`ClockSync`].
+    ///
+    /// `ctrl` must be usable from another thread, e.g.
+    /// [`SharedControlHandle`](crate::u3v::SharedControlHandle) cloned from the camera.
+    /// The thread stops when the handle is dropped.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use cameleon::{
+    ///     clock::ClockSync,
+    ///     u3v::{SharedControlHandle, StreamHandle},
+    ///     Camera,
+    /// };
+    /// use cameleon_device::emulator::EmulatorBuilder;
+    ///
+    /// EmulatorBuilder::new().build();
+    /// let camera = cameleon::u3v::enumerate_emulated_cameras().unwrap().pop().unwrap();
+    /// let mut camera: Camera<SharedControlHandle, StreamHandle> = camera.convert_into();
+    /// camera.open().unwrap();
+    ///
+    /// let clock = ClockSync::spawn(camera.ctrl.clone(), ClockSync::new(), Duration::from_millis(10));
+    /// std::thread::sleep(Duration::from_millis(50));
+    /// assert!(clock.estimate().is_some());
+    /// # drop(clock);
+    /// # camera.close().unwrap();
+    /// ```
+    pub fn spawn<Ctrl>(mut ctrl: Ctrl, clock: ClockSync, interval: Duration) -> ClockSyncHandle
+    where
+        Ctrl: TimestampLatch + Send + 'static,
+    {
+        let clock = Arc::new(Mutex::new(clock));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let shared = clock.clone();
+        let stop_loop = stop.clone();
+        std::thread::spawn(move || {
+            while !stop_loop.load(Ordering::Relaxed) {
+                let res = match shared.lock() {
+                    Ok(mut clock) => clock.sync(&mut ctrl),
+                    Err(_) => break,
+                };
+                if let Err(e) = res {
+                    warn!("failed to sync the device clock: {}", e);
+                }
+                std::thread::sleep(interval);
+            }
+        });
+
+        ClockSyncHandle { clock, stop }
+    }
+
+    fn estimate_fit(&self) -> Option<Fit> {
+        let base = *self.samples.front()?;
+        let points: Vec<(f64, f64)> = self
+            .samples
+            .iter()
+            .map(|s| {
+                let ticks = (s.device_ticks - base.device_ticks) as f64;
+                (ticks, signed_ns(s.instant, base.instant) as f64)
+            })
+            .collect();
+
+        let (intercept, slope) = if points.len() == 1 {
+            (0.0, self.nominal_period_ns?)
+        } else {
+            let n = points.len() as f64;
+            let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+            let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+            let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+            let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+            if sxx == 0.0 {
+                // All samples have the same device timestamp.
+                return None;
+            }
+            let slope = sxy / sxx;
+            (mean_y - slope * mean_x, slope)
+        };
+
+        // The error bound is the largest deviation of the samples from the fit plus the
+        // uncertainty of each sample.
+        let max_residual = points
+            .iter()
+            .map(|(x, y)| (y - (intercept + slope * x)).abs())
+            .fold(0.0, f64::max);
+        let max_half_round_trip = self
+            .samples
+            .iter()
+            .map(|s| s.round_trip / 2)
+            .max()
+            .unwrap_or_default();
+        let error_bound = Duration::from_nanos(max_residual.ceil() as u64) + max_half_round_trip;
+
+        // Wall-clock time is derived from the latest sample so that adjustments of the system
+        // clock are reflected.
+        let latest = *self.samples.back()?;
+        let base_system_time = if latest.instant >= base.instant {
+            latest
+                .system_time
+                .checked_sub(latest.instant - base.instant)?
+        } else {
+            latest
+                .system_time
+                .checked_add(base.instant - latest.instant)?
+        };
+
+        Some(Fit {
+            base_ticks: base.device_ticks,
+            base_instant: base.instant,
+            base_system_time,
+            intercept,
+            slope,
+            error_bound,
+        })
+    }
+}
+
+/// A handle to [`ClockSync`] synced in a background thread by [`ClockSync::spawn`].
+///
+/// The thread stops when the handle is dropped.
+#[derive(Debug)]
+pub struct ClockSyncHandle {
+    clock: Arc<Mutex<ClockSync>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ClockSyncHandle {
+    /// Returns the current estimate, see [`ClockSync::estimate`].
+    #[must_use]
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        self.clock.lock().ok()?.estimate()
+    }
+
+    /// Converts a timestamp of a payload to the host clock, see [`ClockSync::to_host_time`].
+    #[must_use]
+    pub fn to_host_time(&self, device_timestamp: Duration) -> Option<HostTimestamp> {
+        self.clock.lock().ok()?.to_host_time(device_timestamp)
+    }
+
+    /// Returns a snapshot of the shared [`ClockSync`].
+    #[must_use]
+    pub fn snapshot(&self) -> Option<ClockSync> {
+        self.clock.lock().ok().map(|clock| clock.clone())
+    }
+}
+
+impl Drop for ClockSyncHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Returns `a - b` in ns.
+pub(crate) fn signed_ns(a: Instant, b: Instant) -> i128 {
+    if a >= b {
+        i128::try_from((a - b).as_nanos()).unwrap_or(i128::MAX)
+    } else {
+        -i128::try_from((b - a).as_nanos()).unwrap_or(i128::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROUND_TRIP: Duration = Duration::from_micros(2);
+
+    /// Returns a base time far enough from the process start to subtract from.
+    fn base() -> (Instant, SystemTime) {
+        let offset = Duration::from_secs(10);
+        (Instant::now() + offset, SystemTime::now() + offset)
+    }
+
+    fn sample(base: (Instant, SystemTime), device_ticks: u64, host_ns: u64) -> ClockSample {
+        let delta = Duration::from_nanos(host_ns);
+        ClockSample {
+            device_ticks,
+            instant: base.0 + delta,
+            system_time: base.1 + delta,
+            round_trip: ROUND_TRIP,
+        }
+    }
+
+    fn assert_near(actual: Instant, expected: Instant, tolerance_ns: i128) {
+        let diff = signed_ns(actual, expected);
+        assert!(diff.abs() <= tolerance_ns, "off by {} ns", diff);
+    }
+
+    #[test]
+    fn test_recover_offset_and_drift() {
+        let base = base();
+        let mut clock = ClockSync::new();
+        clock.set_nominal_period(1);
+        // The device clock starts at 5000 ticks and runs 100 ppm slower than the host clock.
+        for i in 0..8 {
+            clock.add_sample(sample(base, 5000 + i * 1_000_000, i * 1_000_100));
+        }
+
+        let estimate = clock.estimate().unwrap();
+        assert_eq!(estimate.samples, 8);
+        assert!((estimate.tick_period_ns - 1.0001).abs() < 1e-9);
+        assert!((estimate.drift_ppm + 99.99).abs() < 0.01);
+        assert!(estimate.error_bound >= ROUND_TRIP / 2);
+        assert!(estimate.error_bound < ROUND_TRIP);
+
+        let converted = clock.ticks_to_host_time(5000 + 10_000_000).unwrap();
+        assert_near(
+            converted.instant,
+            base.0 + Duration::from_nanos(10_001_000),
+            1,
+        );
+        let system_diff = converted
+            .system_time
+            .duration_since(base.1 + Duration::from_nanos(10_001_000))
+            .unwrap_or_else(|e| e.duration());
+        assert!(system_diff <= Duration::from_nanos(1));
+        assert_eq!(converted.error_bound, estimate.error_bound);
+
+        assert_eq!(
+            clock.to_host_time(Duration::from_nanos(5000 + 10_000_000)),
+            Some(converted)
+        );
+    }
+
+    #[test]
+    fn test_single_sample() {
+        let base = base();
+        let mut clock = ClockSync::new();
+        clock.add_sample(sample(base, 1000, 0));
+        // The slope can't be estimated from a single sample without the nominal period.
+        assert!(clock.estimate().is_none());
+        assert!(clock.ticks_to_host_time(1000).is_none());
+
+        clock.set_nominal_period(8);
+        let estimate = clock.estimate().unwrap();
+        assert_eq!(estimate.samples, 1);
+        assert!((estimate.tick_period_ns - 8.0).abs() < f64::EPSILON);
+        assert!(estimate.drift_ppm.abs() < f64::EPSILON);
+        assert_eq!(estimate.error_bound, ROUND_TRIP / 2);
+
+        let converted = clock.ticks_to_host_time(1100).unwrap();
+        assert_near(converted.instant, base.0 + Duration::from_nanos(800), 0);
+    }
+
+    #[test]
+    fn test_window_overflow() {
+        let base = base();
+        let mut clock = ClockSync::with_window(3);
+        clock.set_nominal_period(1);
+        // The first two samples have a different slope from the rest.
+        clock.add_sample(sample(base, 0, 0));
+        clock.add_sample(sample(base, 1000, 2000));
+        for i in 2..5 {
+            clock.add_sample(sample(base, i * 1000, 2000 + (i - 1) * 1000));
+        }
+
+        let ticks: Vec<_> = clock.samples().map(|s| s.device_ticks).collect();
+        assert_eq!(ticks, vec![2000, 3000, 4000]);
+        let estimate = clock.estimate().unwrap();
+        assert_eq!(estimate.samples, 3);
+        assert!((estimate.tick_period_ns - 1.0).abs() < 1e-9);
+        assert_eq!(estimate.error_bound, ROUND_TRIP / 2);
+    }
+
+    #[test]
+    fn test_tick_wraparound() {
+        let base = base();
+        let mut clock = ClockSync::new();
+        clock.set_nominal_period(1);
+        clock.add_sample(sample(base, u64::MAX - 1000, 0));
+        clock.add_sample(sample(base, u64::MAX - 500, 500));
+
+        // The counter wraps around, so the previous samples no longer describe the device clock.
+        clock.add_sample(sample(base, 100, 1600));
+        let ticks: Vec<_> = clock.samples().map(|s| s.device_ticks).collect();
+        assert_eq!(ticks, vec![100]);
+
+        let converted = clock.ticks_to_host_time(300).unwrap();
+        assert_near(converted.instant, base.0 + Duration::from_nanos(1800), 0);
+    }
+
+    #[test]
+    fn test_negative_offset() {
+        let base = base();
+        let mut clock = ClockSync::new();
+        clock.set_nominal_period(2);
+        clock.add_sample(sample(base, 10_000, 0));
+        clock.add_sample(sample(base, 20_000, 20_000));
+
+        // Ticks before the first sample are converted to the time before it.
+        let converted = clock.ticks_to_host_time(4000).unwrap();
+        assert_near(converted.instant, base.0 - Duration::from_nanos(12_000), 1);
+        assert!(converted.system_time < base.1);
+
+        let converted = clock.ticks_to_host_time(0).unwrap();
+        assert_near(converted.instant, base.0 - Duration::from_nanos(20_000), 1);
+    }
+}
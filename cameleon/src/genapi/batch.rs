@@ -0,0 +1,762 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains batched reads and transactional writes of `GenApi` features.
+
+use std::{collections::HashSet, convert::TryInto};
+
+use cameleon_genapi::{
+    elem_type::{AddressKind, CachingMode, ImmOrPNode, ValueKind},
+    prelude::*,
+    store::NodeData,
+    GenApiError, GenApiResult, NodeId, NodeStore, RegisterBase,
+};
+use tracing::{error, warn};
+
+use super::{DeviceControl, GenApiCtxt, GenApiDevice, Node, ParamsCtxt};
+
+/// A value written to a feature by [`WriteTransaction`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureValue {
+    /// A value of an `IInteger` node.
+    Integer(i64),
+    /// A value of an `IFloat` node.
+    Float(f64),
+    /// A value of an `IBoolean` node.
+    Boolean(bool),
+    /// A value of an `IString` node.
+    String(String),
+    /// A symbolic name of an entry of an `IEnumeration` node.
+    Enumeration(String),
+}
+
+/// A set of feature writes applied at once by [`ParamsCtxt::write_transaction`].
+///
+/// # Examples
+/// ```rust
+/// # use cameleon::u3v;
+/// use cameleon::genapi::{FeatureValue, WriteTransaction};
+///
+/// # let mut cameras = u3v::enumerate_cameras().unwrap();
+/// # if cameras.is_empty() {
+/// #     return;
+/// # }
+/// # let mut camera = cameras.pop().unwrap();
+/// camera.open().unwrap();
+/// camera.load_context().unwrap();
+///
+/// let mut params_ctxt = camera.params_ctxt().unwrap();
+/// let width = params_ctxt.node("Width").unwrap();
+/// let offset_x = params_ctxt.node("OffsetX").unwrap();
+/// let pixel_format = params_ctxt.node("PixelFormat").unwrap();
+///
+/// let mut transaction = WriteTransaction::new();
+/// transaction
+///     .set(offset_x, FeatureValue::Integer(16))
+///     .set(width, FeatureValue::Integer(320))
+///     .set(pixel_format, FeatureValue::Enumeration("Mono8".into()));
+///
+/// // All features keep their previous values if any of the writes fails.
+/// params_ctxt.write_transaction(&transaction).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct WriteTransaction {
+    writes: Vec<(Node, FeatureValue)>,
+}
+
+impl WriteTransaction {
+    /// Creates an empty transaction.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a write of `value` to `node`.
+    ///
+    /// The kind of `value` must match the interface of `node`.
+    pub fn set(&mut self, node: Node, value: FeatureValue) -> &mut Self {
+        self.writes.push((node, value));
+        self
+    }
+
+    /// Returns the number of writes in the transaction.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Returns `true` if the transaction contains no writes.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty()
+    }
+}
+
+impl<Ctrl, Ctxt> ParamsCtxt<Ctrl, Ctxt>
+where
+    Ctrl: DeviceControl,
+    Ctxt: GenApiCtxt,
+{
+    /// Reads the registers backing `nodes` in as few transactions as possible and stores them
+    /// into the cache of the context.
+    ///
+    /// Register addresses of the nodes are resolved first, then adjacent or overlapping registers
+    /// are merged and read by [`DeviceControl::read_batch`]. Following reads of the nodes are
+    /// served from the cache.
+    ///
+    /// Registers which are already cached, registers with `NoCache` caching mode and registers
+    /// on chunk ports are skipped. Nothing is cached with a context without cache like
+    /// [`NoCacheGenApiCtxt`](super::NoCacheGenApiCtxt).
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use cameleon::u3v;
+    /// # let mut cameras = u3v::enumerate_cameras().unwrap();
+    /// # if cameras.is_empty() {
+    /// #     return;
+    /// # }
+    /// # let mut camera = cameras.pop().unwrap();
+    /// camera.open().unwrap();
+    /// camera.load_context().unwrap();
+    ///
+    /// let mut params_ctxt = camera.params_ctxt().unwrap();
+    /// let nodes: Vec<_> = ["Width", "Height", "OffsetX", "OffsetY", "PixelFormat"]
+    ///     .iter()
+    ///     .filter_map(|name| params_ctxt.node(name))
+    ///     .collect();
+    ///
+    /// params_ctxt.read_batch(&nodes).unwrap();
+    /// // Served from the cache.
+    /// let width = params_ctxt.node("Width").unwrap().as_integer(&params_ctxt).unwrap();
+    /// println!("{}", width.value(&mut params_ctxt).unwrap());
+    /// ```
+    pub fn read_batch(&mut self, nodes: &[Node]) -> GenApiResult<()> {
+        self.enter2(|ctrl, ns, vc| {
+            let mut visited = HashSet::new();
+            let mut registers = vec![];
+            for node in nodes {
+                collect_registers(node.0, ns, &mut visited, &mut registers);
+            }
+
+            // Resolve address ranges of the registers to be read.
+            let mut ranges = vec![];
+            for nid in registers {
+                let base = match register_base(nid, ns) {
+                    Some(base) => base,
+                    None => continue,
+                };
+                if base.cacheable() == CachingMode::NoCache || !is_device_port(base.p_port(), ns) {
+                    continue;
+                }
+
+                let mut device = GenApiDevice::new(ctrl);
+                let address = base.address(&mut device, ns, vc)?;
+                let length = base.length(&mut device, ns, vc)?;
+                if vc.get_cache(nid, address, length).is_some() {
+                    continue;
+                }
+                if let (Ok(start), Ok(len)) = (address.try_into(), length.try_into()) {
+                    ranges.push((nid, start, len));
+                }
+            }
+            if ranges.is_empty() {
+                return Ok(());
+            }
+
+            // Merge adjacent or overlapping ranges.
+            ranges.sort_by_key(|(_, start, _)| *start);
+            let mut blocks: Vec<(u64, Vec<u8>)> = vec![];
+            for (_, start, len) in &ranges {
+                let end = start + *len as u64;
+                match blocks.last_mut() {
+                    Some((block_start, block)) if *start <= *block_start + block.len() as u64 => {
+                        let block_end = *block_start + block.len() as u64;
+                        if block_end < end {
+                            block.resize((end - *block_start) as usize, 0);
+                        }
+                    }
+                    _ => blocks.push((*start, vec![0; *len])),
+                }
+            }
+
+            let mut entries: Vec<_> = blocks
+                .iter_mut()
+                .map(|(start, block)| (*start, block.as_mut_slice()))
+                .collect();
+            ctrl.read_batch(&mut entries)
+                .map_err(|e| GenApiError::Device(e.into()))?;
+
+            // Fill the cache with the read data.
+            for (nid, start, len) in ranges {
+                let (block_start, block) = blocks
+                    .iter()
+                    .rev()
+                    .find(|(block_start, _)| *block_start <= start)
+                    .unwrap();
+                let offset = (start - block_start) as usize;
+                vc.cache_data(nid, start as i64, len as i64, &block[offset..offset + len]);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Applies all writes in `transaction`.
+    ///
+    /// Writes are reordered so that a feature is written after the features it depends on, e.g.
+    /// a selector is written before the features it selects, and `Width` is written before
+    /// `OffsetX` whose maximum is calculated from `Width`. Writes without dependency between
+    /// them keep their order in `transaction`.
+    ///
+    /// If any write fails, features already written are restored to their previous values and
+    /// the error of the failed write is returned.
+    ///
+    /// See [`WriteTransaction`] for an example.
+    pub fn write_transaction(&mut self, transaction: &WriteTransaction) -> GenApiResult<()> {
+        let mut written = Vec::with_capacity(transaction.len());
+        for idx in self.dependency_order(transaction) {
+            let (node, value) = &transaction.writes[idx];
+            // Read the previous value right before the write so that it's read with the selectors
+            // written so far.
+            let result = self.feature_value(*node, value).and_then(|prev| {
+                self.set_feature_value(*node, value)?;
+                Ok(prev)
+            });
+
+            match result {
+                Ok(prev) => written.push((*node, prev)),
+                Err(err) => {
+                    // Restore written features in reverse order, so that selectors are restored
+                    // after the features they select.
+                    for (node, prev) in written.iter().rev() {
+                        if let Err(restore_err) = self.set_feature_value(*node, prev) {
+                            error!(?restore_err);
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the current value of `node` if `node` is a readable value feature.
+    pub(crate) fn current_value(&mut self, node: Node) -> GenApiResult<Option<FeatureValue>> {
+        Ok(if let Some(node) = node.as_integer(self) {
+            node.is_readable(self)?
+                .then(|| node.value(self).map(FeatureValue::Integer))
+                .transpose()?
+        } else if let Some(node) = node.as_float(self) {
+            node.is_readable(self)?
+                .then(|| node.value(self).map(FeatureValue::Float))
+                .transpose()?
+        } else if let Some(node) = node.as_boolean(self) {
+            node.is_readable(self)?
+                .then(|| node.value(self).map(FeatureValue::Boolean))
+                .transpose()?
+        } else if let Some(node) = node.as_string(self) {
+            node.is_readable(self)?
+                .then(|| node.value(self).map(FeatureValue::String))
+                .transpose()?
+        } else if let Some(node) = node.as_enumeration(self) {
+            if node.is_readable(self)? {
+                let entry = node.current_entry(self)?;
+                Some(FeatureValue::Enumeration(entry.symbolic(self).to_string()))
+            } else {
+                None
+            }
+        } else {
+            None
+        })
+    }
+
+    /// Returns `true` if `node` is a writable value feature.
+    pub(crate) fn is_writable_value(&mut self, node: Node) -> GenApiResult<bool> {
+        if let Some(node) = node.as_integer(self) {
+            node.is_writable(self)
+        } else if let Some(node) = node.as_float(self) {
+            node.is_writable(self)
+        } else if let Some(node) = node.as_boolean(self) {
+            node.is_writable(self)
+        } else if let Some(node) = node.as_string(self) {
+            node.is_writable(self)
+        } else if let Some(node) = node.as_enumeration(self) {
+            node.is_writable(self)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Applies writes in `transaction` in dependency order, skipping failed writes.
+    /// Returns the number of succeeded writes.
+    pub(crate) fn write_best_effort(&mut self, transaction: &WriteTransaction) -> usize {
+        let mut written = 0;
+        for idx in self.dependency_order(transaction) {
+            let (node, value) = &transaction.writes[idx];
+            match self.set_feature_value(*node, value) {
+                Ok(()) => written += 1,
+                Err(err) => warn!(?err, ?value, "failed to write a feature"),
+            }
+        }
+
+        written
+    }
+
+    /// Returns indices of the writes in `transaction` sorted in dependency order.
+    fn dependency_order(&self, transaction: &WriteTransaction) -> Vec<usize> {
+        let ns = self.node_store();
+        let nids: Vec<NodeId> = transaction.writes.iter().map(|(node, _)| node.0).collect();
+        let dependencies: Vec<HashSet<NodeId>> =
+            nids.iter().map(|nid| reachable_nodes(*nid, ns)).collect();
+        let selected: Vec<Vec<NodeId>> = nids
+            .iter()
+            .map(|nid| {
+                nid.as_iselector_kind(ns)
+                    .and_then(|selector| selector.selecting_nodes(ns).ok().map(<[_]>::to_vec))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let must_precede = |a: usize, b: usize| {
+            a != b
+                && nids[a] != nids[b]
+                && (dependencies[b].contains(&nids[a]) || selected[a].contains(&nids[b]))
+        };
+
+        let mut pending: Vec<usize> = (0..nids.len()).collect();
+        let mut order = Vec::with_capacity(nids.len());
+        while !pending.is_empty() {
+            // Pick the first write which doesn't depend on other pending writes. If dependencies
+            // are cyclic, fall back to the original order.
+            let pos = pending
+                .iter()
+                .position(|&b| !pending.iter().any(|&a| must_precede(a, b)))
+                .unwrap_or(0);
+            order.push(pending.remove(pos));
+        }
+
+        order
+    }
+
+    /// Reads the current value of `node` in the same kind as `kind`.
+    fn feature_value(&mut self, node: Node, kind: &FeatureValue) -> GenApiResult<FeatureValue> {
+        Ok(match kind {
+            FeatureValue::Integer(_) => {
+                FeatureValue::Integer(expect(node.as_integer(self), "IInteger")?.value(self)?)
+            }
+            FeatureValue::Float(_) => {
+                FeatureValue::Float(expect(node.as_float(self), "IFloat")?.value(self)?)
+            }
+            FeatureValue::Boolean(_) => {
+                FeatureValue::Boolean(expect(node.as_boolean(self), "IBoolean")?.value(self)?)
+            }
+            FeatureValue::String(_) => {
+                FeatureValue::String(expect(node.as_string(self), "IString")?.value(self)?)
+            }
+            FeatureValue::Enumeration(_) => {
+                let node = expect(node.as_enumeration(self), "IEnumeration")?;
+                let entry = node.current_entry(self)?;
+                FeatureValue::Enumeration(entry.symbolic(self).to_string())
+            }
+        })
+    }
+
+    fn set_feature_value(&mut self, node: Node, value: &FeatureValue) -> GenApiResult<()> {
+        match value {
+            FeatureValue::Integer(v) => {
+                expect(node.as_integer(self), "IInteger")?.set_value(self, *v)
+            }
+            FeatureValue::Float(v) => expect(node.as_float(self), "IFloat")?.set_value(self, *v),
+            FeatureValue::Boolean(v) => {
+                expect(node.as_boolean(self), "IBoolean")?.set_value(self, *v)
+            }
+            FeatureValue::String(v) => {
+                expect(node.as_string(self), "IString")?.set_value(self, v.clone())
+            }
+            FeatureValue::Enumeration(v) => {
+                expect(node.as_enumeration(self), "IEnumeration")?.set_entry_by_symbolic(self, v)
+            }
+        }
+    }
+}
+
+fn expect<T>(node: Option<T>, interface: &str) -> GenApiResult<T> {
+    node.ok_or_else(|| {
+        GenApiError::InvalidNode(format!("the node doesn't implement `{}`", interface).into())
+    })
+}
+
+/// Collects register nodes which back the value of the node.
+fn collect_registers(
+    nid: NodeId,
+    ns: &impl NodeStore,
+    visited: &mut HashSet<NodeId>,
+    registers: &mut Vec<NodeId>,
+) {
+    if !visited.insert(nid) {
+        return;
+    }
+
+    if register_base(nid, ns).is_some() {
+        registers.push(nid);
+        return;
+    }
+
+    let mut referenced = vec![];
+    visit_references(nid, ns, false, &mut |nid| referenced.push(nid));
+    for nid in referenced {
+        collect_registers(nid, ns, visited, registers);
+    }
+}
+
+/// Returns all nodes which the node depends on, including the nodes constraining its value.
+fn reachable_nodes(nid: NodeId, ns: &impl NodeStore) -> HashSet<NodeId> {
+    let mut reachable = HashSet::new();
+    let mut stack = vec![nid];
+    while let Some(nid) = stack.pop() {
+        visit_references(nid, ns, true, &mut |referenced| {
+            if reachable.insert(referenced) {
+                stack.push(referenced);
+            }
+        });
+    }
+
+    reachable
+}
+
+/// Calls `f` with each node directly referenced by the node.
+///
+/// If `constraints` is `true`, nodes which constrain the value, e.g. `pMin`, `pMax` and entries
+/// of an enumeration, are also visited.
+fn visit_references(
+    nid: NodeId,
+    ns: &impl NodeStore,
+    constraints: bool,
+    f: &mut impl FnMut(NodeId),
+) {
+    fn pnode<T>(elem: &ImmOrPNode<T>, f: &mut impl FnMut(NodeId)) {
+        if let ImmOrPNode::PNode(nid) = elem {
+            f(*nid);
+        }
+    }
+
+    fn value_kind<T: Copy>(kind: &ValueKind<T>, f: &mut impl FnMut(NodeId)) {
+        match kind {
+            ValueKind::Value(_) => {}
+            ValueKind::PValue(p_value) => f(p_value.p_value()),
+            ValueKind::PIndex(p_index) => {
+                f(p_index.p_index());
+                for indexed in p_index.value_indexed() {
+                    pnode(&indexed.indexed(), f);
+                }
+                pnode(&p_index.value_default(), f);
+            }
+        }
+    }
+
+    if let Some(kind) = nid.as_inode_kind(ns) {
+        let base = kind.node_base_precise();
+        for p in [
+            base.p_is_implemented(),
+            base.p_is_available(),
+            base.p_is_locked(),
+        ]
+        .iter()
+        .flatten()
+        {
+            f(*p);
+        }
+    }
+
+    match ns.node_opt(nid) {
+        Some(NodeData::Integer(n)) => {
+            value_kind(n.value_kind(), f);
+            if constraints {
+                pnode(&n.min_elem(), f);
+                pnode(&n.max_elem(), f);
+                pnode(&n.inc_elem(), f);
+            }
+        }
+        Some(NodeData::Float(n)) => {
+            value_kind(n.value_kind(), f);
+            if constraints {
+                pnode(&n.min_elem(), f);
+                pnode(&n.max_elem(), f);
+                if let Some(inc) = n.inc_elem() {
+                    pnode(inc, f);
+                }
+            }
+        }
+        Some(NodeData::Boolean(n)) => pnode(&n.value_elem(), f),
+        Some(NodeData::String(n)) => pnode(&n.value_elem(), f),
+        Some(NodeData::Enumeration(n)) => {
+            pnode(&n.value_elem(), f);
+            if constraints {
+                n.entries(ns).iter().for_each(|nid| f(*nid));
+            }
+        }
+        Some(NodeData::Command(n)) => {
+            pnode(&n.value_elem(), f);
+            pnode(&n.command_value_elem(), f);
+        }
+        Some(NodeData::Converter(n)) => {
+            f(n.p_value());
+            n.p_variables().iter().for_each(|var| f(var.value()));
+        }
+        Some(NodeData::IntConverter(n)) => {
+            f(n.p_value());
+            n.p_variables().iter().for_each(|var| f(var.value()));
+        }
+        Some(NodeData::SwissKnife(n)) => n.p_variables().iter().for_each(|var| f(var.value())),
+        Some(NodeData::IntSwissKnife(n)) => n.p_variables().iter().for_each(|var| f(var.value())),
+        _ => {
+            if let Some(base) = register_base(nid, ns) {
+                for kind in base.address_kinds() {
+                    match kind {
+                        AddressKind::Address(address) => pnode(address, f),
+                        AddressKind::IntSwissKnife(nid) => f(*nid),
+                        AddressKind::PIndex(p_index) => {
+                            f(p_index.p_index());
+                            if let Some(offset) = p_index.offset() {
+                                pnode(&offset, f);
+                            }
+                        }
+                    }
+                }
+                pnode(base.length_elem(), f);
+            }
+        }
+    }
+}
+
+fn register_base(nid: NodeId, ns: &impl NodeStore) -> Option<&RegisterBase> {
+    match ns.node_opt(nid)? {
+        NodeData::IntReg(n) => Some(n.register_base()),
+        NodeData::MaskedIntReg(n) => Some(n.register_base()),
+        NodeData::FloatReg(n) => Some(n.register_base()),
+        NodeData::StringReg(n) => Some(n.register_base()),
+        NodeData::Register(n) => Some(n.register_base()),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the port reads the device memory directly.
+fn is_device_port(nid: NodeId, ns: &impl NodeStore) -> bool {
+    matches!(ns.node_opt(nid), Some(NodeData::Port(port)) if port.chunk_id().is_none())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{params_ctxt, params_ctxt_with, MemoryControl};
+
+    use super::{
+        super::{DefaultGenApiCtxt, NoCacheGenApiCtxt},
+        *,
+    };
+
+    /// `Selector` at 0x0 selects `Value` at 0x10 + `Selector` * 4, and `Other` is at 0x20.
+    const FEATURES: &str = r#"
+    <IntReg Name="Selector">
+        <Address>0x0</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <pSelected>Value</pSelected>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntSwissKnife Name="ValueAddress">
+        <pVariable Name="SEL">Selector</pVariable>
+        <Formula>0x10 + SEL * 4</Formula>
+    </IntSwissKnife>
+    <IntReg Name="Value">
+        <pAddress>ValueAddress</pAddress>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntReg Name="Other">
+        <Address>0x20</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    "#;
+
+    fn read_u32(ctrl: &MemoryControl, address: usize) -> u32 {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(&ctrl.memory[address..address + 4]);
+        u32::from_le_bytes(buf)
+    }
+
+    fn write_u32(ctrl: &mut MemoryControl, address: usize, value: u32) {
+        ctrl.memory[address..address + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn setup() -> ParamsCtxt<MemoryControl, NoCacheGenApiCtxt> {
+        let mut ctxt = params_ctxt(FEATURES, 0x30);
+        // Selector = 0, Value[0] = 100, Value[1] = 101, Other = 200.
+        write_u32(&mut ctxt.ctrl, 0x10, 100);
+        write_u32(&mut ctxt.ctrl, 0x14, 101);
+        write_u32(&mut ctxt.ctrl, 0x20, 200);
+        ctxt
+    }
+
+    #[test]
+    fn test_write_transaction() {
+        let mut ctxt = setup();
+        let mut transaction = WriteTransaction::new();
+        // The selector is written first even though it's added last.
+        transaction
+            .set(ctxt.node("Value").unwrap(), FeatureValue::Integer(11))
+            .set(ctxt.node("Selector").unwrap(), FeatureValue::Integer(1));
+
+        ctxt.write_transaction(&transaction).unwrap();
+        assert_eq!(read_u32(&ctxt.ctrl, 0x0), 1);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x10), 100);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x14), 11);
+    }
+
+    #[test]
+    fn test_write_transaction_restores_selector() {
+        let mut ctxt = setup();
+        ctxt.ctrl.failing_address = Some(0x14);
+        let mut transaction = WriteTransaction::new();
+        transaction
+            .set(ctxt.node("Selector").unwrap(), FeatureValue::Integer(1))
+            .set(ctxt.node("Value").unwrap(), FeatureValue::Integer(11));
+
+        assert!(ctxt.write_transaction(&transaction).is_err());
+        assert_eq!(read_u32(&ctxt.ctrl, 0x0), 0);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x10), 100);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x14), 101);
+    }
+
+    #[test]
+    fn test_write_transaction_restores_selected_value() {
+        let mut ctxt = setup();
+        ctxt.ctrl.failing_address = Some(0x20);
+        let mut transaction = WriteTransaction::new();
+        transaction
+            .set(ctxt.node("Selector").unwrap(), FeatureValue::Integer(1))
+            .set(ctxt.node("Value").unwrap(), FeatureValue::Integer(11))
+            .set(ctxt.node("Other").unwrap(), FeatureValue::Integer(21));
+
+        assert!(ctxt.write_transaction(&transaction).is_err());
+        // The value selected by the new selector is restored before the selector.
+        assert_eq!(read_u32(&ctxt.ctrl, 0x0), 0);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x10), 100);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x14), 101);
+        assert_eq!(read_u32(&ctxt.ctrl, 0x20), 200);
+    }
+
+    /// `A` and `B` are adjacent, `C` overlaps `B`, `D` is apart from them, and `E` isn't cached.
+    const READ_BATCH_FEATURES: &str = r#"
+    <IntReg Name="A">
+        <Address>0x0</Address>
+        <Length>4</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntReg Name="B">
+        <Address>0x4</Address>
+        <Length>4</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntReg Name="C">
+        <Address>0x6</Address>
+        <Length>4</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntReg Name="D">
+        <Address>0x20</Address>
+        <Length>4</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    <IntReg Name="E">
+        <Address>0x30</Address>
+        <Length>4</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>Device</pPort>
+        <Cachable>NoCache</Cachable>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    "#;
+
+    fn int_value<Ctxt: GenApiCtxt>(ctxt: &mut ParamsCtxt<MemoryControl, Ctxt>, name: &str) -> i64 {
+        let node = ctxt.node(name).unwrap().as_integer(ctxt).unwrap();
+        node.value(ctxt).unwrap()
+    }
+
+    #[test]
+    fn test_read_batch() {
+        let mut ctxt: ParamsCtxt<_, DefaultGenApiCtxt> =
+            params_ctxt_with(READ_BATCH_FEATURES, 0x40);
+        for (i, byte) in ctxt.ctrl.memory.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        let nodes: Vec<_> = ["A", "B", "C", "D", "E"]
+            .iter()
+            .map(|name| ctxt.node(name).unwrap())
+            .collect();
+
+        // `A`, `B` and `C` are merged into a single read of 0x0..0xa, and `E` isn't read.
+        ctxt.read_batch(&nodes).unwrap();
+        assert_eq!(ctxt.ctrl.num_reads, 2);
+
+        // The values are served from the cache.
+        let expected = |address: u32| {
+            i64::from(u32::from_le_bytes([
+                address as u8,
+                address as u8 + 1,
+                address as u8 + 2,
+                address as u8 + 3,
+            ]))
+        };
+        for &(name, address) in &[("A", 0x0), ("B", 0x4), ("C", 0x6), ("D", 0x20)] {
+            assert_eq!(int_value(&mut ctxt, name), expected(address), "{}", name);
+        }
+        assert_eq!(ctxt.ctrl.num_reads, 2);
+
+        // Cached registers aren't read again.
+        ctxt.read_batch(&nodes).unwrap();
+        assert_eq!(ctxt.ctrl.num_reads, 2);
+
+        // `NoCache` register is always read from the device.
+        assert_eq!(int_value(&mut ctxt, "E"), expected(0x30));
+        assert_eq!(ctxt.ctrl.num_reads, 3);
+    }
+
+    #[test]
+    fn test_read_batch_without_cache() {
+        let mut ctxt = params_ctxt(READ_BATCH_FEATURES, 0x40);
+        let nodes: Vec<_> = ["A", "B"]
+            .iter()
+            .map(|name| ctxt.node(name).unwrap())
+            .collect();
+
+        ctxt.read_batch(&nodes).unwrap();
+        assert_eq!(ctxt.ctrl.num_reads, 1);
+
+        // The read data isn't cached, so following reads reach the device.
+        int_value(&mut ctxt, "A");
+        assert_eq!(ctxt.ctrl.num_reads, 2);
+    }
+}
@@ -35,12 +35,16 @@
 //! }
 //! ```
 
+mod batch;
 mod node_kind;
+mod xml_url;
 
+pub use batch::{FeatureValue, WriteTransaction};
 pub use node_kind::{
     BooleanNode, CategoryNode, CommandNode, EnumEntryNode, EnumerationNode, FloatNode, IntegerNode,
     Node, PortNode, RegisterNode, StringNode,
 };
+pub use xml_url::{XmlLocation, XmlResolver, XmlUrl};
 
 use std::{
     convert::TryInto,
@@ -0,0 +1,651 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains the URL of `GenApi` XML files and its resolver.
+
+use std::{
+    convert::TryInto,
+    fmt,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use tracing::{error, info};
+
+use super::CompressionType;
+use crate::{ControlError, ControlResult, DeviceControl};
+
+/// Location of a `GenApi` XML file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XmlLocation {
+    /// The file is on the memory of the device.
+    ///
+    /// Represented as `local:[///]filename.extension;address;length` where `address` and `length`
+    /// are hexadecimal.
+    Local {
+        /// Name of the file.
+        file_name: String,
+        /// Address of the file in the device memory.
+        address: u64,
+        /// Size of the file in bytes.
+        size: usize,
+    },
+
+    /// The file is on the file system of the host.
+    ///
+    /// Represented as `file:[///]filepath.extension`, where reserved characters of the path are
+    /// percent-escaped.
+    File(PathBuf),
+
+    /// The file is on a web server.
+    ///
+    /// Represented as `http://host/path/filename.extension`. The URL is kept as is, and
+    /// [`XmlResolver`] can't retrieve the file because it doesn't have an HTTP client.
+    Web(String),
+}
+
+/// URL of a `GenApi` XML file, which is used by `GenTL` ports and the manifest of devices.
+///
+/// The URL has the form of `{location}[?SchemaVersion=x.x.x][&SHA1=hash]`, see [`XmlLocation`]
+/// for the supported locations.
+///
+/// # Examples
+///
+/// ```rust
+/// use cameleon::genapi::{CompressionType, XmlLocation, XmlUrl};
+///
+/// let url: XmlUrl = "local:///Vendor_Model.zip;8000;1A2B?SchemaVersion=1.1.0"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(
+///     url.location,
+///     XmlLocation::Local {
+///         file_name: "Vendor_Model.zip".into(),
+///         address: 0x8000,
+///         size: 0x1a2b,
+///     }
+/// );
+/// assert_eq!(url.schema_version, Some(semver::Version::new(1, 1, 0)));
+/// assert!(matches!(url.compression_type(), CompressionType::Zip));
+///
+/// // The URL is formatted back to the same form.
+/// assert_eq!(
+///     url.to_string(),
+///     "local:Vendor_Model.zip;8000;1A2B?SchemaVersion=1.1.0"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlUrl {
+    /// Location of the file.
+    pub location: XmlLocation,
+    /// Version of the schema the file follows.
+    pub schema_version: Option<semver::Version>,
+    /// SHA1 hash of the file, which is verified when the file is resolved.
+    pub sha1_hash: Option<[u8; 20]>,
+}
+
+impl XmlUrl {
+    /// Parses `url`.
+    ///
+    /// Schemes are case insensitive.
+    pub fn parse(url: &str) -> ControlResult<Self> {
+        let invalid_url = || ControlError::InvalidDevice(format!("invalid URL: {}", url).into());
+
+        let (body, query) = match url.find('?') {
+            Some(pos) => (&url[..pos], Some(&url[pos + 1..])),
+            None => (url, None),
+        };
+        let scheme_len = body.find(':').ok_or_else(invalid_url)?;
+        let (scheme, path) = (&body[..scheme_len], &body[scheme_len + 1..]);
+
+        let location = match scheme.to_ascii_lowercase().as_str() {
+            "local" => {
+                let mut fields = path.trim_start_matches('/').split(';');
+                let (file_name, address, size) =
+                    match (fields.next(), fields.next(), fields.next(), fields.next()) {
+                        (Some(file_name), Some(address), Some(size), None) => {
+                            (file_name, address, size)
+                        }
+                        _ => return Err(invalid_url()),
+                    };
+                let address = parse_hex(address).ok_or_else(invalid_url)?;
+                let size = parse_hex(size)
+                    .and_then(|size| size.try_into().ok())
+                    .ok_or_else(invalid_url)?;
+                if file_name.is_empty() {
+                    return Err(invalid_url());
+                }
+
+                XmlLocation::Local {
+                    file_name: file_name.into(),
+                    address,
+                    size,
+                }
+            }
+
+            "file" => {
+                let path = percent_decode(path).ok_or_else(invalid_url)?;
+                // Strip the empty authority of `file:///`.
+                let path = path.strip_prefix("//").unwrap_or(&path);
+                // `file:///C|/dir/file.xml` or `file:///C:/dir/file.xml` on Windows.
+                let path = match path.as_bytes() {
+                    [b'/', drive, sep, ..]
+                        if drive.is_ascii_alphabetic() && (*sep == b'|' || *sep == b':') =>
+                    {
+                        format!("{}:{}", *drive as char, &path[3..])
+                    }
+                    _ => path.to_string(),
+                };
+                if path.is_empty() {
+                    return Err(invalid_url());
+                }
+
+                XmlLocation::File(path.into())
+            }
+
+            "http" => {
+                if path.trim_start_matches('/').is_empty() {
+                    return Err(invalid_url());
+                }
+                XmlLocation::Web(body.into())
+            }
+
+            _ => {
+                return Err(ControlError::InvalidDevice(
+                    format!("unsupported URL scheme: {}", url).into(),
+                ))
+            }
+        };
+
+        let mut schema_version = None;
+        let mut sha1_hash = None;
+        for param in query.into_iter().flat_map(|query| query.split('&')) {
+            let mut kv = param.splitn(2, '=');
+            match (kv.next(), kv.next()) {
+                (Some(key), Some(value)) if key.eq_ignore_ascii_case("SchemaVersion") => {
+                    schema_version = Some(parse_version(value).ok_or_else(invalid_url)?);
+                }
+                (Some(key), Some(value)) if key.eq_ignore_ascii_case("SHA1") => {
+                    sha1_hash = Some(parse_sha1(value).ok_or_else(invalid_url)?);
+                }
+                // Unknown parameters are ignored.
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            location,
+            schema_version,
+            sha1_hash,
+        })
+    }
+
+    /// Name of the file.
+    #[must_use]
+    pub fn file_name(&self) -> Option<&str> {
+        match &self.location {
+            XmlLocation::Local { file_name, .. } => Some(file_name),
+            XmlLocation::File(path) => path.file_name().and_then(|name| name.to_str()),
+            XmlLocation::Web(url) => url.rsplit('/').next().filter(|name| !name.is_empty()),
+        }
+    }
+
+    /// Compression type of the file, which is determined by the extension of the file.
+    #[must_use]
+    pub fn compression_type(&self) -> CompressionType {
+        self.file_name()
+            .map_or(CompressionType::Uncompressed, compression_type_of)
+    }
+}
+
+impl FromStr for XmlUrl {
+    type Err = ControlError;
+
+    fn from_str(s: &str) -> ControlResult<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for XmlUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            XmlLocation::Local {
+                file_name,
+                address,
+                size,
+            } => write!(f, "local:{};{:X};{:X}", file_name, address, size)?,
+            XmlLocation::File(path) => {
+                write!(f, "file:{}", percent_encode(&path.to_string_lossy()))?;
+            }
+            XmlLocation::Web(url) => write!(f, "{}", url)?,
+        }
+
+        let mut separator = '?';
+        if let Some(version) = &self.schema_version {
+            write!(
+                f,
+                "{}SchemaVersion={}.{}.{}",
+                separator, version.major, version.minor, version.patch
+            )?;
+            separator = '&';
+        }
+        if let Some(hash) = &self.sha1_hash {
+            write!(f, "{}SHA1=", separator)?;
+            for byte in hash {
+                write!(f, "{:02x}", byte)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Retrieves `GenApi` XML files described by [`XmlUrl`].
+///
+/// The resolver reads the file from the device memory or the host file system, verifies its SHA1
+/// hash if the URL has one, and decompresses it if the file is zipped.
+///
+/// If a local override is set, the resolver ignores URLs and always returns the overriding file.
+/// This is useful when the XML on the device is broken or outdated.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cameleon::u3v;
+/// use cameleon::genapi::XmlResolver;
+///
+/// let mut cameras = u3v::enumerate_cameras().unwrap();
+/// let mut camera = cameras.pop().unwrap();
+///
+/// // Use the XML on the host instead of the one on the device.
+/// let mut resolver = XmlResolver::new();
+/// resolver.set_local_override(Some("fixed_genapi.zip".into()));
+/// camera.ctrl.set_xml_resolver(resolver);
+///
+/// camera.open().unwrap();
+/// camera.load_context().unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct XmlResolver {
+    local_override: Option<PathBuf>,
+}
+
+impl XmlResolver {
+    /// Constructs a resolver without a local override.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Path to the file overriding XML files described by URLs.
+    #[must_use]
+    pub fn local_override(&self) -> Option<&Path> {
+        self.local_override.as_deref()
+    }
+
+    /// Sets the path to the file overriding XML files described by URLs.
+    ///
+    /// The file is decompressed if its extension is `.zip`.
+    pub fn set_local_override(&mut self, path: Option<PathBuf>) {
+        self.local_override = path;
+    }
+
+    /// Retrieves the XML file described by `url`.
+    ///
+    /// `ctrl` is used to read the file if the file is on the device memory.
+    pub fn resolve<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        url: &XmlUrl,
+        ctrl: &mut Ctrl,
+    ) -> ControlResult<String> {
+        if let Some(path) = &self.local_override {
+            info!(?path, "override GenApi XML with the local file");
+            let file = read_file(path)?;
+            let compression_type = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map_or(CompressionType::Uncompressed, compression_type_of);
+            return decompress(file, compression_type);
+        }
+
+        let file = match &url.location {
+            XmlLocation::Local { address, size, .. } => {
+                let mut buf = vec![0; *size];
+                ctrl.read(*address, &mut buf)?;
+                buf
+            }
+            XmlLocation::File(path) => read_file(path)?,
+            XmlLocation::Web(url) => {
+                return Err(ControlError::InvalidDevice(
+                    format!("retrieving XML over HTTP isn't supported: {}", url).into(),
+                ))
+            }
+        };
+
+        if let Some(hash) = &url.sha1_hash {
+            verify_sha1(&file, hash)?;
+        }
+        decompress(file, url.compression_type())
+    }
+}
+
+fn compression_type_of(file_name: &str) -> CompressionType {
+    if file_name.to_ascii_lowercase().ends_with(".zip") {
+        CompressionType::Zip
+    } else {
+        CompressionType::Uncompressed
+    }
+}
+
+fn read_file(path: &Path) -> ControlResult<Vec<u8>> {
+    std::fs::read(path).map_err(|e| {
+        error!(?e, ?path);
+        ControlError::Io(anyhow::Error::new(e).context(format!("failed to read {:?}", path)))
+    })
+}
+
+fn verify_sha1(file: &[u8], hash: &[u8; 20]) -> ControlResult<()> {
+    use sha1::Digest;
+
+    if sha1::Sha1::digest(file)[..] == hash[..] {
+        Ok(())
+    } else {
+        Err(ControlError::InvalidDevice(
+            "sha1 of retrieved xml file isn't same as entry's hash".into(),
+        ))
+    }
+}
+
+fn decompress(file: Vec<u8>, compression_type: CompressionType) -> ControlResult<String> {
+    fn zip_err(err: impl fmt::Debug) -> ControlError {
+        ControlError::InvalidDevice(format!("zipped xml file is broken: {:?}", err).into())
+    }
+
+    match compression_type {
+        CompressionType::Zip => {
+            let mut zip = zip::ZipArchive::new(std::io::Cursor::new(file)).map_err(zip_err)?;
+            if zip.len() != 1 {
+                return Err(zip_err("more than one files in zipped GenApi XML"));
+            }
+            let mut file = zip.by_index(0).map_err(zip_err)?;
+            let file_size: usize = file.size().try_into()?;
+            let mut xml = Vec::with_capacity(file_size);
+            file.read_to_end(&mut xml).map_err(zip_err)?;
+            Ok(String::from_utf8_lossy(&xml).into())
+        }
+
+        CompressionType::Uncompressed => Ok(String::from_utf8_lossy(&file).into()),
+    }
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(s, 16).ok()
+}
+
+fn parse_version(s: &str) -> Option<semver::Version> {
+    let mut numbers = s.split('.').map(|n| n.trim().parse::<u64>().ok());
+    let major = numbers.next()??;
+    let minor = numbers.next().unwrap_or(Some(0))?;
+    let patch = numbers.next().unwrap_or(Some(0))?;
+    Some(semver::Version::new(major, minor, patch))
+}
+
+fn parse_sha1(s: &str) -> Option<[u8; 20]> {
+    let s = s.trim();
+    if s.len() != 40 || !s.is_ascii() {
+        return None;
+    }
+
+    let mut hash = [0; 20];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+/// Escapes bytes of `s` other than unreserved characters and path separators as `%XX`.
+fn percent_encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for &byte in s.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/\\:".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Decodes `%XX` escapes in `s`.
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use sha1::Digest;
+
+    use super::*;
+    use crate::test_utils::MemoryControl;
+
+    const XML: &str = "<RegisterDescription/>";
+
+    fn zip(file_name: &str, data: &[u8]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        zip.start_file(file_name, zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(data).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    /// Returns a device whose memory has `file` at `0x100`, and the URL of the file.
+    fn local_file(file_name: &str, file: &[u8]) -> (MemoryControl, XmlUrl) {
+        let mut ctrl = MemoryControl::new(0x100 + file.len());
+        ctrl.memory[0x100..].copy_from_slice(file);
+        let url = format!("local:{};100;{:x}", file_name, file.len());
+        (ctrl, url.parse().unwrap())
+    }
+
+    /// Path to a temporary file which is unique to the test.
+    fn temp_path(file_name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cameleon-{}-{}", std::process::id(), file_name))
+    }
+
+    #[test]
+    fn test_parse_local() {
+        let url: XmlUrl = "Local:///Vendor_Model.xml;0x1000;1a2B?SchemaVersion=1.1"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            url.location,
+            XmlLocation::Local {
+                file_name: "Vendor_Model.xml".into(),
+                address: 0x1000,
+                size: 0x1a2b,
+            }
+        );
+        assert_eq!(url.schema_version, Some(semver::Version::new(1, 1, 0)));
+        assert_eq!(url.sha1_hash, None);
+        assert_eq!(url.file_name(), Some("Vendor_Model.xml"));
+        assert!(matches!(
+            url.compression_type(),
+            CompressionType::Uncompressed
+        ));
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let url: XmlUrl = "File:///tmp/my%20camera/genapi%25.ZIP?schemaversion=1.0.0"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            url.location,
+            XmlLocation::File("/tmp/my camera/genapi%.ZIP".into())
+        );
+        assert_eq!(url.file_name(), Some("genapi%.ZIP"));
+        assert!(matches!(url.compression_type(), CompressionType::Zip));
+
+        let url: XmlUrl = "file:///C|/my%20camera/genapi.xml".parse().unwrap();
+        assert_eq!(
+            url.location,
+            XmlLocation::File("C:/my camera/genapi.xml".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_http() {
+        let url: XmlUrl = "http://example.com/my%20camera/genapi.zip?SchemaVersion=1.0.0"
+            .parse()
+            .unwrap();
+        // Escapes are kept because the URL is passed to an HTTP client as is.
+        assert_eq!(
+            url.location,
+            XmlLocation::Web("http://example.com/my%20camera/genapi.zip".into())
+        );
+        assert_eq!(url.file_name(), Some("genapi.zip"));
+        assert!(matches!(url.compression_type(), CompressionType::Zip));
+
+        let mut ctrl = MemoryControl::new(0);
+        assert!(XmlResolver::new().resolve(&url, &mut ctrl).is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        for url in &[
+            "genapi.xml",
+            "ftp://example.com/genapi.xml",
+            "local:genapi.xml;100",
+            "local:genapi.xml;100;10;20",
+            "local:;100;10",
+            "local:genapi.xml;xyz;10",
+            "file:",
+            "file:///tmp/genapi%2.xml",
+            "http:",
+            "local:genapi.xml;100;10?SchemaVersion=a.b",
+            "local:genapi.xml;100;10?SHA1=0123",
+        ] {
+            assert!(XmlUrl::parse(url).is_err(), "{} must be rejected", url);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let hash = sha1::Sha1::digest(XML.as_bytes()).into();
+        let urls = vec![
+            XmlUrl {
+                location: XmlLocation::Local {
+                    file_name: "Vendor_Model.zip".into(),
+                    address: 0x8000,
+                    size: 0x1a2b,
+                },
+                schema_version: Some(semver::Version::new(1, 1, 0)),
+                sha1_hash: Some(hash),
+            },
+            XmlUrl {
+                location: XmlLocation::File("/tmp/my camera/100%?.xml".into()),
+                schema_version: None,
+                sha1_hash: Some(hash),
+            },
+            XmlUrl {
+                location: XmlLocation::Web("http://example.com/genapi.xml".into()),
+                schema_version: Some(semver::Version::new(1, 0, 0)),
+                sha1_hash: None,
+            },
+        ];
+
+        for url in urls {
+            assert_eq!(url.to_string().parse::<XmlUrl>().unwrap(), url);
+        }
+    }
+
+    #[test]
+    fn test_resolve_local() {
+        let (mut ctrl, url) = local_file("genapi.xml", XML.as_bytes());
+        assert_eq!(XmlResolver::new().resolve(&url, &mut ctrl).unwrap(), XML);
+    }
+
+    #[test]
+    fn test_resolve_zip() {
+        let (mut ctrl, url) = local_file("genapi.zip", &zip("genapi.xml", XML.as_bytes()));
+        assert_eq!(XmlResolver::new().resolve(&url, &mut ctrl).unwrap(), XML);
+
+        // Not a zip file.
+        let (mut ctrl, url) = local_file("genapi.zip", XML.as_bytes());
+        assert!(XmlResolver::new().resolve(&url, &mut ctrl).is_err());
+    }
+
+    #[test]
+    fn test_resolve_sha1() {
+        let (mut ctrl, mut url) = local_file("genapi.xml", XML.as_bytes());
+        let hash: [u8; 20] = sha1::Sha1::digest(XML.as_bytes()).into();
+
+        url.sha1_hash = Some(hash);
+        assert_eq!(XmlResolver::new().resolve(&url, &mut ctrl).unwrap(), XML);
+
+        let mut wrong_hash = hash;
+        wrong_hash[0] ^= 1;
+        url.sha1_hash = Some(wrong_hash);
+        assert!(matches!(
+            XmlResolver::new().resolve(&url, &mut ctrl),
+            Err(ControlError::InvalidDevice(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_file() {
+        let path = temp_path("resolve file.xml");
+        std::fs::write(&path, XML).unwrap();
+
+        let url = XmlUrl {
+            location: XmlLocation::File(path.clone()),
+            schema_version: None,
+            sha1_hash: None,
+        };
+        let url: XmlUrl = url.to_string().parse().unwrap();
+        let res = XmlResolver::new().resolve(&url, &mut MemoryControl::new(0));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(res.unwrap(), XML);
+    }
+
+    #[test]
+    fn test_local_override() {
+        let overriding_xml = "<RegisterDescription ModelName=\"Override\"/>";
+        let path = temp_path("override.zip");
+        std::fs::write(&path, zip("override.xml", overriding_xml.as_bytes())).unwrap();
+
+        let mut resolver = XmlResolver::new();
+        resolver.set_local_override(Some(path.clone()));
+        assert_eq!(resolver.local_override(), Some(path.as_path()));
+
+        // The override takes precedence over both the device memory and the hash in the URL.
+        let (mut ctrl, mut url) = local_file("genapi.xml", XML.as_bytes());
+        url.sha1_hash = Some([0; 20]);
+        let res = resolver.resolve(&url, &mut ctrl);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(res.unwrap(), overriding_xml);
+        assert_eq!(ctrl.num_reads, 0);
+    }
+}
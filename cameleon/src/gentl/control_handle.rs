@@ -0,0 +1,235 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains the control handle of devices opened through a `GenTL` producer.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tracing::error;
+
+use crate::{
+    genapi::{XmlResolver, XmlUrl},
+    ControlError, ControlResult, DeviceControl,
+};
+
+use super::{
+    ffi::{self, Handle},
+    DeviceState, ProducerInner,
+};
+
+/// This type is used to access the remote device port of a device through a `GenTL` producer.
+pub struct ControlHandle {
+    producer: Arc<ProducerInner>,
+    iface: Handle,
+    device_id: String,
+    state: Arc<Mutex<DeviceState>>,
+    xml_resolver: XmlResolver,
+}
+
+macro_rules! unwrap_or_log {
+    ($expr:expr) => {{
+        match $expr {
+            Ok(v) => v,
+            Err(error) => {
+                error!(?error);
+                return Err(error.into());
+            }
+        }
+    }};
+}
+
+impl ControlHandle {
+    /// ID of the device, which is unique in the interface the device belongs to.
+    #[must_use]
+    pub fn device_id(&self) -> &str {
+        &self.device_id
+    }
+
+    /// Returns the resolver used to retrieve `GenApi` XML in [`DeviceControl::genapi`].
+    #[must_use]
+    pub fn xml_resolver(&self) -> &XmlResolver {
+        &self.xml_resolver
+    }
+
+    /// Sets the resolver used to retrieve `GenApi` XML in [`DeviceControl::genapi`].
+    ///
+    /// See [`XmlResolver::set_local_override`] to use a local XML file instead of the one
+    /// provided by the producer.
+    pub fn set_xml_resolver(&mut self, resolver: XmlResolver) {
+        self.xml_resolver = resolver;
+    }
+
+    pub(super) fn new(
+        producer: Arc<ProducerInner>,
+        iface: Handle,
+        device_id: String,
+        state: Arc<Mutex<DeviceState>>,
+    ) -> Self {
+        Self {
+            producer,
+            iface,
+            device_id,
+            state,
+            xml_resolver: XmlResolver::new(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, DeviceState> {
+        self.state.lock().unwrap()
+    }
+
+    fn remote_port(&self) -> ControlResult<Handle> {
+        self.state().remote_port.ok_or(ControlError::NotOpened)
+    }
+}
+
+impl DeviceControl for ControlHandle {
+    fn open(&mut self) -> ControlResult<()> {
+        if self.is_opened() {
+            return Ok(());
+        }
+
+        let api = &self.producer.api;
+        let c_id = ffi::to_c_string(&self.device_id);
+        let mut device = Handle::null();
+        let mut remote_port = Handle::null();
+        unsafe {
+            // Streaming needs write access, and producers may not support shared access.
+            unwrap_or_log!(api.IFOpenDevice(
+                self.iface.0,
+                c_id.as_ptr(),
+                ffi::DEVICE_ACCESS_EXCLUSIVE,
+                &mut device.0,
+            ));
+            if let Err(error) = api.DevGetPort(device.0, &mut remote_port.0) {
+                error!(?error);
+                api.DevClose(device.0).ok();
+                return Err(error.into());
+            }
+        }
+
+        let mut state = self.state();
+        state.device = Some(device);
+        state.remote_port = Some(remote_port);
+        Ok(())
+    }
+
+    fn close(&mut self) -> ControlResult<()> {
+        let api = &self.producer.api;
+        let mut state = self.state.lock().unwrap();
+
+        // The data stream must be closed before its parent device.
+        if let Some(data_stream) = state.data_stream.take() {
+            unsafe { unwrap_or_log!(api.DSClose(data_stream.0)) };
+        }
+        state.remote_port = None;
+        if let Some(device) = state.device.take() {
+            unsafe { unwrap_or_log!(api.DevClose(device.0)) };
+        }
+
+        Ok(())
+    }
+
+    fn is_opened(&self) -> bool {
+        self.state().device.is_some()
+    }
+
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+        let port = self.remote_port()?;
+        let mut size = buf.len();
+        unsafe {
+            unwrap_or_log!(self.producer.api.GCReadPort(
+                port.0,
+                address,
+                buf.as_mut_ptr().cast(),
+                &mut size
+            ));
+        }
+
+        if size == buf.len() {
+            Ok(())
+        } else {
+            Err(ControlError::InvalidDevice(
+                format!("read {} bytes, but {} bytes are requested", size, buf.len()).into(),
+            ))
+        }
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+        let port = self.remote_port()?;
+        let mut size = data.len();
+        unsafe {
+            unwrap_or_log!(self.producer.api.GCWritePort(
+                port.0,
+                address,
+                data.as_ptr().cast(),
+                &mut size
+            ));
+        }
+
+        if size == data.len() {
+            Ok(())
+        } else {
+            Err(ControlError::InvalidDevice(
+                format!(
+                    "wrote {} bytes, but {} bytes are requested",
+                    size,
+                    data.len()
+                )
+                .into(),
+            ))
+        }
+    }
+
+    fn genapi(&mut self) -> ControlResult<String> {
+        let port = self.remote_port()?;
+        let api = &self.producer.api;
+
+        let mut num_urls = 0;
+        unsafe { unwrap_or_log!(api.GCGetNumPortURLs(port.0, &mut num_urls)) };
+        if num_urls == 0 {
+            return Err(ControlError::InvalidDevice(
+                "the device doesn't provide `GenApi` xml".into(),
+            ));
+        }
+
+        // The producer lists the preferred URL first.
+        let url = unwrap_or_log!(api.query_info_string(|ty, buf, size| unsafe {
+            api.GCGetPortURLInfo(port.0, 0, ffi::URL_INFO_URL, ty, buf, size)
+        }));
+        let mut url = unwrap_or_log!(XmlUrl::parse(&url));
+        if url.sha1_hash.is_none() {
+            url.sha1_hash = match api.query_info::<[u8; 20]>(|ty, buf, size| unsafe {
+                api.GCGetPortURLInfo(port.0, 0, ffi::URL_INFO_FILE_SHA1_HASH, ty, buf, size)
+            }) {
+                Ok(hash) => Some(hash),
+                Err(e) if ffi::is_unavailable(&e) => None,
+                Err(error) => {
+                    error!(?error);
+                    return Err(error.into());
+                }
+            };
+        }
+
+        let resolver = self.xml_resolver.clone();
+        resolver.resolve(&url, self)
+    }
+
+    // Streaming is controlled by the data stream module of the producer, so nothing to do here.
+    fn enable_streaming(&mut self) -> ControlResult<()> {
+        Ok(())
+    }
+
+    fn disable_streaming(&mut self) -> ControlResult<()> {
+        Ok(())
+    }
+}
+
+impl Drop for ControlHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            error!(?e)
+        }
+    }
+}
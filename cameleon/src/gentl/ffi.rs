@@ -0,0 +1,334 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Raw bindings to the C ABI of `GenTL` producers.
+//!
+//! Functions are resolved from the loaded `.cti` file at runtime, so the producer doesn't need to
+//! be present at build time.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::{
+    ffi::CString,
+    os::raw::{c_char, c_void},
+    path::Path,
+};
+
+use libloading::Library;
+
+use crate::{ControlError, StreamError};
+
+pub(super) type GC_ERROR = i32;
+pub(super) type bool8_t = u8;
+
+/// Opaque handle of a module, an event, or a buffer of the producer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) struct Handle(pub(super) *mut c_void);
+
+// The GenTL specification requires producers to be thread safe.
+unsafe impl Send for Handle {}
+unsafe impl Sync for Handle {}
+
+impl Handle {
+    pub(super) fn null() -> Self {
+        Self(std::ptr::null_mut())
+    }
+}
+
+pub(super) const GC_ERR_SUCCESS: GC_ERROR = 0;
+pub(super) const GC_ERR_NOT_INITIALIZED: GC_ERROR = -1002;
+pub(super) const GC_ERR_NOT_IMPLEMENTED: GC_ERROR = -1003;
+pub(super) const GC_ERR_RESOURCE_IN_USE: GC_ERROR = -1004;
+pub(super) const GC_ERR_ACCESS_DENIED: GC_ERROR = -1005;
+pub(super) const GC_ERR_INVALID_HANDLE: GC_ERROR = -1006;
+pub(super) const GC_ERR_NO_DATA: GC_ERROR = -1008;
+pub(super) const GC_ERR_TIMEOUT: GC_ERROR = -1011;
+pub(super) const GC_ERR_ABORT: GC_ERROR = -1012;
+pub(super) const GC_ERR_NOT_AVAILABLE: GC_ERROR = -1014;
+pub(super) const GC_ERR_BUFFER_TOO_SMALL: GC_ERROR = -1016;
+pub(super) const GC_ERR_BUSY: GC_ERROR = -1022;
+
+pub(super) const GENTL_INFINITE: u64 = 0xFFFF_FFFF_FFFF_FFFF;
+
+pub(super) const DEVICE_ACCESS_EXCLUSIVE: i32 = 4;
+
+pub(super) const DEVICE_INFO_VENDOR: i32 = 1;
+pub(super) const DEVICE_INFO_MODEL: i32 = 2;
+pub(super) const DEVICE_INFO_SERIAL_NUMBER: i32 = 7;
+
+pub(super) const URL_INFO_URL: i32 = 0;
+pub(super) const URL_INFO_FILE_SHA1_HASH: i32 = 6;
+
+pub(super) const STREAM_INFO_PAYLOAD_SIZE: i32 = 7;
+pub(super) const STREAM_INFO_BUF_ANNOUNCE_MIN: i32 = 12;
+
+pub(super) const BUFFER_INFO_BASE: i32 = 0;
+pub(super) const BUFFER_INFO_TIMESTAMP: i32 = 3;
+pub(super) const BUFFER_INFO_IS_INCOMPLETE: i32 = 7;
+pub(super) const BUFFER_INFO_SIZE_FILLED: i32 = 9;
+pub(super) const BUFFER_INFO_WIDTH: i32 = 10;
+pub(super) const BUFFER_INFO_HEIGHT: i32 = 11;
+pub(super) const BUFFER_INFO_XOFFSET: i32 = 12;
+pub(super) const BUFFER_INFO_YOFFSET: i32 = 13;
+pub(super) const BUFFER_INFO_FRAMEID: i32 = 16;
+pub(super) const BUFFER_INFO_PAYLOADTYPE: i32 = 19;
+pub(super) const BUFFER_INFO_PIXELFORMAT: i32 = 20;
+pub(super) const BUFFER_INFO_CHUNKLAYOUTID: i32 = 24;
+pub(super) const BUFFER_INFO_TIMESTAMP_NS: i32 = 28;
+
+pub(super) const PAYLOAD_TYPE_IMAGE: usize = 1;
+pub(super) const PAYLOAD_TYPE_CHUNK_DATA: usize = 4;
+pub(super) const PAYLOAD_TYPE_CHUNK_ONLY: usize = 8;
+
+pub(super) const EVENT_NEW_BUFFER: i32 = 1;
+
+pub(super) const ACQ_START_FLAGS_DEFAULT: i32 = 0;
+pub(super) const ACQ_STOP_FLAGS_KILL: i32 = 1;
+pub(super) const ACQ_QUEUE_ALL_DISCARD: i32 = 4;
+
+/// Data of the new buffer event.
+#[repr(C)]
+pub(super) struct EVENT_NEW_BUFFER_DATA {
+    pub(super) BufferHandle: *mut c_void,
+    pub(super) pUserPointer: *mut c_void,
+}
+
+/// An error returned from a function of the producer.
+#[derive(Debug, thiserror::Error)]
+#[error("GenTL producer returned error code {code}: {message}")]
+pub(super) struct GenTlError {
+    pub(super) code: GC_ERROR,
+    pub(super) message: String,
+}
+
+pub(super) type GenTlResult<T> = std::result::Result<T, GenTlError>;
+
+impl From<GenTlError> for ControlError {
+    fn from(err: GenTlError) -> Self {
+        match err.code {
+            GC_ERR_RESOURCE_IN_USE | GC_ERR_ACCESS_DENIED | GC_ERR_BUSY => Self::Busy,
+            GC_ERR_NOT_INITIALIZED | GC_ERR_INVALID_HANDLE => Self::NotOpened,
+            GC_ERR_TIMEOUT => Self::Timeout,
+            GC_ERR_BUFFER_TOO_SMALL => Self::BufferTooSmall,
+            _ => Self::Io(err.into()),
+        }
+    }
+}
+
+impl From<GenTlError> for StreamError {
+    fn from(err: GenTlError) -> Self {
+        match err.code {
+            GC_ERR_TIMEOUT => Self::Timeout,
+            GC_ERR_BUFFER_TOO_SMALL => Self::BufferTooSmall,
+            _ => Self::Io(err.into()),
+        }
+    }
+}
+
+macro_rules! define_api {
+    ($(fn $name:ident($($arg:ident: $ty:ty),*$(,)?);)*) => {
+        /// Functions exported from the producer.
+        pub(super) struct Api {
+            $($name: unsafe extern "system" fn($($ty),*) -> GC_ERROR,)*
+            GCGetLastError: unsafe extern "system" fn(*mut GC_ERROR, *mut c_char, *mut usize) -> GC_ERROR,
+            // Must be kept loaded while the functions above are in use.
+            _lib: Library,
+        }
+
+        impl Api {
+            /// Loads the producer and resolves its functions.
+            pub(super) fn load(path: &Path) -> Result<Self, libloading::Error> {
+                unsafe {
+                    let lib = Library::new(path)?;
+                    $(let $name = *lib.get(concat!(stringify!($name), "\0").as_bytes())?;)*
+                    let GCGetLastError = *lib.get(b"GCGetLastError\0")?;
+                    Ok(Self {
+                        $($name,)*
+                        GCGetLastError,
+                        _lib: lib,
+                    })
+                }
+            }
+
+            $(
+                pub(super) unsafe fn $name(&self, $($arg: $ty),*) -> GenTlResult<()> {
+                    let code = (self.$name)($($arg),*);
+                    self.check(code)
+                }
+            )*
+        }
+    };
+}
+
+define_api! {
+    fn GCInitLib();
+    fn GCCloseLib();
+
+    fn TLOpen(phSystem: *mut *mut c_void);
+    fn TLClose(hSystem: *mut c_void);
+    fn TLUpdateInterfaceList(hSystem: *mut c_void, pbChanged: *mut bool8_t, iTimeout: u64);
+    fn TLGetNumInterfaces(hSystem: *mut c_void, piNumIfaces: *mut u32);
+    fn TLGetInterfaceID(hSystem: *mut c_void, iIndex: u32, sID: *mut c_char, piSize: *mut usize);
+    fn TLOpenInterface(hSystem: *mut c_void, sIfaceID: *const c_char, phIface: *mut *mut c_void);
+
+    fn IFClose(hIface: *mut c_void);
+    fn IFUpdateDeviceList(hIface: *mut c_void, pbChanged: *mut bool8_t, iTimeout: u64);
+    fn IFGetNumDevices(hIface: *mut c_void, piNumDevices: *mut u32);
+    fn IFGetDeviceID(hIface: *mut c_void, iIndex: u32, sIDeviceID: *mut c_char, piSize: *mut usize);
+    fn IFGetDeviceInfo(
+        hIface: *mut c_void,
+        sDeviceID: *const c_char,
+        iInfoCmd: i32,
+        piType: *mut i32,
+        pBuffer: *mut c_void,
+        piSize: *mut usize,
+    );
+    fn IFOpenDevice(
+        hIface: *mut c_void,
+        sDeviceID: *const c_char,
+        iOpenFlag: i32,
+        phDevice: *mut *mut c_void,
+    );
+
+    fn DevClose(hDevice: *mut c_void);
+    fn DevGetPort(hDevice: *mut c_void, phRemoteDevice: *mut *mut c_void);
+    fn DevGetNumDataStreams(hDevice: *mut c_void, piNumDataStreams: *mut u32);
+    fn DevGetDataStreamID(
+        hDevice: *mut c_void,
+        iIndex: u32,
+        sDataStreamID: *mut c_char,
+        piSize: *mut usize,
+    );
+    fn DevOpenDataStream(
+        hDevice: *mut c_void,
+        sDataStreamID: *const c_char,
+        phDataStream: *mut *mut c_void,
+    );
+
+    fn GCReadPort(hPort: *mut c_void, iAddress: u64, pBuffer: *mut c_void, piSize: *mut usize);
+    fn GCWritePort(hPort: *mut c_void, iAddress: u64, pBuffer: *const c_void, piSize: *mut usize);
+    fn GCGetNumPortURLs(hPort: *mut c_void, piNumURLs: *mut u32);
+    fn GCGetPortURLInfo(
+        hPort: *mut c_void,
+        iURLIndex: u32,
+        iInfoCmd: i32,
+        piType: *mut i32,
+        pBuffer: *mut c_void,
+        piSize: *mut usize,
+    );
+
+    fn DSClose(hDataStream: *mut c_void);
+    fn DSGetInfo(
+        hDataStream: *mut c_void,
+        iInfoCmd: i32,
+        piType: *mut i32,
+        pBuffer: *mut c_void,
+        piSize: *mut usize,
+    );
+    fn DSAllocAndAnnounceBuffer(
+        hDataStream: *mut c_void,
+        iSize: usize,
+        pPrivate: *mut c_void,
+        phBuffer: *mut *mut c_void,
+    );
+    fn DSRevokeBuffer(
+        hDataStream: *mut c_void,
+        hBuffer: *mut c_void,
+        ppBuffer: *mut *mut c_void,
+        ppPrivate: *mut *mut c_void,
+    );
+    fn DSQueueBuffer(hDataStream: *mut c_void, hBuffer: *mut c_void);
+    fn DSFlushQueue(hDataStream: *mut c_void, iOperation: i32);
+    fn DSStartAcquisition(hDataStream: *mut c_void, iStartFlags: i32, iNumToAcquire: u64);
+    fn DSStopAcquisition(hDataStream: *mut c_void, iStopFlags: i32);
+    fn DSGetBufferInfo(
+        hDataStream: *mut c_void,
+        hBuffer: *mut c_void,
+        iInfoCmd: i32,
+        piType: *mut i32,
+        pBuffer: *mut c_void,
+        piSize: *mut usize,
+    );
+
+    fn GCRegisterEvent(hEventSrc: *mut c_void, iEventID: i32, phEvent: *mut *mut c_void);
+    fn GCUnregisterEvent(hEventSrc: *mut c_void, iEventID: i32);
+    fn EventGetData(hEvent: *mut c_void, pBuffer: *mut c_void, piSize: *mut usize, iTimeout: u64);
+    fn EventKill(hEvent: *mut c_void);
+}
+
+impl Api {
+    /// Converts the error code to [`GenTlError`] with the message of the last error.
+    fn check(&self, code: GC_ERROR) -> GenTlResult<()> {
+        if code == GC_ERR_SUCCESS {
+            return Ok(());
+        }
+
+        let mut last_code = 0;
+        let mut buf = vec![0_u8; 1024];
+        let mut size = buf.len();
+        let res =
+            unsafe { (self.GCGetLastError)(&mut last_code, buf.as_mut_ptr().cast(), &mut size) };
+        let message = if res == GC_ERR_SUCCESS && last_code == code {
+            c_string(&buf)
+        } else {
+            String::new()
+        };
+
+        Err(GenTlError { code, message })
+    }
+
+    /// Queries a string with the two-step protocol of `GenTL`, where the first call returns the
+    /// required size of the buffer.
+    pub(super) fn query_string(
+        &self,
+        mut query: impl FnMut(*mut c_char, *mut usize) -> GenTlResult<()>,
+    ) -> GenTlResult<String> {
+        let mut size = 0;
+        query(std::ptr::null_mut(), &mut size)?;
+        let mut buf = vec![0_u8; size];
+        query(buf.as_mut_ptr().cast(), &mut size)?;
+        Ok(c_string(&buf))
+    }
+
+    /// Queries an info value of which type is `T`.
+    pub(super) fn query_info<T: Copy + Default>(
+        &self,
+        mut query: impl FnMut(*mut i32, *mut c_void, *mut usize) -> GenTlResult<()>,
+    ) -> GenTlResult<T> {
+        let mut value = T::default();
+        let mut ty = 0;
+        let mut size = std::mem::size_of::<T>();
+        query(&mut ty, (&mut value as *mut T).cast(), &mut size)?;
+        Ok(value)
+    }
+
+    /// Queries a string info value.
+    pub(super) fn query_info_string(
+        &self,
+        mut query: impl FnMut(*mut i32, *mut c_void, *mut usize) -> GenTlResult<()>,
+    ) -> GenTlResult<String> {
+        let mut ty = 0;
+        self.query_string(|buf, size| query(&mut ty, buf.cast(), size))
+    }
+}
+
+/// Converts a null terminated string in `buf` to `String`.
+fn c_string(buf: &[u8]) -> String {
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into()
+}
+
+/// Converts `s` to a null terminated string.
+pub(super) fn to_c_string(s: &str) -> CString {
+    // IDs returned from the producer never contain null characters.
+    CString::new(s).unwrap_or_default()
+}
+
+/// Returns `true` if the error means that the info isn't provided by the producer.
+pub(super) fn is_unavailable(err: &GenTlError) -> bool {
+    matches!(
+        err.code,
+        GC_ERR_NOT_AVAILABLE | GC_ERR_NOT_IMPLEMENTED | GC_ERR_NO_DATA
+    )
+}
@@ -0,0 +1,256 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides access to cameras through third-party `GenTL` producers.
+//!
+//! A `GenTL` producer is a shared library with `.cti` extension provided by camera or frame grabber
+//! vendors. [`Producer`] loads the library, enumerates devices through its C ABI, and wraps each
+//! device as a [`Camera`].
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use cameleon::gentl;
+//!
+//! // Loads the producer and enumerates all cameras reachable through it.
+//! let mut cameras = gentl::enumerate_cameras("/opt/vendor/lib/Vendor.cti").unwrap();
+//!
+//! let mut camera = cameras.pop().unwrap();
+//! camera.open().unwrap();
+//! camera.load_context().unwrap();
+//!
+//! let payload_rx = camera.start_streaming(3).unwrap();
+//! loop {
+//!     if let Ok(payload) = payload_rx.try_recv() {
+//!         println!("{:?}", payload.image_info());
+//!         break;
+//!     }
+//! }
+//!
+//! camera.close().unwrap();
+//! ```
+
+pub mod control_handle;
+pub mod stream_handle;
+
+mod ffi;
+
+pub use control_handle::ControlHandle;
+pub use stream_handle::StreamHandle;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Condvar, Mutex, Weak},
+};
+
+use tracing::{error, warn};
+
+use crate::{camera::CameraInfo, CameleonResult, Camera, ControlError};
+
+use ffi::{Api, GenTlResult, Handle};
+
+/// Timeout of the device enumeration of each interface.
+const DEVICE_LIST_TIMEOUT_MS: u64 = 1000;
+
+lazy_static::lazy_static! {
+    /// Loaded producers keyed by their canonical path.
+    ///
+    /// A producer library is loaded only once per process, so `GCInitLib` must not be called
+    /// again while the producer is in use.
+    static ref PRODUCERS: Mutex<HashMap<PathBuf, Weak<ProducerInner>>> = Mutex::new(HashMap::new());
+
+    /// Notified when a producer is closed and removed from [`PRODUCERS`].
+    static ref PRODUCER_CLOSED: Condvar = Condvar::new();
+}
+
+/// Loads the `GenTL` producer at `path`, and enumerates all cameras reachable through it.
+///
+/// See [`Producer::enumerate_cameras`] for more details.
+pub fn enumerate_cameras(
+    path: impl AsRef<Path>,
+) -> CameleonResult<Vec<Camera<ControlHandle, StreamHandle>>> {
+    Producer::load(path)?.enumerate_cameras()
+}
+
+/// A loaded `GenTL` producer.
+///
+/// The producer is kept loaded until the `Producer` and all cameras enumerated from it are
+/// dropped.
+#[derive(Clone)]
+pub struct Producer {
+    inner: Arc<ProducerInner>,
+}
+
+impl Producer {
+    /// Loads the `GenTL` producer at `path` and opens its system module.
+    ///
+    /// If the producer at the same path is already loaded, the loaded one is shared.
+    pub fn load(path: impl AsRef<Path>) -> CameleonResult<Self> {
+        let path = path
+            .as_ref()
+            .canonicalize()
+            .map_err(|e| ControlError::Io(e.into()))?;
+
+        let mut producers = PRODUCERS.lock().unwrap();
+        while let Some(inner) = producers.get(&path) {
+            if let Some(inner) = inner.upgrade() {
+                return Ok(Self { inner });
+            }
+            // The producer is being closed, wait for it before initializing it again.
+            producers = PRODUCER_CLOSED.wait(producers).unwrap();
+        }
+
+        let api = Api::load(&path).map_err(|e| ControlError::Io(e.into()))?;
+
+        let mut system = Handle::null();
+        unsafe {
+            api.GCInitLib().map_err(ControlError::from)?;
+            if let Err(e) = api.TLOpen(&mut system.0) {
+                api.GCCloseLib().ok();
+                return Err(ControlError::from(e).into());
+            }
+        }
+
+        let inner = Arc::new(ProducerInner {
+            api,
+            path: path.clone(),
+            system,
+            interfaces: Mutex::new(vec![]),
+        });
+        producers.insert(path, Arc::downgrade(&inner));
+
+        Ok(Self { inner })
+    }
+
+    /// Enumerates all cameras reachable through the producer.
+    ///
+    /// Interfaces and devices are updated on each call, and cameras are returned without being
+    /// opened. Interfaces whose device list fails to be updated are skipped.
+    pub fn enumerate_cameras(&self) -> CameleonResult<Vec<Camera<ControlHandle, StreamHandle>>> {
+        let mut cameras = vec![];
+        for iface in self.inner.update_interfaces().map_err(ControlError::from)? {
+            let ids = match self.inner.update_devices(iface) {
+                Ok(ids) => ids,
+                Err(e) => {
+                    warn!("skip interface whose device list can't be updated: {}", e);
+                    continue;
+                }
+            };
+            for id in ids {
+                let info = self.inner.camera_info(iface, &id);
+                let device = Arc::new(Mutex::new(DeviceState::default()));
+                let ctrl = ControlHandle::new(self.inner.clone(), iface, id, device.clone());
+                let strm = StreamHandle::new(self.inner.clone(), device);
+                cameras.push(Camera::new(ctrl, strm, None, info));
+            }
+        }
+
+        Ok(cameras)
+    }
+}
+
+pub(super) struct ProducerInner {
+    api: Api,
+    /// Canonical path of the producer.
+    path: PathBuf,
+    system: Handle,
+    /// Opened interfaces and their IDs.
+    interfaces: Mutex<Vec<(String, Handle)>>,
+}
+
+impl ProducerInner {
+    /// Updates the interface list, and returns handles of all interfaces.
+    /// Interfaces are opened on their first appearance and kept opened until the producer is
+    /// dropped.
+    fn update_interfaces(&self) -> GenTlResult<Vec<Handle>> {
+        let api = &self.api;
+        let mut interfaces = self.interfaces.lock().unwrap();
+
+        unsafe {
+            let mut changed = 0;
+            api.TLUpdateInterfaceList(self.system.0, &mut changed, ffi::GENTL_INFINITE)?;
+
+            let mut num = 0;
+            api.TLGetNumInterfaces(self.system.0, &mut num)?;
+            for i in 0..num {
+                let id = api
+                    .query_string(|buf, size| api.TLGetInterfaceID(self.system.0, i, buf, size))?;
+                if interfaces.iter().any(|(opened, _)| opened == &id) {
+                    continue;
+                }
+
+                let mut iface = Handle::null();
+                let c_id = ffi::to_c_string(&id);
+                api.TLOpenInterface(self.system.0, c_id.as_ptr(), &mut iface.0)?;
+                interfaces.push((id, iface));
+            }
+        }
+
+        Ok(interfaces.iter().map(|(_, iface)| *iface).collect())
+    }
+
+    /// Updates the device list of the interface, and returns IDs of all devices.
+    fn update_devices(&self, iface: Handle) -> GenTlResult<Vec<String>> {
+        let api = &self.api;
+
+        unsafe {
+            let mut changed = 0;
+            api.IFUpdateDeviceList(iface.0, &mut changed, DEVICE_LIST_TIMEOUT_MS)?;
+
+            let mut num = 0;
+            api.IFGetNumDevices(iface.0, &mut num)?;
+            (0..num)
+                .map(|i| api.query_string(|buf, size| api.IFGetDeviceID(iface.0, i, buf, size)))
+                .collect()
+        }
+    }
+
+    fn camera_info(&self, iface: Handle, id: &str) -> CameraInfo {
+        let api = &self.api;
+        let c_id = ffi::to_c_string(id);
+        let query = |cmd| {
+            api.query_info_string(|ty, buf, size| unsafe {
+                api.IFGetDeviceInfo(iface.0, c_id.as_ptr(), cmd, ty, buf, size)
+            })
+            .unwrap_or_default()
+        };
+
+        CameraInfo {
+            vendor_name: query(ffi::DEVICE_INFO_VENDOR),
+            model_name: query(ffi::DEVICE_INFO_MODEL),
+            serial_number: query(ffi::DEVICE_INFO_SERIAL_NUMBER),
+        }
+    }
+}
+
+impl Drop for ProducerInner {
+    fn drop(&mut self) {
+        let api = &self.api;
+        unsafe {
+            for (_, iface) in self.interfaces.get_mut().unwrap().drain(..) {
+                if let Err(e) = api.IFClose(iface.0) {
+                    error!(?e);
+                }
+            }
+            if let Err(e) = api.TLClose(self.system.0) {
+                error!(?e);
+            }
+            if let Err(e) = api.GCCloseLib() {
+                error!(?e);
+            }
+        }
+
+        PRODUCERS.lock().unwrap().remove(&self.path);
+        PRODUCER_CLOSED.notify_all();
+    }
+}
+
+/// Handles of an opened device shared by [`ControlHandle`] and [`StreamHandle`].
+#[derive(Default)]
+pub(super) struct DeviceState {
+    device: Option<Handle>,
+    remote_port: Option<Handle>,
+    data_stream: Option<Handle>,
+}
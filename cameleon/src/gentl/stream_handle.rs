@@ -0,0 +1,446 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains the stream handle of devices opened through a `GenTL` producer.
+
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use async_std::task;
+use futures::channel::oneshot;
+use tracing::{error, info, warn};
+
+use crate::{
+    camera::PayloadStream,
+    payload::{parse_chunks, ImageInfo, Payload, PayloadSender, PayloadType, PixelFormat},
+    DeviceControl, StreamError, StreamResult,
+};
+
+use super::{
+    ffi::{self, GenTlResult, Handle},
+    DeviceState, ProducerInner,
+};
+
+/// Number of buffers announced to the data stream if the producer doesn't require more.
+const DEFAULT_NUM_BUFFERS: usize = 4;
+
+/// Timeout of waiting for a new buffer, which bounds the latency of the loop cancellation.
+const NEW_BUFFER_TIMEOUT_MS: u64 = 100;
+
+/// This type is used to receive payloads from the data stream of a device through a `GenTL`
+/// producer.
+pub struct StreamHandle {
+    producer: Arc<ProducerInner>,
+    state: Arc<Mutex<DeviceState>>,
+    acquisition: Option<Acquisition>,
+}
+
+/// Resources of the running acquisition.
+struct Acquisition {
+    data_stream: Handle,
+    event: Handle,
+    buffers: Vec<Handle>,
+    cancellation_tx: oneshot::Sender<()>,
+    completion_rx: oneshot::Receiver<()>,
+}
+
+impl StreamHandle {
+    pub(super) fn new(producer: Arc<ProducerInner>, state: Arc<Mutex<DeviceState>>) -> Self {
+        Self {
+            producer,
+            state,
+            acquisition: None,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, DeviceState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Announces and queues buffers, then starts the acquisition of the data stream.
+    /// Returns the new buffer event and the announced buffers.
+    fn start_acquisition(&self, data_stream: Handle) -> GenTlResult<(Handle, Vec<Handle>)> {
+        let api = &self.producer.api;
+        let payload_size: usize = api.query_info(|ty, buf, size| unsafe {
+            api.DSGetInfo(data_stream.0, ffi::STREAM_INFO_PAYLOAD_SIZE, ty, buf, size)
+        })?;
+        let num_buffers = match api.query_info::<usize>(|ty, buf, size| unsafe {
+            api.DSGetInfo(
+                data_stream.0,
+                ffi::STREAM_INFO_BUF_ANNOUNCE_MIN,
+                ty,
+                buf,
+                size,
+            )
+        }) {
+            Ok(min) => min.max(DEFAULT_NUM_BUFFERS),
+            Err(e) if ffi::is_unavailable(&e) => DEFAULT_NUM_BUFFERS,
+            Err(e) => return Err(e),
+        };
+
+        let mut event = Handle::null();
+        let mut buffers = Vec::with_capacity(num_buffers);
+        let res = (|| unsafe {
+            for _ in 0..num_buffers {
+                let mut buffer = Handle::null();
+                api.DSAllocAndAnnounceBuffer(
+                    data_stream.0,
+                    payload_size,
+                    std::ptr::null_mut(),
+                    &mut buffer.0,
+                )?;
+                buffers.push(buffer);
+                api.DSQueueBuffer(data_stream.0, buffer.0)?;
+            }
+            api.GCRegisterEvent(data_stream.0, ffi::EVENT_NEW_BUFFER, &mut event.0)?;
+            api.DSStartAcquisition(
+                data_stream.0,
+                ffi::ACQ_START_FLAGS_DEFAULT,
+                ffi::GENTL_INFINITE,
+            )
+        })();
+
+        match res {
+            Ok(()) => Ok((event, buffers)),
+            Err(e) => {
+                let event = if event == Handle::null() {
+                    None
+                } else {
+                    Some(event)
+                };
+                self.release_buffers(data_stream, event, &buffers);
+                Err(e)
+            }
+        }
+    }
+
+    /// Stops the acquisition, then revokes buffers and unregisters the event.
+    fn release_buffers(&self, data_stream: Handle, event: Option<Handle>, buffers: &[Handle]) {
+        let api = &self.producer.api;
+        macro_rules! log_err {
+            ($expr:expr) => {
+                if let Err(e) = $expr {
+                    warn!(?e);
+                }
+            };
+        }
+
+        unsafe {
+            if event.is_some() {
+                log_err!(api.DSStopAcquisition(data_stream.0, ffi::ACQ_STOP_FLAGS_KILL));
+                log_err!(api.GCUnregisterEvent(data_stream.0, ffi::EVENT_NEW_BUFFER));
+            }
+            log_err!(api.DSFlushQueue(data_stream.0, ffi::ACQ_QUEUE_ALL_DISCARD));
+            for buffer in buffers {
+                log_err!(api.DSRevokeBuffer(
+                    data_stream.0,
+                    buffer.0,
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut()
+                ));
+            }
+        }
+    }
+}
+
+impl PayloadStream for StreamHandle {
+    fn open(&mut self) -> StreamResult<()> {
+        let api = &self.producer.api;
+        let mut state = self.state.lock().unwrap();
+        if state.data_stream.is_some() {
+            return Ok(());
+        }
+        let device = state
+            .device
+            .ok_or_else(|| StreamError::Io(anyhow::Error::msg("the device is not opened")))?;
+
+        let mut data_stream = Handle::null();
+        unsafe {
+            let mut num = 0;
+            api.DevGetNumDataStreams(device.0, &mut num)?;
+            if num == 0 {
+                return Err(StreamError::Io(anyhow::Error::msg(
+                    "the device has no data stream",
+                )));
+            }
+            let id =
+                api.query_string(|buf, size| api.DevGetDataStreamID(device.0, 0, buf, size))?;
+            let c_id = ffi::to_c_string(&id);
+            api.DevOpenDataStream(device.0, c_id.as_ptr(), &mut data_stream.0)?;
+        }
+
+        state.data_stream = Some(data_stream);
+        Ok(())
+    }
+
+    fn close(&mut self) -> StreamResult<()> {
+        if self.is_loop_running() {
+            self.stop_streaming_loop()?;
+        }
+
+        // The data stream may be already closed with its parent device.
+        if let Some(data_stream) = self.state().data_stream.take() {
+            unsafe { self.producer.api.DSClose(data_stream.0)? };
+        }
+        Ok(())
+    }
+
+    fn start_streaming_loop(
+        &mut self,
+        sender: PayloadSender,
+        _ctrl: &mut dyn DeviceControl,
+    ) -> StreamResult<()> {
+        if self.is_loop_running() {
+            return Err(StreamError::InStreaming);
+        }
+
+        let data_stream = self
+            .state()
+            .data_stream
+            .ok_or_else(|| StreamError::Io(anyhow::Error::msg("the data stream is not opened")))?;
+        let (event, buffers) = self.start_acquisition(data_stream).map_err(|e| {
+            error!(?e);
+            StreamError::from(e)
+        })?;
+
+        let (cancellation_tx, cancellation_rx) = oneshot::channel();
+        let (completion_tx, completion_rx) = oneshot::channel();
+        self.acquisition = Some(Acquisition {
+            data_stream,
+            event,
+            buffers,
+            cancellation_tx,
+            completion_rx,
+        });
+
+        let strm_loop = StreamingLoop {
+            producer: self.producer.clone(),
+            data_stream,
+            event,
+            sender,
+            completion_tx,
+            cancellation_rx,
+        };
+        std::thread::spawn(|| {
+            strm_loop.run();
+        });
+
+        info!("start streaming loop successfully");
+        Ok(())
+    }
+
+    fn stop_streaming_loop(&mut self) -> StreamResult<()> {
+        if let Some(acquisition) = self.acquisition.take() {
+            acquisition.cancellation_tx.send(()).map_err(|_| {
+                StreamError::Poisoned("failed to send cancellation signal to streaming loop".into())
+            })?;
+            // Wake up the loop waiting for a new buffer.
+            unsafe { self.producer.api.EventKill(acquisition.event.0).ok() };
+            task::block_on(acquisition.completion_rx)
+                .map_err(|e| StreamError::Poisoned(e.to_string().into()))?;
+
+            // Buffers have been released if the data stream is closed with its parent device.
+            if self.state().data_stream == Some(acquisition.data_stream) {
+                self.release_buffers(
+                    acquisition.data_stream,
+                    Some(acquisition.event),
+                    &acquisition.buffers,
+                );
+            }
+        }
+
+        info!("stop streaming loop successfully");
+        Ok(())
+    }
+
+    fn is_loop_running(&self) -> bool {
+        self.acquisition.is_some()
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            error!(?e)
+        }
+    }
+}
+
+struct StreamingLoop {
+    producer: Arc<ProducerInner>,
+    data_stream: Handle,
+    event: Handle,
+    sender: PayloadSender,
+    completion_tx: oneshot::Sender<()>,
+    cancellation_rx: oneshot::Receiver<()>,
+}
+
+impl StreamingLoop {
+    fn run(mut self) {
+        let api = &self.producer.api;
+
+        loop {
+            // Stop the loop when
+            // 1. `cancellation_tx` sends signal.
+            // 2. `cancellation_tx` is dropped.
+            if self.cancellation_rx.try_recv().transpose().is_some() {
+                break;
+            }
+
+            let mut data = ffi::EVENT_NEW_BUFFER_DATA {
+                BufferHandle: std::ptr::null_mut(),
+                pUserPointer: std::ptr::null_mut(),
+            };
+            let mut size = std::mem::size_of::<ffi::EVENT_NEW_BUFFER_DATA>();
+            let res = unsafe {
+                api.EventGetData(
+                    self.event.0,
+                    (&mut data as *mut ffi::EVENT_NEW_BUFFER_DATA).cast(),
+                    &mut size,
+                    NEW_BUFFER_TIMEOUT_MS,
+                )
+            };
+            match res {
+                Ok(()) => {}
+                Err(e) if e.code == ffi::GC_ERR_TIMEOUT || e.code == ffi::GC_ERR_ABORT => continue,
+                Err(e) => {
+                    // The event or the data stream is no longer valid.
+                    error!(?e);
+                    self.sender.try_send(Err(e.into())).ok();
+                    break;
+                }
+            }
+
+            let buffer = Handle(data.BufferHandle);
+            let payload = self.build_payload(buffer);
+
+            // Give the buffer back to the producer as soon as its data is copied.
+            if let Err(e) = unsafe { api.DSQueueBuffer(self.data_stream.0, buffer.0) } {
+                warn!(?e);
+            }
+
+            if let Err(err) = self.sender.try_send(payload) {
+                warn!(?err);
+            }
+        }
+
+        if let Err(e) = self.completion_tx.send(()) {
+            error!(?e);
+        }
+    }
+
+    fn build_payload(&self, buffer: Handle) -> StreamResult<Payload> {
+        let api = &self.producer.api;
+        macro_rules! info {
+            ($cmd:ident) => {
+                api.query_info(|ty, buf, size| unsafe {
+                    api.DSGetBufferInfo(self.data_stream.0, buffer.0, ffi::$cmd, ty, buf, size)
+                })
+            };
+        }
+        macro_rules! optional_info {
+            ($cmd:ident) => {
+                match info!($cmd) {
+                    Ok(value) => Some(value),
+                    Err(e) if ffi::is_unavailable(&e) => None,
+                    Err(e) => return Err(e.into()),
+                }
+            };
+        }
+
+        let is_incomplete: ffi::bool8_t = info!(BUFFER_INFO_IS_INCOMPLETE)?;
+        if is_incomplete != 0 {
+            return Err(StreamError::InvalidPayload(
+                "the producer delivered an incomplete buffer".into(),
+            ));
+        }
+
+        let base: usize = info!(BUFFER_INFO_BASE)?;
+        let valid_payload_size: usize = info!(BUFFER_INFO_SIZE_FILLED)?;
+        let id: u64 = info!(BUFFER_INFO_FRAMEID)?;
+        let timestamp: u64 = match optional_info!(BUFFER_INFO_TIMESTAMP_NS) {
+            Some(timestamp) => timestamp,
+            None => info!(BUFFER_INFO_TIMESTAMP)?,
+        };
+
+        let payload_type = match info!(BUFFER_INFO_PAYLOADTYPE)? {
+            ffi::PAYLOAD_TYPE_IMAGE => PayloadType::Image,
+            ffi::PAYLOAD_TYPE_CHUNK_DATA => PayloadType::ImageExtendedChunk,
+            ffi::PAYLOAD_TYPE_CHUNK_ONLY => PayloadType::Chunk,
+            other => {
+                return Err(StreamError::InvalidPayload(
+                    format!("unsupported payload type: {}", other).into(),
+                ))
+            }
+        };
+
+        // Reuse the buffer of the payload sent back from the receiver if exists.
+        let mut payload = self
+            .sender
+            .try_recv()
+            .map(|payload| payload.payload)
+            .unwrap_or_default();
+        payload.clear();
+        payload.extend_from_slice(unsafe {
+            // Safety: The producer keeps the buffer memory valid until the buffer is requeued.
+            std::slice::from_raw_parts(base as *const u8, valid_payload_size)
+        });
+
+        let image_size = match payload_type {
+            PayloadType::Image => Some(valid_payload_size),
+            // The first chunk of the payload data is the image.
+            PayloadType::ImageExtendedChunk => Some(
+                parse_chunks(&payload)?
+                    .first()
+                    .map(|chunk| chunk.len)
+                    .ok_or_else(|| {
+                        StreamError::InvalidPayload(
+                            "failed to parse chunk data: no chunk found".into(),
+                        )
+                    })?,
+            ),
+            PayloadType::Chunk => None,
+        };
+        let image_info = match image_size {
+            Some(image_size) => {
+                let pixel_format: u64 = info!(BUFFER_INFO_PIXELFORMAT)?;
+                let pixel_format = u32::try_from(pixel_format)
+                    .ok()
+                    .and_then(|pf| PixelFormat::try_from(pf).ok())
+                    .ok_or_else(|| {
+                        StreamError::InvalidPayload(
+                            format!("unknown pixel format: {:#x}", pixel_format).into(),
+                        )
+                    })?;
+                Some(ImageInfo {
+                    width: info!(BUFFER_INFO_WIDTH)?,
+                    height: info!(BUFFER_INFO_HEIGHT)?,
+                    x_offset: info!(BUFFER_INFO_XOFFSET)?,
+                    y_offset: info!(BUFFER_INFO_YOFFSET)?,
+                    pixel_format,
+                    image_size,
+                })
+            }
+            None => None,
+        };
+
+        let chunk_layout_id = match payload_type {
+            PayloadType::Image => None,
+            _ => optional_info!(BUFFER_INFO_CHUNKLAYOUTID).map(|id: u64| id as u32),
+        };
+
+        Ok(Payload {
+            id,
+            payload_type,
+            image_info,
+            payload,
+            valid_payload_size,
+            timestamp: Duration::from_nanos(timestamp),
+            chunk_layout_id,
+        })
+    }
+}
@@ -0,0 +1,725 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides [`CameraGroup`] which operates multiple cameras together.
+//!
+//! [`CameraGroup`] opens cameras, applies the same configuration to them, starts and stops
+//! streaming on all of them, and yields [`FrameSet`]s which contain frames of the cameras matched
+//! by their timestamps or block ids.
+//!
+//! # Examples
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use cameleon::{
+//!     genapi::FeatureValue,
+//!     group::{CameraGroup, GroupConfig},
+//!     u3v,
+//! };
+//! use cameleon_device::emulator::EmulatorBuilder;
+//!
+//! for serial in &["CAM0100", "CAM0101"] {
+//!     EmulatorBuilder::new().serial_number(serial).unwrap().build();
+//! }
+//! let cameras = u3v::enumerate_emulated_cameras().unwrap();
+//!
+//! let mut group = CameraGroup::new(cameras, GroupConfig::default());
+//! group.open().unwrap();
+//! group.load_context().unwrap();
+//! group
+//!     .set_features(&[("Width", FeatureValue::Integer(128))])
+//!     .unwrap();
+//!
+//! group.start_streaming(3).unwrap();
+//! for _ in 0..3 {
+//!     let frame_set = group.next_frame_set(Duration::from_secs(1)).unwrap();
+//!     for idx in frame_set.missing() {
+//!         println!("camera {} missed the frame", idx);
+//!     }
+//! }
+//! group.close().unwrap();
+//! ```
+
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+use tracing::{info, warn};
+
+use crate::{
+    camera::{Camera, DeviceControl, PayloadStream},
+    clock::{signed_ns, ClockSample, ClockSync, TimestampLatch},
+    genapi::{DefaultGenApiCtxt, FeatureValue, FromXml, GenApiCtxt, GenApiError, WriteTransaction},
+    payload::{Payload, PayloadReceiver},
+    CameleonResult, StreamError,
+};
+
+/// Interval of polling payload receivers while waiting a frame set.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// How frames of cameras are matched into a [`FrameSet`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Frames whose timestamps converted to the host clock are within the window are matched.
+    ///
+    /// Clock offsets of the cameras are estimated by the group itself.
+    Timestamp {
+        /// Maximum difference of the host time of frames in a set.
+        window: Duration,
+    },
+
+    /// Frames with the same block id are matched.
+    ///
+    /// This is suitable for cameras triggered by the same hardware trigger, which generate the
+    /// same number of frames since they start streaming.
+    BlockId,
+}
+
+/// Configuration of [`CameraGroup`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupConfig {
+    /// How frames are matched.
+    pub match_mode: MatchMode,
+
+    /// Maximum time to wait for frames of other cameras after the first frame of a set arrives.
+    /// Cameras whose frames don't arrive in time are reported as missing.
+    pub frame_timeout: Duration,
+
+    /// The number of timestamps latched from each camera to take a clock sample. The latch with
+    /// the shortest round trip is used as the sample.
+    pub clock_samples: usize,
+
+    /// Interval of re-estimating clock offsets while streaming. `None` disables re-estimation.
+    pub resync_interval: Option<Duration>,
+}
+
+impl Default for GroupConfig {
+    fn default() -> Self {
+        Self {
+            match_mode: MatchMode::Timestamp {
+                window: Duration::from_millis(5),
+            },
+            frame_timeout: Duration::from_millis(100),
+            clock_samples: 4,
+            resync_interval: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// A set of frames matched across the cameras of a [`CameraGroup`].
+#[derive(Clone, Debug)]
+pub struct FrameSet {
+    frames: Vec<Option<Payload>>,
+    timestamp: Instant,
+}
+
+impl FrameSet {
+    /// Returns the frames indexed in the same order as [`CameraGroup::cameras`].
+    /// `None` means the camera missed the frame.
+    #[must_use]
+    pub fn frames(&self) -> &[Option<Payload>] {
+        &self.frames
+    }
+
+    /// Returns the frames, see [`frames`](Self::frames).
+    #[must_use]
+    pub fn into_frames(self) -> Vec<Option<Payload>> {
+        self.frames
+    }
+
+    /// Returns indices of the cameras which missed the frame.
+    pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.frames
+            .iter()
+            .enumerate()
+            .filter(|(_, frame)| frame.is_none())
+            .map(|(idx, _)| idx)
+    }
+
+    /// Returns `true` if no camera missed the frame.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.frames.iter().all(Option::is_some)
+    }
+
+    /// Returns the host time of the earliest frame in the set.
+    ///
+    /// With [`MatchMode::BlockId`], this is the time when the earliest frame arrived at the host.
+    #[must_use]
+    pub fn timestamp(&self) -> Instant {
+        self.timestamp
+    }
+}
+
+/// Operates multiple cameras together.
+///
+/// See the [module level documentation](self) for an example.
+pub struct CameraGroup<Ctrl, Strm, Ctxt = DefaultGenApiCtxt> {
+    cameras: Vec<Camera<Ctrl, Strm, Ctxt>>,
+    config: GroupConfig,
+    clocks: Vec<ClockSync>,
+    last_sync: Option<Instant>,
+    receivers: Vec<PayloadReceiver>,
+    /// Frames received but not yet yielded, per camera.
+    pending: Vec<VecDeque<PendingFrame>>,
+    missed: Vec<u64>,
+    /// Origin of host time keys of pending frames.
+    origin: Instant,
+}
+
+struct PendingFrame {
+    /// Position of the frame used for matching, ns from the origin of the group or block id.
+    key: i128,
+    /// Host time of the frame.
+    host_time: Instant,
+    arrived: Instant,
+    payload: Payload,
+}
+
+impl<Ctrl, Strm, Ctxt> CameraGroup<Ctrl, Strm, Ctxt> {
+    /// Creates a group of the cameras.
+    pub fn new(cameras: Vec<Camera<Ctrl, Strm, Ctxt>>, config: GroupConfig) -> Self {
+        let len = cameras.len();
+        Self {
+            cameras,
+            config,
+            clocks: vec![ClockSync::new(); len],
+            last_sync: None,
+            receivers: vec![],
+            pending: (0..len).map(|_| VecDeque::new()).collect(),
+            missed: vec![0; len],
+            origin: Instant::now(),
+        }
+    }
+
+    /// Returns the cameras in the group.
+    pub fn cameras(&self) -> &[Camera<Ctrl, Strm, Ctxt>] {
+        &self.cameras
+    }
+
+    /// Returns the cameras in the group.
+    pub fn cameras_mut(&mut self) -> &mut [Camera<Ctrl, Strm, Ctxt>] {
+        &mut self.cameras
+    }
+
+    /// Consumes the group and returns the cameras.
+    pub fn into_cameras(self) -> Vec<Camera<Ctrl, Strm, Ctxt>> {
+        self.cameras
+    }
+
+    /// Returns the number of frames each camera missed since streaming started.
+    pub fn missed_frames(&self) -> &[u64] {
+        &self.missed
+    }
+
+    /// Returns the clock estimators of the cameras, which are updated while streaming with
+    /// [`MatchMode::Timestamp`].
+    pub fn clocks(&self) -> &[ClockSync] {
+        &self.clocks
+    }
+
+    /// Returns `true` if the group is streaming.
+    pub fn is_streaming(&self) -> bool {
+        !self.receivers.is_empty()
+    }
+}
+
+impl<Ctrl, Strm, Ctxt> CameraGroup<Ctrl, Strm, Ctxt>
+where
+    Ctrl: DeviceControl + TimestampLatch,
+    Strm: PayloadStream,
+    Ctxt: GenApiCtxt,
+{
+    /// Opens all cameras.
+    ///
+    /// If any camera fails to open, cameras already opened are closed and the error is returned.
+    pub fn open(&mut self) -> CameleonResult<()> {
+        for idx in 0..self.cameras.len() {
+            if let Err(err) = self.cameras[idx].open() {
+                for camera in &mut self.cameras[..idx] {
+                    camera.close().ok();
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Closes all cameras, and returns the first error if any camera fails to close.
+    pub fn close(&mut self) -> CameleonResult<()> {
+        let stop_res = self.stop_streaming();
+        let mut res = Ok(());
+        for camera in &mut self.cameras {
+            let close_res = camera.close();
+            if res.is_ok() {
+                res = close_res;
+            }
+        }
+        stop_res.and(res)
+    }
+
+    /// Loads `GenApi` context of all cameras.
+    pub fn load_context(&mut self) -> CameleonResult<()>
+    where
+        Ctxt: FromXml,
+    {
+        for camera in &mut self.cameras {
+            camera.load_context()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the same feature values to all cameras.
+    ///
+    /// Writes to each camera are applied by [`ParamsCtxt::write_transaction`], so each camera
+    /// keeps its previous values if any of the writes to the camera fails. Cameras before the
+    /// failed camera keep the written values.
+    ///
+    /// [`ParamsCtxt::write_transaction`]: crate::genapi::ParamsCtxt::write_transaction
+    pub fn set_features(&mut self, values: &[(&str, FeatureValue)]) -> CameleonResult<()> {
+        for camera in &mut self.cameras {
+            let mut ctxt = camera.params_ctxt()?;
+            let mut transaction = WriteTransaction::new();
+            for (name, value) in values {
+                let node = ctxt.node(name).ok_or_else(|| {
+                    GenApiError::InvalidNode(format!("`{}` doesn't exist", name).into())
+                })?;
+                transaction.set(node, value.clone());
+            }
+            ctxt.write_transaction(&transaction)?;
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with each camera, e.g. to apply configuration which differs between cameras.
+    pub fn for_each<F>(&mut self, mut f: F) -> CameleonResult<()>
+    where
+        F: FnMut(usize, &mut Camera<Ctrl, Strm, Ctxt>) -> CameleonResult<()>,
+    {
+        for (idx, camera) in self.cameras.iter_mut().enumerate() {
+            f(idx, camera)?;
+        }
+        Ok(())
+    }
+
+    /// Estimates clock offsets of the cameras, then starts streaming on all cameras.
+    ///
+    /// If any camera fails to start, streaming of cameras already started is stopped and the
+    /// error is returned.
+    ///
+    /// # Arguments
+    /// * `cap` - A capacity of the payload receiver of each camera.
+    ///
+    /// # Panics
+    /// If `cap` is zero, this method will panic.
+    pub fn start_streaming(&mut self, cap: usize) -> CameleonResult<()> {
+        if self.is_streaming() {
+            return Err(StreamError::InStreaming.into());
+        }
+
+        if matches!(self.config.match_mode, MatchMode::Timestamp { .. }) {
+            self.clocks.clear();
+            for camera in &mut self.cameras {
+                let mut clock = ClockSync::new();
+                clock.set_nominal_period(camera.ctrl.timestamp_increment()?);
+                self.clocks.push(clock);
+            }
+            self.sync_clocks()?;
+        }
+
+        let mut receivers = Vec::with_capacity(self.cameras.len());
+        for idx in 0..self.cameras.len() {
+            match self.cameras[idx].start_streaming(cap) {
+                Ok(receiver) => receivers.push(receiver),
+                Err(err) => {
+                    for camera in &mut self.cameras[..idx] {
+                        camera.stop_streaming().ok();
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        self.receivers = receivers;
+        for pending in &mut self.pending {
+            pending.clear();
+        }
+        self.missed = vec![0; self.cameras.len()];
+        self.origin = Instant::now();
+        info!("start streaming of {} cameras", self.cameras.len());
+        Ok(())
+    }
+
+    /// Stops streaming on all cameras, and returns the first error if any camera fails to stop.
+    pub fn stop_streaming(&mut self) -> CameleonResult<()> {
+        self.receivers.clear();
+        for pending in &mut self.pending {
+            pending.clear();
+        }
+
+        let mut res = Ok(());
+        for camera in &mut self.cameras {
+            let stop_res = camera.stop_streaming();
+            if res.is_ok() {
+                res = stop_res;
+            }
+        }
+        res
+    }
+
+    /// Waits for the next set of matched frames up to `timeout`.
+    ///
+    /// A set is yielded once every camera has a frame matching the earliest pending frame, or a
+    /// later frame which shows the camera missed it, or [`GroupConfig::frame_timeout`] elapses
+    /// since the earliest pending frame arrived. Cameras without a matching frame are reported
+    /// by [`FrameSet::missing`].
+    ///
+    /// [`StreamError::Timeout`] is returned if no frame arrives within `timeout`.
+    pub fn next_frame_set(&mut self, timeout: Duration) -> CameleonResult<FrameSet> {
+        if !self.is_streaming() {
+            return Err(StreamError::ReceiveError("the group is not streaming".into()).into());
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.resync_if_needed();
+            self.poll()?;
+
+            let now = Instant::now();
+            if let Some(frame_set) = self.try_match(now, now >= deadline) {
+                return Ok(frame_set);
+            }
+            if now >= deadline {
+                return Err(StreamError::Timeout.into());
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// Takes a clock sample from each camera.
+    ///
+    /// Samples taken in quick succession make the estimated drift unreliable, so only the latch
+    /// with the shortest round trip is added to the clocks.
+    fn sync_clocks(&mut self) -> CameleonResult<()> {
+        for (camera, clock) in self.cameras.iter_mut().zip(&mut self.clocks) {
+            let mut latches = clock.clone();
+            let mut best: Option<ClockSample> = None;
+            for _ in 0..self.config.clock_samples.max(1) {
+                let sample = latches.sync(&mut camera.ctrl)?;
+                match best {
+                    Some(best) if best.round_trip <= sample.round_trip => {}
+                    _ => best = Some(sample),
+                }
+            }
+            if let Some(best) = best {
+                clock.add_sample(best);
+            }
+        }
+        self.last_sync = Some(Instant::now());
+        Ok(())
+    }
+
+    fn resync_if_needed(&mut self) {
+        if !matches!(self.config.match_mode, MatchMode::Timestamp { .. }) {
+            return;
+        }
+        let interval = match self.config.resync_interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        let due = match self.last_sync {
+            Some(last) => last.elapsed() >= interval,
+            None => true,
+        };
+        if due {
+            if let Err(err) = self.sync_clocks() {
+                warn!("failed to re-estimate clock offsets: {}", err);
+            }
+        }
+    }
+
+    /// Moves received payloads into the pending queues.
+    fn poll(&mut self) -> CameleonResult<()> {
+        for (idx, receiver) in self.receivers.iter().enumerate() {
+            loop {
+                let payload = match receiver.try_recv() {
+                    Ok(payload) => payload,
+                    Err(StreamError::ReceiveError(..)) if receiver.is_closed() => {
+                        return Err(StreamError::ReceiveError(
+                            format!("the stream of camera {} is closed", idx).into(),
+                        )
+                        .into());
+                    }
+                    Err(StreamError::ReceiveError(..)) => break,
+                    Err(err @ StreamError::Disconnected) => return Err(err.into()),
+                    Err(err) => {
+                        warn!("camera {} failed to receive a payload: {}", idx, err);
+                        continue;
+                    }
+                };
+
+                let arrived = Instant::now();
+                let (key, host_time) = match self.config.match_mode {
+                    MatchMode::Timestamp { .. } => {
+                        let host_time = self.clocks[idx]
+                            .to_host_time(payload.timestamp())
+                            .map_or(arrived, |ts| ts.instant);
+                        (signed_ns(host_time, self.origin), host_time)
+                    }
+                    MatchMode::BlockId => (i128::from(payload.id()), arrived),
+                };
+                self.pending[idx].push_back(PendingFrame {
+                    key,
+                    host_time,
+                    arrived,
+                    payload,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds a frame set from the pending frames if it's decided.
+    /// If `flush` is `true`, a set is built even if some cameras may still send matching frames.
+    fn try_match(&mut self, now: Instant, flush: bool) -> Option<FrameSet> {
+        let reference = self
+            .pending
+            .iter()
+            .filter_map(VecDeque::front)
+            .min_by_key(|frame| frame.key)?;
+        let (ref_key, ref_arrived) = (reference.key, reference.arrived);
+        let window = match self.config.match_mode {
+            MatchMode::Timestamp { window } => i128::try_from(window.as_nanos()).unwrap_or(0),
+            MatchMode::BlockId => 0,
+        };
+
+        let undecided = self.pending.iter().any(VecDeque::is_empty);
+        if undecided && !flush && now.duration_since(ref_arrived) < self.config.frame_timeout {
+            return None;
+        }
+
+        let mut timestamp = None;
+        let mut frames = Vec::with_capacity(self.pending.len());
+        for (idx, pending) in self.pending.iter_mut().enumerate() {
+            match pending.front() {
+                Some(frame) if frame.key - ref_key <= window => {
+                    let frame = pending.pop_front().unwrap();
+                    timestamp = Some(
+                        timestamp.map_or(frame.host_time, |ts: Instant| ts.min(frame.host_time)),
+                    );
+                    frames.push(Some(frame.payload));
+                }
+                _ => {
+                    self.missed[idx] += 1;
+                    frames.push(None);
+                }
+            }
+        }
+
+        Some(FrameSet {
+            frames,
+            timestamp: timestamp.unwrap_or(ref_arrived),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::{
+        genapi::NoCacheGenApiCtxt,
+        payload::{channel, PayloadSender, PayloadType},
+        test_utils::{params_ctxt, MemoryControl, NullStream},
+        CameleonError, CameraInfo,
+    };
+
+    type TestGroup = CameraGroup<MemoryControl, NullStream, NoCacheGenApiCtxt>;
+
+    const WINDOW: Duration = Duration::from_millis(5);
+
+    /// Builds a streaming group of cameras whose device clocks read `offsets[i]` ticks at the
+    /// origin of the group, and returns it with the senders of the cameras.
+    fn group(match_mode: MatchMode, offsets: &[u64]) -> (TestGroup, Vec<PayloadSender>) {
+        let config = GroupConfig {
+            match_mode,
+            ..GroupConfig::default()
+        };
+        let mut group = TestGroup::new(vec![], config);
+        let origin = Instant::now();
+        group.origin = origin;
+        group.clocks = offsets
+            .iter()
+            .map(|&offset| {
+                let mut clock = ClockSync::new();
+                clock.set_nominal_period(1);
+                clock.add_sample(ClockSample {
+                    device_ticks: offset,
+                    instant: origin,
+                    system_time: SystemTime::now(),
+                    round_trip: Duration::default(),
+                });
+                clock
+            })
+            .collect();
+        group.pending = offsets.iter().map(|_| VecDeque::new()).collect();
+        group.missed = vec![0; offsets.len()];
+
+        let mut senders = vec![];
+        for _ in offsets {
+            let (sender, receiver) = channel(8, 8);
+            senders.push(sender);
+            group.receivers.push(receiver);
+        }
+        (group, senders)
+    }
+
+    fn send(sender: &PayloadSender, id: u64, timestamp_ns: u64) {
+        let payload = Payload {
+            id,
+            payload_type: PayloadType::Image,
+            image_info: None,
+            payload: vec![],
+            valid_payload_size: 0,
+            timestamp: Duration::from_nanos(timestamp_ns),
+            chunk_layout_id: None,
+        };
+        sender.try_send(Ok(payload)).unwrap();
+    }
+
+    fn ids(frame_set: &FrameSet) -> Vec<Option<u64>> {
+        frame_set
+            .frames()
+            .iter()
+            .map(|frame| frame.as_ref().map(Payload::id))
+            .collect()
+    }
+
+    fn timestamp_mode() -> MatchMode {
+        MatchMode::Timestamp { window: WINDOW }
+    }
+
+    #[test]
+    fn test_match_in_tolerance() {
+        let (mut group, senders) = group(timestamp_mode(), &[0, 1_000_000_000]);
+        send(&senders[0], 0, 10_000_000);
+        // Arrives 3ms later than the first camera on the host clock.
+        send(&senders[1], 7, 1_013_000_000);
+        group.poll().unwrap();
+
+        let frame_set = group.try_match(Instant::now(), false).unwrap();
+        assert!(frame_set.is_complete());
+        assert_eq!(ids(&frame_set), vec![Some(0), Some(7)]);
+        assert_eq!(
+            frame_set.timestamp(),
+            group.origin + Duration::from_millis(10)
+        );
+        assert_eq!(group.missed_frames(), &[0, 0]);
+        assert!(group.try_match(Instant::now(), true).is_none());
+    }
+
+    #[test]
+    fn test_match_out_of_tolerance() {
+        let (mut group, senders) = group(timestamp_mode(), &[0, 1_000_000_000]);
+        send(&senders[0], 0, 10_000_000);
+        send(&senders[1], 7, 1_020_000_000);
+        group.poll().unwrap();
+
+        // The frame of the second camera is too late to match the first one.
+        let frame_set = group.try_match(Instant::now(), false).unwrap();
+        assert_eq!(ids(&frame_set), vec![Some(0), None]);
+        assert_eq!(frame_set.missing().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(group.missed_frames(), &[0, 1]);
+
+        // The remaining frame is yielded once the first camera is known to have missed it.
+        assert!(group.try_match(Instant::now(), false).is_none());
+        let frame_set = group.try_match(Instant::now(), true).unwrap();
+        assert_eq!(ids(&frame_set), vec![None, Some(7)]);
+        assert_eq!(group.missed_frames(), &[1, 1]);
+    }
+
+    #[test]
+    fn test_match_missing_stream() {
+        let (mut group, senders) = group(timestamp_mode(), &[0, 0]);
+        send(&senders[0], 0, 10_000_000);
+        group.poll().unwrap();
+
+        // Wait for the other camera until the frame timeout elapses.
+        let arrived = group.pending[0].front().unwrap().arrived;
+        assert!(group.try_match(arrived, false).is_none());
+        let frame_set = group
+            .try_match(arrived + group.config.frame_timeout, false)
+            .unwrap();
+        assert_eq!(ids(&frame_set), vec![Some(0), None]);
+        assert_eq!(
+            frame_set.timestamp(),
+            group.origin + Duration::from_millis(10)
+        );
+        assert_eq!(group.missed_frames(), &[0, 1]);
+    }
+
+    #[test]
+    fn test_match_block_id() {
+        let (mut group, senders) = group(MatchMode::BlockId, &[0, 0]);
+        send(&senders[0], 1, 0);
+        send(&senders[0], 2, 0);
+        send(&senders[1], 2, 0);
+        group.poll().unwrap();
+
+        let frame_set = group.try_match(Instant::now(), false).unwrap();
+        assert_eq!(ids(&frame_set), vec![Some(1), None]);
+        let frame_set = group.try_match(Instant::now(), false).unwrap();
+        assert_eq!(ids(&frame_set), vec![Some(2), Some(2)]);
+    }
+
+    #[test]
+    fn test_poll_closed_stream() {
+        let (mut group, mut senders) = group(timestamp_mode(), &[0, 0]);
+        send(&senders[1], 0, 10_000_000);
+        senders.pop();
+
+        // A closed stream is an error, while the empty stream is not.
+        assert!(matches!(
+            group.poll(),
+            Err(CameleonError::StreamError(StreamError::ReceiveError(_)))
+        ));
+        assert_eq!(group.pending[1].len(), 1);
+        assert!(group.pending[0].is_empty());
+    }
+
+    #[test]
+    fn test_set_missing_feature() {
+        let features = r#"
+        <IntReg Name="Gain">
+            <Address>0x0</Address>
+            <Length>4</Length>
+            <AccessMode>RW</AccessMode>
+            <pPort>Device</pPort>
+            <Sign>Unsigned</Sign>
+            <Endianess>LittleEndian</Endianess>
+        </IntReg>
+        "#;
+        let info = CameraInfo {
+            vendor_name: "Cameleon".into(),
+            model_name: "Test".into(),
+            serial_number: "0".into(),
+        };
+        let ctxt = params_ctxt(features, 0x10).ctxt;
+        let camera = Camera::new(MemoryControl::new(0x10), NullStream, Some(ctxt), info);
+        let mut group = TestGroup::new(vec![camera], GroupConfig::default());
+
+        let res = group.set_features(&[
+            ("Gain", FeatureValue::Integer(3)),
+            ("Missing", FeatureValue::Integer(1)),
+        ]);
+        assert!(matches!(
+            res,
+            Err(CameleonError::GenApiError(GenApiError::InvalidNode(_)))
+        ));
+        assert_eq!(group.cameras()[0].ctrl.memory[0], 0);
+    }
+}
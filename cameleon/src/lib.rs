@@ -144,11 +144,21 @@
 )]
 
 pub mod camera;
+pub mod clock;
 pub mod genapi;
+#[cfg(feature = "gentl")]
+pub mod gentl;
+pub mod group;
 pub mod payload;
-#[cfg(feature = "libusb")]
+pub mod record;
+pub mod recovery;
+pub mod sfnc;
+#[cfg(any(feature = "libusb", feature = "emulator"))]
 pub mod u3v;
 
+#[cfg(test)]
+mod test_utils;
+
 pub use camera::{Camera, CameraInfo, DeviceControl, PayloadStream};
 
 use std::{borrow::Cow, num::TryFromIntError};
@@ -178,6 +188,65 @@ pub enum CameleonError {
     /// An error when `GenApi` node operation failed.
     #[error("`GenApi` error: {0}")]
     GenApiError(#[from] cameleon_genapi::GenApiError),
+
+    /// Recovery is requested while it's disabled.
+    #[error("recovery is disabled")]
+    RecoveryDisabled,
+}
+
+impl CameleonError {
+    /// Returns the status and the request if the device refused the request with a non-success
+    /// status, including the case where the request is sent through a `GenApi` node operation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # use cameleon::u3v;
+    /// use cameleon::ProtocolStatus;
+    ///
+    /// # let mut cameras = u3v::enumerate_cameras().unwrap();
+    /// # if cameras.is_empty() {
+    /// #     return;
+    /// # }
+    /// # let mut camera = cameras.pop().unwrap();
+    /// camera.open().unwrap();
+    /// camera.load_context().unwrap();
+    ///
+    /// let mut params_ctxt = camera.params_ctxt().unwrap();
+    /// let width = params_ctxt.node("Width").unwrap().as_integer(&params_ctxt).unwrap();
+    /// if let Err(err) = width.set_value(&mut params_ctxt, 64) {
+    ///     let err = cameleon::CameleonError::from(err);
+    ///     if let Some((ProtocolStatus::WriteProtect, request)) = err.protocol_status() {
+    ///         println!("{:?} is write protected", request);
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn protocol_status(&self) -> Option<(ProtocolStatus, ControlRequest)> {
+        match self {
+            Self::ControlError(err) => err.protocol_status(),
+            Self::GenApiError(cameleon_genapi::GenApiError::Device(err)) => err
+                .downcast_ref::<ControlError>()
+                .and_then(ControlError::protocol_status),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the error is caused by disconnection of the device, including the case
+    /// where the error occurs in a `GenApi` node operation.
+    ///
+    /// See [`Camera::with_recovery`] to recover the camera from the error.
+    #[must_use]
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            Self::ControlError(ControlError::Disconnected)
+            | Self::StreamError(StreamError::Disconnected) => true,
+            Self::GenApiError(cameleon_genapi::GenApiError::Device(err)) => matches!(
+                err.downcast_ref::<ControlError>(),
+                Some(ControlError::Disconnected)
+            ),
+            _ => false,
+        }
+    }
 }
 
 /// A specialized `Result` type for device control.
@@ -218,6 +287,129 @@ pub enum ControlError {
     /// e.g. try to write too large data that will overrun register.
     #[error("try to write invalid data to the device: {0}")]
     InvalidData(Box<dyn std::error::Error>),
+
+    /// The device returned a non-success status to the request.
+    #[error("the device returned {status:?} status to {request:?}")]
+    ProtocolStatus {
+        /// Status returned from the device.
+        status: ProtocolStatus,
+        /// The request which the device refused.
+        request: ControlRequest,
+    },
+}
+
+impl ControlError {
+    /// Returns the status and the request if the error is [`ControlError::ProtocolStatus`].
+    #[must_use]
+    pub fn protocol_status(&self) -> Option<(ProtocolStatus, ControlRequest)> {
+        match self {
+            Self::ProtocolStatus { status, request } => Some((*status, *request)),
+            _ => None,
+        }
+    }
+}
+
+/// A non-success status of `GenCP` based control protocols returned from the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProtocolStatus {
+    /// Command not implemented in the device.
+    NotImplemented,
+
+    /// Command parameter is invalid.
+    InvalidParameter,
+
+    /// Attempt to access an address that doesn't exist.
+    InvalidAddress,
+
+    /// Attempt to write to a read only address.
+    WriteProtect,
+
+    /// Attempt to access an address with bad alignment.
+    BadAlignment,
+
+    /// Attempt to read unreadable address or write to unwritable address.
+    AccessDenied,
+
+    /// The command receiver is busy.
+    Busy,
+
+    /// Timeout waiting for an acknowledge.
+    Timeout,
+
+    /// Header is inconsistent with data.
+    InvalidHeader,
+
+    /// The receiver configuration does not allow the execution of the sent command.
+    WrongConfig,
+
+    /// Generic error.
+    GenericError,
+
+    /// `USB3 Vision` specific status.
+    U3v(U3vStatus),
+
+    /// Device specific status with its raw status code.
+    DeviceSpecific(u16),
+}
+
+/// A `USB3 Vision` specific status returned from the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum U3vStatus {
+    /// Resend command is not supported by the device.
+    ResendNotSupported,
+
+    /// Stream endpoint is halted when stream flag is set.
+    StreamEndpointHalted,
+
+    /// Command that attempts to set payload size is invalid because of bad alignment.
+    PayloadSizeNotAligned,
+
+    /// Event endpoint is halted when event enable flag is set.
+    EventEndpointHalted,
+
+    /// Command that attempts to enable stream is failed because streaming interface is invalid
+    /// state.
+    InvalidSiState,
+}
+
+/// A request to the device's memory, which is reported with [`ControlError::ProtocolStatus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ControlRequest {
+    /// Read `len` bytes from `address`.
+    ReadMem {
+        /// Address to read from.
+        address: u64,
+        /// Length of the data to read.
+        len: usize,
+    },
+
+    /// Write `len` bytes to `address`.
+    WriteMem {
+        /// Address to write to.
+        address: u64,
+        /// Length of the data to write.
+        len: usize,
+    },
+
+    /// Read multiple regions in a single stacked command.
+    ReadMemStacked {
+        /// Address of the first region.
+        address: u64,
+        /// Number of regions to read.
+        count: usize,
+        /// Total length of the data to read.
+        len: usize,
+    },
+
+    /// Write multiple regions in a single stacked command.
+    WriteMemStacked {
+        /// Address of the first region.
+        address: u64,
+        /// Number of regions to write.
+        count: usize,
+        /// Total length of the data to write.
+        len: usize,
+    },
 }
 
 /// A specialized `Result` type for streaming.
@@ -270,3 +462,65 @@ impl From<TryFromIntError> for ControlError {
         Self::InvalidDevice(format!("internal data has invalid num type: {}", e).into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUEST: ControlRequest = ControlRequest::ReadMem {
+        address: 0x100,
+        len: 4,
+    };
+
+    fn status_error() -> ControlError {
+        ControlError::ProtocolStatus {
+            status: ProtocolStatus::AccessDenied,
+            request: REQUEST,
+        }
+    }
+
+    #[test]
+    fn test_protocol_status() {
+        let expected = Some((ProtocolStatus::AccessDenied, REQUEST));
+        assert_eq!(status_error().protocol_status(), expected);
+        assert_eq!(
+            CameleonError::from(status_error()).protocol_status(),
+            expected
+        );
+        assert_eq!(ControlError::Busy.protocol_status(), None);
+        assert_eq!(
+            CameleonError::from(ControlError::Busy).protocol_status(),
+            None
+        );
+        assert_eq!(
+            CameleonError::from(StreamError::Timeout).protocol_status(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_protocol_status_in_genapi_error() {
+        let device_error = |err: Box<dyn std::error::Error>| {
+            CameleonError::from(cameleon_genapi::GenApiError::Device(err))
+        };
+
+        assert_eq!(
+            device_error(Box::new(status_error())).protocol_status(),
+            Some((ProtocolStatus::AccessDenied, REQUEST))
+        );
+        // Errors other than `ControlError::ProtocolStatus` don't carry a status.
+        assert_eq!(
+            device_error(Box::new(ControlError::Disconnected)).protocol_status(),
+            None
+        );
+        assert_eq!(
+            device_error("not a control error".into()).protocol_status(),
+            None
+        );
+        assert_eq!(
+            CameleonError::from(cameleon_genapi::GenApiError::InvalidNode("Width".into()))
+                .protocol_status(),
+            None
+        );
+    }
+}
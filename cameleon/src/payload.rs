@@ -9,7 +9,7 @@
 
 pub use cameleon_device::PixelFormat;
 
-use std::time;
+use std::{convert::TryInto, time};
 
 use async_std::channel::{Receiver, Sender};
 
@@ -45,6 +45,18 @@ pub struct ImageInfo {
     pub image_size: usize,
 }
 
+/// Location of a chunk in the payload of [`PayloadType::ImageExtendedChunk`] or
+/// [`PayloadType::Chunk`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkEntry {
+    /// ID of the chunk, which corresponds to `ChunkID` of `GenApi` `Port` node.
+    pub id: u32,
+    /// Offset of the chunk data from the beginning of the payload.
+    pub offset: usize,
+    /// Length of the chunk data in bytes, excluding the trailing chunk ID and length fields.
+    pub len: usize,
+}
+
 /// A payload sent from the device.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Payload {
@@ -54,6 +66,7 @@ pub struct Payload {
     pub(crate) payload: Vec<u8>,
     pub(crate) valid_payload_size: usize,
     pub(crate) timestamp: time::Duration,
+    pub(crate) chunk_layout_id: Option<u32>,
 }
 
 impl Payload {
@@ -92,6 +105,24 @@ impl Payload {
         self.timestamp
     }
 
+    /// Returns the chunk layout ID reported by the device if `payload_type` is
+    /// [`PayloadType::ImageExtendedChunk`] or [`PayloadType::Chunk`].
+    ///
+    /// The ID changes only when the layout of the chunks changes.
+    pub fn chunk_layout_id(&self) -> Option<u32> {
+        self.chunk_layout_id
+    }
+
+    /// Returns chunks in the payload in the order of their appearance.
+    ///
+    /// Returns an empty vector if `payload_type` is [`PayloadType::Image`].
+    pub fn chunks(&self) -> StreamResult<Vec<ChunkEntry>> {
+        match self.payload_type {
+            PayloadType::Image => Ok(vec![]),
+            PayloadType::ImageExtendedChunk | PayloadType::Chunk => parse_chunks(self.payload()),
+        }
+    }
+
     /// Returns the payload as `Vec<u8>`.
     pub fn into_vec(mut self) -> Vec<u8> {
         self.payload.resize(self.valid_payload_size, 0);
@@ -99,6 +130,61 @@ impl Payload {
     }
 }
 
+/// Parses chunk data and returns chunks in the order of their appearance.
+///
+/// Each chunk consists of its data followed by a 4 bytes big endian chunk ID and a 4 bytes big
+/// endian data length, so chunk data is decoded from the last byte to the first byte.
+///
+/// # Examples
+///
+/// ```rust
+/// use cameleon::payload::{parse_chunks, ChunkEntry};
+///
+/// let mut data = vec![0xaa; 4];
+/// data.extend_from_slice(&1_u32.to_be_bytes()); // Chunk ID.
+/// data.extend_from_slice(&4_u32.to_be_bytes()); // Data length.
+///
+/// let chunks = parse_chunks(&data).unwrap();
+/// assert_eq!(chunks, vec![ChunkEntry { id: 1, offset: 0, len: 4 }]);
+/// ```
+pub fn parse_chunks(data: &[u8]) -> StreamResult<Vec<ChunkEntry>> {
+    const CHUNK_ID_LEN: usize = 4;
+    const CHUNK_SIZE_LEN: usize = 4;
+
+    let read_u32 = |offset: usize| {
+        // Ok to unwrap because the slice has exactly 4 bytes.
+        u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+    };
+
+    let mut chunks = vec![];
+    let mut current_offset = data.len();
+    while current_offset > 0 {
+        current_offset = current_offset
+            .checked_sub(CHUNK_SIZE_LEN + CHUNK_ID_LEN)
+            .ok_or_else(|| {
+                StreamError::InvalidPayload(
+                    "failed to parse chunk data: chunk id or size field missing".into(),
+                )
+            })?;
+        let len = read_u32(current_offset + CHUNK_ID_LEN) as usize;
+        let id = read_u32(current_offset);
+
+        current_offset = current_offset.checked_sub(len).ok_or_else(|| {
+            StreamError::InvalidPayload(
+                "failed to parse chunk data: chunk data size is smaller than specified size".into(),
+            )
+        })?;
+        chunks.push(ChunkEntry {
+            id,
+            offset: current_offset,
+            len,
+        });
+    }
+
+    chunks.reverse();
+    Ok(chunks)
+}
+
 /// An Receiver of the `Payload` which is sent from a device.
 #[derive(Debug, Clone)]
 pub struct PayloadReceiver {
@@ -122,6 +208,13 @@ impl PayloadReceiver {
         self.rx.try_recv()?
     }
 
+    /// Returns `true` if the sender side is dropped, e.g. the streaming loop is stopped.
+    ///
+    /// Payloads already sent can still be received after the channel is closed.
+    pub fn is_closed(&self) -> bool {
+        self.rx.is_closed()
+    }
+
     /// Sends back [`Payload`] to the device to reuse already allocated `payload`.
     ///
     /// Sending back `payload` may improve performance of streaming, but not required to call this
@@ -199,3 +292,101 @@ impl<T> From<async_std::channel::TrySendError<T>> for StreamError {
         StreamError::ReceiveError(err.to_string().into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Appends a chunk, i.e. its data followed by the chunk ID and the data length.
+    fn push_chunk(buf: &mut Vec<u8>, id: u32, data: &[u8]) {
+        buf.extend_from_slice(data);
+        buf.extend_from_slice(&id.to_be_bytes());
+        buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    }
+
+    fn chunk_payload(payload: Vec<u8>) -> Payload {
+        Payload {
+            id: 0,
+            payload_type: PayloadType::Chunk,
+            image_info: None,
+            valid_payload_size: payload.len(),
+            payload,
+            timestamp: time::Duration::default(),
+            chunk_layout_id: Some(1),
+        }
+    }
+
+    #[test]
+    fn test_parse_multiple_chunks() {
+        let mut data = vec![];
+        push_chunk(&mut data, 0x10, &[1, 2, 3, 4]);
+        push_chunk(&mut data, 0x20, &[]);
+        push_chunk(&mut data, 0x30, &[5, 6]);
+
+        let chunks = parse_chunks(&data).unwrap();
+        assert_eq!(
+            chunks,
+            vec![
+                ChunkEntry {
+                    id: 0x10,
+                    offset: 0,
+                    len: 4
+                },
+                ChunkEntry {
+                    id: 0x20,
+                    offset: 12,
+                    len: 0
+                },
+                ChunkEntry {
+                    id: 0x30,
+                    offset: 20,
+                    len: 2
+                },
+            ]
+        );
+        assert_eq!(&data[20..22], &[5, 6]);
+
+        assert_eq!(chunk_payload(data).chunks().unwrap(), chunks);
+        assert!(parse_chunks(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_truncated_trailer() {
+        let mut data = vec![];
+        push_chunk(&mut data, 0x10, &[1, 2, 3, 4]);
+
+        // The leading bytes are too short to hold the trailer of another chunk.
+        let mut truncated = vec![0; 7];
+        truncated.extend_from_slice(&data);
+        assert!(matches!(
+            parse_chunks(&truncated),
+            Err(StreamError::InvalidPayload(_))
+        ));
+        assert!(matches!(
+            parse_chunks(&data[data.len() - 7..]),
+            Err(StreamError::InvalidPayload(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_overrunning_length() {
+        let mut data = vec![];
+        push_chunk(&mut data, 0x10, &[1, 2, 3, 4]);
+        // Claim the chunk is longer than the whole buffer.
+        let len = data.len();
+        data[len - 4..].copy_from_slice(&100_u32.to_be_bytes());
+
+        assert!(matches!(
+            parse_chunks(&data),
+            Err(StreamError::InvalidPayload(_))
+        ));
+        assert!(chunk_payload(data).chunks().is_err());
+    }
+
+    #[test]
+    fn test_image_has_no_chunks() {
+        let mut payload = chunk_payload(vec![0; 4]);
+        payload.payload_type = PayloadType::Image;
+        assert!(payload.chunks().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,448 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Serializer and deserializer of the recording file.
+//!
+//! All integers are little endian. The file consists of a header followed by payload records.
+//!
+//! Header:
+//! | Field        | Size          | Description                                   |
+//! |--------------|---------------|-----------------------------------------------|
+//! | magic        | 8             | `b"CMLNREC\0"`                                |
+//! | version      | 4             | Version of the format                         |
+//! | camera info  | variable      | Vendor name, model name and serial number     |
+//! | xml          | variable      | `GenICam` XML of the camera                   |
+//! | memory       | variable      | Regions of the device memory                  |
+//!
+//! Payload record:
+//! | Field        | Size          | Description                                   |
+//! |--------------|---------------|-----------------------------------------------|
+//! | elapsed      | 8             | Elapsed time since the first record in ns     |
+//! | id           | 8             | Block id of the payload                       |
+//! | timestamp    | 8             | Device timestamp of the payload in ns         |
+//! | payload type | 1             | Type of the payload                           |
+//! | image info   | 1 + 44 or 1   | [`ImageInfo`] if the first byte is 1          |
+//! | chunk layout | 1 + 4 or 1    | Chunk layout ID if the first byte is 1        |
+//! | payload      | variable      | Valid bytes of the payload                    |
+//!
+//! Variable length fields are prefixed with their length in 8 bytes.
+
+use std::{
+    collections::BTreeMap,
+    convert::{TryFrom, TryInto},
+    io::{self, Read, Write},
+    time::Duration,
+};
+
+use crate::{
+    payload::{ImageInfo, Payload, PayloadType, PixelFormat},
+    CameraInfo,
+};
+
+use super::{RecordError, RecordResult};
+
+const MAGIC: &[u8; 8] = b"CMLNREC\0";
+const VERSION: u32 = 1;
+
+/// Header of the recording file.
+pub(super) struct Header {
+    pub(super) info: CameraInfo,
+    pub(super) xml: String,
+    pub(super) memory: BTreeMap<u64, u8>,
+}
+
+impl Header {
+    pub(super) fn write_to(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        write_u32(w, VERSION)?;
+
+        write_bytes(w, self.info.vendor_name.as_bytes())?;
+        write_bytes(w, self.info.model_name.as_bytes())?;
+        write_bytes(w, self.info.serial_number.as_bytes())?;
+        write_bytes(w, self.xml.as_bytes())?;
+
+        let regions = regions(&self.memory);
+        write_u64(w, regions.len() as u64)?;
+        for (address, data) in regions {
+            write_u64(w, address)?;
+            write_bytes(w, &data)?;
+        }
+
+        Ok(())
+    }
+
+    pub(super) fn read_from(r: &mut impl Read) -> RecordResult<Self> {
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(RecordError::InvalidFormat("not a recording file".into()));
+        }
+        let version = read_u32(r)?;
+        if version != VERSION {
+            return Err(RecordError::InvalidFormat(
+                format!("unsupported version: {}", version).into(),
+            ));
+        }
+
+        let info = CameraInfo {
+            vendor_name: read_string(r)?,
+            model_name: read_string(r)?,
+            serial_number: read_string(r)?,
+        };
+        let xml = read_string(r)?;
+
+        let mut memory = BTreeMap::new();
+        let region_num = read_u64(r)?;
+        for _ in 0..region_num {
+            let address = read_u64(r)?;
+            let data = read_bytes(r)?;
+            memory.extend((address..).zip(data));
+        }
+
+        Ok(Self { info, xml, memory })
+    }
+}
+
+pub(super) fn write_payload(
+    w: &mut impl Write,
+    elapsed: Duration,
+    payload: &Payload,
+) -> io::Result<()> {
+    write_u64(w, elapsed.as_nanos() as u64)?;
+    write_u64(w, payload.id)?;
+    write_u64(w, payload.timestamp.as_nanos() as u64)?;
+    let payload_type = match payload.payload_type {
+        PayloadType::Image => 0,
+        PayloadType::ImageExtendedChunk => 1,
+        PayloadType::Chunk => 2,
+    };
+    w.write_all(&[payload_type])?;
+
+    match &payload.image_info {
+        Some(info) => {
+            w.write_all(&[1])?;
+            write_u64(w, info.width as u64)?;
+            write_u64(w, info.height as u64)?;
+            write_u64(w, info.x_offset as u64)?;
+            write_u64(w, info.y_offset as u64)?;
+            write_u32(w, info.pixel_format.into())?;
+            write_u64(w, info.image_size as u64)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+
+    match payload.chunk_layout_id {
+        Some(id) => {
+            w.write_all(&[1])?;
+            write_u32(w, id)?;
+        }
+        None => w.write_all(&[0])?,
+    }
+
+    write_bytes(w, payload.payload())
+}
+
+/// Reads a payload record, returns `None` if the reader reaches the end of the file at a record
+/// boundary.
+///
+/// Returns [`RecordError::InvalidFormat`] if the file ends in the middle of a record.
+///
+/// `buf` is reused as the buffer of the payload.
+pub(super) fn read_payload(
+    r: &mut impl Read,
+    buf: Vec<u8>,
+) -> RecordResult<Option<(Duration, Payload)>> {
+    let mut elapsed = [0; 8];
+    let mut filled = 0;
+    while filled < elapsed.len() {
+        match r.read(&mut elapsed[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    match filled {
+        0 => return Ok(None),
+        8 => {}
+        _ => return Err(truncated()),
+    }
+    let elapsed = Duration::from_nanos(u64::from_le_bytes(elapsed));
+
+    match read_payload_body(r, buf) {
+        Ok(payload) => Ok(Some((elapsed, payload))),
+        Err(RecordError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => Err(truncated()),
+        Err(e) => Err(e),
+    }
+}
+
+fn read_payload_body(r: &mut impl Read, mut buf: Vec<u8>) -> RecordResult<Payload> {
+    let id = read_u64(r)?;
+    let timestamp = Duration::from_nanos(read_u64(r)?);
+    let payload_type = match read_u8(r)? {
+        0 => PayloadType::Image,
+        1 => PayloadType::ImageExtendedChunk,
+        2 => PayloadType::Chunk,
+        otherwise => {
+            return Err(RecordError::InvalidFormat(
+                format!("invalid payload type: {}", otherwise).into(),
+            ))
+        }
+    };
+
+    let image_info = if read_u8(r)? == 0 {
+        None
+    } else {
+        let width = read_usize(r)?;
+        let height = read_usize(r)?;
+        let x_offset = read_usize(r)?;
+        let y_offset = read_usize(r)?;
+        let pixel_format = PixelFormat::try_from(read_u32(r)?)
+            .map_err(|e| RecordError::InvalidFormat(e.into()))?;
+        let image_size = read_usize(r)?;
+        Some(ImageInfo {
+            width,
+            height,
+            x_offset,
+            y_offset,
+            pixel_format,
+            image_size,
+        })
+    };
+
+    let chunk_layout_id = if read_u8(r)? == 0 {
+        None
+    } else {
+        Some(read_u32(r)?)
+    };
+
+    let len = read_u64(r)?;
+    read_into(r, len, &mut buf)?;
+    let valid_payload_size = buf.len();
+
+    Ok(Payload {
+        id,
+        payload_type,
+        image_info,
+        payload: buf,
+        valid_payload_size,
+        timestamp,
+        chunk_layout_id,
+    })
+}
+
+fn truncated() -> RecordError {
+    RecordError::InvalidFormat("the file ends in the middle of a payload record".into())
+}
+
+/// Splits the memory into contiguous regions.
+fn regions(memory: &BTreeMap<u64, u8>) -> Vec<(u64, Vec<u8>)> {
+    let mut regions: Vec<(u64, Vec<u8>)> = vec![];
+    for (&address, &byte) in memory {
+        match regions.last_mut() {
+            Some((start, data)) if *start + data.len() as u64 == address => data.push(byte),
+            _ => regions.push((address, vec![byte])),
+        }
+    }
+    regions
+}
+
+fn write_u32(w: &mut impl Write, value: u32) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_u64(w: &mut impl Write, value: u64) -> io::Result<()> {
+    w.write_all(&value.to_le_bytes())
+}
+
+fn write_bytes(w: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    write_u64(w, data.len() as u64)?;
+    w.write_all(data)
+}
+
+fn read_u8(r: &mut impl Read) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_usize(r: &mut impl Read) -> RecordResult<usize> {
+    read_u64(r)?
+        .try_into()
+        .map_err(|_| RecordError::InvalidFormat("too large value for usize".into()))
+}
+
+fn read_bytes(r: &mut impl Read) -> RecordResult<Vec<u8>> {
+    let len = read_u64(r)?;
+    let mut buf = vec![];
+    read_into(r, len, &mut buf)?;
+    Ok(buf)
+}
+
+/// Reads exactly `len` bytes into `buf`.
+///
+/// Unlike `read_exact`, this doesn't allocate `len` bytes in advance, so a corrupted length
+/// doesn't cause a huge allocation.
+fn read_into(r: &mut impl Read, len: u64, buf: &mut Vec<u8>) -> io::Result<()> {
+    buf.clear();
+    r.take(len).read_to_end(buf)?;
+    if buf.len() as u64 == len {
+        Ok(())
+    } else {
+        Err(io::ErrorKind::UnexpectedEof.into())
+    }
+}
+
+fn read_string(r: &mut impl Read) -> RecordResult<String> {
+    String::from_utf8(read_bytes(r)?)
+        .map_err(|_| RecordError::InvalidFormat("string is not valid UTF-8".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> Header {
+        Header {
+            info: CameraInfo {
+                vendor_name: "Cameleon".into(),
+                model_name: "Model".into(),
+                serial_number: "CAM1984".into(),
+            },
+            xml: "<RegisterDescription/>".into(),
+            memory: (0x10..0x14)
+                .chain(0x100..0x102)
+                .map(|a| (a, a as u8))
+                .collect(),
+        }
+    }
+
+    fn payloads() -> Vec<(Duration, Payload)> {
+        let image = Payload {
+            id: 1,
+            payload_type: PayloadType::Image,
+            image_info: Some(ImageInfo {
+                width: 2,
+                height: 2,
+                x_offset: 0,
+                y_offset: 1,
+                pixel_format: PixelFormat::Mono8,
+                image_size: 4,
+            }),
+            payload: vec![1, 2, 3, 4],
+            valid_payload_size: 4,
+            timestamp: Duration::from_nanos(1000),
+            chunk_layout_id: None,
+        };
+        let chunk = Payload {
+            id: 2,
+            payload_type: PayloadType::Chunk,
+            image_info: None,
+            payload: vec![5, 6],
+            valid_payload_size: 2,
+            timestamp: Duration::from_nanos(2000),
+            chunk_layout_id: Some(0x1234_5678),
+        };
+        let extended_chunk = Payload {
+            id: 3,
+            payload_type: PayloadType::ImageExtendedChunk,
+            image_info: Some(ImageInfo {
+                width: 1,
+                height: 1,
+                x_offset: 0,
+                y_offset: 0,
+                pixel_format: PixelFormat::Mono8,
+                image_size: 1,
+            }),
+            payload: vec![7, 8, 9],
+            valid_payload_size: 3,
+            timestamp: Duration::from_nanos(3000),
+            chunk_layout_id: Some(0),
+        };
+        vec![
+            (Duration::from_millis(0), image),
+            (Duration::from_millis(33), chunk),
+            (Duration::from_millis(66), extended_chunk),
+        ]
+    }
+
+    fn write_file() -> Vec<u8> {
+        let mut file = vec![];
+        header().write_to(&mut file).unwrap();
+        for (elapsed, payload) in payloads() {
+            write_payload(&mut file, elapsed, &payload).unwrap();
+        }
+        file
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let file = write_file();
+        let mut r = file.as_slice();
+
+        let header = Header::read_from(&mut r).unwrap();
+        assert_eq!(header.info, self::header().info);
+        assert_eq!(header.xml, self::header().xml);
+        assert_eq!(header.memory, self::header().memory);
+
+        for expected in payloads() {
+            let read = read_payload(&mut r, vec![]).unwrap().unwrap();
+            assert_eq!(read, expected);
+        }
+        assert!(read_payload(&mut r, vec![]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut file = write_file();
+        file[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(
+            Header::read_from(&mut file.as_slice()),
+            Err(RecordError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_truncated_file() {
+        let file = write_file();
+        let mut header_len = vec![];
+        header().write_to(&mut header_len).unwrap();
+        let header_len = header_len.len();
+        let first_record_len = {
+            let mut record = vec![];
+            let (elapsed, payload) = &payloads()[0];
+            write_payload(&mut record, *elapsed, payload).unwrap();
+            record.len()
+        };
+
+        // Truncated in the header.
+        assert!(Header::read_from(&mut &file[..header_len - 1]).is_err());
+
+        // Truncated at a record boundary.
+        let mut r = &file[header_len..header_len + first_record_len];
+        assert!(read_payload(&mut r, vec![]).unwrap().is_some());
+        assert!(read_payload(&mut r, vec![]).unwrap().is_none());
+
+        // Truncated in `elapsed`, in the fixed size fields and in the payload data.
+        for &cut in &[3, 20, first_record_len - 1] {
+            let mut r = &file[header_len..header_len + cut];
+            assert!(matches!(
+                read_payload(&mut r, vec![]),
+                Err(RecordError::InvalidFormat(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,428 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides recording of payload streams and their replay.
+//!
+//! [`Recorder`] writes [`Payload`]s to a file together with `GenICam` XML of the camera and a
+//! snapshot of the device memory backing its features.
+//! The file is replayed by [`open_replay`], which returns a [`Camera`] operated in the same way as
+//! a live camera. This enables you to develop and test algorithms offline.
+//!
+//! # Examples
+//! ```rust
+//! # use cameleon::u3v;
+//! use std::time::Duration;
+//!
+//! use cameleon::record::{open_replay, Recorder, ReplaySpeed};
+//!
+//! # let mut cameras = u3v::enumerate_cameras().unwrap();
+//! # if cameras.is_empty() {
+//! #     return;
+//! # }
+//! # let mut camera = cameras.pop().unwrap();
+//! camera.open().unwrap();
+//! camera.load_context().unwrap();
+//!
+//! // Record 10 payloads.
+//! let mut recorder = Recorder::create("stream.rec", &mut camera).unwrap();
+//! let payload_rx = camera.start_streaming(3).unwrap();
+//! for _ in 0..10 {
+//!     let payload = async_std::task::block_on(payload_rx.recv()).unwrap();
+//!     recorder.record(&payload).unwrap();
+//!     payload_rx.send_back(payload);
+//! }
+//! recorder.finish().unwrap();
+//! camera.close().unwrap();
+//!
+//! // Replay the recorded payloads twice as fast as they were recorded.
+//! let mut replay = open_replay("stream.rec", ReplaySpeed::Scaled(2.0)).unwrap();
+//! replay.open().unwrap();
+//! replay.load_context().unwrap();
+//! let payloads = replay.grab_n(10, Duration::from_secs(1)).unwrap();
+//! ```
+
+mod format;
+mod replay;
+
+pub use replay::{open_replay, ReplayControl, ReplaySpeed, ReplayStream};
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Instant,
+};
+
+use crate::{
+    genapi::{FromXml, NoCacheGenApiCtxt, Node, ParamsCtxt},
+    payload::Payload,
+    CameleonError, Camera, ControlResult, DeviceControl,
+};
+
+/// A specialized `Result` type for recording and replay.
+pub type RecordResult<T> = std::result::Result<T, RecordError>;
+
+/// An error type related to recording and replay.
+#[derive(Debug, thiserror::Error)]
+pub enum RecordError {
+    /// IO error of the recording file.
+    #[error("input/output error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The recording file is broken or not a recording file.
+    #[error("invalid recording file: {0}")]
+    InvalidFormat(Cow<'static, str>),
+
+    /// An error from the camera.
+    #[error("camera error: {0}")]
+    CameraError(#[from] CameleonError),
+
+    /// The factor of [`ReplaySpeed::Scaled`] isn't a positive finite number.
+    #[error("invalid replay speed: {0}")]
+    InvalidSpeed(f64),
+}
+
+/// Writes [`Payload`]s to a recording file.
+///
+/// See [the module level documentation](self) for an example.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Option<Instant>,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Creates a recording file at `path` and writes `GenICam` XML and the feature snapshot of the
+    /// camera to it.
+    ///
+    /// The camera must be opened.
+    pub fn create<Ctrl, Strm, Ctxt>(
+        path: impl AsRef<Path>,
+        camera: &mut Camera<Ctrl, Strm, Ctxt>,
+    ) -> RecordResult<Self>
+    where
+        Ctrl: DeviceControl,
+    {
+        let file = File::create(path)?;
+        Self::new(BufWriter::new(file), camera)
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Writes `GenICam` XML and the feature snapshot of the camera to `writer`, and returns a
+    /// recorder that writes [`Payload`]s to `writer`.
+    ///
+    /// The feature snapshot consists of the device memory read while reading all features in the
+    /// camera. The camera must be opened.
+    pub fn new<Ctrl, Strm, Ctxt>(
+        mut writer: W,
+        camera: &mut Camera<Ctrl, Strm, Ctxt>,
+    ) -> RecordResult<Self>
+    where
+        Ctrl: DeviceControl,
+    {
+        let info = camera.info().clone();
+        let xml = camera.ctrl.genapi().map_err(CameleonError::from)?;
+        let memory = snapshot(&mut camera.ctrl, &xml)?;
+
+        format::Header { info, xml, memory }.write_to(&mut writer)?;
+        Ok(Self {
+            writer,
+            start: None,
+        })
+    }
+
+    /// Writes the payload with the time elapsed since the first recorded payload.
+    pub fn record(&mut self, payload: &Payload) -> RecordResult<()> {
+        let start = *self.start.get_or_insert_with(Instant::now);
+        format::write_payload(&mut self.writer, start.elapsed(), payload)?;
+        Ok(())
+    }
+
+    /// Flushes the recording and returns the inner writer.
+    pub fn finish(mut self) -> RecordResult<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads all features of the device and returns the device memory read meanwhile.
+fn snapshot<Ctrl>(ctrl: &mut Ctrl, xml: &str) -> RecordResult<BTreeMap<u64, u8>>
+where
+    Ctrl: DeviceControl,
+{
+    // Use a context without cache so that all reads reach the device.
+    let ctxt = NoCacheGenApiCtxt::from_xml(&xml).map_err(CameleonError::from)?;
+    let mut ctxt = ParamsCtxt {
+        ctrl: SnapshotControl {
+            inner: ctrl,
+            memory: BTreeMap::new(),
+        },
+        ctxt,
+    };
+
+    if let Some(root) = ctxt.node("Root") {
+        let mut visited = HashSet::new();
+        read_features(root, &mut ctxt, &mut visited);
+    }
+
+    Ok(ctxt.ctrl.memory)
+}
+
+/// Reads all features under the node.
+///
+/// Errors are ignored because some features may not be readable depending on the device state.
+fn read_features<Ctrl>(
+    node: Node,
+    ctxt: &mut ParamsCtxt<SnapshotControl<Ctrl>, NoCacheGenApiCtxt>,
+    visited: &mut HashSet<Node>,
+) where
+    Ctrl: DeviceControl,
+{
+    if !visited.insert(node) {
+        return;
+    }
+
+    if let Some(category) = node.as_category(ctxt) {
+        for child in category.nodes(ctxt) {
+            read_features(child, ctxt, visited);
+        }
+    } else if let Some(node) = node.as_integer(ctxt) {
+        if let Ok(true) = node.is_readable(ctxt) {
+            node.value(ctxt).ok();
+        }
+    } else if let Some(node) = node.as_float(ctxt) {
+        if let Ok(true) = node.is_readable(ctxt) {
+            node.value(ctxt).ok();
+        }
+    } else if let Some(node) = node.as_string(ctxt) {
+        if let Ok(true) = node.is_readable(ctxt) {
+            node.value(ctxt).ok();
+        }
+    } else if let Some(node) = node.as_boolean(ctxt) {
+        if let Ok(true) = node.is_readable(ctxt) {
+            node.value(ctxt).ok();
+        }
+    } else if let Some(node) = node.as_enumeration(ctxt) {
+        if let Ok(true) = node.is_readable(ctxt) {
+            node.current_entry(ctxt).ok();
+        }
+    }
+}
+
+/// A [`DeviceControl`] that records memory read from the inner control.
+struct SnapshotControl<Ctrl> {
+    inner: Ctrl,
+    memory: BTreeMap<u64, u8>,
+}
+
+impl<Ctrl> DeviceControl for SnapshotControl<Ctrl>
+where
+    Ctrl: DeviceControl,
+{
+    fn open(&mut self) -> ControlResult<()> {
+        self.inner.open()
+    }
+
+    fn close(&mut self) -> ControlResult<()> {
+        self.inner.close()
+    }
+
+    fn is_opened(&self) -> bool {
+        self.inner.is_opened()
+    }
+
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+        self.inner.read(address, buf)?;
+        self.memory.extend((address..).zip(buf.iter().copied()));
+        Ok(())
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+        self.inner.write(address, data)
+    }
+
+    fn read_batch(&mut self, entries: &mut [(u64, &mut [u8])]) -> ControlResult<()> {
+        self.inner.read_batch(entries)?;
+        for (address, buf) in entries {
+            self.memory.extend((*address..).zip(buf.iter().copied()));
+        }
+        Ok(())
+    }
+
+    fn write_batch(&mut self, entries: &[(u64, &[u8])]) -> ControlResult<()> {
+        self.inner.write_batch(entries)
+    }
+
+    fn genapi(&mut self) -> ControlResult<String> {
+        self.inner.genapi()
+    }
+
+    fn enable_streaming(&mut self) -> ControlResult<()> {
+        self.inner.enable_streaming()
+    }
+
+    fn disable_streaming(&mut self) -> ControlResult<()> {
+        self.inner.disable_streaming()
+    }
+}
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use async_std::{future, task};
+    use cameleon_device::emulator::EmulatorBuilder;
+
+    use crate::{
+        genapi::DefaultGenApiCtxt,
+        payload::PayloadReceiver,
+        u3v::{open_emulated_by, CameraFilter},
+    };
+
+    use super::*;
+
+    const NUM_PAYLOADS: usize = 4;
+    const INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Records payloads of an emulated camera at `INTERVAL`, and returns the path of the
+    /// recording file with the recorded payloads.
+    fn record(serial_number: &str) -> (PathBuf, Vec<Payload>) {
+        EmulatorBuilder::new()
+            .serial_number(serial_number)
+            .unwrap()
+            .build();
+        let filter = CameraFilter::new().serial_number(serial_number);
+        let mut camera = open_emulated_by(&filter).unwrap().unwrap();
+        camera.load_context().unwrap();
+
+        let mut recorder = Recorder::new(vec![], &mut camera).unwrap();
+        let payload_rx = camera.start_streaming(NUM_PAYLOADS).unwrap();
+        let mut payloads = vec![];
+        for _ in 0..NUM_PAYLOADS {
+            let payload = recv(&payload_rx).unwrap();
+            recorder.record(&payload).unwrap();
+            payloads.push(payload);
+            std::thread::sleep(INTERVAL);
+        }
+        camera.close().unwrap();
+
+        let path = std::env::temp_dir().join(format!(
+            "cameleon-{}-{}.rec",
+            serial_number,
+            std::process::id()
+        ));
+        std::fs::write(&path, recorder.finish().unwrap()).unwrap();
+        (path, payloads)
+    }
+
+    fn recv(payload_rx: &PayloadReceiver) -> Option<Payload> {
+        task::block_on(future::timeout(Duration::from_secs(1), payload_rx.recv()))
+            .ok()
+            .map(Result::unwrap)
+    }
+
+    fn open(path: &Path, speed: ReplaySpeed) -> Camera<ReplayControl, ReplayStream> {
+        let mut camera = open_replay(path, speed).unwrap();
+        camera.open().unwrap();
+        camera.load_context().unwrap();
+        camera
+    }
+
+    fn set_entry<Ctrl>(ctxt: &mut ParamsCtxt<Ctrl, &mut DefaultGenApiCtxt>, name: &str, entry: &str)
+    where
+        Ctrl: DeviceControl,
+    {
+        let node = ctxt.node(name).unwrap().as_enumeration(ctxt).unwrap();
+        node.set_entry_by_symbolic(ctxt, entry).unwrap();
+    }
+
+    #[test]
+    fn test_replay_order() {
+        let (path, recorded) = record("REPLAY01");
+        let mut camera = open(&path, ReplaySpeed::Unlimited);
+
+        // The recorded payloads are replayed in order, then the stream becomes idle.
+        let payload_rx = camera.start_streaming(NUM_PAYLOADS).unwrap();
+        for expected in &recorded {
+            let payload = recv(&payload_rx).unwrap();
+            assert_eq!(payload.id(), expected.id());
+            assert_eq!(payload.payload(), expected.payload());
+            assert_eq!(payload.image_info(), expected.image_info());
+            payload_rx.send_back(payload);
+        }
+        assert!(task::block_on(future::timeout(
+            Duration::from_millis(100),
+            payload_rx.recv()
+        ))
+        .is_err());
+
+        camera.close().unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_software_trigger() {
+        let (path, recorded) = record("REPLAY02");
+        let mut camera = open(&path, ReplaySpeed::Unlimited);
+        {
+            let mut ctxt = camera.params_ctxt().unwrap();
+            set_entry(&mut ctxt, "TriggerSelector", "FrameStart");
+            set_entry(&mut ctxt, "TriggerSource", "Software");
+            set_entry(&mut ctxt, "TriggerMode", "On");
+        }
+
+        // Triggers are shared by the clones of the control.
+        let mut ctrl = camera.ctrl.clone();
+        let payload_rx = camera.start_streaming(NUM_PAYLOADS).unwrap();
+        let mut ctxt = ParamsCtxt {
+            ctrl: &mut ctrl,
+            ctxt: camera.ctxt.as_mut().unwrap(),
+        };
+        let trigger_software = ctxt
+            .node("TriggerSoftware")
+            .unwrap()
+            .as_command(&ctxt)
+            .unwrap();
+
+        for expected in &recorded[..2] {
+            // No payload is sent without a trigger.
+            assert!(payload_rx.try_recv().is_err());
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(payload_rx.try_recv().is_err());
+
+            trigger_software.execute(&mut ctxt).unwrap();
+            assert_eq!(recv(&payload_rx).unwrap().id(), expected.id());
+        }
+
+        camera.close().unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_speed() {
+        let (path, _) = record("REPLAY03");
+        let recorded_duration = INTERVAL * (NUM_PAYLOADS as u32 - 1);
+
+        let replay_duration = |speed| {
+            let mut camera = open(&path, speed);
+            let payload_rx = camera.start_streaming(NUM_PAYLOADS).unwrap();
+            let start = Instant::now();
+            for _ in 0..NUM_PAYLOADS {
+                recv(&payload_rx).unwrap();
+            }
+            let elapsed = start.elapsed();
+            camera.close().unwrap();
+            elapsed
+        };
+
+        assert!(replay_duration(ReplaySpeed::Recorded) >= recorded_duration);
+        let scaled = replay_duration(ReplaySpeed::Scaled(4.0));
+        assert!(scaled >= recorded_duration / 4);
+        assert!(scaled < recorded_duration / 2);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
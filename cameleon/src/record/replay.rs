@@ -0,0 +1,456 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains [`DeviceControl`] and [`PayloadStream`] implementations which replay a
+//! recording file.
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use async_std::task;
+use futures::{
+    channel::oneshot,
+    future::{self, Either},
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    camera::PayloadStream,
+    genapi::{FromXml, NoCacheGenApiCtxt, ParamsCtxt},
+    payload::{Payload, PayloadSender},
+    Camera, ControlError, ControlResult, DeviceControl, StreamError, StreamResult,
+};
+
+use super::{format, RecordError, RecordResult};
+
+/// Maximum duration to sleep at once in the replay loop, which bounds the latency of
+/// cancellation.
+const MAX_SLEEP: Duration = Duration::from_millis(10);
+
+/// Interval to poll software triggers in the replay loop.
+const TRIGGER_POLLING_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Speed of the replay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Replays payloads at the same intervals as they were recorded.
+    Recorded,
+    /// Replays payloads at the recorded speed multiplied by the factor, e.g. `2.0` replays
+    /// twice as fast.
+    ///
+    /// The factor must be a positive finite number, otherwise [`open_replay`] returns
+    /// [`RecordError::InvalidSpeed`].
+    Scaled(f64),
+    /// Replays payloads as fast as possible.
+    Unlimited,
+}
+
+/// Opens a recording file and returns a [`Camera`] which replays it.
+///
+/// See [the module level documentation](super) for an example.
+pub fn open_replay(
+    path: impl AsRef<Path>,
+    speed: ReplaySpeed,
+) -> RecordResult<Camera<ReplayControl, ReplayStream>> {
+    if let ReplaySpeed::Scaled(factor) = speed {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(RecordError::InvalidSpeed(factor));
+        }
+    }
+
+    let path = path.as_ref();
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = format::Header::read_from(&mut reader)?;
+    let payload_offset = reader.stream_position()?;
+
+    let mut ctxt = NoCacheGenApiCtxt::from_xml(&header.xml)
+        .map_err(|e| RecordError::InvalidFormat(e.to_string().into()))?;
+    let mut ctrl = ReplayControl {
+        xml: header.xml,
+        memory: header.memory,
+        is_opened: false,
+        last_written_address: None,
+        trigger_address: None,
+        triggers: Arc::new(AtomicUsize::new(0)),
+    };
+    ctrl.trigger_address = trigger_software_address(&ctrl, &mut ctxt);
+
+    let strm = ReplayStream {
+        path: path.to_owned(),
+        payload_offset,
+        speed,
+        ctxt,
+        triggers: ctrl.trigger_address.map(|_| ctrl.triggers.clone()),
+        is_opened: false,
+        cancellation_tx: None,
+        completion_rx: None,
+    };
+
+    Ok(Camera::new(ctrl, strm, None, header.info))
+}
+
+/// Returns the address written by `TriggerSoftware` command.
+fn trigger_software_address(ctrl: &ReplayControl, ctxt: &mut NoCacheGenApiCtxt) -> Option<u64> {
+    // Execute the command on a copy of the control to find the address it writes to.
+    let mut ctrl = ctrl.clone();
+    ctrl.is_opened = true;
+    let mut ctxt = ParamsCtxt { ctrl, ctxt };
+    let node = ctxt.node("TriggerSoftware")?.as_command(&ctxt)?;
+    node.execute(&mut ctxt).ok()?;
+    ctxt.ctrl.last_written_address
+}
+
+/// Returns `true` if the camera is configured to wait for software triggers.
+fn is_software_triggered<Ctrl>(ctxt: &mut ParamsCtxt<Ctrl, &mut NoCacheGenApiCtxt>) -> bool
+where
+    Ctrl: DeviceControl,
+{
+    let mut current_entry = |name: &str| {
+        let node = ctxt.node(name)?.as_enumeration(ctxt)?;
+        let entry = node.current_entry(ctxt).ok()?;
+        Some(entry.symbolic(ctxt).to_string())
+    };
+
+    current_entry("TriggerMode").as_deref() == Some("On")
+        && matches!(
+            current_entry("TriggerSource").as_deref(),
+            None | Some("Software")
+        )
+}
+
+/// A [`DeviceControl`] which emulates the recorded camera.
+///
+/// Reads return the recorded memory, and writes update it. Memory which wasn't recorded is read
+/// as zeros.
+///
+/// Executing `TriggerSoftware` command makes [`ReplayStream`] send the next payload if
+/// `TriggerMode` is `On` when streaming starts.
+#[derive(Debug, Clone)]
+pub struct ReplayControl {
+    xml: String,
+    memory: BTreeMap<u64, u8>,
+    is_opened: bool,
+    last_written_address: Option<u64>,
+    trigger_address: Option<u64>,
+    /// The number of software triggers which aren't consumed by the stream yet.
+    triggers: Arc<AtomicUsize>,
+}
+
+impl ReplayControl {
+    fn assert_open(&self) -> ControlResult<()> {
+        if self.is_opened {
+            Ok(())
+        } else {
+            Err(ControlError::NotOpened)
+        }
+    }
+}
+
+impl DeviceControl for ReplayControl {
+    fn open(&mut self) -> ControlResult<()> {
+        self.is_opened = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> ControlResult<()> {
+        self.is_opened = false;
+        Ok(())
+    }
+
+    fn is_opened(&self) -> bool {
+        self.is_opened
+    }
+
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+        self.assert_open()?;
+        for (addr, byte) in (address..).zip(buf.iter_mut()) {
+            *byte = self.memory.get(&addr).copied().unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+        self.assert_open()?;
+        self.memory.extend((address..).zip(data.iter().copied()));
+        self.last_written_address = Some(address);
+        if Some(address) == self.trigger_address {
+            self.triggers.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    fn genapi(&mut self) -> ControlResult<String> {
+        self.assert_open()?;
+        Ok(self.xml.clone())
+    }
+
+    fn enable_streaming(&mut self) -> ControlResult<()> {
+        self.assert_open()
+    }
+
+    fn disable_streaming(&mut self) -> ControlResult<()> {
+        self.assert_open()
+    }
+}
+
+/// A [`PayloadStream`] which replays the recorded payloads.
+///
+/// The stream sends each recorded payload once, and sends nothing after the last payload.
+/// Unlike live devices, the stream waits for the receiver to have room instead of dropping
+/// payloads, so that the receiver always gets all the recorded payloads.
+#[derive(Debug)]
+pub struct ReplayStream {
+    path: PathBuf,
+    payload_offset: u64,
+    speed: ReplaySpeed,
+    ctxt: NoCacheGenApiCtxt,
+    /// Shared with [`ReplayControl`], `None` if the camera doesn't have `TriggerSoftware`.
+    triggers: Option<Arc<AtomicUsize>>,
+    is_opened: bool,
+    cancellation_tx: Option<oneshot::Sender<()>>,
+    completion_rx: Option<oneshot::Receiver<()>>,
+}
+
+impl PayloadStream for ReplayStream {
+    fn open(&mut self) -> StreamResult<()> {
+        self.is_opened = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> StreamResult<()> {
+        self.stop_streaming_loop()?;
+        self.is_opened = false;
+        Ok(())
+    }
+
+    fn start_streaming_loop(
+        &mut self,
+        sender: PayloadSender,
+        ctrl: &mut dyn DeviceControl,
+    ) -> StreamResult<()> {
+        if !self.is_opened {
+            return Err(StreamError::Io(anyhow::Error::msg("stream is not opened")));
+        }
+        if self.is_loop_running() {
+            return Err(StreamError::InStreaming);
+        }
+
+        let mut reader =
+            BufReader::new(File::open(&self.path).map_err(|e| StreamError::Io(e.into()))?);
+        reader
+            .seek(SeekFrom::Start(self.payload_offset))
+            .map_err(|e| StreamError::Io(e.into()))?;
+
+        let triggers = match &self.triggers {
+            Some(triggers)
+                if is_software_triggered(&mut ParamsCtxt {
+                    ctrl,
+                    ctxt: &mut self.ctxt,
+                }) =>
+            {
+                triggers.store(0, Ordering::SeqCst);
+                Some(triggers.clone())
+            }
+            _ => None,
+        };
+
+        let (cancellation_tx, cancellation_rx) = oneshot::channel();
+        let (completion_tx, completion_rx) = oneshot::channel();
+        self.cancellation_tx = Some(cancellation_tx);
+        self.completion_rx = Some(completion_rx);
+
+        let replay_loop = ReplayLoop {
+            reader,
+            speed: self.speed,
+            triggers,
+            sender,
+            completion_tx,
+            cancellation_rx,
+        };
+        std::thread::spawn(|| {
+            replay_loop.run();
+        });
+
+        info!("start replay loop successfully");
+        Ok(())
+    }
+
+    fn stop_streaming_loop(&mut self) -> StreamResult<()> {
+        if self.is_loop_running() {
+            let (cancellation_tx, completion_rx) = (
+                self.cancellation_tx.take().unwrap(),
+                self.completion_rx.take().unwrap(),
+            );
+            cancellation_tx.send(()).map_err(|_| {
+                StreamError::Poisoned("failed to send cancellation signal to replay loop".into())
+            })?;
+            task::block_on(completion_rx)
+                .map_err(|e| StreamError::Poisoned(e.to_string().into()))?;
+        }
+
+        info!("stop replay loop successfully");
+        Ok(())
+    }
+
+    fn is_loop_running(&self) -> bool {
+        debug_assert_eq!(self.completion_rx.is_some(), self.cancellation_tx.is_some());
+        self.completion_rx.is_some()
+    }
+}
+
+struct ReplayLoop {
+    reader: BufReader<File>,
+    speed: ReplaySpeed,
+    /// Software triggers to wait for before sending each payload, `None` if the camera isn't
+    /// triggered by software.
+    triggers: Option<Arc<AtomicUsize>>,
+    sender: PayloadSender,
+    completion_tx: oneshot::Sender<()>,
+    cancellation_rx: oneshot::Receiver<()>,
+}
+
+impl ReplayLoop {
+    fn run(mut self) {
+        let start = Instant::now();
+        let mut first_elapsed = None;
+
+        loop {
+            if self.is_cancelled() || !self.wait_trigger() {
+                break;
+            }
+
+            let buf = self
+                .sender
+                .try_recv()
+                .map(|payload| payload.payload)
+                .unwrap_or_default();
+            let (elapsed, payload) = match format::read_payload(&mut self.reader, buf) {
+                Ok(Some(record)) => record,
+                Ok(None) => {
+                    info!("reached the end of the recording");
+                    // Wait for cancellation so that the stream behaves like an idle device.
+                    task::block_on(&mut self.cancellation_rx).ok();
+                    break;
+                }
+                Err(e) => {
+                    error!(?e);
+                    self.sender
+                        .try_send(Err(StreamError::Io(anyhow::Error::msg(e.to_string()))))
+                        .ok();
+                    task::block_on(&mut self.cancellation_rx).ok();
+                    break;
+                }
+            };
+
+            let first_elapsed = *first_elapsed.get_or_insert(elapsed);
+            let offset = match self.speed {
+                // Triggers determine the timing of payloads instead of the recorded intervals.
+                _ if self.triggers.is_some() => None,
+                ReplaySpeed::Recorded => Some(elapsed - first_elapsed),
+                // The factor is validated in `open_replay`.
+                ReplaySpeed::Scaled(factor) => Some((elapsed - first_elapsed).div_f64(factor)),
+                ReplaySpeed::Unlimited => None,
+            };
+            if let Some(offset) = offset {
+                if !self.sleep_until(start + offset) {
+                    break;
+                }
+            }
+
+            if !self.send(payload) {
+                break;
+            }
+        }
+
+        if let Err(e) = self.completion_tx.send(()) {
+            error!(?e);
+        }
+    }
+
+    /// Sends the payload, waiting for the receiver to have room for it.
+    ///
+    /// Returns `false` if the loop is cancelled meanwhile.
+    fn send(&mut self, payload: Payload) -> bool {
+        let send = self.sender.send(Ok(payload));
+        futures::pin_mut!(send);
+        match task::block_on(future::select(send, &mut self.cancellation_rx)) {
+            Either::Left((res, _)) => {
+                if let Err(err) = res {
+                    warn!(?err);
+                }
+                true
+            }
+            Either::Right(_) => false,
+        }
+    }
+
+    /// Waits for a software trigger if the loop is triggered by software.
+    ///
+    /// Returns `false` if the loop is cancelled meanwhile.
+    fn wait_trigger(&mut self) -> bool {
+        let triggers = match &self.triggers {
+            Some(triggers) => triggers.clone(),
+            None => return true,
+        };
+        loop {
+            let consumed = triggers
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if consumed {
+                return true;
+            }
+            std::thread::sleep(TRIGGER_POLLING_INTERVAL);
+            if self.is_cancelled() {
+                return false;
+            }
+        }
+    }
+
+    /// Sleeps until `deadline`, returns `false` if the loop is cancelled meanwhile.
+    fn sleep_until(&mut self, deadline: Instant) -> bool {
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            std::thread::sleep(std::cmp::min(deadline - now, MAX_SLEEP));
+            if self.is_cancelled() {
+                return false;
+            }
+        }
+    }
+
+    /// Returns `true` if `cancellation_tx` sends signal or is dropped.
+    fn is_cancelled(&mut self) -> bool {
+        self.cancellation_rx.try_recv().transpose().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_speed() {
+        for &factor in &[0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(
+                open_replay("not_exist.rec", ReplaySpeed::Scaled(factor)),
+                Err(RecordError::InvalidSpeed(_))
+            ));
+        }
+        assert!(matches!(
+            open_replay("not_exist.rec", ReplaySpeed::Scaled(0.5)),
+            Err(RecordError::Io(_))
+        ));
+    }
+}
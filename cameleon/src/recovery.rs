@@ -0,0 +1,587 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides recovery of a [`Camera`](crate::Camera) after its device is lost.
+//!
+//! Once recovery is enabled by [`Camera::enable_recovery`](crate::Camera::enable_recovery),
+//! [`Camera::recover`](crate::Camera::recover) finds the same device again, reopens it, restores
+//! the `GenApi` context and features written so far, and restarts streaming if it was active.
+//! [`Camera::with_recovery`](crate::Camera::with_recovery) runs an operation and recovers the
+//! camera automatically if the operation fails due to disconnection.
+//!
+//! # Examples
+//! ```rust
+//! # use cameleon::u3v;
+//! use std::time::Duration;
+//!
+//! use cameleon::recovery::RecoveryConfig;
+//!
+//! # let mut cameras = u3v::enumerate_cameras().unwrap();
+//! # if cameras.is_empty() {
+//! #     return;
+//! # }
+//! # let mut camera = cameras.pop().unwrap();
+//! camera.open().unwrap();
+//! camera.load_context().unwrap();
+//! camera.enable_recovery(RecoveryConfig::default(), |event| println!("{:?}", event));
+//!
+//! // Grab frames. If the camera is disconnected meanwhile, it's recovered and frames are grabbed
+//! // again.
+//! let payloads = camera
+//!     .with_recovery(|camera| camera.grab_n(5, Duration::from_secs(1)))
+//!     .unwrap();
+//! ```
+
+use std::{
+    collections::HashSet,
+    fmt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    genapi::{GenApiCtxt, Node, ParamsCtxt, WriteTransaction},
+    payload::PayloadSender,
+    CameleonError, CameleonResult, ControlError, ControlResult, DeviceControl,
+};
+
+/// Transport specific operations to recover a [`Camera`](crate::Camera) after its device is lost.
+pub trait Reconnect<Strm>: DeviceControl {
+    /// Finds the same device again and replaces the channels of `self` and `strm` with the
+    /// channels of the found device. Both channels are closed after this call.
+    ///
+    /// Returns `false` if the device isn't found.
+    fn reconnect(&mut self, strm: &mut Strm) -> CameleonResult<bool>;
+
+    /// Starts recording writes to the device if `enabled` is `true`, otherwise stops recording
+    /// and discards the recorded writes.
+    fn set_write_journal(&mut self, enabled: bool);
+
+    /// Returns writes recorded since the journal was enabled, or `None` if the journal is
+    /// disabled.
+    fn write_journal(&self) -> Option<WriteJournal>;
+}
+
+/// Writes to the device recorded for recovery.
+///
+/// Only the last write to each address is kept.
+#[derive(Debug, Clone, Default)]
+pub struct WriteJournal {
+    entries: Vec<(u64, Vec<u8>)>,
+}
+
+impl WriteJournal {
+    /// Creates an empty journal.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a write of `data` to `address`.
+    pub fn record(&mut self, address: u64, data: &[u8]) {
+        self.entries.retain(|(addr, _)| *addr != address);
+        self.entries.push((address, data.to_vec()));
+    }
+
+    /// Returns the recorded writes in the order they were last written.
+    #[must_use]
+    pub fn entries(&self) -> &[(u64, Vec<u8>)] {
+        &self.entries
+    }
+
+    /// Returns `true` if no write is recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Overwrites `buf` read from `address` with the recorded writes.
+    fn overlay(&self, address: u64, buf: &mut [u8]) {
+        let end = address + buf.len() as u64;
+        for (addr, data) in &self.entries {
+            let data_end = addr + data.len() as u64;
+            if data_end <= address || end <= *addr {
+                continue;
+            }
+            let start = std::cmp::max(address, *addr);
+            let stop = std::cmp::min(end, data_end);
+            buf[(start - address) as usize..(stop - address) as usize]
+                .copy_from_slice(&data[(start - addr) as usize..(stop - addr) as usize]);
+        }
+    }
+}
+
+/// Configuration of the recovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryConfig {
+    /// Maximum number of attempts to recover the camera.
+    pub max_attempts: u32,
+    /// Interval between attempts.
+    pub retry_interval: Duration,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            retry_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+/// A step of the recovery reported to the callback registered by
+/// [`Camera::enable_recovery`](crate::Camera::enable_recovery).
+#[derive(Debug)]
+pub enum RecoveryEvent<'a> {
+    /// The recovery is started.
+    Started,
+    /// The device is found again.
+    DeviceFound {
+        /// The attempt number starting from 1.
+        attempt: u32,
+    },
+    /// The control and stream channels are reopened.
+    Reopened,
+    /// The `GenApi` context is restored.
+    ContextRestored,
+    /// The features written before the device was lost are written again.
+    FeaturesRestored {
+        /// The number of the restored features.
+        count: usize,
+    },
+    /// Streaming is restarted. Payloads are sent to the receiver returned before the device was
+    /// lost.
+    StreamingRestarted,
+    /// The camera is recovered.
+    Recovered,
+    /// An attempt failed. The next attempt is started after
+    /// [`RecoveryConfig::retry_interval`].
+    AttemptFailed {
+        /// The attempt number starting from 1.
+        attempt: u32,
+        /// The cause of the failure.
+        error: &'a CameleonError,
+    },
+}
+
+type Callback = dyn FnMut(RecoveryEvent<'_>) + Send;
+
+/// Recovery state held by a camera.
+#[derive(Clone)]
+pub(crate) struct Recovery {
+    pub(crate) config: RecoveryConfig,
+    callback: Arc<Mutex<Box<Callback>>>,
+    /// Sender of the active streaming.
+    pub(crate) sender: Option<PayloadSender>,
+}
+
+impl Recovery {
+    pub(crate) fn new(
+        config: RecoveryConfig,
+        callback: impl FnMut(RecoveryEvent<'_>) + Send + 'static,
+    ) -> Self {
+        Self {
+            config,
+            callback: Arc::new(Mutex::new(Box::new(callback))),
+            sender: None,
+        }
+    }
+
+    pub(crate) fn report(&self, event: RecoveryEvent<'_>) {
+        if let Ok(mut callback) = self.callback.lock() {
+            callback(event);
+        }
+    }
+}
+
+impl fmt::Debug for Recovery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recovery")
+            .field("config", &self.config)
+            .field("sender", &self.sender)
+            .finish()
+    }
+}
+
+/// Writes the features changed by `journal` again, and returns the number of restored features.
+///
+/// Values of the features before the device was lost are read through the device memory
+/// overlaid with `journal`, then the features whose values differ from the current values are
+/// written in dependency order.
+pub(crate) fn restore_features<Ctrl, Ctxt>(
+    ctrl: &mut Ctrl,
+    ctxt: &mut Ctxt,
+    journal: &WriteJournal,
+) -> usize
+where
+    Ctrl: DeviceControl,
+    Ctxt: GenApiCtxt,
+{
+    // `TLParamsLocked` is controlled by the camera itself while streaming.
+    const EXCLUDED: &[&str] = &["TLParamsLocked"];
+
+    if journal.is_empty() {
+        return 0;
+    }
+
+    let features = {
+        let mut params = ParamsCtxt {
+            ctrl: &mut *ctrl,
+            ctxt: &mut *ctxt,
+        };
+        let mut visited = HashSet::new();
+        let mut features = vec![];
+        if let Some(root) = params.node("Root") {
+            collect_features(root, &mut params, &mut visited, &mut features);
+        }
+        features.retain(|node| {
+            !EXCLUDED.contains(&node.name(&params))
+                && matches!(params.is_writable_value(*node), Ok(true))
+        });
+        features
+    };
+    ctxt.clear_cache();
+
+    let journaled: Vec<_> = {
+        let mut params = ParamsCtxt {
+            ctrl: JournalOverlay {
+                inner: &mut *ctrl,
+                journal,
+            },
+            ctxt: &mut *ctxt,
+        };
+        features
+            .iter()
+            .map(|node| params.current_value(*node).ok().flatten())
+            .collect()
+    };
+    // The cache holds values read through the overlay.
+    ctxt.clear_cache();
+
+    let mut params = ParamsCtxt { ctrl, ctxt };
+    let mut transaction = WriteTransaction::new();
+    for (node, journaled) in features.into_iter().zip(journaled) {
+        if let Some(journaled) = journaled {
+            if params.current_value(node).ok().flatten().as_ref() != Some(&journaled) {
+                transaction.set(node, journaled);
+            }
+        }
+    }
+
+    params.write_best_effort(&transaction)
+}
+
+/// Collects value features under the category.
+fn collect_features<Ctrl, Ctxt>(
+    node: Node,
+    params: &mut ParamsCtxt<Ctrl, Ctxt>,
+    visited: &mut HashSet<Node>,
+    features: &mut Vec<Node>,
+) where
+    Ctrl: DeviceControl,
+    Ctxt: GenApiCtxt,
+{
+    if !visited.insert(node) {
+        return;
+    }
+
+    if let Some(category) = node.as_category(params) {
+        for child in category.nodes(params) {
+            collect_features(child, params, visited, features);
+        }
+    } else {
+        features.push(node);
+    }
+}
+
+/// A [`DeviceControl`] which reads the device memory overlaid with a journal.
+struct JournalOverlay<'a, Ctrl> {
+    inner: &'a mut Ctrl,
+    journal: &'a WriteJournal,
+}
+
+impl<'a, Ctrl> DeviceControl for JournalOverlay<'a, Ctrl>
+where
+    Ctrl: DeviceControl,
+{
+    fn open(&mut self) -> ControlResult<()> {
+        self.inner.open()
+    }
+
+    fn close(&mut self) -> ControlResult<()> {
+        self.inner.close()
+    }
+
+    fn is_opened(&self) -> bool {
+        self.inner.is_opened()
+    }
+
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+        self.inner.read(address, buf)?;
+        self.journal.overlay(address, buf);
+        Ok(())
+    }
+
+    fn write(&mut self, _address: u64, _data: &[u8]) -> ControlResult<()> {
+        Err(ControlError::Io(anyhow::Error::msg(
+            "can't write to the device while reading journaled values",
+        )))
+    }
+
+    fn genapi(&mut self) -> ControlResult<String> {
+        self.inner.genapi()
+    }
+
+    fn enable_streaming(&mut self) -> ControlResult<()> {
+        self.inner.enable_streaming()
+    }
+
+    fn disable_streaming(&mut self) -> ControlResult<()> {
+        self.inner.disable_streaming()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::{
+        camera::CameraInfo,
+        genapi::NoCacheGenApiCtxt,
+        test_utils::{params_ctxt, MemoryControl, NullStream},
+        Camera,
+    };
+
+    use super::*;
+
+    /// `Gain` at 0x0 under `Root`.
+    const FEATURES: &str = r#"
+    <Category Name="Root">
+        <pFeature>Gain</pFeature>
+    </Category>
+    <IntReg Name="Gain">
+        <Address>0x0</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+    "#;
+
+    /// A device which is found again at `found_at`-th attempt of reconnection.
+    /// The device memory is cleared on reconnection.
+    struct MockControl {
+        inner: MemoryControl,
+        attempts: u32,
+        found_at: Option<u32>,
+        journal: Option<WriteJournal>,
+    }
+
+    impl DeviceControl for MockControl {
+        fn open(&mut self) -> ControlResult<()> {
+            self.inner.open()
+        }
+
+        fn close(&mut self) -> ControlResult<()> {
+            self.inner.close()
+        }
+
+        fn is_opened(&self) -> bool {
+            self.inner.is_opened()
+        }
+
+        fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+            self.inner.read(address, buf)
+        }
+
+        fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+            self.inner.write(address, data)?;
+            if let Some(journal) = &mut self.journal {
+                journal.record(address, data);
+            }
+            Ok(())
+        }
+
+        fn genapi(&mut self) -> ControlResult<String> {
+            self.inner.genapi()
+        }
+
+        fn enable_streaming(&mut self) -> ControlResult<()> {
+            self.inner.enable_streaming()
+        }
+
+        fn disable_streaming(&mut self) -> ControlResult<()> {
+            self.inner.disable_streaming()
+        }
+    }
+
+    impl Reconnect<NullStream> for MockControl {
+        fn reconnect(&mut self, _strm: &mut NullStream) -> CameleonResult<bool> {
+            self.attempts += 1;
+            if self.found_at == Some(self.attempts) {
+                self.inner = MemoryControl::new(self.inner.memory.len());
+                self.inner.close()?;
+                Ok(true)
+            } else {
+                Ok(false)
+            }
+        }
+
+        fn set_write_journal(&mut self, enabled: bool) {
+            self.journal = if enabled {
+                Some(self.journal.take().unwrap_or_default())
+            } else {
+                None
+            };
+        }
+
+        fn write_journal(&self) -> Option<WriteJournal> {
+            self.journal.clone()
+        }
+    }
+
+    type MockCamera = Camera<MockControl, NullStream, NoCacheGenApiCtxt>;
+
+    const RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+    /// Builds a camera with recovery enabled, and returns it with the reported events.
+    fn camera(found_at: Option<u32>, max_attempts: u32) -> (MockCamera, Arc<Mutex<Vec<String>>>) {
+        let ctxt = params_ctxt(FEATURES, 0x10).ctxt;
+        let ctrl = MockControl {
+            inner: MemoryControl::new(0x10),
+            attempts: 0,
+            found_at,
+            journal: None,
+        };
+        let info = CameraInfo {
+            vendor_name: "Cameleon".into(),
+            model_name: "Test".into(),
+            serial_number: "CAM0000".into(),
+        };
+        let mut camera = Camera::new(ctrl, NullStream, Some(ctxt), info);
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let events_clone = events.clone();
+        let config = RecoveryConfig {
+            max_attempts,
+            retry_interval: RETRY_INTERVAL,
+        };
+        camera.enable_recovery(config, move |event| {
+            let event = match event {
+                RecoveryEvent::AttemptFailed { attempt, .. } => {
+                    format!("AttemptFailed({})", attempt)
+                }
+                event => format!("{:?}", event),
+            };
+            events_clone.lock().unwrap().push(event);
+        });
+
+        (camera, events)
+    }
+
+    #[test]
+    fn test_recover_after_retries() {
+        let (mut camera, events) = camera(Some(3), 5);
+
+        let start = Instant::now();
+        camera.recover().unwrap();
+        // Attempts are separated by the retry interval.
+        assert!(start.elapsed() >= RETRY_INTERVAL * 2);
+
+        assert_eq!(camera.ctrl.attempts, 3);
+        assert!(camera.ctrl.is_opened());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "Started",
+                "AttemptFailed(1)",
+                "AttemptFailed(2)",
+                "DeviceFound { attempt: 3 }",
+                "Reopened",
+                "ContextRestored",
+                "FeaturesRestored { count: 0 }",
+                "Recovered",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_give_up() {
+        let (mut camera, events) = camera(None, 3);
+
+        let err = camera.recover().unwrap_err();
+        assert!(err.is_disconnected());
+        assert_eq!(camera.ctrl.attempts, 3);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "Started",
+                "AttemptFailed(1)",
+                "AttemptFailed(2)",
+                "AttemptFailed(3)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_restore_features() {
+        let (mut camera, events) = camera(Some(1), 1);
+        let mut params = camera.params_ctxt().unwrap();
+        let gain = params.node("Gain").unwrap().as_integer(&params).unwrap();
+        gain.set_value(&mut params, 42).unwrap();
+
+        camera.recover().unwrap();
+        assert_eq!(&camera.ctrl.inner.memory[..4], &42_u32.to_le_bytes());
+        assert!(events
+            .lock()
+            .unwrap()
+            .contains(&"FeaturesRestored { count: 1 }".to_string()));
+    }
+
+    #[test]
+    fn test_with_recovery() {
+        let (mut camera, _) = camera(Some(1), 1);
+
+        let mut calls = 0;
+        let res = camera.with_recovery(|_| {
+            calls += 1;
+            if calls == 1 {
+                Err(ControlError::Disconnected.into())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(camera.ctrl.attempts, 1);
+    }
+
+    #[test]
+    fn test_recovery_disabled() {
+        let (mut camera, _) = camera(Some(1), 1);
+        camera.disable_recovery();
+
+        assert!(matches!(
+            camera.recover(),
+            Err(CameleonError::RecoveryDisabled)
+        ));
+        let res: CameleonResult<()> =
+            camera.with_recovery(|_| Err(ControlError::Disconnected.into()));
+        assert!(res.unwrap_err().is_disconnected());
+        assert_eq!(camera.ctrl.attempts, 0);
+    }
+
+    #[test]
+    fn test_journal_overlay() {
+        let mut journal = WriteJournal::new();
+        journal.record(0x2, &[1, 2]);
+        journal.record(0x6, &[3, 4, 5]);
+        journal.record(0x2, &[6, 7]);
+        assert_eq!(journal.entries().len(), 2);
+
+        let mut buf = [0; 6];
+        journal.overlay(0x3, &mut buf);
+        assert_eq!(buf, [7, 0, 0, 3, 4, 5]);
+    }
+}
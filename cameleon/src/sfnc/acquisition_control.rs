@@ -0,0 +1,156 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Features of `AcquisitionControl` category which describe the acquisition and trigger
+//! settings.
+
+use super::{features, sfnc_enum, Auto, EnumFeature};
+use crate::genapi::{BooleanNode, CommandNode, FloatNode, IntegerNode};
+
+features! {
+    /// `AcquisitionMode` feature.
+    pub fn acquisition_mode() -> EnumFeature<AcquisitionMode> = "AcquisitionMode";
+    /// `AcquisitionStart` feature.
+    pub fn acquisition_start() -> CommandNode = "AcquisitionStart";
+    /// `AcquisitionStop` feature.
+    pub fn acquisition_stop() -> CommandNode = "AcquisitionStop";
+    /// `AcquisitionAbort` feature.
+    pub fn acquisition_abort() -> CommandNode = "AcquisitionAbort";
+    /// `AcquisitionFrameCount` feature, the number of frames to acquire in `MultiFrame` mode.
+    pub fn acquisition_frame_count() -> IntegerNode = "AcquisitionFrameCount";
+    /// `AcquisitionFrameRate` feature in Hz.
+    pub fn acquisition_frame_rate() -> FloatNode = "AcquisitionFrameRate";
+    /// `AcquisitionFrameRateEnable` feature.
+    pub fn acquisition_frame_rate_enable() -> BooleanNode = "AcquisitionFrameRateEnable";
+    /// `TriggerSelector` feature.
+    pub fn trigger_selector() -> EnumFeature<TriggerSelector> = "TriggerSelector";
+    /// `TriggerMode` feature.
+    pub fn trigger_mode() -> EnumFeature<TriggerMode> = "TriggerMode";
+    /// `TriggerSoftware` feature.
+    pub fn trigger_software() -> CommandNode = "TriggerSoftware";
+    /// `TriggerSource` feature.
+    pub fn trigger_source() -> EnumFeature<TriggerSource> = "TriggerSource";
+    /// `TriggerActivation` feature.
+    pub fn trigger_activation() -> EnumFeature<TriggerActivation> = "TriggerActivation";
+    /// `TriggerDelay` feature in microseconds.
+    pub fn trigger_delay() -> FloatNode = "TriggerDelay";
+    /// `ExposureMode` feature.
+    pub fn exposure_mode() -> EnumFeature<ExposureMode> = "ExposureMode";
+    /// `ExposureTime` feature in microseconds.
+    pub fn exposure_time() -> FloatNode = "ExposureTime";
+    /// `ExposureAuto` feature.
+    pub fn exposure_auto() -> EnumFeature<Auto> = "ExposureAuto";
+}
+
+sfnc_enum! {
+    /// Entries of `AcquisitionMode`.
+    pub enum AcquisitionMode {
+        /// One frame is captured.
+        SingleFrame,
+        /// The number of frames specified by `AcquisitionFrameCount` is captured.
+        MultiFrame,
+        /// Frames are captured continuously until `AcquisitionStop` is executed.
+        Continuous,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `TriggerSelector`.
+    pub enum TriggerSelector {
+        /// Trigger that starts the acquisition.
+        AcquisitionStart,
+        /// Trigger that ends the acquisition.
+        AcquisitionEnd,
+        /// Trigger that controls the duration of the acquisition.
+        AcquisitionActive,
+        /// Trigger that starts the capture of a frame.
+        FrameStart,
+        /// Trigger that ends the capture of a frame.
+        FrameEnd,
+        /// Trigger that controls the duration of the capture of a frame.
+        FrameActive,
+        /// Trigger that starts the capture of a burst of frames.
+        FrameBurstStart,
+        /// Trigger that ends the capture of a burst of frames.
+        FrameBurstEnd,
+        /// Trigger that controls the duration of the capture of a burst of frames.
+        FrameBurstActive,
+        /// Trigger that starts the capture of a line.
+        LineStart,
+        /// Trigger that starts the exposure.
+        ExposureStart,
+        /// Trigger that ends the exposure.
+        ExposureEnd,
+        /// Trigger that controls the duration of the exposure.
+        ExposureActive,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `TriggerMode`.
+    pub enum TriggerMode {
+        /// The selected trigger is disabled.
+        Off,
+        /// The selected trigger is enabled.
+        On,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `TriggerSource`.
+    pub enum TriggerSource {
+        /// The trigger is generated by `TriggerSoftware`.
+        Software,
+        /// I/O line 0.
+        Line0,
+        /// I/O line 1.
+        Line1,
+        /// I/O line 2.
+        Line2,
+        /// I/O line 3.
+        Line3,
+        /// The start of counter 0.
+        Counter0Start,
+        /// The end of counter 0.
+        Counter0End,
+        /// The start of timer 0.
+        Timer0Start,
+        /// The end of timer 0.
+        Timer0End,
+        /// User output 0.
+        UserOutput0,
+        /// Action command 1.
+        Action1,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `TriggerActivation`.
+    pub enum TriggerActivation {
+        /// The trigger is activated on the rising edge of the source signal.
+        RisingEdge,
+        /// The trigger is activated on the falling edge of the source signal.
+        FallingEdge,
+        /// The trigger is activated on both edges of the source signal.
+        AnyEdge,
+        /// The trigger is active while the source signal is high.
+        LevelHigh,
+        /// The trigger is active while the source signal is low.
+        LevelLow,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `ExposureMode`.
+    pub enum ExposureMode {
+        /// The exposure is disabled.
+        Off,
+        /// The exposure duration is set by `ExposureTime`.
+        Timed,
+        /// The exposure duration is set by the width of the trigger signal.
+        TriggerWidth,
+        /// The exposure duration is controlled by one or more triggers.
+        TriggerControlled,
+    }
+}
@@ -0,0 +1,62 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Features of `AnalogControl` category which describe the gain, black level, and white
+//! balance settings.
+
+use super::{features, sfnc_enum, Auto, EnumFeature};
+use crate::genapi::FloatNode;
+
+features! {
+    /// `GainSelector` feature.
+    pub fn gain_selector() -> EnumFeature<GainSelector> = "GainSelector";
+    /// `Gain` feature in dB.
+    pub fn gain() -> FloatNode = "Gain";
+    /// `GainAuto` feature.
+    pub fn gain_auto() -> EnumFeature<Auto> = "GainAuto";
+    /// `BlackLevel` feature.
+    pub fn black_level() -> FloatNode = "BlackLevel";
+    /// `BlackLevelAuto` feature.
+    pub fn black_level_auto() -> EnumFeature<Auto> = "BlackLevelAuto";
+    /// `Gamma` feature.
+    pub fn gamma() -> FloatNode = "Gamma";
+    /// `BalanceRatioSelector` feature.
+    pub fn balance_ratio_selector() -> EnumFeature<BalanceRatioSelector> = "BalanceRatioSelector";
+    /// `BalanceRatio` feature.
+    pub fn balance_ratio() -> FloatNode = "BalanceRatio";
+    /// `BalanceWhiteAuto` feature.
+    pub fn balance_white_auto() -> EnumFeature<Auto> = "BalanceWhiteAuto";
+}
+
+sfnc_enum! {
+    /// Entries of `GainSelector`.
+    pub enum GainSelector {
+        /// Gain applied to all channels.
+        All,
+        /// Gain applied to the red channel.
+        Red,
+        /// Gain applied to the green channel.
+        Green,
+        /// Gain applied to the blue channel.
+        Blue,
+        /// Analog gain applied to all channels.
+        AnalogAll,
+        /// Digital gain applied to all channels.
+        DigitalAll,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `BalanceRatioSelector`.
+    pub enum BalanceRatioSelector {
+        /// Ratio applied to all channels.
+        All,
+        /// Ratio applied to the red channel.
+        Red,
+        /// Ratio applied to the green channel.
+        Green,
+        /// Ratio applied to the blue channel.
+        Blue,
+    }
+}
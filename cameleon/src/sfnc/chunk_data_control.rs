@@ -0,0 +1,68 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Features of `ChunkDataControl` category which describe the chunk data appended to the
+//! payload.
+//!
+//! NOTE: Values of `Chunk*` features other than the controls are only available while the chunk
+//! data of the payload is attached to the context.
+
+use super::{features, sfnc_enum, EnumFeature};
+use crate::{
+    genapi::{BooleanNode, FloatNode, IntegerNode},
+    payload::PixelFormat,
+};
+
+features! {
+    /// `ChunkModeActive` feature.
+    pub fn chunk_mode_active() -> BooleanNode = "ChunkModeActive";
+    /// `ChunkSelector` feature.
+    pub fn chunk_selector() -> EnumFeature<ChunkSelector> = "ChunkSelector";
+    /// `ChunkEnable` feature.
+    pub fn chunk_enable() -> BooleanNode = "ChunkEnable";
+    /// `ChunkWidth` feature.
+    pub fn chunk_width() -> IntegerNode = "ChunkWidth";
+    /// `ChunkHeight` feature.
+    pub fn chunk_height() -> IntegerNode = "ChunkHeight";
+    /// `ChunkOffsetX` feature.
+    pub fn chunk_offset_x() -> IntegerNode = "ChunkOffsetX";
+    /// `ChunkOffsetY` feature.
+    pub fn chunk_offset_y() -> IntegerNode = "ChunkOffsetY";
+    /// `ChunkPixelFormat` feature.
+    pub fn chunk_pixel_format() -> EnumFeature<PixelFormat> = "ChunkPixelFormat";
+    /// `ChunkTimestamp` feature.
+    pub fn chunk_timestamp() -> IntegerNode = "ChunkTimestamp";
+    /// `ChunkFrameID` feature.
+    pub fn chunk_frame_id() -> IntegerNode = "ChunkFrameID";
+    /// `ChunkExposureTime` feature.
+    pub fn chunk_exposure_time() -> FloatNode = "ChunkExposureTime";
+    /// `ChunkGain` feature.
+    pub fn chunk_gain() -> FloatNode = "ChunkGain";
+}
+
+sfnc_enum! {
+    /// Entries of `ChunkSelector`.
+    pub enum ChunkSelector {
+        /// The image data.
+        Image,
+        /// `Width` of the image.
+        Width,
+        /// `Height` of the image.
+        Height,
+        /// `OffsetX` of the image.
+        OffsetX,
+        /// `OffsetY` of the image.
+        OffsetY,
+        /// `PixelFormat` of the image.
+        PixelFormat,
+        /// Timestamp of the image.
+        Timestamp,
+        /// Frame ID of the image.
+        FrameID,
+        /// `ExposureTime` used to capture the image.
+        ExposureTime,
+        /// `Gain` used to capture the image.
+        Gain,
+    }
+}
@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Features of `DeviceControl` category which describe the information and the general
+//! control of the device.
+
+use super::{features, sfnc_enum, EnumFeature};
+use crate::genapi::{CommandNode, FloatNode, IntegerNode, StringNode};
+
+features! {
+    /// `DeviceScanType` feature.
+    pub fn device_scan_type() -> EnumFeature<DeviceScanType> = "DeviceScanType";
+    /// `DeviceVendorName` feature.
+    pub fn device_vendor_name() -> StringNode = "DeviceVendorName";
+    /// `DeviceModelName` feature.
+    pub fn device_model_name() -> StringNode = "DeviceModelName";
+    /// `DeviceVersion` feature.
+    pub fn device_version() -> StringNode = "DeviceVersion";
+    /// `DeviceFirmwareVersion` feature.
+    pub fn device_firmware_version() -> StringNode = "DeviceFirmwareVersion";
+    /// `DeviceSerialNumber` feature.
+    pub fn device_serial_number() -> StringNode = "DeviceSerialNumber";
+    /// `DeviceUserID` feature.
+    pub fn device_user_id() -> StringNode = "DeviceUserID";
+    /// `DeviceSFNCVersionMajor` feature.
+    pub fn device_sfnc_version_major() -> IntegerNode = "DeviceSFNCVersionMajor";
+    /// `DeviceSFNCVersionMinor` feature.
+    pub fn device_sfnc_version_minor() -> IntegerNode = "DeviceSFNCVersionMinor";
+    /// `DeviceSFNCVersionSubMinor` feature.
+    pub fn device_sfnc_version_sub_minor() -> IntegerNode = "DeviceSFNCVersionSubMinor";
+    /// `DeviceLinkThroughputLimit` feature in bytes per second.
+    pub fn device_link_throughput_limit() -> IntegerNode = "DeviceLinkThroughputLimit";
+    /// `DeviceTemperature` feature in degrees Celsius.
+    pub fn device_temperature() -> FloatNode = "DeviceTemperature";
+    /// `DeviceReset` feature.
+    pub fn device_reset() -> CommandNode = "DeviceReset";
+    /// `TimestampLatch` feature.
+    pub fn timestamp_latch() -> CommandNode = "TimestampLatch";
+    /// `TimestampLatchValue` feature in nanoseconds.
+    pub fn timestamp_latch_value() -> IntegerNode = "TimestampLatchValue";
+}
+
+sfnc_enum! {
+    /// Entries of `DeviceScanType`.
+    pub enum DeviceScanType {
+        /// 2D area scan.
+        Areascan,
+        /// 1D line scan.
+        Linescan,
+    }
+}
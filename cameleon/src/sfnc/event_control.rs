@@ -0,0 +1,71 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Features of `EventControl` category which describe the event notification settings.
+
+use super::{features, sfnc_enum, EnumFeature};
+use crate::genapi::IntegerNode;
+
+features! {
+    /// `EventSelector` feature.
+    pub fn event_selector() -> EnumFeature<EventSelector> = "EventSelector";
+    /// `EventNotification` feature.
+    pub fn event_notification() -> EnumFeature<EventNotification> = "EventNotification";
+    /// `EventAcquisitionStart` feature, the ID of `AcquisitionStart` event.
+    pub fn event_acquisition_start() -> IntegerNode = "EventAcquisitionStart";
+    /// `EventAcquisitionStartTimestamp` feature.
+    pub fn event_acquisition_start_timestamp() -> IntegerNode = "EventAcquisitionStartTimestamp";
+    /// `EventAcquisitionEnd` feature, the ID of `AcquisitionEnd` event.
+    pub fn event_acquisition_end() -> IntegerNode = "EventAcquisitionEnd";
+    /// `EventAcquisitionEndTimestamp` feature.
+    pub fn event_acquisition_end_timestamp() -> IntegerNode = "EventAcquisitionEndTimestamp";
+    /// `EventFrameStart` feature, the ID of `FrameStart` event.
+    pub fn event_frame_start() -> IntegerNode = "EventFrameStart";
+    /// `EventFrameStartTimestamp` feature.
+    pub fn event_frame_start_timestamp() -> IntegerNode = "EventFrameStartTimestamp";
+    /// `EventFrameStartFrameID` feature.
+    pub fn event_frame_start_frame_id() -> IntegerNode = "EventFrameStartFrameID";
+    /// `EventExposureEnd` feature, the ID of `ExposureEnd` event.
+    pub fn event_exposure_end() -> IntegerNode = "EventExposureEnd";
+    /// `EventExposureEndTimestamp` feature.
+    pub fn event_exposure_end_timestamp() -> IntegerNode = "EventExposureEndTimestamp";
+    /// `EventExposureEndFrameID` feature.
+    pub fn event_exposure_end_frame_id() -> IntegerNode = "EventExposureEndFrameID";
+}
+
+sfnc_enum! {
+    /// Entries of `EventSelector`.
+    pub enum EventSelector {
+        /// The device is ready to be triggered by `AcquisitionStart` trigger.
+        AcquisitionTrigger,
+        /// The device started the acquisition.
+        AcquisitionStart,
+        /// The device ended the acquisition.
+        AcquisitionEnd,
+        /// The device received `FrameStart` trigger.
+        FrameTrigger,
+        /// The device started the capture of a frame.
+        FrameStart,
+        /// The device ended the capture of a frame.
+        FrameEnd,
+        /// The device started the exposure.
+        ExposureStart,
+        /// The device ended the exposure.
+        ExposureEnd,
+        /// The device is unable to send data to the host.
+        Stream0TransferOverflow,
+    }
+}
+
+sfnc_enum! {
+    /// Entries of `EventNotification`.
+    pub enum EventNotification {
+        /// The selected event is disabled.
+        Off,
+        /// The selected event is enabled.
+        On,
+        /// The selected event is notified only once.
+        Once,
+    }
+}
@@ -0,0 +1,45 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Features of `ImageFormatControl` category which describe the format of the transferred
+//! image.
+
+use super::{features, EnumFeature};
+use crate::{
+    genapi::{BooleanNode, IntegerNode},
+    payload::PixelFormat,
+};
+
+features! {
+    /// `SensorWidth` feature in pixels.
+    pub fn sensor_width() -> IntegerNode = "SensorWidth";
+    /// `SensorHeight` feature in pixels.
+    pub fn sensor_height() -> IntegerNode = "SensorHeight";
+    /// `WidthMax` feature in pixels.
+    pub fn width_max() -> IntegerNode = "WidthMax";
+    /// `HeightMax` feature in pixels.
+    pub fn height_max() -> IntegerNode = "HeightMax";
+    /// `Width` feature in pixels.
+    pub fn width() -> IntegerNode = "Width";
+    /// `Height` feature in pixels.
+    pub fn height() -> IntegerNode = "Height";
+    /// `OffsetX` feature in pixels.
+    pub fn offset_x() -> IntegerNode = "OffsetX";
+    /// `OffsetY` feature in pixels.
+    pub fn offset_y() -> IntegerNode = "OffsetY";
+    /// `BinningHorizontal` feature.
+    pub fn binning_horizontal() -> IntegerNode = "BinningHorizontal";
+    /// `BinningVertical` feature.
+    pub fn binning_vertical() -> IntegerNode = "BinningVertical";
+    /// `DecimationHorizontal` feature.
+    pub fn decimation_horizontal() -> IntegerNode = "DecimationHorizontal";
+    /// `DecimationVertical` feature.
+    pub fn decimation_vertical() -> IntegerNode = "DecimationVertical";
+    /// `ReverseX` feature.
+    pub fn reverse_x() -> BooleanNode = "ReverseX";
+    /// `ReverseY` feature.
+    pub fn reverse_y() -> BooleanNode = "ReverseY";
+    /// `PixelFormat` feature.
+    pub fn pixel_format() -> EnumFeature<PixelFormat> = "PixelFormat";
+}
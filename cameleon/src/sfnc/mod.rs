@@ -0,0 +1,412 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides typed accessors to the features defined in `GenICam Standard Features
+//! Naming Convention (SFNC)`.
+//!
+//! Each submodule corresponds to a category of `SFNC`. An accessor returns `None` if the camera
+//! doesn't implement the feature or the feature doesn't have the interface defined in `SFNC`.
+//!
+//! # Examples
+//! ```rust
+//! # use cameleon::u3v;
+//! use cameleon::sfnc::{acquisition_control, TriggerMode};
+//!
+//! # let mut cameras = u3v::enumerate_cameras().unwrap();
+//! # if cameras.is_empty() {
+//! #     return;
+//! # }
+//! # let mut camera = cameras.pop().unwrap();
+//! camera.open().unwrap();
+//! camera.load_context().unwrap();
+//!
+//! let mut params_ctxt = camera.params_ctxt().unwrap();
+//!
+//! // Get the current value of `ExposureTime`.
+//! if let Some(exposure_time) = acquisition_control::exposure_time(&params_ctxt) {
+//!     let value = exposure_time.value(&mut params_ctxt).unwrap();
+//!     println!("{}", value);
+//! }
+//!
+//! // Disable trigger.
+//! if let Some(trigger_mode) = acquisition_control::trigger_mode(&params_ctxt) {
+//!     trigger_mode.set_value(&mut params_ctxt, TriggerMode::Off).unwrap();
+//! }
+//! ```
+
+pub mod acquisition_control;
+pub mod analog_control;
+pub mod chunk_data_control;
+pub mod device_control;
+pub mod event_control;
+pub mod image_format_control;
+
+pub use acquisition_control::{
+    AcquisitionMode, ExposureMode, TriggerActivation, TriggerMode, TriggerSelector, TriggerSource,
+};
+pub use analog_control::{BalanceRatioSelector, GainSelector};
+pub use chunk_data_control::ChunkSelector;
+pub use device_control::DeviceScanType;
+pub use event_control::{EventNotification, EventSelector};
+
+use std::{convert::TryFrom, marker::PhantomData};
+
+use cameleon_genapi::GenApiResult;
+
+use super::{
+    genapi::{
+        BooleanNode, CommandNode, EnumerationNode, FloatNode, GenApiCtxt, GenApiError, IntegerNode,
+        Node, ParamsCtxt, RegisterNode, StringNode,
+    },
+    payload::PixelFormat,
+    DeviceControl,
+};
+
+/// An enumeration which corresponds to the entries of an `SFNC` enumeration feature.
+pub trait SfncEnum: Sized + Clone {
+    /// Converts an entry of the enumeration node to `Self`. Returns `None` if the entry isn't
+    /// known.
+    fn from_entry(symbolic: &str, value: i64) -> Option<Self>;
+
+    /// Returns the key to select the entry corresponding to `self`.
+    fn entry_key(&self) -> EntryKey<'_>;
+}
+
+/// A key to select an entry of an enumeration node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKey<'a> {
+    /// Selects the entry by its symbolic name.
+    Symbolic(&'a str),
+    /// Selects the entry by its value.
+    Value(i64),
+}
+
+/// An enumeration node whose entries are represented by `E`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct EnumFeature<E> {
+    node: EnumerationNode,
+    _phantom: PhantomData<fn() -> E>,
+}
+
+impl<E> Clone for EnumFeature<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EnumFeature<E> {}
+
+impl<E> EnumFeature<E> {
+    /// Constructs `EnumFeature` from [`EnumerationNode`].
+    ///
+    /// NOTE: Entries of the node aren't checked against `E`.
+    pub fn new(node: EnumerationNode) -> Self {
+        Self {
+            node,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<E> EnumFeature<E>
+where
+    E: SfncEnum,
+{
+    /// Returns the current value of the feature.
+    ///
+    /// Returns [`GenApiError::InvalidNode`] if the current entry isn't known to `E`. Enumerations
+    /// defined in this module never fail here since unlisted entries are mapped to their `Other`
+    /// variant.
+    pub fn value<Ctrl, Ctxt>(self, ctxt: &mut ParamsCtxt<Ctrl, Ctxt>) -> GenApiResult<E>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        let entry = self.node.current_entry(ctxt)?;
+        let symbolic = entry.symbolic(ctxt);
+        E::from_entry(symbolic, entry.value(ctxt)).ok_or_else(|| {
+            GenApiError::InvalidNode(
+                format!(
+                    "{} is not a known entry of {}",
+                    symbolic,
+                    self.node.as_node().name(ctxt)
+                )
+                .into(),
+            )
+        })
+    }
+
+    /// Sets the value of the feature.
+    pub fn set_value<Ctrl, Ctxt>(
+        self,
+        ctxt: &mut ParamsCtxt<Ctrl, Ctxt>,
+        value: E,
+    ) -> GenApiResult<()>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        match value.entry_key() {
+            EntryKey::Symbolic(name) => self.node.set_entry_by_symbolic(ctxt, name),
+            EntryKey::Value(value) => self.node.set_entry_by_value(ctxt, value),
+        }
+    }
+
+    /// Returns values which are currently available.
+    ///
+    /// Entries that aren't known to `E` are skipped.
+    pub fn available_values<Ctrl, Ctxt>(
+        self,
+        ctxt: &mut ParamsCtxt<Ctrl, Ctxt>,
+    ) -> GenApiResult<Vec<E>>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        let mut values = vec![];
+        for entry in self.node.entries(ctxt) {
+            if !entry.is_available(ctxt)? {
+                continue;
+            }
+            if let Some(value) = E::from_entry(entry.symbolic(ctxt), entry.value(ctxt)) {
+                values.push(value);
+            }
+        }
+        Ok(values)
+    }
+
+    /// Returns `true` if the node is readable.
+    pub fn is_readable<Ctrl, Ctxt>(self, ctxt: &mut ParamsCtxt<Ctrl, Ctxt>) -> GenApiResult<bool>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        self.node.is_readable(ctxt)
+    }
+
+    /// Returns `true` if the node is writable.
+    pub fn is_writable<Ctrl, Ctxt>(self, ctxt: &mut ParamsCtxt<Ctrl, Ctxt>) -> GenApiResult<bool>
+    where
+        Ctrl: DeviceControl,
+        Ctxt: GenApiCtxt,
+    {
+        self.node.is_writable(ctxt)
+    }
+
+    /// Returns the underlying [`EnumerationNode`].
+    pub fn as_enumeration(self) -> EnumerationNode {
+        self.node
+    }
+}
+
+/// `PixelFormat` entries are selected by their `PFNC` value since vendors are less consistent
+/// about symbolic names.
+impl SfncEnum for PixelFormat {
+    fn from_entry(_: &str, value: i64) -> Option<Self> {
+        let value = u32::try_from(value).ok()?;
+        PixelFormat::try_from(value).ok()
+    }
+
+    fn entry_key(&self) -> EntryKey<'_> {
+        EntryKey::Value(u32::from(*self).into())
+    }
+}
+
+/// Downcasts [`Node`] to the interface defined in `SFNC`.
+trait FromNode: Sized {
+    fn from_node<Ctrl, Ctxt>(node: Node, ctxt: &ParamsCtxt<Ctrl, Ctxt>) -> Option<Self>
+    where
+        Ctxt: GenApiCtxt;
+}
+
+macro_rules! impl_from_node {
+    ($(($ty:ty, $method:ident),)*) => {
+        $(
+            impl FromNode for $ty {
+                fn from_node<Ctrl, Ctxt>(node: Node, ctxt: &ParamsCtxt<Ctrl, Ctxt>) -> Option<Self>
+                where
+                    Ctxt: GenApiCtxt,
+                {
+                    node.$method(ctxt)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_node! {
+    (IntegerNode, as_integer),
+    (FloatNode, as_float),
+    (StringNode, as_string),
+    (BooleanNode, as_boolean),
+    (CommandNode, as_command),
+    (RegisterNode, as_register),
+}
+
+impl<E> FromNode for EnumFeature<E> {
+    fn from_node<Ctrl, Ctxt>(node: Node, ctxt: &ParamsCtxt<Ctrl, Ctxt>) -> Option<Self>
+    where
+        Ctxt: GenApiCtxt,
+    {
+        node.as_enumeration(ctxt).map(EnumFeature::new)
+    }
+}
+
+/// Defines accessors to `SFNC` features.
+macro_rules! features {
+    ($(
+        $(#[$meta:meta])*
+        $vis:vis fn $method:ident() -> $ty:ty = $name:literal;
+    )*) => {
+        $(
+            $(#[$meta])*
+            $vis fn $method<Ctrl, Ctxt>(ctxt: &$crate::genapi::ParamsCtxt<Ctrl, Ctxt>) -> Option<$ty>
+            where
+                Ctxt: $crate::genapi::GenApiCtxt,
+            {
+                let node = ctxt.node($name)?;
+                <$ty as $crate::sfnc::FromNode>::from_node(node, ctxt)
+            }
+        )*
+    };
+}
+
+/// Defines an enumeration whose variants correspond to the symbolic names of the entries.
+///
+/// `Other` variant is appended to hold entries that aren't listed, e.g. entries with other
+/// indices such as `Line4` or vendor specific entries.
+macro_rules! sfnc_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )*
+            /// An entry which isn't listed above. Holds the symbolic name of the entry.
+            Other(String),
+        }
+
+        impl $crate::sfnc::SfncEnum for $name {
+            fn from_entry(symbolic: &str, _: i64) -> Option<Self> {
+                match symbolic {
+                    $(stringify!($variant) => Some(Self::$variant),)*
+                    _ => Some(Self::Other(symbolic.to_string())),
+                }
+            }
+
+            fn entry_key(&self) -> $crate::sfnc::EntryKey<'_> {
+                match self {
+                    $(Self::$variant => $crate::sfnc::EntryKey::Symbolic(stringify!($variant)),)*
+                    Self::Other(symbolic) => $crate::sfnc::EntryKey::Symbolic(symbolic),
+                }
+            }
+        }
+    };
+}
+
+use {features, sfnc_enum};
+
+sfnc_enum! {
+    /// Mode of an automatic adjustment such as `ExposureAuto` and `GainAuto`.
+    pub enum Auto {
+        /// The feature is manually controlled.
+        Off,
+        /// The feature is adjusted once by the device, then returns to `Off`.
+        Once,
+        /// The feature is adjusted continuously by the device.
+        Continuous,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::params_ctxt;
+
+    const FEATURES: &str = r#"
+    <Enumeration Name="TriggerMode">
+        <EnumEntry Name="Off"><Value>0</Value></EnumEntry>
+        <EnumEntry Name="On"><Value>1</Value></EnumEntry>
+        <EnumEntry Name="VendorSpecific"><Value>2</Value></EnumEntry>
+        <pValue>TriggerModeReg</pValue>
+    </Enumeration>
+
+    <IntReg Name="TriggerModeReg">
+        <Address>0x0</Address>
+        <Length>4</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>Device</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Integer Name="ExposureTime">
+        <Value>100</Value>
+    </Integer>
+    "#;
+
+    #[test]
+    fn test_enum_round_trip() {
+        let modes = [
+            TriggerMode::Off,
+            TriggerMode::On,
+            TriggerMode::Other("VendorSpecific".into()),
+        ];
+        for mode in &modes {
+            let symbolic = match mode.entry_key() {
+                EntryKey::Symbolic(symbolic) => symbolic,
+                EntryKey::Value(_) => panic!("TriggerMode must be selected by its symbolic name"),
+            };
+            assert_eq!(TriggerMode::from_entry(symbolic, 0).as_ref(), Some(mode));
+        }
+
+        let mut ctxt = params_ctxt(FEATURES, 4);
+        let trigger_mode = acquisition_control::trigger_mode(&ctxt).unwrap();
+        for mode in modes.iter().rev() {
+            trigger_mode.set_value(&mut ctxt, mode.clone()).unwrap();
+            assert_eq!(trigger_mode.value(&mut ctxt).unwrap(), *mode);
+        }
+    }
+
+    #[test]
+    fn test_unknown_entry() {
+        let mut ctxt = params_ctxt(FEATURES, 4);
+        let trigger_mode = acquisition_control::trigger_mode(&ctxt).unwrap();
+        trigger_mode
+            .as_enumeration()
+            .set_entry_by_symbolic(&mut ctxt, "VendorSpecific")
+            .unwrap();
+
+        // Entries which aren't listed in `TriggerMode` are still readable.
+        let vendor_specific = TriggerMode::Other("VendorSpecific".into());
+        assert_eq!(trigger_mode.value(&mut ctxt).unwrap(), vendor_specific);
+        assert_eq!(
+            trigger_mode.available_values(&mut ctxt).unwrap(),
+            vec![TriggerMode::Off, TriggerMode::On, vendor_specific]
+        );
+
+        // Setting an entry which doesn't exist in the node fails.
+        assert!(trigger_mode
+            .set_value(&mut ctxt, TriggerMode::Other("Missing".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_missing_or_mismatched_feature() {
+        let ctxt = params_ctxt(FEATURES, 4);
+        // Not defined in the context.
+        assert!(acquisition_control::acquisition_mode(&ctxt).is_none());
+        // Defined as `Integer` while `SFNC` defines it as `Float`.
+        assert!(acquisition_control::exposure_time(&ctxt).is_none());
+    }
+}
@@ -0,0 +1,212 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Helpers shared by unit tests.
+
+use crate::{
+    clock::TimestampLatch,
+    genapi::{FromXml, GenApiCtxt, NoCacheGenApiCtxt, ParamsCtxt},
+    payload::PayloadSender,
+    ControlError, ControlResult, DeviceControl, PayloadStream, StreamResult,
+};
+
+#[cfg(feature = "emulator")]
+use crate::u3v::DeviceInfo;
+
+/// Returns [`DeviceInfo`] of an emulated device shared by tests.
+///
+/// [`DeviceInfo`] is `#[non_exhaustive]`, so tests overwrite fields of the returned value instead
+/// of constructing it.
+#[cfg(feature = "emulator")]
+pub(crate) fn device_info() -> DeviceInfo {
+    use cameleon_device::emulator::{self, EmulatorBuilder};
+
+    const SERIAL_NUMBER: &str = "TESTUTILS";
+
+    let find = || {
+        emulator::enumerate_devices()
+            .unwrap()
+            .into_iter()
+            .map(|dev| dev.device_info)
+            .find(|info| info.serial_number == SERIAL_NUMBER)
+    };
+
+    find().unwrap_or_else(|| {
+        EmulatorBuilder::new()
+            .serial_number(SERIAL_NUMBER)
+            .unwrap()
+            .build();
+        find().unwrap()
+    })
+}
+
+/// [`DeviceControl`] backed by an in-memory register space.
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryControl {
+    pub(crate) memory: Vec<u8>,
+    /// Writes to this address fail with [`ControlError::Io`].
+    pub(crate) failing_address: Option<u64>,
+    pub(crate) num_reads: usize,
+    pub(crate) num_writes: usize,
+    is_opened: bool,
+}
+
+impl MemoryControl {
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            memory: vec![0; len],
+            failing_address: None,
+            num_reads: 0,
+            num_writes: 0,
+            is_opened: true,
+        }
+    }
+
+    fn range(&self, address: u64, len: usize) -> ControlResult<std::ops::Range<usize>> {
+        let start = address as usize;
+        let end = start + len;
+        if end > self.memory.len() {
+            Err(ControlError::InvalidData(
+                format!("address {:#x} is out of range", address).into(),
+            ))
+        } else {
+            Ok(start..end)
+        }
+    }
+}
+
+impl DeviceControl for MemoryControl {
+    fn open(&mut self) -> ControlResult<()> {
+        self.is_opened = true;
+        Ok(())
+    }
+
+    fn close(&mut self) -> ControlResult<()> {
+        self.is_opened = false;
+        Ok(())
+    }
+
+    fn is_opened(&self) -> bool {
+        self.is_opened
+    }
+
+    fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()> {
+        let range = self.range(address, buf.len())?;
+        self.num_reads += 1;
+        buf.copy_from_slice(&self.memory[range]);
+        Ok(())
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+        if self.failing_address == Some(address) {
+            return Err(ControlError::Io(anyhow::anyhow!(
+                "write to {:#x} failed",
+                address
+            )));
+        }
+        let range = self.range(address, data.len())?;
+        self.num_writes += 1;
+        self.memory[range].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn genapi(&mut self) -> ControlResult<String> {
+        Err(ControlError::InvalidDevice("no GenApi XML".into()))
+    }
+
+    fn enable_streaming(&mut self) -> ControlResult<()> {
+        Ok(())
+    }
+
+    fn disable_streaming(&mut self) -> ControlResult<()> {
+        Ok(())
+    }
+}
+
+/// The timestamp counter of [`MemoryControl`] always reads zero with 1ns ticks.
+impl TimestampLatch for MemoryControl {
+    fn latch_timestamp(&mut self) -> ControlResult<()> {
+        Ok(())
+    }
+
+    fn latched_timestamp(&mut self) -> ControlResult<u64> {
+        Ok(0)
+    }
+
+    fn timestamp_increment(&mut self) -> ControlResult<u64> {
+        Ok(1)
+    }
+}
+
+/// [`PayloadStream`] which never sends payloads.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct NullStream;
+
+impl PayloadStream for NullStream {
+    fn open(&mut self) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn close(&mut self) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn start_streaming_loop(
+        &mut self,
+        _sender: PayloadSender,
+        _ctrl: &mut dyn DeviceControl,
+    ) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn stop_streaming_loop(&mut self) -> StreamResult<()> {
+        Ok(())
+    }
+
+    fn is_loop_running(&self) -> bool {
+        false
+    }
+}
+
+/// Builds a context without cache whose nodes are described by `features`, which is inserted into
+/// a `RegisterDescription` with a port named `Device`.
+pub(crate) fn params_ctxt(
+    features: &str,
+    memory_len: usize,
+) -> ParamsCtxt<MemoryControl, NoCacheGenApiCtxt> {
+    params_ctxt_with(features, memory_len)
+}
+
+/// Same as [`params_ctxt`], but the type of the context is selectable, e.g. to use a context with
+/// cache.
+pub(crate) fn params_ctxt_with<Ctxt: GenApiCtxt + FromXml>(
+    features: &str,
+    memory_len: usize,
+) -> ParamsCtxt<MemoryControl, Ctxt> {
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<RegisterDescription
+    ModelName="Test"
+    VendorName="Cameleon"
+    StandardNameSpace="None"
+    SchemaMajorVersion="1"
+    SchemaMinorVersion="1"
+    SchemaSubMinorVersion="0"
+    MajorVersion="1"
+    MinorVersion="0"
+    SubMinorVersion="0"
+    ProductGuid="01234567-0123-0123-0123-0123456789ab"
+    VersionGuid="76543210-3210-3210-3210-ba9876543210"
+    xmlns="http://www.genicam.org/GenApi/Version_1_1">
+    <Port Name="Device" />
+    {}
+</RegisterDescription>"#,
+        features
+    );
+
+    ParamsCtxt {
+        ctrl: MemoryControl::new(memory_len),
+        ctxt: Ctxt::from_xml(&xml).unwrap(),
+    }
+}
@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains channels which abstract over real `U3V` devices and emulated devices.
+
+use std::time::Duration;
+
+#[cfg(feature = "emulator")]
+use cameleon_device::emulator;
+use cameleon_device::u3v;
+
+/// Control channel of a `U3V` device.
+pub enum ControlChannel {
+    /// Channel of a device connected via `libusb`.
+    #[cfg(feature = "libusb")]
+    LibUsb(u3v::ControlChannel),
+    /// Channel of an emulated device.
+    #[cfg(feature = "emulator")]
+    Emulator(emulator::ControlChannel),
+}
+
+/// Receive channel of a `U3V` device, which is used for streaming and events.
+pub enum ReceiveChannel {
+    /// Channel of a device connected via `libusb`.
+    #[cfg(feature = "libusb")]
+    LibUsb(u3v::ReceiveChannel),
+    /// Channel of an emulated device.
+    #[cfg(feature = "emulator")]
+    Emulator(emulator::ReceiveChannel),
+}
+
+macro_rules! delegate {
+    ($self:ident.$method:ident($($arg:expr),*)) => {
+        match $self {
+            #[cfg(feature = "libusb")]
+            Self::LibUsb(channel) => channel.$method($($arg),*),
+            #[cfg(feature = "emulator")]
+            Self::Emulator(channel) => channel.$method($($arg),*),
+        }
+    };
+}
+
+impl ControlChannel {
+    /// Open the channel.
+    pub fn open(&mut self) -> u3v::Result<()> {
+        delegate!(self.open())
+    }
+
+    /// Close the channel.
+    pub fn close(&mut self) -> u3v::Result<()> {
+        delegate!(self.close())
+    }
+
+    /// Returns `true` if the channel is opened.
+    #[must_use]
+    pub fn is_opened(&self) -> bool {
+        delegate!(self.is_opened())
+    }
+
+    /// Send data to the device.
+    pub fn send(&self, buf: &[u8], timeout: Duration) -> u3v::Result<usize> {
+        delegate!(self.send(buf, timeout))
+    }
+
+    /// Receive data from the device.
+    pub fn recv(&self, buf: &mut [u8], timeout: Duration) -> u3v::Result<usize> {
+        delegate!(self.recv(buf, timeout))
+    }
+
+    /// Set halt to the endpoint of the channel.
+    pub fn set_halt(&self, timeout: Duration) -> u3v::Result<()> {
+        delegate!(self.set_halt(timeout))
+    }
+
+    /// Clear halt of the endpoint of the channel.
+    pub fn clear_halt(&mut self) -> u3v::Result<()> {
+        delegate!(self.clear_halt())
+    }
+}
+
+impl ReceiveChannel {
+    /// Open the channel.
+    pub fn open(&mut self) -> u3v::Result<()> {
+        delegate!(self.open())
+    }
+
+    /// Close the channel.
+    pub fn close(&mut self) -> u3v::Result<()> {
+        delegate!(self.close())
+    }
+
+    /// Returns `true` if the channel is opened.
+    #[must_use]
+    pub fn is_opened(&self) -> bool {
+        delegate!(self.is_opened())
+    }
+
+    /// Receive data from the device.
+    pub fn recv(&self, buf: &mut [u8], timeout: Duration) -> u3v::Result<usize> {
+        delegate!(self.recv(buf, timeout))
+    }
+
+    /// Set halt to the endpoint of the channel.
+    pub fn set_halt(&self, timeout: Duration) -> u3v::Result<()> {
+        delegate!(self.set_halt(timeout))
+    }
+
+    /// Clear halt of the endpoint of the channel.
+    pub fn clear_halt(&mut self) -> u3v::Result<()> {
+        delegate!(self.clear_halt())
+    }
+}
+
+#[cfg(feature = "libusb")]
+impl From<u3v::ControlChannel> for ControlChannel {
+    fn from(channel: u3v::ControlChannel) -> Self {
+        Self::LibUsb(channel)
+    }
+}
+
+#[cfg(feature = "libusb")]
+impl From<u3v::ReceiveChannel> for ReceiveChannel {
+    fn from(channel: u3v::ReceiveChannel) -> Self {
+        Self::LibUsb(channel)
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl From<emulator::ControlChannel> for ControlChannel {
+    fn from(channel: emulator::ControlChannel) -> Self {
+        Self::Emulator(channel)
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl From<emulator::ReceiveChannel> for ReceiveChannel {
+    fn from(channel: emulator::ReceiveChannel) -> Self {
+        Self::Emulator(channel)
+    }
+}
@@ -6,20 +6,31 @@
 
 use std::{
     convert::TryInto,
-    io::Read,
     sync::{Arc, Mutex},
     time::Duration,
 };
 
+#[cfg(feature = "emulator")]
+use cameleon_device::emulator;
 use cameleon_device::{
     u3v,
     u3v::protocol::{ack, cmd},
 };
 use tracing::error;
 
-use super::register_map::{self, Abrm, ManifestTable, Sbrm, Sirm};
+use super::{
+    channel::{ControlChannel, ReceiveChannel},
+    register_map::{self, Abrm, ManifestTable, Sbrm, Sirm},
+    StreamHandle,
+};
 
-use crate::{camera::DeviceControl, genapi::CompressionType, ControlError, ControlResult};
+use crate::{
+    camera::DeviceControl,
+    clock::TimestampLatch,
+    genapi::{CompressionType, XmlLocation, XmlResolver, XmlUrl},
+    recovery::{Reconnect, WriteJournal},
+    CameleonResult, ControlError, ControlRequest, ControlResult, ProtocolStatus, U3vStatus,
+};
 
 /// Initial timeout duration for transaction between device and host.
 /// This value is temporarily used until the device's bootstrap register value is read.
@@ -35,6 +46,16 @@ const INITIAL_MAXIMUM_ACK_LENGTH: u32 = 128;
 
 const PAYLOAD_TRANSFER_SIZE: u32 = 1024 * 64;
 
+/// Length of the prefix magic and CCD of command and acknowledge packets.
+const PACKET_HEADER_LENGTH: usize = 4 + 8;
+
+/// Length of the address(8bytes), reserved(2bytes) and length(2bytes) fields of each entry in
+/// stacked commands.
+const STACKED_ENTRY_HEADER_LENGTH: usize = 12;
+
+/// Length of each entry in `WriteMemStacked` acknowledge.
+const WRITE_MEM_STACKED_ACK_ENTRY_LENGTH: usize = 4;
+
 /// This handle provides low level API to read and write data from the device.  
 /// See [`ControlHandle::abrm`] and [`register_map`](super::register_map) which provide more
 /// convenient way to communicate with `u3v` specific registers.
@@ -64,7 +85,7 @@ const PAYLOAD_TRANSFER_SIZE: u32 = 1024 * 64;
 /// camera.ctrl.read(address, &mut buffer).unwrap();
 /// ```
 pub struct ControlHandle {
-    inner: u3v::ControlChannel,
+    inner: ControlChannel,
     config: ConnectionConfig,
     /// Request id of the next packet.
     next_req_id: u16,
@@ -82,6 +103,12 @@ pub struct ControlHandle {
     sirm: Option<Sirm>,
     /// Cache for `ManifestTable`.
     manifest_table: Option<ManifestTable>,
+
+    /// Writes recorded for recovery.
+    journal: Option<WriteJournal>,
+
+    /// Resolver of `GenApi` XML files.
+    xml_resolver: XmlResolver,
 }
 
 impl ControlHandle {
@@ -137,6 +164,31 @@ impl ControlHandle {
         &self.info
     }
 
+    /// Returns `true` if [`DeviceControl::read_batch`] and [`DeviceControl::write_batch`] send
+    /// stacked commands to the device.
+    ///
+    /// The value is read from the device capability when the handle is opened, and turns into
+    /// `false` if the device answers stacked commands with
+    /// [`ProtocolStatus::NotImplemented`]. When the value is `false`, batches are sent as
+    /// sequential `ReadMem` and `WriteMem` commands.
+    #[must_use]
+    pub fn is_stacked_commands_supported(&self) -> bool {
+        self.config.is_stacked_commands_supported
+    }
+
+    /// Returns the resolver used to retrieve `GenApi` XML in [`DeviceControl::genapi`].
+    pub fn xml_resolver(&self) -> &XmlResolver {
+        &self.xml_resolver
+    }
+
+    /// Sets the resolver used to retrieve `GenApi` XML in [`DeviceControl::genapi`].
+    ///
+    /// See [`XmlResolver::set_local_override`] to use a local XML file instead of the one on the
+    /// device.
+    pub fn set_xml_resolver(&mut self, resolver: XmlResolver) {
+        self.xml_resolver = resolver;
+    }
+
     /// Returns [`Abrm`].
     pub fn abrm(&mut self) -> ControlResult<Abrm> {
         if let Some(abrm) = self.abrm {
@@ -186,20 +238,75 @@ impl ControlHandle {
         Ok(manifest_table)
     }
 
+    #[cfg(feature = "libusb")]
     pub(super) fn new(device: &u3v::Device) -> ControlResult<Self> {
-        let inner = device.control_channel()?;
+        let inner = device.control_channel()?.into();
+        Ok(Self::with_channel(inner, device.device_info.clone()))
+    }
 
-        Ok(Self {
+    #[cfg(feature = "emulator")]
+    pub(super) fn new_emulated(device: &emulator::Device) -> ControlResult<Self> {
+        let inner = device.control_channel()?.into();
+        Ok(Self::with_channel(inner, device.device_info.clone()))
+    }
+
+    fn with_channel(inner: ControlChannel, info: u3v::DeviceInfo) -> Self {
+        Self {
             inner,
             config: ConnectionConfig::default(),
             next_req_id: 0,
             buffer: Vec::new(),
-            info: device.device_info.clone(),
+            info,
             abrm: None,
             sbrm: None,
             sirm: None,
             manifest_table: None,
-        })
+            journal: None,
+            xml_resolver: XmlResolver::new(),
+        }
+    }
+
+    /// Finds the device which has the same GUID, or the same serial number if the GUID is empty,
+    /// and returns its channels.
+    fn find_channels(&self) -> ControlResult<Option<(ControlChannel, ReceiveChannel)>> {
+        let is_same = |info: &u3v::DeviceInfo| {
+            if self.info.guid.is_empty() {
+                info.serial_number == self.info.serial_number
+            } else {
+                info.guid == self.info.guid
+            }
+        };
+
+        match &self.inner {
+            #[cfg(feature = "libusb")]
+            ControlChannel::LibUsb(..) => {
+                for dev in u3v::enumerate_devices()? {
+                    if is_same(&dev.device_info) {
+                        if let Some(strm) = dev.stream_channel()? {
+                            return Ok(Some((dev.control_channel()?.into(), strm.into())));
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "emulator")]
+            ControlChannel::Emulator(..) => {
+                for dev in emulator::enumerate_devices()? {
+                    if is_same(&dev.device_info) {
+                        if let Some(strm) = dev.stream_channel()? {
+                            return Ok(Some((dev.control_channel()?.into(), strm.into())));
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn record_write(&mut self, address: u64, data: &[u8]) {
+        if let Some(journal) = &mut self.journal {
+            journal.record(address, data);
+        }
     }
 
     fn assert_open(&self) -> ControlResult<()> {
@@ -217,15 +324,18 @@ impl ControlHandle {
         let timeout_duration = abrm.maximum_device_response_time(self)?;
         let maximum_cmd_length = sbrm.maximum_command_transfer_length(self)?;
         let maximum_ack_length = sbrm.maximum_acknowledge_trasfer_length(self)?;
+        let is_stacked_commands_supported =
+            abrm.device_capability()?.is_stacked_commands_supported();
 
         self.config.timeout_duration = timeout_duration;
         self.config.maximum_cmd_length = maximum_cmd_length;
         self.config.maximum_ack_length = maximum_ack_length;
+        self.config.is_stacked_commands_supported = is_stacked_commands_supported;
 
         Ok(())
     }
 
-    fn send_cmd<'a, T, U>(&'a mut self, cmd: T) -> ControlResult<U>
+    fn send_cmd<'a, T, U>(&'a mut self, cmd: T, request: ControlRequest) -> ControlResult<U>
     where
         T: cmd::CommandScd,
         U: ack::ParseScd<'a>,
@@ -242,16 +352,29 @@ impl ControlHandle {
         self.inner
             .send(&self.buffer[..cmd_len], self.config.timeout_duration)?;
 
-        // Receive ack and interpret the packet.
+        let recv_len = self.recv_ack(request);
+        // Advance request id even if the transaction fails, otherwise a late ack of the failed
+        // command would be taken as the ack of the next command.
+        self.next_req_id = self.next_req_id.wrapping_add(1);
+        let recv_len = recv_len?;
+
+        // This codes seems weird due to a lifetime problem.
+        // `ack::AckPacket::parse` is a fast operation, so it's ok to call it repeatedly.
+        Ok(ack::AckPacket::parse(&self.buffer[0..recv_len])
+            .unwrap()
+            .scd_as()?)
+    }
+
+    /// Receive ack and interpret the packet. Returns the length of the received ack.
+    fn recv_ack(&mut self, request: ControlRequest) -> ControlResult<usize> {
         let mut retry_count = self.config.retry_count;
-        let mut ok = None;
         while retry_count > 0 {
             let recv_len = self
                 .inner
                 .recv(&mut self.buffer, self.config.timeout_duration)?;
 
             let ack = ack::AckPacket::parse(&self.buffer[0..recv_len])?;
-            self.verify_ack(&ack)?;
+            self.verify_ack(&ack, request)?;
 
             // Retry up to retry count.
             if ack.scd_kind() == ack::ScdKind::Pending {
@@ -261,55 +384,204 @@ impl ControlHandle {
                 continue;
             }
 
-            self.next_req_id = self.next_req_id.wrapping_add(1);
-            ok = Some(recv_len);
-            break;
+            return Ok(recv_len);
         }
 
-        // This codes seems weird due to a lifetime problem.
-        // `ack::AckPacket::parse` is a fast operation, so it's ok to call it repeatedly.
-        if let Some(recv_len) = ok {
-            Ok(ack::AckPacket::parse(&self.buffer[0..recv_len])
-                .unwrap()
-                .scd_as()?)
-        } else {
-            Err(ControlError::Io(anyhow::Error::msg(
-                "the number of times pending was returned exceeds the retry_count.",
-            )))
+        Err(ControlError::Io(anyhow::Error::msg(
+            "the number of times pending was returned exceeds the retry_count.",
+        )))
+    }
+
+    fn read_stacked(&mut self, entries: &mut [(u64, &mut [u8])]) -> ControlResult<()> {
+        let maximum_read_length =
+            cmd::ReadMem::maximum_read_length(self.config.maximum_ack_length as usize) as usize;
+        let maximum_entry_count = (self.config.maximum_cmd_length as usize)
+            .saturating_sub(PACKET_HEADER_LENGTH)
+            / STACKED_ENTRY_HEADER_LENGTH;
+
+        // Split regions so that each of them fits into an acknowledge.
+        let mut chunks = vec![];
+        for (address, buf) in entries.iter_mut() {
+            let mut address = *address;
+            for chunk in buf.chunks_mut(maximum_read_length) {
+                let chunk_len = chunk.len() as u64;
+                chunks.push((address, chunk));
+                address += chunk_len;
+            }
+        }
+
+        // Pack as many chunks as possible into each command.
+        let mut chunks = chunks.as_mut_slice();
+        while !chunks.is_empty() {
+            let mut count = 0;
+            let mut len = 0;
+            while count < chunks.len()
+                && count < maximum_entry_count
+                && len + chunks[count].1.len() <= maximum_read_length
+            {
+                len += chunks[count].1.len();
+                count += 1;
+            }
+            // Always make progress even if the command can't contain any entry.
+            let count = std::cmp::max(count, 1);
+
+            let (group, rest) = std::mem::take(&mut chunks).split_at_mut(count);
+            self.read_group(group)?;
+            chunks = rest;
         }
+
+        Ok(())
     }
 
-    fn verify_ack(&self, ack: &ack::AckPacket) -> ControlResult<()> {
-        let status = ack.status().kind();
-        if status != ack::StatusKind::GenCp(ack::GenCpStatus::Success) {
-            return Err(ControlError::Io(anyhow::Error::msg(format!(
-                "invalid status: {:?}",
-                ack.status().kind()
-            ))));
+    fn read_group(&mut self, group: &mut [(u64, &mut [u8])]) -> ControlResult<()> {
+        if group.len() > 1 && self.config.is_stacked_commands_supported {
+            let len = group.iter().map(|(_, buf)| buf.len()).sum();
+            let cmd = cmd::ReadMemStacked::new(
+                group
+                    .iter()
+                    .map(|(address, buf)| cmd::ReadMem::new(*address, buf.len() as u16))
+                    .collect(),
+            )?;
+            let request = ControlRequest::ReadMemStacked {
+                address: group[0].0,
+                count: group.len(),
+                len,
+            };
+
+            match self.send_cmd::<_, ack::ReadMemStacked>(cmd, request) {
+                Ok(ack) => {
+                    if ack.data.len() != len {
+                        let err_msg = "read mem stacked failed: read length mismatch";
+                        return Err(ControlError::Io(anyhow::Error::msg(err_msg)));
+                    }
+
+                    let mut data = ack.data;
+                    for (_, buf) in group.iter_mut() {
+                        let (head, tail) = data.split_at(buf.len());
+                        buf.copy_from_slice(head);
+                        data = tail;
+                    }
+                    return Ok(());
+                }
+
+                Err(ControlError::ProtocolStatus {
+                    status: ProtocolStatus::NotImplemented,
+                    ..
+                }) => {
+                    // Fall back to sequential commands.
+                    self.config.is_stacked_commands_supported = false;
+                }
+
+                Err(err) => return Err(err),
+            }
         }
 
-        if ack.request_id() != self.next_req_id {
-            return Err(ControlError::Io(anyhow::Error::msg("request id mismatch")));
+        for (address, buf) in group {
+            self.read(*address, buf)?;
+        }
+        Ok(())
+    }
+
+    fn write_stacked(&mut self, entries: &[(u64, &[u8])]) -> ControlResult<()> {
+        let maximum_cmd_length = self.config.maximum_cmd_length as usize;
+        let maximum_data_len = maximum_cmd_length
+            .saturating_sub(PACKET_HEADER_LENGTH + STACKED_ENTRY_HEADER_LENGTH)
+            .max(1);
+        let maximum_entry_count = (self.config.maximum_ack_length as usize)
+            .saturating_sub(PACKET_HEADER_LENGTH)
+            / WRITE_MEM_STACKED_ACK_ENTRY_LENGTH;
+
+        // Split data so that each of them fits into a command.
+        let mut chunks = vec![];
+        for (address, data) in entries {
+            let mut address = *address;
+            for chunk in data.chunks(maximum_data_len) {
+                chunks.push((address, chunk));
+                address += chunk.len() as u64;
+            }
+        }
+
+        // Pack as many chunks as possible into each command.
+        let mut chunks = chunks.as_slice();
+        while !chunks.is_empty() {
+            let mut count = 0;
+            let mut cmd_len = PACKET_HEADER_LENGTH;
+            while count < chunks.len()
+                && count < maximum_entry_count
+                && cmd_len + STACKED_ENTRY_HEADER_LENGTH + chunks[count].1.len()
+                    <= maximum_cmd_length
+            {
+                cmd_len += STACKED_ENTRY_HEADER_LENGTH + chunks[count].1.len();
+                count += 1;
+            }
+            // Always make progress even if the command can't contain any entry.
+            let count = std::cmp::max(count, 1);
+
+            let (group, rest) = chunks.split_at(count);
+            self.write_group(group)?;
+            chunks = rest;
         }
 
         Ok(())
     }
 
-    fn verify_xml(&mut self, xml: &[u8], ent: register_map::ManifestEntry) -> ControlResult<()> {
-        use sha1::Digest;
+    fn write_group(&mut self, group: &[(u64, &[u8])]) -> ControlResult<()> {
+        if group.len() > 1 && self.config.is_stacked_commands_supported {
+            let cmd = cmd::WriteMemStacked::new(
+                group
+                    .iter()
+                    .map(|(address, data)| cmd::WriteMem::new(*address, data))
+                    .collect::<Result<_, _>>()?,
+            )?;
+            let request = ControlRequest::WriteMemStacked {
+                address: group[0].0,
+                count: group.len(),
+                len: group.iter().map(|(_, data)| data.len()).sum(),
+            };
 
-        if let Some(hash) = ent.sha1_hash(self)? {
-            let xml_hash = sha1::Sha1::digest(xml);
-            if xml_hash.as_slice() == hash {
-                Ok(())
-            } else {
-                Err(ControlError::InvalidDevice(
-                    "sha1 of retrieved xml file isn't same as entry's hash".into(),
-                ))
+            match self.send_cmd::<_, ack::WriteMemStacked>(cmd, request) {
+                Ok(ack) => {
+                    let is_written = ack.lengths.len() == group.len()
+                        && ack
+                            .lengths
+                            .iter()
+                            .zip(group)
+                            .all(|(len, (_, data))| *len as usize == data.len());
+                    if !is_written {
+                        let err_msg = "write mem stacked failed: written length mismatch";
+                        return Err(ControlError::Io(anyhow::Error::msg(err_msg)));
+                    }
+                    return Ok(());
+                }
+
+                Err(ControlError::ProtocolStatus {
+                    status: ProtocolStatus::NotImplemented,
+                    ..
+                }) => {
+                    // Fall back to sequential commands.
+                    self.config.is_stacked_commands_supported = false;
+                }
+
+                Err(err) => return Err(err),
             }
-        } else {
-            Ok(())
         }
+
+        for (address, data) in group {
+            self.write(*address, data)?;
+        }
+        Ok(())
+    }
+
+    fn verify_ack(&self, ack: &ack::AckPacket, request: ControlRequest) -> ControlResult<()> {
+        if let Some(status) = protocol_status(*ack.status()) {
+            return Err(ControlError::ProtocolStatus { status, request });
+        }
+
+        if ack.request_id() != self.next_req_id {
+            return Err(ControlError::Io(anyhow::Error::msg("request id mismatch")));
+        }
+
+        Ok(())
     }
 }
 
@@ -351,22 +623,29 @@ impl DeviceControl for ControlHandle {
         Ok(())
     }
 
-    fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()> {
+    fn write(&mut self, mut address: u64, data: &[u8]) -> ControlResult<()> {
         unwrap_or_log!(self.assert_open());
+        let start_address = address;
 
         let cmd = unwrap_or_log!(cmd::WriteMem::new(address, data));
         let maximum_cmd_length = self.config.maximum_cmd_length;
 
         for chunk in cmd.chunks(maximum_cmd_length as usize).unwrap() {
             let chunk_data_len = chunk.data_len();
-            let ack: ack::WriteMem = unwrap_or_log!(self.send_cmd(chunk));
+            let request = ControlRequest::WriteMem {
+                address,
+                len: chunk_data_len,
+            };
+            let ack: ack::WriteMem = unwrap_or_log!(self.send_cmd(chunk, request));
 
             if ack.length as usize != chunk_data_len {
                 let err_msg = "write mem failed: written length mismatch";
                 return Err(ControlError::Io(anyhow::Error::msg(err_msg)));
             }
+            address += chunk_data_len as u64;
         }
 
+        self.record_write(start_address, data);
         Ok(())
     }
 
@@ -382,7 +661,11 @@ impl DeviceControl for ControlHandle {
             let read_len: u16 = buf_chunk.len().try_into().unwrap();
 
             let cmd = cmd::ReadMem::new(address, read_len);
-            let ack: ack::ReadMem = unwrap_or_log!(self.send_cmd(cmd));
+            let request = ControlRequest::ReadMem {
+                address,
+                len: buf_chunk.len(),
+            };
+            let ack: ack::ReadMem = unwrap_or_log!(self.send_cmd(cmd, request));
             buf_chunk.copy_from_slice(ack.data);
             address += read_len as u64;
         }
@@ -390,11 +673,22 @@ impl DeviceControl for ControlHandle {
         Ok(())
     }
 
-    fn genapi(&mut self) -> ControlResult<String> {
-        fn zip_err(err: impl std::fmt::Debug) -> ControlError {
-            ControlError::InvalidDevice(format!("zipped xml file is broken: {:?}", err).into())
+    fn read_batch(&mut self, entries: &mut [(u64, &mut [u8])]) -> ControlResult<()> {
+        unwrap_or_log!(self.assert_open());
+        unwrap_or_log!(self.read_stacked(entries));
+        Ok(())
+    }
+
+    fn write_batch(&mut self, entries: &[(u64, &[u8])]) -> ControlResult<()> {
+        unwrap_or_log!(self.assert_open());
+        unwrap_or_log!(self.write_stacked(entries));
+        for (address, data) in entries {
+            self.record_write(*address, data);
         }
+        Ok(())
+    }
 
+    fn genapi(&mut self) -> ControlResult<String> {
         let table = unwrap_or_log!(self.manifest_table());
         // Use newest version if there are more than one entries.
         let mut newest_ent = None;
@@ -411,38 +705,35 @@ impl DeviceControl for ControlHandle {
             }
         }
 
-        let (ent, _, file_info) = unwrap_or_log!(newest_ent.ok_or_else(|| {
+        let (ent, version, file_info) = unwrap_or_log!(newest_ent.ok_or_else(|| {
             ControlError::InvalidDevice("device doesn't have valid `ManifestEntry`".into())
         }));
 
-        let file_address: u64 = unwrap_or_log!(ent.file_address(self));
-        let file_size: usize = unwrap_or_log!(unwrap_or_log!(ent.file_size(self)).try_into());
-        let comp_type = unwrap_or_log!(file_info.compression_type());
+        let extension = match unwrap_or_log!(file_info.compression_type()) {
+            CompressionType::Zip => "zip",
+            CompressionType::Uncompressed => "xml",
+        };
+        let url = XmlUrl {
+            location: XmlLocation::Local {
+                // Same naming as `GenTL` producers use for XML files on the device memory.
+                file_name: format!(
+                    "{}_{}_{}.{}",
+                    self.info.vendor_name, self.info.model_name, version, extension
+                ),
+                address: unwrap_or_log!(ent.file_address(self)),
+                size: unwrap_or_log!(unwrap_or_log!(ent.file_size(self)).try_into()),
+            },
+            schema_version: Some(file_info.schema_version()),
+            sha1_hash: unwrap_or_log!(ent.sha1_hash(self)),
+        };
 
         // Store current capacity so that we can set back it after XML retrieval because this needs exceptional large size of internal buffer.
         let current_capacity = self.buffer_capacity();
-        let mut buf = vec![0; file_size];
-        unwrap_or_log!(self.read(file_address, &mut buf));
+        let resolver = self.xml_resolver.clone();
+        let xml = resolver.resolve(&url, self);
         self.resize_buffer(current_capacity);
 
-        // Verify retrieved xml has correct hash.
-        unwrap_or_log!(self.verify_xml(&buf, ent));
-
-        match comp_type {
-            CompressionType::Zip => {
-                let mut zip = zip::ZipArchive::new(std::io::Cursor::new(buf)).unwrap();
-                if zip.len() != 1 {
-                    return Err(zip_err("more than one files in zipped GenApi XML"));
-                }
-                let mut file = unwrap_or_log!(zip.by_index(0).map_err(zip_err));
-                let file_size: usize = unwrap_or_log!(file.size().try_into());
-                let mut xml = Vec::with_capacity(file_size);
-                unwrap_or_log!(file.read_to_end(&mut xml).map_err(zip_err));
-                Ok(String::from_utf8_lossy(&xml).into())
-            }
-
-            CompressionType::Uncompressed => Ok(String::from_utf8_lossy(&buf).into()),
-        }
+        Ok(unwrap_or_log!(xml))
     }
 
     fn enable_streaming(&mut self) -> ControlResult<()> {
@@ -494,6 +785,56 @@ impl DeviceControl for ControlHandle {
     }
 }
 
+impl TimestampLatch for ControlHandle {
+    fn latch_timestamp(&mut self) -> ControlResult<()> {
+        let abrm = self.abrm()?;
+        abrm.set_timestamp_latch_bit(self)
+    }
+
+    fn latched_timestamp(&mut self) -> ControlResult<u64> {
+        let abrm = self.abrm()?;
+        abrm.timestamp(self)
+    }
+
+    fn timestamp_increment(&mut self) -> ControlResult<u64> {
+        let abrm = self.abrm()?;
+        abrm.timestamp_increment(self)
+    }
+}
+
+impl Reconnect<StreamHandle> for ControlHandle {
+    fn reconnect(&mut self, strm: &mut StreamHandle) -> CameleonResult<bool> {
+        let (ctrl_channel, strm_channel) = match self.find_channels()? {
+            Some(channels) => channels,
+            None => return Ok(false),
+        };
+
+        self.close().ok();
+        self.inner = ctrl_channel;
+        self.config = ConnectionConfig::default();
+        self.next_req_id = 0;
+        self.abrm = None;
+        self.sbrm = None;
+        self.sirm = None;
+        self.manifest_table = None;
+        strm.replace_channel(strm_channel);
+
+        Ok(true)
+    }
+
+    fn set_write_journal(&mut self, enabled: bool) {
+        self.journal = if enabled {
+            Some(self.journal.take().unwrap_or_default())
+        } else {
+            None
+        };
+    }
+
+    fn write_journal(&self) -> Option<WriteJournal> {
+        self.journal.clone()
+    }
+}
+
 impl Drop for ControlHandle {
     fn drop(&mut self) {
         if let Err(e) = self.close() {
@@ -553,13 +894,43 @@ impl SharedControlHandle {
         #[must_use]
         pub fn retry_count(&self) -> u16,
         /// Thread safe version of [`ControlHandle::set_retry_count`].
-        pub fn set_retry_count(&self, count: u16) -> ()
+        pub fn set_retry_count(&self, count: u16) -> (),
+        /// Thread safe version of [`ControlHandle::is_stacked_commands_supported`].
+        #[must_use]
+        pub fn is_stacked_commands_supported(&self) -> bool,
+        /// Thread safe version of [`ControlHandle::set_xml_resolver`].
+        pub fn set_xml_resolver(&self, resolver: XmlResolver) -> ()
     );
 
     /// Returns the device info of the handle.
     pub fn device_info(&self) -> u3v::DeviceInfo {
         self.0.lock().unwrap().device_info().clone()
     }
+
+    /// Thread safe version of [`ControlHandle::xml_resolver`].
+    #[must_use]
+    pub fn xml_resolver(&self) -> XmlResolver {
+        self.0.lock().unwrap().xml_resolver().clone()
+    }
+}
+
+impl TimestampLatch for SharedControlHandle {
+    impl_shared_control_handle! {
+        fn latch_timestamp(&mut self) -> ControlResult<()>,
+        fn latched_timestamp(&mut self) -> ControlResult<u64>,
+        fn timestamp_increment(&mut self) -> ControlResult<u64>
+    }
+}
+
+impl Reconnect<StreamHandle> for SharedControlHandle {
+    impl_shared_control_handle! {
+        fn write_journal(&self) -> Option<WriteJournal>
+    }
+
+    impl_shared_control_handle! {
+        fn reconnect(&mut self, strm: &mut StreamHandle) -> CameleonResult<bool>,
+        fn set_write_journal(&mut self, enabled: bool) -> ()
+    }
 }
 
 impl DeviceControl for SharedControlHandle {
@@ -572,6 +943,8 @@ impl DeviceControl for SharedControlHandle {
         fn close(&mut self) -> ControlResult<()>,
         fn read(&mut self, address: u64, buf: &mut [u8]) -> ControlResult<()>,
         fn write(&mut self, address: u64, data: &[u8]) -> ControlResult<()>,
+        fn read_batch(&mut self, entries: &mut [(u64, &mut [u8])]) -> ControlResult<()>,
+        fn write_batch(&mut self, entries: &[(u64, &[u8])]) -> ControlResult<()>,
         fn genapi(&mut self) -> ControlResult<String>,
         fn enable_streaming(&mut self) -> ControlResult<()>,
         fn disable_streaming(&mut self) -> ControlResult<()>
@@ -591,6 +964,9 @@ struct ConnectionConfig {
 
     /// Maximum length of a acknowledge sent to host from device. Unit is byte.
     maximum_ack_length: u32,
+
+    /// `true` if stacked commands are sent for batched reads and writes.
+    is_stacked_commands_supported: bool,
 }
 
 impl Default for ConnectionConfig {
@@ -600,6 +976,7 @@ impl Default for ConnectionConfig {
             retry_count: 3,
             maximum_cmd_length: INITIAL_MAXIMUM_CMD_LENGTH,
             maximum_ack_length: INITIAL_MAXIMUM_ACK_LENGTH,
+            is_stacked_commands_supported: false,
         }
     }
 }
@@ -615,3 +992,380 @@ impl From<ControlHandle> for Box<dyn DeviceControl> {
         Box::new(ctrl)
     }
 }
+
+/// Converts the status of an ack packet into [`ProtocolStatus`], returns `None` if the status
+/// indicates success.
+fn protocol_status(status: ack::Status) -> Option<ProtocolStatus> {
+    use ack::{GenCpStatus, StatusKind, UsbSpecificStatus};
+
+    let status = match status.kind() {
+        StatusKind::GenCp(status) => match status {
+            GenCpStatus::Success => return None,
+            GenCpStatus::NotImplemented => ProtocolStatus::NotImplemented,
+            GenCpStatus::InvalidParameter => ProtocolStatus::InvalidParameter,
+            GenCpStatus::InvalidAddress => ProtocolStatus::InvalidAddress,
+            GenCpStatus::WriteProtect => ProtocolStatus::WriteProtect,
+            GenCpStatus::BadAlignment => ProtocolStatus::BadAlignment,
+            GenCpStatus::AccessDenied => ProtocolStatus::AccessDenied,
+            GenCpStatus::Busy => ProtocolStatus::Busy,
+            GenCpStatus::Timeout => ProtocolStatus::Timeout,
+            GenCpStatus::InvalidHeader => ProtocolStatus::InvalidHeader,
+            GenCpStatus::WrongConfig => ProtocolStatus::WrongConfig,
+            GenCpStatus::GenericError => ProtocolStatus::GenericError,
+        },
+        StatusKind::UsbSpecific(status) => ProtocolStatus::U3v(match status {
+            UsbSpecificStatus::ResendNotSupported => U3vStatus::ResendNotSupported,
+            UsbSpecificStatus::StreamEndpointHalted => U3vStatus::StreamEndpointHalted,
+            UsbSpecificStatus::PayloadSizeNotAligned => U3vStatus::PayloadSizeNotAligned,
+            UsbSpecificStatus::EventEndpointHalted => U3vStatus::EventEndpointHalted,
+            UsbSpecificStatus::InvalidSiState => U3vStatus::InvalidSiState,
+        }),
+        StatusKind::DeviceSpecific => ProtocolStatus::DeviceSpecific(status.code()),
+    };
+
+    Some(status)
+}
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use cameleon_device::{
+        emulator::{ControlFault, EmulatorBuilder, FaultSchedule},
+        u3v::register_map::abrm,
+    };
+
+    use super::super::{open_emulated_by, CameraFilter};
+    use super::*;
+    use crate::{genapi::ParamsCtxt, test_utils::params_ctxt, CameleonError};
+
+    fn open_emulated(
+        serial_number: &str,
+        configure: impl FnOnce(EmulatorBuilder) -> EmulatorBuilder,
+    ) -> ControlHandle {
+        configure(EmulatorBuilder::new().serial_number(serial_number).unwrap()).build();
+        let filter = CameraFilter::new().serial_number(serial_number);
+        let camera = open_emulated_by(&filter).unwrap().unwrap();
+        camera.ctrl
+    }
+
+    /// Opens an emulator whose first command after [`ControlHandle::open`] is faulted.
+    fn open_with_fault(serial_number: &str, fault: ControlFault) -> ControlHandle {
+        // Count commands sent while opening a healthy device.
+        let num_open_commands = open_emulated(&format!("{}H", serial_number), |b| b).next_req_id;
+        let schedule = FaultSchedule::Once(u64::from(num_open_commands));
+        open_emulated(serial_number, |b| b.control_fault(fault, schedule).unwrap())
+    }
+
+    fn read_serial_number(ctrl: &mut ControlHandle) -> ControlResult<String> {
+        let (serial_addr, _) = abrm::SERIAL_NUMBER;
+        let mut buf = [0; 8];
+        ctrl.read(serial_addr, &mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+
+    /// Writes two halves of the user defined name in a batch, then reads them back together with
+    /// the serial number in a batch.
+    fn batch_round_trip(ctrl: &mut ControlHandle, serial_number: &str) {
+        let (name_addr, _) = abrm::USER_DEFINED_NAME;
+        let (serial_addr, _) = abrm::SERIAL_NUMBER;
+
+        ctrl.write_batch(&[(name_addr, b"Batch"), (name_addr + 5, b"Test\0")])
+            .unwrap();
+
+        let mut name = [0; 10];
+        let mut serial = vec![0; serial_number.len()];
+        ctrl.read_batch(&mut [(name_addr, &mut name), (serial_addr, &mut serial)])
+            .unwrap();
+        assert_eq!(&name, b"BatchTest\0");
+        assert_eq!(serial, serial_number.as_bytes());
+    }
+
+    #[test]
+    fn test_batch_with_stacked_commands() {
+        let mut ctrl = open_emulated("BATCH001", |b| b);
+        assert!(ctrl.is_stacked_commands_supported());
+
+        batch_round_trip(&mut ctrl, "BATCH001");
+        assert!(ctrl.is_stacked_commands_supported());
+    }
+
+    #[test]
+    fn test_batch_without_stacked_commands() {
+        let mut ctrl = open_emulated("BATCH002", |b| b.stacked_commands(false));
+        assert!(!ctrl.is_stacked_commands_supported());
+
+        batch_round_trip(&mut ctrl, "BATCH002");
+    }
+
+    #[test]
+    fn test_batch_falls_back_on_not_implemented() {
+        let mut ctrl = open_emulated("BATCH003", |b| b.stacked_commands(false));
+        let (name_addr, _) = abrm::USER_DEFINED_NAME;
+
+        // Pretend the capability claims stacked commands so that the device rejects them.
+        ctrl.config.is_stacked_commands_supported = true;
+        ctrl.write_batch(&[(name_addr, b"Fall"), (name_addr + 4, b"back\0")])
+            .unwrap();
+        assert!(!ctrl.is_stacked_commands_supported());
+
+        ctrl.config.is_stacked_commands_supported = true;
+        let mut head = [0; 4];
+        let mut tail = [0; 5];
+        ctrl.read_batch(&mut [(name_addr, &mut head), (name_addr + 4, &mut tail)])
+            .unwrap();
+        assert_eq!(&head, b"Fall");
+        assert_eq!(&tail, b"back\0");
+        assert!(!ctrl.is_stacked_commands_supported());
+    }
+
+    #[test]
+    fn test_pending_fault() {
+        let pending = ControlFault::Pending {
+            count: 2,
+            timeout: Duration::from_millis(1),
+        };
+        let mut ctrl = open_emulated("FAULT001", |b| {
+            b.control_fault(pending, FaultSchedule::Always).unwrap()
+        });
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT001");
+
+        // The device keeps answering pending until the retry count is exhausted.
+        ctrl.set_retry_count(2);
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Io(..))
+        ));
+    }
+
+    #[test]
+    fn test_drop_ack_fault() {
+        let mut ctrl = open_with_fault("FAULT002", ControlFault::DropAck);
+        ctrl.set_timeout_duration(Duration::from_millis(50));
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Timeout)
+        ));
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT002");
+    }
+
+    #[test]
+    fn test_corrupt_ack_fault() {
+        let mut ctrl = open_with_fault("FAULT003", ControlFault::CorruptAck);
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Io(..))
+        ));
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT003");
+    }
+
+    #[test]
+    fn test_wrong_request_id_fault() {
+        let mut ctrl = open_with_fault("FAULT004", ControlFault::WrongRequestId);
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Io(..))
+        ));
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT004");
+    }
+
+    #[test]
+    fn test_halt_fault() {
+        let mut ctrl = open_with_fault("FAULT005", ControlFault::Halt);
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Io(..))
+        ));
+
+        // Reopening clears the halt.
+        ctrl.close().unwrap();
+        ctrl.open().unwrap();
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT005");
+    }
+
+    #[test]
+    fn test_latency() {
+        let mut ctrl = open_emulated("FAULT006", |b| b.latency(Duration::from_millis(50)));
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT006");
+
+        ctrl.set_timeout_duration(Duration::from_millis(10));
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let mut ctrl = open_emulated("FAULT007", |b| {
+            b.disconnect_after(Duration::from_millis(200))
+        });
+        assert_eq!(read_serial_number(&mut ctrl).unwrap(), "FAULT007");
+
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(matches!(
+            read_serial_number(&mut ctrl),
+            Err(ControlError::Disconnected)
+        ));
+    }
+
+    /// Builds a `WriteMem` acknowledge packet without `SCD`.
+    fn ack_packet(status: u16, request_id: u16) -> Vec<u8> {
+        let mut buf = 0x4356_3355_u32.to_le_bytes().to_vec();
+        buf.extend_from_slice(&status.to_le_bytes());
+        buf.extend_from_slice(&0x0803_u16.to_le_bytes());
+        buf.extend_from_slice(&0_u16.to_le_bytes());
+        buf.extend_from_slice(&request_id.to_le_bytes());
+        buf
+    }
+
+    fn parse_status(code: u16) -> ControlResult<Option<ProtocolStatus>> {
+        let buf = ack_packet(code, 0);
+        let ack = ack::AckPacket::parse(&buf)?;
+        Ok(protocol_status(*ack.status()))
+    }
+
+    #[test]
+    fn test_protocol_status() {
+        assert_eq!(parse_status(0x0000).unwrap(), None);
+
+        let statuses = [
+            (0x8001, ProtocolStatus::NotImplemented),
+            (0x8002, ProtocolStatus::InvalidParameter),
+            (0x8003, ProtocolStatus::InvalidAddress),
+            (0x8004, ProtocolStatus::WriteProtect),
+            (0x8005, ProtocolStatus::BadAlignment),
+            (0x8006, ProtocolStatus::AccessDenied),
+            (0x8007, ProtocolStatus::Busy),
+            (0x800B, ProtocolStatus::Timeout),
+            (0x800E, ProtocolStatus::InvalidHeader),
+            (0x800F, ProtocolStatus::WrongConfig),
+            (0x8FFF, ProtocolStatus::GenericError),
+            (0xA001, ProtocolStatus::U3v(U3vStatus::ResendNotSupported)),
+            (0xA002, ProtocolStatus::U3v(U3vStatus::StreamEndpointHalted)),
+            (
+                0xA003,
+                ProtocolStatus::U3v(U3vStatus::PayloadSizeNotAligned),
+            ),
+            (0xA004, ProtocolStatus::U3v(U3vStatus::InvalidSiState)),
+            (0xA005, ProtocolStatus::U3v(U3vStatus::EventEndpointHalted)),
+            // Device specific statuses keep their raw code.
+            (0xC001, ProtocolStatus::DeviceSpecific(0xC001)),
+            (0xC123, ProtocolStatus::DeviceSpecific(0xC123)),
+        ];
+        for &(code, status) in &statuses {
+            assert_eq!(parse_status(code).unwrap(), Some(status), "{:#X}", code);
+        }
+
+        // Unknown codes in the `GenCP` and `USB3 Vision` namespaces make the packet invalid.
+        for &code in &[0x8008, 0x8010, 0xA006, 0xE001] {
+            assert!(
+                matches!(parse_status(code), Err(ControlError::Io(..))),
+                "{:#X}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_ack() {
+        let ctrl = open_emulated("STATUS01", |b| b);
+        let req_id = ctrl.next_req_id;
+        let request = ControlRequest::WriteMem {
+            address: 0x1C4,
+            len: 8,
+        };
+        let verify = |status, request_id| {
+            let buf = ack_packet(status, request_id);
+            ctrl.verify_ack(&ack::AckPacket::parse(&buf).unwrap(), request)
+        };
+
+        assert!(verify(0x0000, req_id).is_ok());
+        assert!(matches!(
+            verify(0x8004, req_id),
+            Err(ControlError::ProtocolStatus {
+                status: ProtocolStatus::WriteProtect,
+                request: req,
+            }) if req == request
+        ));
+        // The status is reported even if the request id doesn't match.
+        assert!(matches!(
+            verify(0xA002, req_id.wrapping_add(1)),
+            Err(ControlError::ProtocolStatus {
+                status: ProtocolStatus::U3v(U3vStatus::StreamEndpointHalted),
+                ..
+            })
+        ));
+        assert!(matches!(
+            verify(0x0000, req_id.wrapping_add(1)),
+            Err(ControlError::Io(..))
+        ));
+    }
+
+    #[test]
+    fn test_write_chunks() {
+        let mut ctrl = open_emulated("STATUS02", |b| b);
+        // Each `WriteMem` command carries at most 8 bytes of data.
+        ctrl.config.maximum_cmd_length = 28;
+        let (name_addr, _) = abrm::USER_DEFINED_NAME;
+        let (capability_addr, _) = abrm::DEVICE_CAPABILITY;
+        let data: Vec<u8> = (1..=20).collect();
+
+        ctrl.write(name_addr, &data).unwrap();
+        let mut buf = vec![0; data.len()];
+        ctrl.read(name_addr, &mut buf).unwrap();
+        assert_eq!(buf, data);
+
+        // The second chunk hits the read only `DeviceCapability`, so the error reports the
+        // address of the chunk instead of the start address.
+        let start = capability_addr - 8;
+        let err = ctrl.write(start, &data).unwrap_err();
+        assert_eq!(
+            err.protocol_status(),
+            Some((
+                ProtocolStatus::WriteProtect,
+                ControlRequest::WriteMem {
+                    address: capability_addr,
+                    len: 8,
+                }
+            ))
+        );
+        // The chunk before the failing one is already written.
+        let mut buf = [0; 8];
+        ctrl.read(start, &mut buf).unwrap();
+        assert_eq!(buf, data[..8]);
+    }
+
+    #[test]
+    fn test_protocol_status_through_genapi() {
+        const SERIAL_NUMBER: &str = r#"
+        <StringReg Name="DeviceSerialNumber">
+            <Address>0x144</Address>
+            <Length>64</Length>
+            <AccessMode>RW</AccessMode>
+            <pPort>Device</pPort>
+        </StringReg>
+        "#;
+
+        let mut ctrl = open_emulated("STATUS03", |b| b);
+        let ParamsCtxt { ctxt, .. } = params_ctxt(SERIAL_NUMBER, 0);
+        let mut ctxt = ParamsCtxt {
+            ctrl: &mut ctrl,
+            ctxt,
+        };
+        let node = ctxt
+            .node("DeviceSerialNumber")
+            .unwrap()
+            .as_string(&ctxt)
+            .unwrap();
+        assert_eq!(node.value(&mut ctxt).unwrap(), "STATUS03");
+
+        // The device refuses the write since the register is read only.
+        let err = CameleonError::from(node.set_value(&mut ctxt, "X".into()).unwrap_err());
+        assert!(matches!(
+            err.protocol_status(),
+            Some((
+                ProtocolStatus::WriteProtect,
+                ControlRequest::WriteMem { address: 0x144, .. }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains low level event handle of `U3V` device.
+
+use std::time::Duration;
+
+#[cfg(feature = "emulator")]
+use cameleon_device::emulator;
+#[cfg(feature = "libusb")]
+use cameleon_device::u3v;
+use cameleon_device::u3v::protocol::event::EventPacket;
+use tracing::error;
+
+use crate::{ControlError, ControlResult, DeviceControl};
+
+use super::{
+    channel::ReceiveChannel,
+    register_map::{Abrm, Eirm},
+};
+
+/// Size of the buffer used when the device doesn't report maximum event transfer length.
+const DEFAULT_EVENT_BUFFER_SIZE: usize = 1024;
+
+/// An event sent from the device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceEvent {
+    /// ID of the event, which corresponds to `EventID` attribute of the `GenApi` `Port` node.
+    pub event_id: u16,
+
+    /// Timestamp of the device when the event occurred.
+    pub timestamp: u64,
+
+    /// Request ID of the event packet which contains the event.
+    pub request_id: u16,
+
+    /// Event specific data.
+    pub data: Vec<u8>,
+}
+
+impl DeviceEvent {
+    /// Parses a `U3V` event packet, which may contain multiple events.
+    pub fn parse_packet(packet: &[u8]) -> ControlResult<Vec<Self>> {
+        let packet = EventPacket::parse(packet)?;
+        let request_id = packet.request_id();
+
+        Ok(packet
+            .scd
+            .into_iter()
+            .map(|scd| Self {
+                event_id: scd.event_id,
+                timestamp: scd.timestamp,
+                request_id,
+                data: scd.data.to_vec(),
+            })
+            .collect())
+    }
+}
+
+/// This type is used to receive event packets from the device.
+///
+/// The event interface of the device must be enabled by [`EventHandle::enable`] to receive
+/// events.
+pub struct EventHandle {
+    inner: ReceiveChannel,
+    buf: Vec<u8>,
+    eirm: Option<Eirm>,
+}
+
+impl EventHandle {
+    /// Opens the handle.
+    pub fn open(&mut self) -> ControlResult<()> {
+        Ok(self.inner.open()?)
+    }
+
+    /// Closes the handle.
+    pub fn close(&mut self) -> ControlResult<()> {
+        Ok(self.inner.close()?)
+    }
+
+    /// Returns `true` if the handle is opened.
+    #[must_use]
+    pub fn is_opened(&self) -> bool {
+        self.inner.is_opened()
+    }
+
+    /// Enables the event interface of the device.
+    ///
+    /// Returns [`ControlError::InvalidDevice`] if the device doesn't have `EIRM`.
+    pub fn enable<Ctrl: DeviceControl + ?Sized>(&mut self, ctrl: &mut Ctrl) -> ControlResult<()> {
+        let eirm = match self.eirm {
+            Some(eirm) => eirm,
+            None => {
+                let eirm = Abrm::new(ctrl)?.sbrm(ctrl)?.eirm(ctrl)?.ok_or_else(|| {
+                    ControlError::InvalidDevice("the u3v device doesn't have `EIRM`".into())
+                })?;
+                self.eirm = Some(eirm);
+                eirm
+            }
+        };
+
+        let max_len = eirm.maximum_event_transfer_length(ctrl)? as usize;
+        let buf_size = if max_len == 0 {
+            DEFAULT_EVENT_BUFFER_SIZE
+        } else {
+            max_len
+        };
+        self.buf.resize(buf_size, 0);
+
+        eirm.enable_event(ctrl)
+    }
+
+    /// Disables the event interface of the device.
+    pub fn disable<Ctrl: DeviceControl + ?Sized>(&mut self, ctrl: &mut Ctrl) -> ControlResult<()> {
+        match self.eirm {
+            Some(eirm) => eirm.disable_event(ctrl),
+            None => Ok(()),
+        }
+    }
+
+    /// Maximum size of an event packet in bytes.
+    #[must_use]
+    pub fn maximum_packet_size(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Receives an event packet as raw bytes.
+    ///
+    /// The returned packet is a valid `U3V` event packet, which can be parsed by
+    /// [`DeviceEvent::parse_packet`].
+    /// Returns [`ControlError::Timeout`] if no packet arrives within `timeout`.
+    pub fn recv_packet(&mut self, timeout: Duration) -> ControlResult<&[u8]> {
+        let len = self.inner.recv(&mut self.buf, timeout)?;
+        let packet = &self.buf[..len];
+        if let Err(e) = EventPacket::parse(packet) {
+            error!(?e);
+            return Err(ControlError::InvalidData(
+                format!("invalid event packet: {}", e).into(),
+            ));
+        }
+
+        Ok(packet)
+    }
+
+    /// Receives events sent from the device.
+    ///
+    /// Returns [`ControlError::Timeout`] if no event arrives within `timeout`.
+    pub fn recv(&mut self, timeout: Duration) -> ControlResult<Vec<DeviceEvent>> {
+        let packet = self.recv_packet(timeout)?;
+        DeviceEvent::parse_packet(packet)
+    }
+
+    #[cfg(feature = "libusb")]
+    pub(super) fn new(device: &u3v::Device) -> ControlResult<Option<Self>> {
+        let inner = device.event_channel()?;
+        Ok(inner.map(|inner| Self::with_channel(inner.into())))
+    }
+
+    #[cfg(feature = "emulator")]
+    pub(super) fn new_emulated(device: &emulator::Device) -> ControlResult<Option<Self>> {
+        let inner = device.event_channel()?;
+        Ok(inner.map(|inner| Self::with_channel(inner.into())))
+    }
+
+    fn with_channel(inner: ReceiveChannel) -> Self {
+        Self {
+            inner,
+            buf: vec![0; DEFAULT_EVENT_BUFFER_SIZE],
+            eirm: None,
+        }
+    }
+}
+
+impl Drop for EventHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.close() {
+            error!(?e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serializes an event packet header followed by `scd`.
+    fn packet(prefix: u32, request_id: u16, scd: &[u8]) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend(&prefix.to_le_bytes());
+        buf.extend(&(1_u16 << 14).to_le_bytes()); // Request ack.
+        buf.extend(&0x0c00_u16.to_le_bytes());
+        buf.extend(&(scd.len() as u16).to_le_bytes());
+        buf.extend(&request_id.to_le_bytes());
+        buf.extend(scd);
+        buf
+    }
+
+    fn scd(event_size: u16, event_id: u16, timestamp: u64, data: &[u8]) -> Vec<u8> {
+        let mut buf = vec![];
+        buf.extend(&event_size.to_le_bytes());
+        buf.extend(&event_id.to_le_bytes());
+        buf.extend(&timestamp.to_le_bytes());
+        buf.extend(data);
+        buf
+    }
+
+    const PREFIX: u32 = 0x4556_3355;
+
+    #[test]
+    fn test_parse_single_event() {
+        let raw = packet(
+            PREFIX,
+            3,
+            &scd(0, 0x9001, 0x0123_4567_89ab_cdef, &[1, 2, 3]),
+        );
+
+        let events = DeviceEvent::parse_packet(&raw).unwrap();
+        assert_eq!(
+            events,
+            vec![DeviceEvent {
+                event_id: 0x9001,
+                timestamp: 0x0123_4567_89ab_cdef,
+                request_id: 3,
+                data: vec![1, 2, 3],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_multiple_events() {
+        let mut raw_scd = scd(14, 0x9001, 10, &[0xaa, 0xbb]);
+        raw_scd.extend(scd(12, 0x9002, 20, &[]));
+        let raw = packet(PREFIX, 7, &raw_scd);
+
+        let events = DeviceEvent::parse_packet(&raw).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event_id, 0x9001);
+        assert_eq!(events[0].timestamp, 10);
+        assert_eq!(events[0].data, vec![0xaa, 0xbb]);
+        assert_eq!(events[1].event_id, 0x9002);
+        assert_eq!(events[1].timestamp, 20);
+        assert!(events[1].data.is_empty());
+        assert!(events.iter().all(|event| event.request_id == 7));
+    }
+
+    #[test]
+    fn test_parse_bad_prefix() {
+        let raw = packet(0x4356_3355, 0, &scd(0, 0x9001, 0, &[]));
+        assert!(DeviceEvent::parse_packet(&raw).is_err());
+    }
+
+    #[test]
+    fn test_parse_short_packet() {
+        let raw = packet(PREFIX, 0, &scd(0, 0x9001, 0, &[1, 2, 3, 4]));
+
+        // Truncated in the header, in the SCD header, and in the event data.
+        for len in &[6, 18, raw.len() - 1] {
+            assert!(DeviceEvent::parse_packet(&raw[..*len]).is_err());
+        }
+
+        // Event size overruns the SCD length in the header.
+        let raw = packet(PREFIX, 0, &scd(20, 0x9001, 0, &[1, 2]));
+        assert!(DeviceEvent::parse_packet(&raw).is_err());
+    }
+}
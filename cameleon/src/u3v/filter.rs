@@ -0,0 +1,183 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides [`CameraFilter`] which selects cameras by their device information.
+
+use super::DeviceInfo;
+
+/// Selects cameras by fields of [`DeviceInfo`].
+///
+/// All conditions set to the filter must match, and each condition is compared exactly.
+/// A filter without any condition matches all cameras.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cameleon::u3v::{self, CameraFilter};
+///
+/// // Enumerates cameras of the vendor.
+/// let filter = CameraFilter::new().vendor_name("Cameleon");
+/// let cameras = u3v::enumerate_cameras_with(&filter).unwrap();
+///
+/// // Opens the camera connected to port 3 of the hub which is connected to port 1 of bus 2.
+/// let filter = CameraFilter::new().bus_path("2-1.3");
+/// if let Some(camera) = u3v::open_by(&filter).unwrap() {
+///     println!("{:?}", camera.info());
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CameraFilter {
+    guid: Option<String>,
+    serial_number: Option<String>,
+    vendor_name: Option<String>,
+    model_name: Option<String>,
+    user_defined_name: Option<String>,
+    bus_path: Option<String>,
+}
+
+impl CameraFilter {
+    /// Creates a filter which matches all cameras.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches cameras with the GUID.
+    #[must_use]
+    pub fn guid(mut self, guid: &str) -> Self {
+        self.guid = Some(guid.to_string());
+        self
+    }
+
+    /// Matches cameras with the serial number.
+    #[must_use]
+    pub fn serial_number(mut self, serial_number: &str) -> Self {
+        self.serial_number = Some(serial_number.to_string());
+        self
+    }
+
+    /// Matches cameras with the vendor name.
+    #[must_use]
+    pub fn vendor_name(mut self, vendor_name: &str) -> Self {
+        self.vendor_name = Some(vendor_name.to_string());
+        self
+    }
+
+    /// Matches cameras with the model name.
+    #[must_use]
+    pub fn model_name(mut self, model_name: &str) -> Self {
+        self.model_name = Some(model_name.to_string());
+        self
+    }
+
+    /// Matches cameras with the user defined name.
+    #[must_use]
+    pub fn user_defined_name(mut self, user_defined_name: &str) -> Self {
+        self.user_defined_name = Some(user_defined_name.to_string());
+        self
+    }
+
+    /// Matches cameras connected to the USB port, e.g. `2-1.3`.
+    ///
+    /// See [`BusPath`](cameleon_device::u3v::BusPath) for the notation.
+    #[must_use]
+    pub fn bus_path(mut self, bus_path: &str) -> Self {
+        self.bus_path = Some(bus_path.to_string());
+        self
+    }
+
+    /// Returns `true` if the device matches all conditions of the filter.
+    #[must_use]
+    pub fn matches(&self, info: &DeviceInfo) -> bool {
+        fn eq(cond: &Option<String>, value: Option<&str>) -> bool {
+            cond.is_none() || cond.as_deref() == value
+        }
+
+        eq(&self.guid, Some(&info.guid))
+            && eq(&self.serial_number, Some(&info.serial_number))
+            && eq(&self.vendor_name, Some(&info.vendor_name))
+            && eq(&self.model_name, Some(&info.model_name))
+            && eq(&self.user_defined_name, info.user_defined_name.as_deref())
+            && eq(
+                &self.bus_path,
+                info.bus_path.as_ref().map(ToString::to_string).as_deref(),
+            )
+    }
+}
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use super::{super::BusPath, *};
+
+    fn device_info() -> DeviceInfo {
+        let mut info = crate::test_utils::device_info();
+        info.guid = "CAML-00000001".into();
+        info.serial_number = "CAM0001".into();
+        info.vendor_name = "Cameleon".into();
+        info.model_name = "Model".into();
+        info.user_defined_name = Some("Front".into());
+        info.bus_path = Some(BusPath {
+            bus_number: 2,
+            port_numbers: vec![1, 3],
+        });
+        info
+    }
+
+    #[test]
+    fn test_matches() {
+        let cases = [
+            (CameraFilter::new().guid("CAML-00000001"), true),
+            (CameraFilter::new().guid("CAML-00000002"), false),
+            (CameraFilter::new().serial_number("CAM0001"), true),
+            (CameraFilter::new().serial_number("CAM0002"), false),
+            (CameraFilter::new().vendor_name("Cameleon"), true),
+            (CameraFilter::new().vendor_name("cameleon"), false),
+            (CameraFilter::new().model_name("Model"), true),
+            (CameraFilter::new().model_name("Model2"), false),
+            (CameraFilter::new().user_defined_name("Front"), true),
+            (CameraFilter::new().user_defined_name("Rear"), false),
+            (CameraFilter::new().bus_path("2-1.3"), true),
+            (CameraFilter::new().bus_path("2-1"), false),
+            (
+                CameraFilter::new()
+                    .serial_number("CAM0001")
+                    .bus_path("2-1.3"),
+                true,
+            ),
+            (
+                CameraFilter::new().serial_number("CAM0001").bus_path("2-1"),
+                false,
+            ),
+        ];
+
+        let info = device_info();
+        for (filter, expected) in &cases {
+            assert_eq!(filter.matches(&info), *expected, "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_matches_missing_fields() {
+        let mut info = device_info();
+        info.user_defined_name = None;
+        info.bus_path = None;
+
+        // Conditions on missing fields never match.
+        assert!(!CameraFilter::new().user_defined_name("").matches(&info));
+        assert!(!CameraFilter::new().bus_path("").matches(&info));
+        assert!(!CameraFilter::new().bus_path("2-1.3").matches(&info));
+        // Filters without conditions on missing fields still match.
+        assert!(CameraFilter::new().serial_number("CAM0001").matches(&info));
+    }
+
+    #[test]
+    fn test_empty_filter() {
+        let mut info = device_info();
+        assert!(CameraFilter::new().matches(&info));
+
+        info.user_defined_name = None;
+        info.bus_path = None;
+        assert!(CameraFilter::new().matches(&info));
+    }
+}
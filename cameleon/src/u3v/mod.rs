@@ -46,18 +46,35 @@
 #![allow(clippy::missing_panics_doc)]
 
 pub mod control_handle;
+pub mod event_handle;
+pub mod filter;
 pub mod register_map;
 pub mod stream_handle;
+pub mod watcher;
 
+mod channel;
+
+pub use channel::{ControlChannel, ReceiveChannel};
 pub use control_handle::{ControlHandle, SharedControlHandle};
+pub use event_handle::{DeviceEvent, EventHandle};
+pub use filter::CameraFilter;
 pub use stream_handle::{StreamHandle, StreamParams};
+#[cfg(feature = "libusb")]
+pub use watcher::watch_cameras;
+#[cfg(feature = "emulator")]
+pub use watcher::watch_emulated_cameras;
+pub use watcher::{CameraEvent, CameraWatcher};
 
-pub use cameleon_device::u3v::DeviceInfo;
+pub use cameleon_device::u3v::{BusPath, BusSpeed, DeviceInfo};
 
+#[cfg(feature = "emulator")]
+use cameleon_device::emulator;
 use cameleon_device::u3v;
+use tracing::warn;
 
 use super::{
-    genapi::DefaultGenApiCtxt, CameleonResult, Camera, CameraInfo, ControlError, StreamError,
+    genapi::DefaultGenApiCtxt, CameleonError, CameleonResult, Camera, CameraInfo, ControlError,
+    ControlResult, StreamError,
 };
 
 /// Enumerate all U3V compatible cameras connected to the host.
@@ -72,35 +89,255 @@ use super::{
 /// // Enumerate cameras connected to the host.
 /// let mut cameras = u3v::enumerate_cameras().unwrap();
 /// ```
+#[cfg(feature = "libusb")]
 pub fn enumerate_cameras() -> CameleonResult<Vec<Camera<ControlHandle, StreamHandle>>> {
+    enumerate_cameras_with(&CameraFilter::new())
+}
+
+/// Enumerate U3V compatible cameras connected to the host which match `filter`.
+///
+/// Devices which are busy, e.g. opened by another application, are skipped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cameleon::u3v::{self, CameraFilter};
+///
+/// // Enumerate cameras of the model.
+/// let filter = CameraFilter::new().model_name("Cameleon Model");
+/// let cameras = u3v::enumerate_cameras_with(&filter).unwrap();
+/// ```
+#[cfg(feature = "libusb")]
+pub fn enumerate_cameras_with(
+    filter: &CameraFilter,
+) -> CameleonResult<Vec<Camera<ControlHandle, StreamHandle>>> {
     let devices = u3v::enumerate_devices().map_err(ControlError::from)?;
 
     let mut cameras: Vec<Camera<ControlHandle, StreamHandle>> = Vec::with_capacity(devices.len());
 
     for dev in devices {
-        let ctrl = ControlHandle::new(&dev)?;
-        let strm = if let Some(strm) = StreamHandle::new(&dev)? {
-            strm
-        } else {
+        if !filter.matches(&dev.device_info) {
             continue;
-        };
-        let ctxt = None;
+        }
 
-        let dev_info = dev.device_info;
-        let camera_info = CameraInfo {
-            vendor_name: dev_info.vendor_name,
-            model_name: dev_info.model_name,
-            serial_number: dev_info.serial_number,
-        };
+        let camera = new_camera_or_skip(
+            ControlHandle::new(&dev),
+            StreamHandle::new(&dev),
+            dev.device_info,
+        )?;
+        cameras.extend(camera);
+    }
+
+    Ok(cameras)
+}
 
-        let camera: Camera<ControlHandle, StreamHandle, DefaultGenApiCtxt> =
-            Camera::new(ctrl, strm, ctxt, camera_info);
-        cameras.push(camera)
+/// Opens the first U3V compatible camera connected to the host which matches `filter`.
+///
+/// Cameras which are busy, e.g. opened by another application, are skipped.
+/// Returns `None` if no camera can be opened.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cameleon::u3v::{self, CameraFilter};
+///
+/// // Open the camera by its serial number.
+/// let filter = CameraFilter::new().serial_number("CAM1984");
+/// let mut camera = u3v::open_by(&filter).unwrap().expect("camera not found");
+/// camera.load_context().unwrap();
+/// ```
+#[cfg(feature = "libusb")]
+pub fn open_by(
+    filter: &CameraFilter,
+) -> CameleonResult<Option<Camera<ControlHandle, StreamHandle>>> {
+    open_first(enumerate_cameras_with(filter)?)
+}
+
+/// Enumerate all emulated U3V cameras built by [`cameleon_device::emulator::EmulatorBuilder`].
+///
+/// Emulated cameras are operated in the same way as real cameras.
+///
+/// # Examples
+///
+/// ```rust
+/// use cameleon::u3v;
+/// use cameleon_device::emulator::EmulatorBuilder;
+///
+/// // Build an emulator.
+/// EmulatorBuilder::new().build();
+///
+/// // Enumerate emulated cameras.
+/// let mut cameras = u3v::enumerate_emulated_cameras().unwrap();
+/// assert_eq!(cameras.len(), 1);
+/// ```
+#[cfg(feature = "emulator")]
+pub fn enumerate_emulated_cameras() -> CameleonResult<Vec<Camera<ControlHandle, StreamHandle>>> {
+    enumerate_emulated_cameras_with(&CameraFilter::new())
+}
+
+/// Enumerate emulated U3V cameras which match `filter`.
+///
+/// Devices which are busy are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use cameleon::u3v::{self, CameraFilter};
+/// use cameleon_device::emulator::EmulatorBuilder;
+///
+/// EmulatorBuilder::new().serial_number("CAM0001").unwrap().build();
+/// EmulatorBuilder::new().serial_number("CAM0002").unwrap().build();
+///
+/// let filter = CameraFilter::new().serial_number("CAM0002");
+/// let cameras = u3v::enumerate_emulated_cameras_with(&filter).unwrap();
+/// assert_eq!(cameras.len(), 1);
+/// assert_eq!(cameras[0].info().serial_number, "CAM0002");
+/// ```
+#[cfg(feature = "emulator")]
+pub fn enumerate_emulated_cameras_with(
+    filter: &CameraFilter,
+) -> CameleonResult<Vec<Camera<ControlHandle, StreamHandle>>> {
+    let devices = emulator::enumerate_devices().map_err(ControlError::from)?;
+
+    let mut cameras: Vec<Camera<ControlHandle, StreamHandle>> = Vec::with_capacity(devices.len());
+
+    for dev in devices {
+        if !filter.matches(&dev.device_info) {
+            continue;
+        }
+
+        let camera = new_camera_or_skip(
+            ControlHandle::new_emulated(&dev),
+            StreamHandle::new_emulated(&dev),
+            dev.device_info,
+        )?;
+        cameras.extend(camera);
     }
 
     Ok(cameras)
 }
 
+/// Opens the first emulated U3V camera which matches `filter`.
+///
+/// Cameras which are busy are skipped. Returns `None` if no camera can be opened.
+///
+/// # Examples
+///
+/// ```rust
+/// use cameleon::u3v::{self, CameraFilter};
+/// use cameleon_device::emulator::EmulatorBuilder;
+///
+/// EmulatorBuilder::new().serial_number("CAM1985").unwrap().build();
+///
+/// let filter = CameraFilter::new().serial_number("CAM1985");
+/// let mut camera = u3v::open_emulated_by(&filter).unwrap().unwrap();
+///
+/// // The camera is already opened, so it's skipped.
+/// assert!(u3v::open_emulated_by(&filter).unwrap().is_none());
+/// camera.close().unwrap();
+/// ```
+#[cfg(feature = "emulator")]
+pub fn open_emulated_by(
+    filter: &CameraFilter,
+) -> CameleonResult<Option<Camera<ControlHandle, StreamHandle>>> {
+    open_first(enumerate_emulated_cameras_with(filter)?)
+}
+
+/// Opens the event handle of the U3V device described by `device_info`.
+///
+/// Returns `None` if the device doesn't have an event interface.
+/// Returns [`ControlError::Disconnected`] if the device isn't connected to the host.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cameleon::u3v;
+///
+/// let mut cameras = u3v::enumerate_cameras().unwrap();
+/// let mut camera = cameras.pop().unwrap();
+/// camera.open().unwrap();
+///
+/// let dev_info = camera.ctrl.device_info().clone();
+/// if let Some(mut event) = u3v::find_event_handle(&dev_info).unwrap() {
+///     event.open().unwrap();
+///     event.enable(&mut camera.ctrl).unwrap();
+/// }
+/// ```
+#[cfg(feature = "libusb")]
+pub fn find_event_handle(device_info: &DeviceInfo) -> ControlResult<Option<EventHandle>> {
+    let devices = u3v::enumerate_devices()?;
+    let dev = devices
+        .iter()
+        .find(|dev| dev.device_info.guid == device_info.guid)
+        .ok_or(ControlError::Disconnected)?;
+    EventHandle::new(dev)
+}
+
+/// Opens the event handle of the emulated U3V device described by `device_info`.
+///
+/// See [`find_event_handle`] for details.
+#[cfg(feature = "emulator")]
+pub fn find_emulated_event_handle(device_info: &DeviceInfo) -> ControlResult<Option<EventHandle>> {
+    let devices = emulator::enumerate_devices()?;
+    let dev = devices
+        .iter()
+        .find(|dev| dev.device_info.guid == device_info.guid)
+        .ok_or(ControlError::Disconnected)?;
+    EventHandle::new_emulated(dev)
+}
+
+/// Builds a camera from the handles. Returns `None` if the device has no stream channel or is
+/// busy.
+fn new_camera_or_skip(
+    ctrl: ControlResult<ControlHandle>,
+    strm: ControlResult<Option<StreamHandle>>,
+    dev_info: DeviceInfo,
+) -> CameleonResult<Option<Camera<ControlHandle, StreamHandle>>> {
+    match (ctrl, strm) {
+        (Ok(ctrl), Ok(Some(strm))) => Ok(Some(new_camera(ctrl, strm, dev_info))),
+        (Ok(_), Ok(None)) => Ok(None),
+        (Err(ControlError::Busy), _) | (_, Err(ControlError::Busy)) => {
+            warn!("skip busy device: {}", dev_info.guid);
+            Ok(None)
+        }
+        (Err(err), _) | (_, Err(err)) => Err(err.into()),
+    }
+}
+
+/// Opens the first camera which isn't busy, and returns it.
+fn open_first(
+    cameras: Vec<Camera<ControlHandle, StreamHandle>>,
+) -> CameleonResult<Option<Camera<ControlHandle, StreamHandle>>> {
+    for mut camera in cameras {
+        match camera.open() {
+            Ok(()) => return Ok(Some(camera)),
+            Err(CameleonError::ControlError(ControlError::Busy)) => {
+                warn!("skip busy device: {}", camera.ctrl.device_info().guid);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(None)
+}
+
+fn new_camera(
+    ctrl: ControlHandle,
+    strm: StreamHandle,
+    dev_info: DeviceInfo,
+) -> Camera<ControlHandle, StreamHandle, DefaultGenApiCtxt> {
+    let ctxt = None;
+    Camera::new(ctrl, strm, ctxt, camera_info(&dev_info))
+}
+
+fn camera_info(dev_info: &DeviceInfo) -> CameraInfo {
+    CameraInfo {
+        vendor_name: dev_info.vendor_name.clone(),
+        model_name: dev_info.model_name.clone(),
+        serial_number: dev_info.serial_number.clone(),
+    }
+}
+
 impl From<u3v::Error> for ControlError {
     fn from(err: u3v::Error) -> ControlError {
         use u3v::Error::{BufferIo, InvalidDevice, InvalidPacket, LibUsb};
@@ -50,7 +50,7 @@ use std::{convert::TryInto, time::Duration};
 
 use cameleon_device::u3v::{
     self,
-    register_map::{abrm, manifest_entry, sbrm, sirm},
+    register_map::{abrm, eirm, manifest_entry, sbrm, sirm},
 };
 
 use crate::{genapi::CompressionType, ControlError, ControlResult, DeviceControl};
@@ -466,6 +466,19 @@ impl Sbrm {
         }
     }
 
+    /// Return [`Eirm`] if it's available.
+    pub fn eirm<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+    ) -> ControlResult<Option<Eirm>> {
+        // `Abrm` is located at address 0, so `Eirm` at address 0 means the device doesn't
+        // implement it even if the capability says so.
+        Ok(self
+            .eirm_address(device)?
+            .filter(|addr| *addr != 0)
+            .map(Eirm::new))
+    }
+
     /// The initial address of `Eirm`.
     ///
     ///
@@ -771,6 +784,95 @@ impl Sirm {
     }
 }
 
+/// Represent Event Interface Register Map (EIRM).
+///
+/// To maintain consistency with the device data, `Eirm` doesn't cache any data. It means
+/// that all methods of this struct cause communication with the device every time, thus the device
+/// is expected to be opened when methods are called.
+#[derive(Clone, Copy, Debug)]
+pub struct Eirm {
+    eirm_addr: u64,
+}
+
+impl Eirm {
+    /// Constructs new `Eirm`.
+    ///
+    /// To construct `Eirm`, Use [`Sbrm::eirm`] also can be used.
+    #[must_use]
+    pub fn new(eirm_addr: u64) -> Self {
+        Self { eirm_addr }
+    }
+
+    /// Enables the event interface.
+    pub fn enable_event<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+    ) -> ControlResult<()> {
+        let value = 1_u32;
+        self.write_register(device, eirm::EI_CONTROL, value)
+    }
+
+    /// Disables the event interface.
+    pub fn disable_event<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+    ) -> ControlResult<()> {
+        let value = 0_u32;
+        self.write_register(device, eirm::EI_CONTROL, value)
+    }
+
+    /// Returns `true` if the event interface is enabled.
+    pub fn is_event_enable<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+    ) -> ControlResult<bool> {
+        let ei_ctrl: u32 = self.read_register(device, eirm::EI_CONTROL)?;
+        Ok((ei_ctrl & 1) == 1)
+    }
+
+    /// Maximum size of an event packet sent from the device in bytes.
+    pub fn maximum_event_transfer_length<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+    ) -> ControlResult<u32> {
+        self.read_register(device, eirm::MAXIMUM_EVENT_TRANSFER_LENGTH)
+    }
+
+    /// Sets maximum size of an event packet sent from the device in bytes.
+    ///
+    /// It's forbidden to write to the register while the event interface is enabled.
+    pub fn set_maximum_event_transfer_length<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+        length: u32,
+    ) -> ControlResult<()> {
+        self.write_register(device, eirm::MAXIMUM_EVENT_TRANSFER_LENGTH, length)
+    }
+
+    fn read_register<T, Ctrl>(&self, device: &mut Ctrl, register: (u64, u16)) -> ControlResult<T>
+    where
+        T: ParseBytes,
+        Ctrl: DeviceControl + ?Sized,
+    {
+        let (offset, len) = register;
+        let addr = offset + self.eirm_addr;
+        read_register(device, addr, len)
+    }
+
+    fn write_register<Ctrl: DeviceControl + ?Sized>(
+        &self,
+        device: &mut Ctrl,
+        register: (u64, u16),
+        data: impl DumpBytes,
+    ) -> ControlResult<()> {
+        let (offset, len) = register;
+        let addr = self.eirm_addr + offset;
+        let mut buf = vec![0; len as usize];
+        data.dump_bytes(&mut buf)?;
+        device.write(addr, &buf)
+    }
+}
+
 /// `ManifestTable` provides iterator of [`ManifestEntry`].
 #[derive(Clone, Copy, Debug)]
 pub struct ManifestTable {
@@ -1212,3 +1314,56 @@ impl_dump_bytes_for_numeric!(i8);
 impl_dump_bytes_for_numeric!(i16);
 impl_dump_bytes_for_numeric!(i32);
 impl_dump_bytes_for_numeric!(i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::MemoryControl;
+
+    const EIRM_ADDR: u64 = 0x100;
+
+    #[test]
+    fn test_eirm_event_control() {
+        let mut device = MemoryControl::new(0x200);
+        let eirm = Eirm::new(EIRM_ADDR);
+        let ei_control = (EIRM_ADDR + eirm::EI_CONTROL.0) as usize;
+
+        assert!(!eirm.is_event_enable(&mut device).unwrap());
+
+        eirm.enable_event(&mut device).unwrap();
+        assert_eq!(device.memory[ei_control..ei_control + 4], [1, 0, 0, 0]);
+        assert!(eirm.is_event_enable(&mut device).unwrap());
+
+        eirm.disable_event(&mut device).unwrap();
+        assert_eq!(device.memory[ei_control..ei_control + 4], [0, 0, 0, 0]);
+        assert!(!eirm.is_event_enable(&mut device).unwrap());
+    }
+
+    #[test]
+    fn test_eirm_maximum_event_transfer_length() {
+        let mut device = MemoryControl::new(0x200);
+        let eirm = Eirm::new(EIRM_ADDR);
+        let length_addr = (EIRM_ADDR + eirm::MAXIMUM_EVENT_TRANSFER_LENGTH.0) as usize;
+
+        eirm.set_maximum_event_transfer_length(&mut device, 0x1234)
+            .unwrap();
+        assert_eq!(
+            device.memory[length_addr..length_addr + 4],
+            0x1234_u32.to_le_bytes()
+        );
+        assert_eq!(
+            eirm.maximum_event_transfer_length(&mut device).unwrap(),
+            0x1234
+        );
+    }
+
+    #[test]
+    fn test_eirm_out_of_range() {
+        let mut device = MemoryControl::new(0x10);
+        let eirm = Eirm::new(EIRM_ADDR);
+
+        assert!(eirm.enable_event(&mut device).is_err());
+        assert!(eirm.maximum_event_transfer_length(&mut device).is_err());
+    }
+}
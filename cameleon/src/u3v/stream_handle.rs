@@ -5,28 +5,31 @@
 //! This module contains low level streaming implementation for `U3V` device.
 
 use std::{
-    convert::TryInto,
     sync::{Arc, Mutex, MutexGuard},
     time::Duration,
 };
 
 use async_std::task;
-use cameleon_device::u3v::{self, async_read::AsyncPool, protocol::stream as u3v_stream};
+#[cfg(feature = "emulator")]
+use cameleon_device::emulator;
+use cameleon_device::u3v::protocol::stream as u3v_stream;
+#[cfg(feature = "libusb")]
+use cameleon_device::u3v::{self, async_read::AsyncPool};
 use futures::channel::oneshot;
 use tracing::{error, info, warn};
 
 use crate::{
     camera::PayloadStream,
-    payload::{ImageInfo, Payload, PayloadSender, PayloadType},
+    payload::{parse_chunks, ImageInfo, Payload, PayloadSender, PayloadType},
     ControlError, ControlResult, DeviceControl, StreamError, StreamResult,
 };
 
-use super::register_map::Abrm;
+use super::{channel::ReceiveChannel, register_map::Abrm};
 
 /// This type is used to receive stream packets from the device.
 pub struct StreamHandle {
     /// Inner channel to receive payload data.
-    pub inner: Arc<Mutex<u3v::ReceiveChannel>>,
+    inner: Arc<Mutex<ReceiveChannel>>,
     /// Parameters for streaming.
     params: StreamParams,
     cancellation_tx: Option<oneshot::Sender<()>>,
@@ -98,14 +101,36 @@ impl StreamHandle {
         &mut self.params
     }
 
+    #[cfg(feature = "libusb")]
     pub(super) fn new(device: &u3v::Device) -> ControlResult<Option<Self>> {
         let inner = device.stream_channel()?;
-        Ok(inner.map(|inner| Self {
+        Ok(inner.map(|inner| Self::with_channel(inner.into())))
+    }
+
+    #[cfg(feature = "emulator")]
+    pub(super) fn new_emulated(device: &emulator::Device) -> ControlResult<Option<Self>> {
+        let inner = device.stream_channel()?;
+        Ok(inner.map(|inner| Self::with_channel(inner.into())))
+    }
+
+    /// Replaces the channel with the channel of a reconnected device.
+    pub(super) fn replace_channel(&mut self, inner: ReceiveChannel) {
+        if self.is_loop_running() {
+            self.stop_streaming_loop().ok();
+        }
+        if let Ok(mut old) = self.inner.lock() {
+            old.close().ok();
+        }
+        self.inner = Arc::new(Mutex::new(inner));
+    }
+
+    fn with_channel(inner: ReceiveChannel) -> Self {
+        Self {
             inner: Arc::new(Mutex::new(inner)),
             params: StreamParams::default(),
             cancellation_tx: None,
             completion_rx: None,
-        }))
+        }
     }
 }
 
@@ -203,7 +228,7 @@ impl From<StreamHandle> for Box<dyn PayloadStream> {
 }
 
 struct StreamingLoop {
-    inner: Arc<Mutex<u3v::ReceiveChannel>>,
+    inner: Arc<Mutex<ReceiveChannel>>,
     params: StreamParams,
     sender: PayloadSender,
     completion_tx: oneshot::Sender<()>,
@@ -351,42 +376,24 @@ impl<'a> PayloadBuilder<'a> {
             payload: self.payload_buf,
             valid_payload_size,
             timestamp: leader.timestamp(),
+            chunk_layout_id: None,
         })
     }
 
     fn build_image_extended_payload(self) -> StreamResult<Payload> {
-        const CHUNK_ID_LEN: usize = 4;
-        const CHUNK_SIZE_LEN: usize = 4;
-
         let leader: u3v_stream::ImageExtendedChunkLeader = self.specific_leader_as()?;
         let trailer: u3v_stream::ImageExtendedChunkTrailer = self.specific_trailer_as()?;
 
         let id = self.leader.block_id();
         let valid_payload_size = self.trailer.valid_payload_size() as usize;
 
-        // Extract image size from the first chunk of the paload data.
-        // Chunk data is designed to be decoded from the last byte to the first byte.
-        // Use chunk parser of `cameleon_genapi` once it gets implemented.
-        let mut current_offset = valid_payload_size;
-        let image_size = loop {
-            current_offset = current_offset.checked_sub(CHUNK_SIZE_LEN).ok_or_else(|| {
-                StreamError::InvalidPayload("failed to parse chunk data: size field missing".into())
+        // The first chunk of the payload data is the image.
+        let image_size = parse_chunks(&self.payload_buf[..valid_payload_size])?
+            .first()
+            .map(|chunk| chunk.len)
+            .ok_or_else(|| {
+                StreamError::InvalidPayload("failed to parse chunk data: no chunk found".into())
             })?;
-            let data_size = u32::from_be_bytes(
-                self.payload_buf[current_offset..current_offset + CHUNK_SIZE_LEN]
-                    .try_into()
-                    .unwrap(),
-            ) as usize;
-            current_offset = current_offset.checked_sub(data_size + CHUNK_ID_LEN).ok_or_else(|| {
-                StreamError::InvalidPayload(
-                    "failed to parse chunk data: chunk data size is smaller than specified size".into()
-                )
-            })?;
-
-            if current_offset == 0 {
-                break data_size;
-            }
-        };
 
         let image_info = Some(ImageInfo {
             width: leader.width() as usize,
@@ -404,12 +411,13 @@ impl<'a> PayloadBuilder<'a> {
             payload: self.payload_buf,
             valid_payload_size,
             timestamp: leader.timestamp(),
+            chunk_layout_id: Some(trailer.chunk_layout_id()),
         })
     }
 
     fn build_chunk_payload(self) -> StreamResult<Payload> {
         let leader: u3v_stream::ChunkLeader = self.specific_leader_as()?;
-        let _: u3v_stream::ChunkTrailer = self.specific_trailer_as()?;
+        let trailer: u3v_stream::ChunkTrailer = self.specific_trailer_as()?;
 
         let id = self.leader.block_id();
         let valid_payload_size = self.trailer.valid_payload_size() as usize;
@@ -421,6 +429,7 @@ impl<'a> PayloadBuilder<'a> {
             payload: self.payload_buf,
             valid_payload_size,
             timestamp: leader.timestamp(),
+            chunk_layout_id: Some(trailer.chunk_layout_id()),
         })
     }
 
@@ -526,7 +535,7 @@ impl StreamParams {
 }
 
 fn read_leader<'a>(
-    inner: &mut MutexGuard<'_, u3v::ReceiveChannel>,
+    inner: &mut MutexGuard<'_, ReceiveChannel>,
     params: &StreamParams,
     buf: &'a mut [u8],
 ) -> StreamResult<u3v_stream::Leader<'a>> {
@@ -537,12 +546,26 @@ fn read_leader<'a>(
 }
 
 fn read_payload(
-    inner: &mut MutexGuard<'_, u3v::ReceiveChannel>,
+    inner: &mut MutexGuard<'_, ReceiveChannel>,
+    params: &StreamParams,
+    buf: &mut [u8],
+) -> StreamResult<usize> {
+    match &**inner {
+        #[cfg(feature = "libusb")]
+        ReceiveChannel::LibUsb(channel) => read_payload_async(channel, params, buf),
+        #[cfg(feature = "emulator")]
+        ReceiveChannel::Emulator(channel) => read_payload_sync(channel, params, buf),
+    }
+}
+
+#[cfg(feature = "libusb")]
+fn read_payload_async(
+    channel: &u3v::ReceiveChannel,
     params: &StreamParams,
     buf: &mut [u8],
 ) -> StreamResult<usize> {
     let payload_size = params.payload_size;
-    let mut async_pool = AsyncPool::new(inner);
+    let mut async_pool = AsyncPool::new(channel);
     let mut cursor = 0;
     for _ in 0..params.payload_count {
         async_pool.submit(&mut buf[cursor..cursor + payload_size])?;
@@ -565,8 +588,30 @@ fn read_payload(
     Ok(read_len)
 }
 
+/// Emulated devices don't support asynchronous transfer, so receive payload transfers one by one.
+#[cfg(feature = "emulator")]
+fn read_payload_sync(
+    channel: &emulator::ReceiveChannel,
+    params: &StreamParams,
+    buf: &mut [u8],
+) -> StreamResult<usize> {
+    let transfer_sizes = (0..params.payload_count)
+        .map(|_| params.payload_size)
+        .chain(std::iter::once(params.payload_final1_size))
+        .chain(std::iter::once(params.payload_final2_size));
+
+    let mut cursor = 0;
+    let mut read_len = 0;
+    for size in transfer_sizes.filter(|size| *size != 0) {
+        read_len += channel.recv(&mut buf[cursor..cursor + size], params.timeout)?;
+        cursor += size;
+    }
+
+    Ok(read_len)
+}
+
 fn read_trailer<'a>(
-    inner: &mut MutexGuard<'_, u3v::ReceiveChannel>,
+    inner: &mut MutexGuard<'_, ReceiveChannel>,
     params: &StreamParams,
     buf: &'a mut [u8],
 ) -> StreamResult<u3v_stream::Trailer<'a>> {
@@ -578,7 +623,7 @@ fn read_trailer<'a>(
 }
 
 fn recv(
-    inner: &mut MutexGuard<'_, u3v::ReceiveChannel>,
+    inner: &mut MutexGuard<'_, ReceiveChannel>,
     params: &StreamParams,
     buf: &mut [u8],
     len: usize,
@@ -595,3 +640,72 @@ fn recv(
         .recv(&mut buf[..len], params.timeout)
         .map_err(|e| e.into())
 }
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use async_std::future;
+    use cameleon_device::emulator::{EmulatorBuilder, FaultSchedule, StreamFault};
+
+    use crate::{
+        payload::PayloadReceiver,
+        u3v::{open_emulated_by, CameraFilter, ControlHandle},
+        Camera, ProtocolStatus, U3vStatus,
+    };
+
+    use super::*;
+
+    fn start_streaming(
+        serial_number: &str,
+        fault: StreamFault,
+        schedule: FaultSchedule,
+    ) -> (Camera<ControlHandle, StreamHandle>, PayloadReceiver) {
+        EmulatorBuilder::new()
+            .serial_number(serial_number)
+            .unwrap()
+            .stream_fault(fault, schedule)
+            .build();
+        let filter = CameraFilter::new().serial_number(serial_number);
+        let mut camera = open_emulated_by(&filter).unwrap().unwrap();
+        camera.load_context().unwrap();
+        let payload_rx = camera.start_streaming(3).unwrap();
+        (camera, payload_rx)
+    }
+
+    fn recv(payload_rx: &PayloadReceiver) -> StreamResult<Payload> {
+        task::block_on(future::timeout(Duration::from_secs(3), payload_rx.recv()))
+            .expect("no payload arrived in time")
+    }
+
+    #[test]
+    fn test_drop_packet_fault() {
+        // Drop the leader of the first frame.
+        let (mut camera, payload_rx) =
+            start_streaming("STRM001", StreamFault::DropPacket, FaultSchedule::Once(0));
+
+        // The broken frame is never delivered.
+        let payload = loop {
+            if let Ok(payload) = recv(&payload_rx) {
+                break payload;
+            }
+        };
+        assert_eq!(payload.id(), 1);
+
+        camera.close().unwrap();
+    }
+
+    #[test]
+    fn test_halt_fault() {
+        let (mut camera, payload_rx) =
+            start_streaming("STRM002", StreamFault::Halt, FaultSchedule::Once(0));
+
+        assert!(matches!(recv(&payload_rx), Err(StreamError::Io(..))));
+
+        // The device rejects control commands while the stream endpoint is halted.
+        let mut buf = [0; 4];
+        let err = camera.ctrl.read(0, &mut buf).unwrap_err();
+        assert_eq!(
+            err.protocol_status().map(|(status, _)| status),
+            Some(ProtocolStatus::U3v(U3vStatus::StreamEndpointHalted))
+        );
+    }
+}
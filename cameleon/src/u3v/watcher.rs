@@ -0,0 +1,396 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module provides [`CameraWatcher`] which notifies arrival and removal of cameras.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_std::channel::{self, Receiver, Sender};
+#[cfg(feature = "libusb")]
+use cameleon_device::u3v;
+use tracing::warn;
+
+use crate::{CameleonResult, CameraInfo, ControlError};
+
+use super::{camera_info, BusPath, DeviceInfo};
+
+/// An event notified by [`CameraWatcher`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum CameraEvent {
+    /// The camera is plugged in.
+    Arrived(CameraInfo),
+
+    /// The camera is plugged out.
+    Removed(CameraInfo),
+}
+
+/// Watches cameras connected to the host, and notifies [`CameraEvent`] when a camera is plugged in
+/// or out.
+///
+/// Cameras which are already connected when the watcher is created are notified as
+/// [`CameraEvent::Arrived`] first.
+///
+/// The watcher stops watching when it's dropped.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use cameleon::u3v::{self, CameraEvent};
+///
+/// let watcher = u3v::watch_cameras(Duration::from_secs(1)).unwrap();
+/// while let Some(event) = async_std::task::block_on(watcher.recv()) {
+///     match event {
+///         CameraEvent::Arrived(info) => println!("arrived: {}", info.serial_number),
+///         CameraEvent::Removed(info) => println!("removed: {}", info.serial_number),
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CameraWatcher {
+    rx: Receiver<CameraEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl CameraWatcher {
+    /// Receives the next [`CameraEvent`].
+    ///
+    /// Returns `None` if the watcher stops due to an error.
+    pub async fn recv(&self) -> Option<CameraEvent> {
+        self.rx.recv().await.ok()
+    }
+
+    /// Tries to receive the next [`CameraEvent`].
+    /// This method doesn't wait arrival of an event and immediately returns `None` if no event
+    /// occurs.
+    pub fn try_recv(&self) -> Option<CameraEvent> {
+        self.rx.try_recv().ok()
+    }
+
+    fn start<S, W>(scan: S, wait: W) -> Self
+    where
+        S: FnMut() -> CameleonResult<Scan> + Send + 'static,
+        W: FnMut() + Send + 'static,
+    {
+        let (tx, rx) = channel::unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let watch_loop = WatchLoop {
+            tx,
+            stop: stop.clone(),
+            cameras: HashMap::new(),
+        };
+        std::thread::spawn(move || watch_loop.run(scan, wait));
+
+        Self { rx, stop }
+    }
+}
+
+impl Drop for CameraWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Watch cameras connected to the host.
+///
+/// libusb hotplug is used to detect changes if the platform supports it, otherwise the host is
+/// rescanned every `rescan_interval`. Even when hotplug is available, the host is rescanned every
+/// `rescan_interval` to catch up changes that hotplug misses.
+///
+/// See [`CameraWatcher`] for an example.
+#[cfg(feature = "libusb")]
+pub fn watch_cameras(rescan_interval: Duration) -> CameleonResult<CameraWatcher> {
+    let notifier = u3v::HotplugNotifier::new().map_err(ControlError::from)?;
+    let scan = || {
+        let devices = u3v::enumerate_devices().map_err(ControlError::from)?;
+        let connected = u3v::enumerate_bus_paths().map_err(ControlError::from)?;
+        Ok(Scan {
+            devices: devices.into_iter().map(|dev| dev.device_info).collect(),
+            connected,
+        })
+    };
+    let wait = move || match &notifier {
+        Some(notifier) => {
+            let _ = notifier.wait(rescan_interval);
+        }
+        None => std::thread::sleep(rescan_interval),
+    };
+
+    Ok(CameraWatcher::start(scan, wait))
+}
+
+/// Watch emulated cameras built by [`cameleon_device::emulator::EmulatorBuilder`].
+///
+/// The device pool of emulators is rescanned every `rescan_interval`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use cameleon::u3v::{self, CameraEvent};
+/// use cameleon_device::emulator::EmulatorBuilder;
+///
+/// let watcher = u3v::watch_emulated_cameras(Duration::from_millis(10)).unwrap();
+///
+/// EmulatorBuilder::new().serial_number("CAM1984").unwrap().build();
+/// let event = async_std::task::block_on(watcher.recv()).unwrap();
+/// assert!(matches!(event, CameraEvent::Arrived(info) if info.serial_number == "CAM1984"));
+/// ```
+#[cfg(feature = "emulator")]
+pub fn watch_emulated_cameras(rescan_interval: Duration) -> CameleonResult<CameraWatcher> {
+    use cameleon_device::emulator;
+
+    // Emulators are enumerated even while they are opened.
+    let scan = || {
+        let devices = emulator::enumerate_devices().map_err(ControlError::from)?;
+        Ok(Scan {
+            devices: devices.into_iter().map(|dev| dev.device_info).collect(),
+            connected: vec![],
+        })
+    };
+    let wait = move || std::thread::sleep(rescan_interval);
+
+    Ok(CameraWatcher::start(scan, wait))
+}
+
+/// A result of a scan of cameras.
+struct Scan {
+    /// Cameras which are enumerated.
+    devices: Vec<DeviceInfo>,
+    /// Bus paths of all connected cameras, including ones which can't be enumerated because they
+    /// are opened exclusively by another handle.
+    connected: Vec<BusPath>,
+}
+
+struct WatchLoop {
+    tx: Sender<CameraEvent>,
+    stop: Arc<AtomicBool>,
+    /// Connected cameras and their bus paths keyed by GUID.
+    cameras: HashMap<String, (CameraInfo, Option<BusPath>)>,
+}
+
+impl WatchLoop {
+    fn run<S, W>(mut self, mut scan: S, mut wait: W)
+    where
+        S: FnMut() -> CameleonResult<Scan>,
+        W: FnMut(),
+    {
+        while !self.stop.load(Ordering::Relaxed) {
+            match scan() {
+                Ok(scan) => {
+                    if !self.update(scan) {
+                        break;
+                    }
+                }
+                Err(e) => warn!("failed to scan cameras: {}", e),
+            }
+
+            wait();
+        }
+    }
+
+    /// Notifies the difference between the previous scan and `scan`.
+    /// Returns `false` if the watcher is dropped.
+    fn update(&mut self, scan: Scan) -> bool {
+        let mut current: HashMap<_, _> = scan
+            .devices
+            .iter()
+            .map(|info| {
+                (
+                    info.guid.clone(),
+                    (camera_info(info), info.bus_path.clone()),
+                )
+            })
+            .collect();
+
+        let mut events = vec![];
+        for (guid, (info, bus_path)) in &self.cameras {
+            if current.contains_key(guid) {
+                continue;
+            }
+
+            // Keep the camera if it's still connected, but isn't enumerated because it's busy.
+            let is_busy = matches!(bus_path, Some(path) if scan.connected.contains(path)
+                && !current.values().any(|(_, current_path)| current_path.as_ref() == Some(path)));
+            if is_busy {
+                current.insert(guid.clone(), (info.clone(), bus_path.clone()));
+            } else {
+                events.push(CameraEvent::Removed(info.clone()));
+            }
+        }
+        for (guid, (info, _)) in &current {
+            if !self.cameras.contains_key(guid) {
+                events.push(CameraEvent::Arrived(info.clone()));
+            }
+        }
+        std::mem::swap(&mut self.cameras, &mut current);
+
+        events
+            .into_iter()
+            .all(|event| self.tx.try_send(event).is_ok())
+    }
+}
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use super::*;
+
+    fn device_info(serial_number: &str, port: u8) -> DeviceInfo {
+        let mut info = crate::test_utils::device_info();
+        info.guid = format!("TEST-{}", serial_number);
+        info.serial_number = serial_number.into();
+        info.bus_path = Some(bus_path(port));
+        info
+    }
+
+    fn bus_path(port: u8) -> BusPath {
+        BusPath {
+            bus_number: 1,
+            port_numbers: vec![port],
+        }
+    }
+
+    fn watch_loop() -> (WatchLoop, Receiver<CameraEvent>) {
+        let (tx, rx) = channel::unbounded();
+        let watch_loop = WatchLoop {
+            tx,
+            stop: Arc::new(AtomicBool::new(false)),
+            cameras: HashMap::new(),
+        };
+        (watch_loop, rx)
+    }
+
+    /// Updates `watch_loop` with a scan, and returns notified events.
+    fn update(
+        watch_loop: &mut WatchLoop,
+        rx: &Receiver<CameraEvent>,
+        devices: Vec<DeviceInfo>,
+        connected: Vec<BusPath>,
+    ) -> Vec<CameraEvent> {
+        assert!(watch_loop.update(Scan { devices, connected }));
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    fn arrived(serial_number: &str, port: u8) -> CameraEvent {
+        CameraEvent::Arrived(camera_info(&device_info(serial_number, port)))
+    }
+
+    fn removed(serial_number: &str, port: u8) -> CameraEvent {
+        CameraEvent::Removed(camera_info(&device_info(serial_number, port)))
+    }
+
+    #[test]
+    fn test_arrived() {
+        let (mut watch_loop, rx) = watch_loop();
+
+        let events = update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM1", 1), device_info("CAM2", 2)],
+            vec![bus_path(1), bus_path(2)],
+        );
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&arrived("CAM1", 1)));
+        assert!(events.contains(&arrived("CAM2", 2)));
+    }
+
+    #[test]
+    fn test_removed() {
+        let (mut watch_loop, rx) = watch_loop();
+        update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM1", 1), device_info("CAM2", 2)],
+            vec![bus_path(1), bus_path(2)],
+        );
+
+        let events = update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM2", 2)],
+            vec![bus_path(2)],
+        );
+        assert_eq!(events, vec![removed("CAM1", 1)]);
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let (mut watch_loop, rx) = watch_loop();
+        update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM1", 1)],
+            vec![bus_path(1)],
+        );
+
+        let events = update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM1", 1)],
+            vec![bus_path(1)],
+        );
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_busy_camera_is_kept() {
+        let (mut watch_loop, rx) = watch_loop();
+        update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM1", 1)],
+            vec![bus_path(1)],
+        );
+
+        // The camera is still connected, but can't be enumerated because it's opened.
+        let events = update(&mut watch_loop, &rx, vec![], vec![bus_path(1)]);
+        assert!(events.is_empty());
+
+        // The camera is unplugged.
+        let events = update(&mut watch_loop, &rx, vec![], vec![]);
+        assert_eq!(events, vec![removed("CAM1", 1)]);
+    }
+
+    #[test]
+    fn test_replaced_camera() {
+        let (mut watch_loop, rx) = watch_loop();
+        update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM1", 1)],
+            vec![bus_path(1)],
+        );
+
+        // Another camera is plugged into the same port.
+        let events = update(
+            &mut watch_loop,
+            &rx,
+            vec![device_info("CAM2", 1)],
+            vec![bus_path(1)],
+        );
+        assert_eq!(events.len(), 2);
+        assert!(events.contains(&removed("CAM1", 1)));
+        assert!(events.contains(&arrived("CAM2", 1)));
+    }
+
+    #[test]
+    fn test_update_after_drop() {
+        let (mut watch_loop, rx) = watch_loop();
+        drop(rx);
+
+        assert!(!watch_loop.update(Scan {
+            devices: vec![device_info("CAM1", 1)],
+            connected: vec![bus_path(1)],
+        }));
+    }
+}
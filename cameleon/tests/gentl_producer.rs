@@ -0,0 +1,111 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Loads the `GenTL` producer of this workspace, which exposes emulated cameras, through
+//! [`cameleon::gentl`].
+
+#![cfg(feature = "gentl")]
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use async_std::{future, task};
+use cameleon::{
+    gentl::{ControlHandle, Producer, StreamHandle},
+    payload::PayloadType,
+    Camera,
+};
+
+/// Number of emulated cameras exposed by the producer.
+const NUM_EMULATED_CAMERAS: usize = 2;
+
+/// Builds `cameleon-gentl` with the emulator, and returns the path to the built library.
+fn build_producer() -> PathBuf {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("gentl");
+    cameleon_gentl_build_helper::build_producer(&target_dir, &["emulator"])
+}
+
+/// Builds the producer and loads it.
+fn load_producer() -> (Producer, PathBuf) {
+    // Read by the system module of the producer when the library is initialized.
+    env::set_var("CAMELEON_GENTL_EMULATOR", NUM_EMULATED_CAMERAS.to_string());
+    let path = build_producer();
+    (Producer::load(&path).unwrap(), path)
+}
+
+/// Opens the first camera which isn't opened by another test sharing the producer.
+fn open_any(
+    cameras: Vec<Camera<ControlHandle, StreamHandle>>,
+) -> Camera<ControlHandle, StreamHandle> {
+    cameras
+        .into_iter()
+        .find_map(|mut camera| camera.open().ok().map(|_| camera))
+        .expect("all emulated cameras are in use")
+}
+
+#[test]
+fn test_emulated_producer() {
+    let (producer, path) = load_producer();
+    // The second load shares the initialized library instead of initializing it again.
+    let shared = Producer::load(&path).unwrap();
+
+    let cameras = shared.enumerate_cameras().unwrap();
+    assert!(cameras.len() >= NUM_EMULATED_CAMERAS);
+    drop(shared);
+
+    let mut camera = open_any(cameras);
+    camera.load_context().unwrap();
+
+    let serial_number = camera.info().serial_number.clone();
+    let mut params_ctxt = camera.params_ctxt().unwrap();
+    let node = params_ctxt
+        .node("DeviceSerialNumber")
+        .unwrap()
+        .as_string(&params_ctxt)
+        .unwrap();
+    assert_eq!(node.value(&mut params_ctxt).unwrap(), serial_number);
+
+    camera.close().unwrap();
+    drop(camera);
+    drop(producer);
+
+    // The library can be initialized again after all handles are dropped.
+    let producer = Producer::load(&path).unwrap();
+    assert!(producer.enumerate_cameras().unwrap().len() >= NUM_EMULATED_CAMERAS);
+}
+
+#[test]
+fn test_emulated_producer_streaming() {
+    const TIMEOUT: Duration = Duration::from_secs(3);
+
+    let (producer, _) = load_producer();
+
+    let mut camera = open_any(producer.enumerate_cameras().unwrap());
+    camera.load_context().unwrap();
+
+    // Payloads are delivered through buffers of the data stream module and new buffer events.
+    let payload_rx = camera.start_streaming(3).unwrap();
+    let mut last_id = None;
+    for _ in 0..3 {
+        let payload = task::block_on(future::timeout(TIMEOUT, payload_rx.recv()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(payload.payload_type(), PayloadType::Image);
+        assert!(!payload.image().unwrap().is_empty());
+        assert!(last_id < Some(payload.id()));
+        last_id = Some(payload.id());
+        payload_rx.send_back(payload);
+    }
+    camera.stop_streaming().unwrap();
+
+    let payload = camera.grab_one(TIMEOUT).unwrap();
+    let image_info = payload.image_info().unwrap();
+    assert!(image_info.width > 0 && image_info.height > 0);
+    assert_eq!(payload.image().unwrap().len(), image_info.image_size);
+
+    camera.close().unwrap();
+}
@@ -0,0 +1,572 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! `cameleon-cli` is a command line tool to inspect and operate `GenICam` compatible cameras.
+//!
+//! Run `cameleon-cli --help` to see available subcommands.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+    time::Duration,
+};
+
+use structopt::StructOpt;
+
+use cameleon::{
+    genapi::{DefaultGenApiCtxt, Node, ParamsCtxt},
+    payload::{ImageInfo, Payload, PixelFormat},
+    u3v::{self, ControlHandle, StreamHandle},
+    Camera, DeviceControl,
+};
+use cameleon_device::emulator::EmulatorBuilder;
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+macro_rules! err {
+    ($($arg:tt)*) => {
+        Box::<dyn std::error::Error>::from(format!($($arg)*))
+    };
+}
+
+macro_rules! bail {
+    ($($arg:tt)*) => {
+        return Err(err!($($arg)*))
+    };
+}
+
+type U3VCamera = Camera<ControlHandle, StreamHandle>;
+type Ctxt<'a> = ParamsCtxt<&'a mut ControlHandle, &'a mut DefaultGenApiCtxt>;
+
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "cameleon-cli",
+    about = "Inspect and operate GenICam compatible cameras"
+)]
+struct Opt {
+    /// Operate on an emulated camera instead of cameras connected to the host.
+    ///
+    /// The emulator lives only while the command runs, so its state isn't preserved between
+    /// invocations.
+    #[structopt(long, global = true)]
+    emulator: bool,
+
+    /// Serial number of the camera to operate on. The first found camera is used if omitted.
+    #[structopt(short, long, global = true)]
+    serial: Option<String>,
+
+    #[structopt(subcommand)]
+    cmd: Command,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// List cameras with their device information.
+    List,
+
+    /// Dump the feature tree of the camera.
+    Tree {
+        /// Name of the category where the dump starts.
+        #[structopt(default_value = "Root")]
+        root: String,
+    },
+
+    /// Get the value of a feature.
+    Get {
+        /// Name of the feature.
+        name: String,
+    },
+
+    /// Set a value to a feature.
+    ///
+    /// Integers accept hexadecimal values with `0x` prefix, and enumerations accept symbolic
+    /// names of their entries.
+    Set {
+        /// Name of the feature.
+        name: String,
+        /// Value to set.
+        value: String,
+    },
+
+    /// Execute a command feature.
+    Execute {
+        /// Name of the command feature.
+        name: String,
+    },
+
+    /// Download the `GenICam` XML of the camera.
+    Xml {
+        /// Path to write the XML to. The XML is written to stdout if omitted.
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+
+    /// Grab frames and write them to a directory.
+    ///
+    /// `Mono8` and `Mono16` images are written as PGM, `RGB8` images as PPM, and other payloads
+    /// as raw bytes.
+    Grab {
+        /// Number of frames to grab.
+        #[structopt(short = "n", long, default_value = "1")]
+        count: usize,
+        /// Directory to write frames to.
+        #[structopt(short, long, default_value = "frames", parse(from_os_str))]
+        output: PathBuf,
+        /// Timeout in milliseconds to wait for each frame.
+        #[structopt(long, default_value = "3000")]
+        timeout_ms: u64,
+    },
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    if let Err(e) = try_main(opt) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn try_main(opt: Opt) -> Result<()> {
+    if opt.emulator {
+        EmulatorBuilder::new().build();
+    }
+
+    let cameras = if opt.emulator {
+        u3v::enumerate_emulated_cameras()?
+    } else {
+        u3v::enumerate_cameras()?
+    };
+
+    if let Command::List = opt.cmd {
+        list(&cameras);
+        return Ok(());
+    }
+
+    let mut camera = select(cameras, opt.serial.as_deref())?;
+    camera.open()?;
+    let res = run(&mut camera, opt.cmd);
+    camera.close()?;
+    res
+}
+
+fn run(camera: &mut U3VCamera, cmd: Command) -> Result<()> {
+    match cmd {
+        Command::List => unreachable!(),
+
+        Command::Tree { root } => {
+            camera.load_context()?;
+            let mut ctxt = camera.params_ctxt()?;
+            let root = find_node(&ctxt, &root)?;
+            print_tree(root, &mut ctxt, 0);
+        }
+
+        Command::Get { name } => {
+            camera.load_context()?;
+            let mut ctxt = camera.params_ctxt()?;
+            let node = find_node(&ctxt, &name)?;
+            match read_value(node, &mut ctxt)? {
+                Some(value) => println!("{}", value),
+                None => bail!("{} is not readable", name),
+            }
+        }
+
+        Command::Set { name, value } => {
+            camera.load_context()?;
+            let mut ctxt = camera.params_ctxt()?;
+            let node = find_node(&ctxt, &name)?;
+            write_value(node, &mut ctxt, &value)?;
+        }
+
+        Command::Execute { name } => {
+            camera.load_context()?;
+            let mut ctxt = camera.params_ctxt()?;
+            let node = find_node(&ctxt, &name)?
+                .as_command(&ctxt)
+                .ok_or_else(|| err!("{} is not a command", name))?;
+            node.execute(&mut ctxt)?;
+        }
+
+        Command::Xml { output } => {
+            let xml = camera.ctrl.genapi()?;
+            match output {
+                Some(path) => fs::write(&path, xml)
+                    .map_err(|e| err!("failed to write {}: {}", path.display(), e))?,
+                None => println!("{}", xml),
+            }
+        }
+
+        Command::Grab {
+            count,
+            output,
+            timeout_ms,
+        } => {
+            camera.load_context()?;
+            let payloads = camera.grab_n(count, Duration::from_millis(timeout_ms))?;
+            fs::create_dir_all(&output)
+                .map_err(|e| err!("failed to create {}: {}", output.display(), e))?;
+            for payload in &payloads {
+                let path = save_payload(payload, &output)?;
+                println!("{}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn list(cameras: &[U3VCamera]) {
+    if cameras.is_empty() {
+        println!("no camera found");
+        return;
+    }
+
+    for (i, camera) in cameras.iter().enumerate() {
+        let info = camera.ctrl.device_info();
+        println!("[{}] {} {}", i, info.vendor_name, info.model_name);
+        println!("    serial number:     {}", info.serial_number);
+        println!("    guid:              {}", info.guid);
+        if let Some(family_name) = &info.family_name {
+            println!("    family name:       {}", family_name);
+        }
+        if let Some(user_defined_name) = &info.user_defined_name {
+            println!("    user defined name: {}", user_defined_name);
+        }
+        println!("    device version:    {}", info.device_version);
+        println!("    manufacturer info: {}", info.manufacturer_info);
+        println!("    GenCP version:     {}", info.gencp_version);
+        println!("    U3V version:       {}", info.u3v_version);
+        println!("    supported speed:   {:?}", info.supported_speed);
+    }
+}
+
+fn select(cameras: Vec<U3VCamera>, serial: Option<&str>) -> Result<U3VCamera> {
+    match serial {
+        Some(serial) => cameras
+            .into_iter()
+            .find(|camera| camera.info().serial_number == serial)
+            .ok_or_else(|| err!("no camera found with serial number {}", serial)),
+        None => cameras
+            .into_iter()
+            .next()
+            .ok_or_else(|| err!("no camera found")),
+    }
+}
+
+fn find_node(ctxt: &Ctxt, name: &str) -> Result<Node> {
+    ctxt.node(name)
+        .ok_or_else(|| err!("no feature named {}", name))
+}
+
+fn print_tree(node: Node, ctxt: &mut Ctxt, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let name = node.name(ctxt).to_string();
+
+    if let Some(category) = node.as_category(ctxt) {
+        println!("{}{}", indent, name);
+        for child in category.nodes(ctxt) {
+            print_tree(child, ctxt, depth + 1);
+        }
+        return;
+    }
+
+    match read_value(node, ctxt) {
+        Ok(Some(value)) => println!("{}{}: {}", indent, name, value),
+        Ok(None) => println!("{}{}", indent, name),
+        Err(e) => println!("{}{}: <{}>", indent, name, e),
+    }
+}
+
+/// Returns the value of the node as a string, returns `None` if the node has no readable value.
+fn read_value(node: Node, ctxt: &mut Ctxt) -> Result<Option<String>> {
+    let value = if let Some(node) = node.as_integer(ctxt) {
+        if !node.is_readable(ctxt)? {
+            return Ok(None);
+        }
+        node.value(ctxt)?.to_string()
+    } else if let Some(node) = node.as_float(ctxt) {
+        if !node.is_readable(ctxt)? {
+            return Ok(None);
+        }
+        node.value(ctxt)?.to_string()
+    } else if let Some(node) = node.as_string(ctxt) {
+        if !node.is_readable(ctxt)? {
+            return Ok(None);
+        }
+        node.value(ctxt)?
+    } else if let Some(node) = node.as_boolean(ctxt) {
+        if !node.is_readable(ctxt)? {
+            return Ok(None);
+        }
+        node.value(ctxt)?.to_string()
+    } else if let Some(node) = node.as_enumeration(ctxt) {
+        if !node.is_readable(ctxt)? {
+            return Ok(None);
+        }
+        node.current_entry(ctxt)?.symbolic(ctxt).to_string()
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(value))
+}
+
+fn write_value(node: Node, ctxt: &mut Ctxt, value: &str) -> Result<()> {
+    let name = node.name(ctxt).to_string();
+    let not_writable = || err!("{} is not writable", name);
+    let invalid_value = || err!("invalid value for {}: {}", name, value);
+
+    if let Some(node) = node.as_integer(ctxt) {
+        if !node.is_writable(ctxt)? {
+            return Err(not_writable());
+        }
+        let value = parse_integer(value).ok_or_else(invalid_value)?;
+        node.set_value(ctxt, value)?;
+    } else if let Some(node) = node.as_float(ctxt) {
+        if !node.is_writable(ctxt)? {
+            return Err(not_writable());
+        }
+        let value = value.parse().map_err(|_| invalid_value())?;
+        node.set_value(ctxt, value)?;
+    } else if let Some(node) = node.as_string(ctxt) {
+        if !node.is_writable(ctxt)? {
+            return Err(not_writable());
+        }
+        node.set_value(ctxt, value.to_string())?;
+    } else if let Some(node) = node.as_boolean(ctxt) {
+        if !node.is_writable(ctxt)? {
+            return Err(not_writable());
+        }
+        let value = match value {
+            "true" | "1" => true,
+            "false" | "0" => false,
+            _ => return Err(invalid_value()),
+        };
+        node.set_value(ctxt, value)?;
+    } else if let Some(node) = node.as_enumeration(ctxt) {
+        if !node.is_writable(ctxt)? {
+            return Err(not_writable());
+        }
+        node.set_entry_by_symbolic(ctxt, value)?;
+    } else {
+        bail!("{} doesn't have a value", name);
+    }
+
+    Ok(())
+}
+
+fn parse_integer(s: &str) -> Option<i64> {
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// Writes the payload into `dir` and returns the path of the written file.
+fn save_payload(payload: &Payload, dir: &Path) -> Result<PathBuf> {
+    let id = payload.id();
+    let (path, data) = match (payload.image_info(), payload.image()) {
+        (Some(info), Some(image)) => match netpbm(info, image)? {
+            Some((ext, data)) => (dir.join(format!("frame_{:06}.{}", id, ext)), data),
+            None => (
+                dir.join(format!(
+                    "frame_{:06}_{}x{}_{:?}.raw",
+                    id, info.width, info.height, info.pixel_format
+                )),
+                image.to_vec(),
+            ),
+        },
+        _ => (
+            dir.join(format!("payload_{:06}.raw", id)),
+            payload.payload().to_vec(),
+        ),
+    };
+
+    let mut file =
+        fs::File::create(&path).map_err(|e| err!("failed to create {}: {}", path.display(), e))?;
+    file.write_all(&data)
+        .map_err(|e| err!("failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Encodes the image in the `netpbm` format and returns it with the file extension, returns
+/// `None` if the pixel format isn't supported by the format.
+fn netpbm(info: &ImageInfo, image: &[u8]) -> Result<Option<(&'static str, Vec<u8>)>> {
+    let (ext, magic, max_value, bytes_per_pixel) = match info.pixel_format {
+        PixelFormat::Mono8 => ("pgm", "P5", 255, 1),
+        PixelFormat::Mono16 => ("pgm", "P5", 65535, 2),
+        PixelFormat::RGB8 => ("ppm", "P6", 255, 3),
+        _ => return Ok(None),
+    };
+
+    // Each line may be followed by padding bytes, which `netpbm` doesn't allow.
+    let line_len = info.width * bytes_per_pixel;
+    let stride = image.len().checked_div(info.height).unwrap_or(line_len);
+    if stride < line_len {
+        bail!(
+            "image size {} is too small for {}x{} {:?}",
+            image.len(),
+            info.width,
+            info.height,
+            info.pixel_format
+        );
+    }
+
+    let mut data =
+        format!("{}\n{} {}\n{}\n", magic, info.width, info.height, max_value).into_bytes();
+    for line in image.chunks(stride.max(1)).take(info.height) {
+        let line = &line[..line_len];
+        if info.pixel_format == PixelFormat::Mono16 {
+            // `PGM` expects 16-bit samples in big endian.
+            for sample in line.chunks_exact(2) {
+                data.extend_from_slice(&[sample[1], sample[0]]);
+            }
+        } else {
+            data.extend_from_slice(line);
+        }
+    }
+    Ok(Some((ext, data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> std::result::Result<Opt, structopt::clap::Error> {
+        Opt::from_iter_safe(std::iter::once("cameleon-cli").chain(args.iter().copied()))
+    }
+
+    fn image_info(pixel_format: PixelFormat, width: usize, height: usize) -> ImageInfo {
+        ImageInfo {
+            width,
+            height,
+            x_offset: 0,
+            y_offset: 0,
+            pixel_format,
+            image_size: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let opt = parse(&["grab"]).unwrap();
+        assert!(!opt.emulator);
+        assert!(opt.serial.is_none());
+        assert!(matches!(
+            opt.cmd,
+            Command::Grab { count: 1, ref output, timeout_ms: 3000 } if output == Path::new("frames")
+        ));
+
+        // Global options are accepted after the subcommand.
+        let opt = parse(&[
+            "grab",
+            "-n",
+            "3",
+            "-o",
+            "out",
+            "--timeout-ms",
+            "100",
+            "--emulator",
+            "-s",
+            "ABC",
+        ])
+        .unwrap();
+        assert!(opt.emulator);
+        assert_eq!(opt.serial.as_deref(), Some("ABC"));
+        assert!(matches!(
+            opt.cmd,
+            Command::Grab { count: 3, ref output, timeout_ms: 100 } if output == Path::new("out")
+        ));
+
+        assert!(
+            matches!(parse(&["tree"]).unwrap().cmd, Command::Tree { ref root } if root == "Root")
+        );
+        assert!(matches!(
+            parse(&["set", "Width", "0x10"]).unwrap().cmd,
+            Command::Set { ref name, ref value } if name == "Width" && value == "0x10"
+        ));
+
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["set", "Width"]).is_err());
+        assert!(parse(&["grab", "-n", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer("42"), Some(42));
+        assert_eq!(parse_integer("-1"), Some(-1));
+        assert_eq!(parse_integer("0x1F"), Some(31));
+        assert_eq!(parse_integer("0X1f"), Some(31));
+        assert_eq!(parse_integer("0x"), None);
+        assert_eq!(parse_integer("ten"), None);
+    }
+
+    #[test]
+    fn test_netpbm() {
+        let info = image_info(PixelFormat::Mono8, 2, 2);
+        let (ext, data) = netpbm(&info, &[1, 2, 3, 4]).unwrap().unwrap();
+        assert_eq!(ext, "pgm");
+        assert_eq!(data, b"P5\n2 2\n255\n\x01\x02\x03\x04");
+
+        // 16-bit samples are converted to big endian.
+        let info = image_info(PixelFormat::Mono16, 1, 2);
+        let (ext, data) = netpbm(&info, &[1, 2, 3, 4]).unwrap().unwrap();
+        assert_eq!(ext, "pgm");
+        assert_eq!(data, b"P5\n1 2\n65535\n\x02\x01\x04\x03");
+
+        let info = image_info(PixelFormat::BayerRG8, 2, 2);
+        assert!(netpbm(&info, &[0; 4]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_netpbm_padding() {
+        // Each line of 2 RGB pixels is followed by 2 padding bytes.
+        let info = image_info(PixelFormat::RGB8, 2, 2);
+        let image = [
+            1, 2, 3, 4, 5, 6, 0xff, 0xff, //
+            7, 8, 9, 10, 11, 12, 0xff, 0xff,
+        ];
+        let (ext, data) = netpbm(&info, &image).unwrap().unwrap();
+        assert_eq!(ext, "ppm");
+        assert_eq!(
+            data,
+            b"P6\n2 2\n255\n\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c"
+        );
+
+        // The image is smaller than its size.
+        assert!(netpbm(&info, &image[..11]).is_err());
+    }
+
+    #[test]
+    fn test_grab_emulator() {
+        EmulatorBuilder::new()
+            .serial_number("CLI0001")
+            .unwrap()
+            .build();
+        let cameras = u3v::enumerate_emulated_cameras().unwrap();
+        let mut camera = select(cameras, Some("CLI0001")).unwrap();
+
+        let output = std::env::temp_dir().join(format!("cameleon-cli-{}", process::id()));
+        camera.open().unwrap();
+        let res = run(
+            &mut camera,
+            Command::Grab {
+                count: 2,
+                output: output.clone(),
+                timeout_ms: 3000,
+            },
+        );
+        camera.close().unwrap();
+        res.unwrap();
+
+        for id in 0..2 {
+            let path = output.join(format!("frame_{:06}.pgm", id));
+            let data = fs::read(&path).unwrap();
+            assert!(data.starts_with(b"P5\n"));
+        }
+        fs::remove_dir_all(&output).unwrap();
+    }
+}
@@ -0,0 +1,10 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    cameleon_codegen::Generator::new()
+        .generate_to_file("odd_names.xml", format!("{}/odd_names.rs", out_dir))
+        .unwrap();
+}
@@ -0,0 +1,65 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Compiles the code generated from `odd_names.xml`, whose names collide or need escaping after
+//! being converted to identifiers.
+
+use cameleon::{
+    genapi::{
+        BooleanNode, CommandNode, FloatNode, GenApiCtxt, IntegerNode, ParamsCtxt, StringNode,
+    },
+    sfnc::EnumFeature,
+    DeviceControl,
+};
+
+pub mod odd_names {
+    include!(concat!(env!("OUT_DIR"), "/odd_names.rs"));
+}
+
+/// Uses all the accessors with the types `cameleon` expects.
+pub fn use_accessors<Ctrl, Ctxt>(ctxt: &mut ParamsCtxt<Ctrl, Ctxt>)
+where
+    Ctrl: DeviceControl,
+    Ctxt: GenApiCtxt,
+{
+    if let Some(mode) = odd_names::vendor_mode(ctxt) {
+        let value: odd_names::Vendor_Mode = mode.value(ctxt).unwrap();
+        mode.set_value(ctxt, value).unwrap();
+    }
+    if let Some(mode) = odd_names::vendor_mode_1(ctxt) {
+        let _: Vec<odd_names::Vendor_Mode_1> = mode.available_values(ctxt).unwrap();
+    }
+    let _: Option<EnumFeature<odd_names::PixelSize>> = odd_names::pixel_size(ctxt);
+    let _: Option<IntegerNode> = odd_names::type_(ctxt);
+    let _: Option<IntegerNode> = odd_names::self_(ctxt);
+    let _: Option<BooleanNode> = odd_names::crate_(ctxt);
+    let _: Option<IntegerNode> = odd_names::_8_bit_gain(ctxt);
+    let _: Option<FloatNode> = odd_names::exposure_time(ctxt);
+    let _: Option<CommandNode> = odd_names::acquisition_start(ctxt);
+    let _: Option<StringNode> = odd_names::device_vendor_name(ctxt);
+}
+
+#[cfg(test)]
+mod tests {
+    use cameleon::sfnc::{EntryKey, SfncEnum};
+
+    use super::odd_names::{PixelSize, Vendor_Mode_1};
+
+    #[test]
+    fn test_entry_mapping() {
+        // Entries are mapped by their original symbolic names.
+        for &(symbolic, variant) in &[
+            ("Self", Vendor_Mode_1::Self_),
+            ("type", Vendor_Mode_1::type_),
+            ("A-B", Vendor_Mode_1::A_B),
+            ("A_B", Vendor_Mode_1::A_B_1),
+            ("Other", Vendor_Mode_1::Other),
+        ] {
+            assert_eq!(Vendor_Mode_1::from_entry(symbolic, 0), Some(variant));
+            assert_eq!(variant.entry_key(), EntryKey::Symbolic(symbolic));
+        }
+        assert_eq!(Vendor_Mode_1::from_entry("Missing", 0), None);
+        assert_eq!(PixelSize::from_entry("8Bit", 8), Some(PixelSize::_8Bit));
+    }
+}
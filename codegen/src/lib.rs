@@ -0,0 +1,550 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! `cameleon-codegen` generates typed accessors to `GenApi` features from `GenApi` XML of a
+//! camera.
+//!
+//! The generated module contains
+//! * An accessor function for each feature reachable from `Root` category. The function returns
+//!   the node wrapper of `cameleon::genapi` corresponding to the interface of the feature.
+//! * An enum for each `Enumeration` feature whose variants correspond to `EnumEntry` symbolics.
+//!   The enum implements `cameleon::sfnc::SfncEnum`, so the accessor returns
+//!   `cameleon::sfnc::EnumFeature`.
+//!
+//! `Description`, `ToolTip`, and `Unit` of the features are emitted as doc comments.
+//!
+//! # Examples
+//! In `build.rs`.
+//! ```no_run
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! cameleon_codegen::Generator::new()
+//!     .generate_to_file("camera.xml", format!("{}/camera.rs", out_dir))
+//!     .unwrap();
+//! ```
+//!
+//! Then, include the generated module.
+//! ```ignore
+//! mod camera {
+//!     include!(concat!(env!("OUT_DIR"), "/camera.rs"));
+//! }
+//! ```
+
+#![allow(
+    clippy::module_name_repetitions,
+    clippy::similar_names,
+    clippy::missing_errors_doc
+)]
+
+use std::{borrow::Cow, collections::HashSet, fs, path::Path};
+
+use cameleon_genapi::{
+    builder::GenApiBuilder, parser::ParseError, prelude::*, store::DefaultNodeStore, NodeId,
+    NodeStore,
+};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+/// A specialized `Result` type for code generation.
+pub type CodegenResult<T> = std::result::Result<T, CodegenError>;
+
+/// An error type returned from [`Generator`].
+#[derive(Debug, thiserror::Error)]
+pub enum CodegenError {
+    /// Failed to read the XML or write the generated code.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Failed to parse the XML.
+    #[error("failed to parse GenApi XML: {0}")]
+    Parse(#[from] ParseError),
+
+    /// The XML doesn't follow the structure defined in `GenApi` standard.
+    #[error("invalid GenApi XML: {0}")]
+    InvalidXml(Cow<'static, str>),
+}
+
+/// Generates typed accessors from `GenApi` XML.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    crate_path: String,
+}
+
+impl Generator {
+    /// Constructs a generator with the default configuration.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            crate_path: "::cameleon".into(),
+        }
+    }
+
+    /// Sets the path to `cameleon` crate used in the generated code, `::cameleon` by default.
+    ///
+    /// This is useful when `cameleon` is re-exported from another crate.
+    #[must_use]
+    pub fn crate_path(mut self, path: impl Into<String>) -> Self {
+        self.crate_path = path.into();
+        self
+    }
+
+    /// Generates the source code of the module from `xml`.
+    pub fn generate(&self, xml: &impl AsRef<str>) -> CodegenResult<String> {
+        let builder: GenApiBuilder = GenApiBuilder::default();
+        let (_, store, _) = builder.build(xml)?;
+        let crate_path: TokenStream = self.crate_path.parse().map_err(|_| {
+            CodegenError::InvalidXml(format!("invalid crate path: {}", self.crate_path).into())
+        })?;
+
+        let mut ctxt = GenCtxt::new(&store, crate_path);
+        let root = store
+            .id_by_name("Root")
+            .ok_or_else(|| CodegenError::InvalidXml("`Root` category is missing".into()))?;
+        ctxt.visit(root);
+
+        let items = ctxt.items;
+        Ok(quote!(#(#items)*).to_string())
+    }
+
+    /// Generates the module from the XML file at `xml_path`, then writes it to `out_path`.
+    ///
+    /// Also emits `cargo:rerun-if-changed` for `xml_path`, so this is intended to be called from
+    /// `build.rs`.
+    pub fn generate_to_file(
+        &self,
+        xml_path: impl AsRef<Path>,
+        out_path: impl AsRef<Path>,
+    ) -> CodegenResult<()> {
+        let xml_path = xml_path.as_ref();
+        println!("cargo:rerun-if-changed={}", xml_path.display());
+
+        let xml = fs::read_to_string(xml_path)?;
+        let code = self.generate(&xml)?;
+        fs::write(out_path, code)?;
+        Ok(())
+    }
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct GenCtxt<'a> {
+    store: &'a DefaultNodeStore,
+    crate_path: TokenStream,
+    visited: HashSet<NodeId>,
+    fn_names: HashSet<String>,
+    type_names: HashSet<String>,
+    items: Vec<TokenStream>,
+}
+
+impl<'a> GenCtxt<'a> {
+    fn new(store: &'a DefaultNodeStore, crate_path: TokenStream) -> Self {
+        Self {
+            store,
+            crate_path,
+            visited: HashSet::new(),
+            fn_names: HashSet::new(),
+            type_names: HashSet::new(),
+            items: vec![],
+        }
+    }
+
+    fn visit(&mut self, nid: NodeId) {
+        if !self.visited.insert(nid) {
+            return;
+        }
+
+        if let Some(category) = nid.as_icategory_kind(self.store) {
+            for feature in category.nodes(self.store) {
+                self.visit(*feature);
+            }
+        } else {
+            self.gen_feature(nid);
+        }
+    }
+
+    fn gen_feature(&mut self, nid: NodeId) {
+        let store = self.store;
+        let crate_path = &self.crate_path.clone();
+        let name = nid.name(store);
+
+        let (ret_ty, downcast) = if let Some(kind) = nid.as_ienumeration_kind(store) {
+            let enum_ident = self.type_ident(name);
+            let entries = kind.entries(store);
+            self.items.push(self.gen_enum(&enum_ident, name, entries));
+            (
+                quote!(#crate_path::sfnc::EnumFeature<#enum_ident>),
+                quote!(as_enumeration(ctxt).map(#crate_path::sfnc::EnumFeature::new)),
+            )
+        } else if nid.as_icommand_kind(store).is_some() {
+            node_ty(crate_path, "CommandNode", "as_command")
+        } else if nid.as_iboolean_kind(store).is_some() {
+            node_ty(crate_path, "BooleanNode", "as_boolean")
+        } else if nid.as_ifloat_kind(store).is_some() {
+            node_ty(crate_path, "FloatNode", "as_float")
+        } else if nid.as_iinteger_kind(store).is_some() {
+            node_ty(crate_path, "IntegerNode", "as_integer")
+        } else if nid.as_istring_kind(store).is_some() {
+            node_ty(crate_path, "StringNode", "as_string")
+        } else if nid.as_iregister_kind(store).is_some() {
+            node_ty(crate_path, "RegisterNode", "as_register")
+        } else {
+            // Ports and uninterpreted nodes don't have value to access.
+            return;
+        };
+
+        let fn_ident = self.fn_ident(name);
+        let mut docs = node_docs(nid, store);
+        let unit = nid
+            .as_ifloat_kind(store)
+            .and_then(|kind| kind.unit(store).map(String::from))
+            .or_else(|| {
+                nid.as_iinteger_kind(store)
+                    .and_then(|kind| kind.unit(store).map(String::from))
+            });
+        if let Some(unit) = unit {
+            if !docs.is_empty() {
+                docs.push(String::new());
+            }
+            docs.push(format!("Unit: `{}`", unit));
+        }
+        let docs = docs.iter().map(|doc| format!(" {}", doc));
+
+        self.items.push(quote! {
+            #(#[doc = #docs])*
+            pub fn #fn_ident<Ctrl, Ctxt>(
+                ctxt: &#crate_path::genapi::ParamsCtxt<Ctrl, Ctxt>
+            ) -> ::std::option::Option<#ret_ty>
+            where
+                Ctxt: #crate_path::genapi::GenApiCtxt,
+            {
+                ctxt.node(#name)?.#downcast
+            }
+        });
+    }
+
+    fn gen_enum(&self, enum_ident: &Ident, name: &str, entries: &[NodeId]) -> TokenStream {
+        let store = self.store;
+        let crate_path = &self.crate_path;
+        let enum_doc = format!(" Entries of `{}`.", name);
+
+        let mut variant_names = HashSet::new();
+        let mut variants = vec![];
+        let mut symbolics = vec![];
+        let mut variant_docs = vec![];
+        for entry in entries {
+            let symbolic = match entry.as_enum_entry(store) {
+                Some(entry) => entry.symbolic(),
+                None => continue,
+            };
+            let variant = unique_name(&mut variant_names, sanitize(symbolic));
+            let docs = node_docs(*entry, store);
+            let docs = if docs.is_empty() {
+                vec![format!("`{}`.", symbolic)]
+            } else {
+                docs
+            };
+            variants.push(Ident::new(&variant, Span::call_site()));
+            symbolics.push(symbolic);
+            variant_docs.push(
+                docs.into_iter()
+                    .map(|doc| format!(" {}", doc))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        quote! {
+            #[doc = #enum_doc]
+            #[allow(non_camel_case_types)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            pub enum #enum_ident {
+                #(
+                    #(#[doc = #variant_docs])*
+                    #variants,
+                )*
+            }
+
+            impl #crate_path::sfnc::SfncEnum for #enum_ident {
+                fn from_entry(symbolic: &str, _: i64) -> ::std::option::Option<Self> {
+                    match symbolic {
+                        #(#symbolics => ::std::option::Option::Some(Self::#variants),)*
+                        _ => ::std::option::Option::None,
+                    }
+                }
+
+                fn entry_key(&self) -> #crate_path::sfnc::EntryKey<'_> {
+                    match self {
+                        #(Self::#variants => #crate_path::sfnc::EntryKey::Symbolic(#symbolics),)*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Different node names may be sanitized to the same identifier, e.g. `Vendor-Mode` and
+    /// `Vendor_Mode`, so a suffix is appended to the later one.
+    fn type_ident(&mut self, name: &str) -> Ident {
+        let name = unique_name(&mut self.type_names, sanitize(name));
+        Ident::new(&name, Span::call_site())
+    }
+
+    fn fn_ident(&mut self, name: &str) -> Ident {
+        let name = unique_name(&mut self.fn_names, to_snake_case(name));
+        if KEYWORDS.contains(&name.as_str()) {
+            Ident::new_raw(&name, Span::call_site())
+        } else {
+            Ident::new(&name, Span::call_site())
+        }
+    }
+}
+
+/// Keywords that need to be escaped as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try", "typeof",
+    "unsized", "virtual", "yield",
+];
+
+fn node_ty(crate_path: &TokenStream, ty: &str, method: &str) -> (TokenStream, TokenStream) {
+    let ty = Ident::new(ty, Span::call_site());
+    let method = Ident::new(method, Span::call_site());
+    (quote!(#crate_path::genapi::#ty), quote!(#method(ctxt)))
+}
+
+/// Returns lines of doc comment made from `Description` and `ToolTip` of the node.
+fn node_docs(nid: NodeId, store: &DefaultNodeStore) -> Vec<String> {
+    let node_base = match nid.as_inode_kind(store) {
+        Some(kind) => kind.node_base_precise(),
+        None => return vec![],
+    };
+
+    let mut docs = vec![];
+    let description = node_base.description();
+    if let Some(description) = description {
+        docs.extend(description.lines().map(|line| line.trim().to_string()));
+    }
+    match node_base.tooltip() {
+        Some(tooltip) if Some(tooltip) != description => {
+            if !docs.is_empty() {
+                docs.push(String::new());
+            }
+            docs.extend(tooltip.lines().map(|line| line.trim().to_string()));
+        }
+        _ => {}
+    }
+    docs
+}
+
+/// Converts `name` to a valid identifier.
+fn sanitize(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if ident == "_" || ident == "Self" || KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+/// Converts `CamelCase` name to `snake_case`, e.g. `DeviceSFNCVersionMajor` to
+/// `device_sfnc_version_major`.
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = sanitize(name).chars().collect();
+    let mut snake = String::with_capacity(chars.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(char::is_ascii_lowercase);
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+
+    match snake.as_str() {
+        "self" | "super" | "crate" => snake.push('_'),
+        _ => {}
+    }
+    snake
+}
+
+/// Appends a suffix to `name` if it's already used.
+fn unique_name(used: &mut HashSet<String>, name: String) -> String {
+    let mut unique = name.clone();
+    let mut i = 1;
+    while !used.insert(unique.clone()) {
+        unique = format!("{}_{}", name, i);
+        i += 1;
+    }
+    unique
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"
+        <RegisterDescription
+          ModelName="CameleonModel"
+          VendorName="CameleonVendor"
+          StandardNameSpace="None"
+          SchemaMajorVersion="1"
+          SchemaMinorVersion="1"
+          SchemaSubMinorVersion="0"
+          MajorVersion="1"
+          MinorVersion="2"
+          SubMinorVersion="3"
+          ProductGuid="01234567-0123-0123-0123-0123456789ab"
+          VersionGuid="76543210-3210-3210-3210-ba9876543210"
+          xmlns="http://www.genicam.org/GenApi/Version_1_0"
+          xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+          xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_0 GenApiSchema.xsd">
+
+            <Category Name="Root" NameSpace="Standard">
+                <pFeature>AcquisitionControl</pFeature>
+                <pFeature>DeviceSFNCVersionMajor</pFeature>
+            </Category>
+
+            <Category Name="AcquisitionControl" NameSpace="Standard">
+                <pFeature>ExposureTime</pFeature>
+                <pFeature>TriggerMode</pFeature>
+                <pFeature>AcquisitionStart</pFeature>
+            </Category>
+
+            <Float Name="ExposureTime" NameSpace="Standard">
+                <ToolTip>Exposure time.</ToolTip>
+                <Description>Sets the exposure time.</Description>
+                <Value>100.0</Value>
+                <Unit>us</Unit>
+            </Float>
+
+            <Enumeration Name="TriggerMode" NameSpace="Standard">
+                <EnumEntry Name="Off">
+                    <Value>0</Value>
+                </EnumEntry>
+                <EnumEntry Name="On">
+                    <Value>1</Value>
+                </EnumEntry>
+                <Value>0</Value>
+            </Enumeration>
+
+            <Command Name="AcquisitionStart" NameSpace="Standard">
+                <Value>0</Value>
+                <CommandValue>1</CommandValue>
+            </Command>
+
+            <Integer Name="DeviceSFNCVersionMajor" NameSpace="Standard">
+                <Value>2</Value>
+            </Integer>
+
+            <Integer Name="NotAFeature">
+                <Value>0</Value>
+            </Integer>
+
+        </RegisterDescription>
+        "#;
+
+    fn normalize(code: &str) -> String {
+        code.chars().filter(|c| !c.is_whitespace()).collect()
+    }
+
+    #[test]
+    fn test_generate() {
+        let code = normalize(&Generator::new().generate(&XML).unwrap());
+
+        assert!(code.contains(&normalize(
+            "pub fn exposure_time<Ctrl, Ctxt>(ctxt: &::cameleon::genapi::ParamsCtxt<Ctrl, Ctxt>) \
+             -> ::std::option::Option<::cameleon::genapi::FloatNode>"
+        )));
+        assert!(code.contains(&normalize(r#"#[doc = " Unit: `us`"]"#)));
+        assert!(code.contains(&normalize(r#"#[doc = " Sets the exposure time."]"#)));
+        assert!(code.contains(&normalize("pub enum TriggerMode")));
+        assert!(code.contains(&normalize(
+            "-> ::std::option::Option<::cameleon::sfnc::EnumFeature<TriggerMode>>"
+        )));
+        assert!(code.contains(&normalize(
+            r#""On" => ::std::option::Option::Some(Self::On)"#
+        )));
+        assert!(code.contains(&normalize("pub fn acquisition_start")));
+        assert!(code.contains(&normalize("pub fn device_sfnc_version_major")));
+        assert!(!code.contains("not_a_feature"));
+        assert!(!code.contains("acquisition_control"));
+    }
+
+    #[test]
+    fn test_conflicting_type_names() {
+        let xml = XML.replace(
+            r#"<pFeature>DeviceSFNCVersionMajor</pFeature>"#,
+            r#"<pFeature>DeviceSFNCVersionMajor</pFeature>
+                <pFeature>Vendor-Mode</pFeature>
+                <pFeature>Vendor_Mode</pFeature>"#,
+        );
+        let enumeration = |name| {
+            format!(
+                r#"<Enumeration Name="{}">
+                    <EnumEntry Name="A"><Value>0</Value></EnumEntry>
+                    <Value>0</Value>
+                </Enumeration>"#,
+                name
+            )
+        };
+        let xml = xml.replace(
+            "</RegisterDescription>",
+            &format!(
+                "{}{}</RegisterDescription>",
+                enumeration("Vendor-Mode"),
+                enumeration("Vendor_Mode")
+            ),
+        );
+
+        let code = normalize(&Generator::new().generate(&xml).unwrap());
+        assert_eq!(code.matches("pubenumVendor_Mode{").count(), 1);
+        assert_eq!(code.matches("pubenumVendor_Mode_1{").count(), 1);
+        assert!(code.contains(&normalize(
+            "pub fn vendor_mode_1<Ctrl, Ctxt>(ctxt: &::cameleon::genapi::ParamsCtxt<Ctrl, Ctxt>) \
+             -> ::std::option::Option<::cameleon::sfnc::EnumFeature<Vendor_Mode_1>>"
+        )));
+    }
+
+    #[test]
+    fn test_crate_path() {
+        let code = Generator::new()
+            .crate_path("my_crate::cameleon")
+            .generate(&XML)
+            .unwrap();
+        assert!(normalize(&code).contains(&normalize("my_crate::cameleon::genapi::FloatNode")));
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("ExposureTime"), "exposure_time");
+        assert_eq!(
+            to_snake_case("DeviceSFNCVersionMajor"),
+            "device_sfnc_version_major"
+        );
+        assert_eq!(to_snake_case("ChunkFrameID"), "chunk_frame_id");
+        assert_eq!(to_snake_case("OffsetX"), "offset_x");
+        assert_eq!(to_snake_case("Line0Status"), "line0_status");
+    }
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("Mono8"), "Mono8");
+        assert_eq!(sanitize("8Bit"), "_8Bit");
+        assert_eq!(sanitize("Self"), "Self_");
+        assert_eq!(sanitize("Vendor-Mode"), "Vendor_Mode");
+    }
+}
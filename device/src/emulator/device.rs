@@ -9,22 +9,27 @@ use super::{
     emulator_impl::{DeviceHandle, IfaceKind},
 };
 
+/// Emulated `U3V` device.
 pub struct Device {
     device_id: u32,
+    /// Information of the device.
     pub device_info: DeviceInfo,
 }
 
 impl Device {
+    /// Returns the control channel of the device.
     pub fn control_channel(&self) -> Result<ControlChannel> {
         let handle = DeviceHandle::new(self.device_id, IfaceKind::Control);
         Ok(ControlChannel::new(handle))
     }
 
+    /// Returns the event channel of the device.
     pub fn event_channel(&self) -> Result<Option<ReceiveChannel>> {
         let handle = DeviceHandle::new(self.device_id, IfaceKind::Event);
         Ok(Some(ReceiveChannel::new(handle)))
     }
 
+    /// Returns the stream channel of the device.
     pub fn stream_channel(&self) -> Result<Option<ReceiveChannel>> {
         let handle = DeviceHandle::new(self.device_id, IfaceKind::Stream);
         Ok(Some(ReceiveChannel::new(handle)))
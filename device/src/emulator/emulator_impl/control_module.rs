@@ -2,9 +2,12 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
 use async_std::{
@@ -18,8 +21,9 @@ use cameleon_impl::memory::{prelude::*, MemoryError};
 
 use super::{
     device::Timestamp,
+    fault::{self, ControlFault, ControlFaults},
     interface::IfaceState,
-    memory::{Memory, SBRM},
+    memory::{Memory, ABRM, SBRM, SIRM},
     memory_event_handler::MemoryEventHandler,
     shared_queue::SharedQueue,
     signal::{ControlSignal, InterfaceSignal},
@@ -33,6 +37,7 @@ pub(super) struct ControlModule {
     memory: Arc<Mutex<Memory>>,
     timestamp: Timestamp,
     queue: SharedQueue<Vec<u8>>,
+    faults: ControlFaults,
 }
 
 impl ControlModule {
@@ -41,12 +46,14 @@ impl ControlModule {
         memory: Arc<Mutex<Memory>>,
         timestamp: Timestamp,
         queue: SharedQueue<Vec<u8>>,
+        faults: ControlFaults,
     ) -> Self {
         Self {
             iface_state,
             memory,
             timestamp,
             queue,
+            faults,
         }
     }
 
@@ -64,6 +71,7 @@ impl ControlModule {
             event_handler,
             self.queue.clone(),
             signal_tx,
+            self.faults.clone(),
         )
         .await;
 
@@ -76,9 +84,16 @@ impl ControlModule {
 
                 ControlSignal::CancelJobs(_completed) => worker_manager.wait_completion().await,
 
-                ControlSignal::ClearSiRegister | ControlSignal::ClearEiRegister => {
-                    // TODO:
-                    todo!()
+                ControlSignal::ClearSiRegister => {
+                    // Stream module is disabled by interface, so just clear the register.
+                    let mut memory = self.memory.lock().await;
+                    if let Err(e) = memory.write::<SIRM::Control>(0) {
+                        log::error!("failed to clear SI control register: {}", e);
+                    }
+                }
+
+                ControlSignal::ClearEiRegister => {
+                    // EIRM is not implemented yet, so there is nothing to clear.
                 }
 
                 ControlSignal::Shutdown => {
@@ -105,6 +120,8 @@ struct WorkerManager {
     maximum_cmd_length: usize,
     maximum_ack_length: usize,
 
+    faults: ControlFaults,
+
     /// Work as join handle coupled with `completed_rx`.
     /// All workers spawnd by the manager share this sender.
     completed_tx: Sender<()>,
@@ -121,6 +138,7 @@ impl WorkerManager {
         memory_event_handler: MemoryEventHandler,
         queue: SharedQueue<Vec<u8>>,
         signal_tx: Sender<InterfaceSignal>,
+        faults: ControlFaults,
     ) -> Self {
         let (completed_tx, completed_rx) = channel::bounded(1);
         let on_processing = Arc::new(AtomicBool::new(false));
@@ -149,6 +167,8 @@ impl WorkerManager {
             maximum_cmd_length,
             maximum_ack_length,
 
+            faults,
+
             completed_tx,
             completed_rx,
         }
@@ -170,6 +190,9 @@ impl WorkerManager {
             maximum_cmd_length: self.maximum_cmd_length,
             maximum_ack_length: self.maximum_ack_length,
 
+            latency: self.faults.latency(),
+            fault: self.faults.next(),
+
             _completed: self.completed_tx.clone(),
         }
     }
@@ -201,12 +224,20 @@ pub(super) struct Worker {
     maximum_cmd_length: usize,
     maximum_ack_length: usize,
 
+    /// Delay before the command is processed.
+    latency: Option<Duration>,
+    /// Fault injected into the command.
+    fault: Option<ControlFault>,
+
     _completed: Sender<()>,
 }
 
 impl Worker {
-    // TODO: Emulate pending situation.
     async fn run(self, command: Vec<u8>) {
+        if let Some(latency) = self.latency {
+            task::sleep(latency).await;
+        }
+
         let cmd_packet = match self.try_parse_command(&command) {
             Some(packet) => packet,
             None => return,
@@ -239,8 +270,8 @@ impl Worker {
         // If another thread is processing command simultaneously, return busy error ack.
         if self
             .on_processing
-            .compare_exchange(true, false, Ordering::Relaxed, Ordering::Relaxed)
-            .is_ok()
+            .compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
         {
             let ack = ack::ErrorAck::new(ack::GenCpStatus::Busy, ccd.scd_kind())
                 .finalize(ccd.request_id());
@@ -248,7 +279,23 @@ impl Worker {
             return;
         }
 
+        if let Some(ControlFault::Pending { count, timeout }) = self.fault {
+            for _ in 0..count {
+                let ack = ack::Pending::new(timeout).finalize(ccd.request_id());
+                self.enqueue_or_halt(&ack);
+                task::sleep(timeout).await;
+            }
+        }
+
         match ccd.scd_kind() {
+            // Stacked commands are rejected if the device capability doesn't advertise them.
+            cmd::ScdKind::ReadMemStacked | cmd::ScdKind::WriteMemStacked
+                if !self.is_stacked_commands_supported().await =>
+            {
+                let ack = ack::ErrorAck::new(ack::GenCpStatus::NotImplemented, ccd.scd_kind())
+                    .finalize(ccd.request_id());
+                self.enqueue_or_halt(&ack);
+            }
             cmd::ScdKind::ReadMem => self.process_read_mem(cmd_packet).await,
             cmd::ScdKind::WriteMem => self.process_write_mem(cmd_packet).await,
             cmd::ScdKind::ReadMemStacked => self.process_read_mem_stacked(cmd_packet).await,
@@ -258,6 +305,16 @@ impl Worker {
         self.on_processing.store(false, Ordering::Relaxed);
     }
 
+    async fn is_stacked_commands_supported(&self) -> bool {
+        let capability = self
+            .memory
+            .lock()
+            .await
+            .read::<ABRM::DeviceCapability>()
+            .unwrap();
+        capability[1] & 0b0010_0000 != 0
+    }
+
     fn try_parse_command<'a>(&self, command: &'a [u8]) -> Option<cmd::CommandPacket<'a>> {
         match cmd::CommandPacket::parse(command) {
             Ok(packet) => Some(packet),
@@ -357,8 +414,11 @@ impl Worker {
         };
     }
 
+    /// Reads all entries and replies with their data concatenated.
+    ///
+    /// If any entry fails, only an error ack for the first failed entry is sent.
     async fn process_read_mem_stacked(&self, command: cmd::CommandPacket<'_>) {
-        let _scd: cmd::WriteMemStacked = match self.try_extract_scd(&command) {
+        let scd: cmd::ReadMemStacked = match self.try_extract_scd(&command) {
             Some(scd) => scd,
             None => return,
         };
@@ -366,13 +426,32 @@ impl Worker {
         let req_id = ccd.request_id();
         let scd_kind = ccd.scd_kind();
 
-        // TODO: Should we implement this command?
-        let ack = ack::ErrorAck::new(ack::GenCpStatus::NotImplemented, scd_kind).finalize(req_id);
+        let memory = self.memory.lock().await;
+        let mut data = vec![];
+        for entry in &scd.entries {
+            let address = entry.address as usize;
+            let read_length = entry.read_length as usize;
+
+            match memory.read_raw(address..address + read_length) {
+                Ok(entry_data) => data.extend_from_slice(entry_data),
+                Err(err) => {
+                    let ack = ack::ErrorAck::new(error_status(&err), scd_kind).finalize(req_id);
+                    self.enqueue_or_halt(&ack);
+                    return;
+                }
+            }
+        }
+
+        let ack = ack::ReadMemStacked::new(&data).finalize(req_id);
         self.enqueue_or_halt(&ack);
     }
 
+    /// Writes entries in order.
+    ///
+    /// Processing stops at the first failed entry and an error ack for it is sent. Entries
+    /// preceding the failed one are NOT rolled back, so they stay written.
     async fn process_write_mem_stacked(&self, command: cmd::CommandPacket<'_>) {
-        let _scd: cmd::WriteMemStacked = match self.try_extract_scd(&command) {
+        let scd: cmd::WriteMemStacked = match self.try_extract_scd(&command) {
             Some(scd) => scd,
             None => return,
         };
@@ -380,8 +459,30 @@ impl Worker {
         let req_id = ccd.request_id();
         let scd_kind = ccd.scd_kind();
 
-        // TODO: Should we implement this command?
-        let ack = ack::ErrorAck::new(ack::GenCpStatus::NotImplemented, scd_kind).finalize(req_id);
+        let mut lengths = Vec::with_capacity(scd.entries.len());
+        for entry in &scd.entries {
+            let mut memory = self.memory.lock().await;
+            if let Err(err) = memory.write_raw(entry.address as usize, entry.data) {
+                let ack = ack::ErrorAck::new(error_status(&err), scd_kind).finalize(req_id);
+                self.enqueue_or_halt(&ack);
+                return;
+            }
+            // Explicitly drop memory to avoid race condition.
+            drop(memory);
+
+            // Handle events of each entry before writing the next entry.
+            if let Err(error_ack) = self
+                .memory_event_handler
+                .handle_events(self, scd_kind)
+                .await
+            {
+                self.enqueue_or_halt(&error_ack.finalize(req_id));
+                return;
+            }
+            lengths.push(entry.data.len() as u16);
+        }
+
+        let ack = ack::WriteMemStacked::new(lengths).finalize(req_id);
         self.enqueue_or_halt(&ack);
     }
 
@@ -422,6 +523,23 @@ impl Worker {
             buf
         };
 
+        // Pending acknowledges are a fault in themselves, so other faults aren't applied to them.
+        let fault = if ack.ccd.scd_kind == ack::ScdKind::Pending {
+            None
+        } else {
+            self.fault
+        };
+        let buf = match fault::apply_to_ack(fault, buf) {
+            Some(buf) => buf,
+            None => {
+                if fault == Some(ControlFault::Halt) {
+                    log::info!("inject fault: entering a halted state");
+                    self.try_send_signal(InterfaceSignal::Halt(IfaceKind::Control));
+                }
+                return;
+            }
+        };
+
         if !self.queue.enqueue(buf) {
             log::warn!("control queue is full, entering a halted state");
             self.try_send_signal(InterfaceSignal::Halt(IfaceKind::Control));
@@ -437,3 +555,207 @@ impl Worker {
         }
     }
 }
+
+/// Converts an error of memory access into the status of an error ack.
+fn error_status(err: &MemoryError) -> ack::GenCpStatus {
+    match err {
+        MemoryError::InvalidAddress => ack::GenCpStatus::InvalidAddress,
+        MemoryError::AddressNotReadable => ack::GenCpStatus::AccessDenied,
+        MemoryError::AddressNotWritable => ack::GenCpStatus::WriteProtect,
+        MemoryError::InvalidRegisterData(..) => ack::GenCpStatus::InvalidParameter,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_std::{channel, task};
+
+    use crate::u3v::protocol::{ack as host_ack, cmd as host_cmd, cmd::CommandScd};
+
+    use super::{super::fault::FaultConfig, *};
+
+    const TO: Duration = Duration::from_millis(100);
+
+    const SERIAL_NUMBER: u64 = 0x144;
+    const USER_DEFINED_NAME: u64 = 0x184;
+    const INVALID_ADDRESS: u64 = u64::MAX - 0x100;
+
+    struct Module {
+        signal_tx: Sender<ControlSignal>,
+        memory: Arc<Mutex<Memory>>,
+        queue: SharedQueue<Vec<u8>>,
+        _iface_signal_rx: Receiver<InterfaceSignal>,
+    }
+
+    impl Module {
+        fn spawn(memory: Memory) -> Self {
+            let (signal_tx, signal_rx) = channel::bounded(10);
+            let (iface_signal_tx, iface_signal_rx) = channel::bounded(10);
+            let memory = Arc::new(Mutex::new(memory));
+            let queue = SharedQueue::new(10);
+            let control_module = ControlModule::new(
+                IfaceState::new(),
+                memory.clone(),
+                Timestamp::new(),
+                queue.clone(),
+                ControlFaults::new(&FaultConfig::default()),
+            );
+            task::spawn(control_module.run(iface_signal_tx, signal_rx));
+
+            Self {
+                signal_tx,
+                memory,
+                queue,
+                _iface_signal_rx: iface_signal_rx,
+            }
+        }
+
+        fn send_command<T: CommandScd>(&self, scd: T) -> Vec<u8> {
+            let mut buf = vec![];
+            scd.finalize(1).serialize(&mut buf).unwrap();
+            self.signal_tx
+                .try_send(ControlSignal::ReceiveData(buf))
+                .unwrap();
+
+            let now = std::time::Instant::now();
+            while now.elapsed() < TO {
+                if let Some(data) = self.queue.dequeue() {
+                    return data;
+                }
+            }
+            panic!("no acknowledge is received");
+        }
+
+        fn user_defined_name(&self) -> String {
+            task::block_on(self.memory.lock())
+                .read::<ABRM::UserDefinedName>()
+                .unwrap()
+        }
+    }
+
+    impl Drop for Module {
+        fn drop(&mut self) {
+            self.signal_tx.try_send(ControlSignal::Shutdown).ok();
+        }
+    }
+
+    fn status(ack: &[u8]) -> host_ack::StatusKind {
+        host_ack::AckPacket::parse(ack).unwrap().status().kind()
+    }
+
+    fn read_stacked(entries: &[(u64, u16)]) -> host_cmd::ReadMemStacked {
+        let entries = entries
+            .iter()
+            .map(|&(address, len)| host_cmd::ReadMem::new(address, len))
+            .collect();
+        host_cmd::ReadMemStacked::new(entries).unwrap()
+    }
+
+    fn write_stacked<'a>(entries: &[(u64, &'a [u8])]) -> host_cmd::WriteMemStacked<'a> {
+        let entries = entries
+            .iter()
+            .map(|&(address, data)| host_cmd::WriteMem::new(address, data).unwrap())
+            .collect();
+        host_cmd::WriteMemStacked::new(entries).unwrap()
+    }
+
+    #[test]
+    fn test_read_mem_stacked() {
+        let mut memory = Memory::new();
+        memory
+            .write::<ABRM::UserDefinedName>("Stacked".into())
+            .unwrap();
+        let module = Module::spawn(memory);
+
+        let ack = module.send_command(read_stacked(&[
+            (USER_DEFINED_NAME, 4),
+            (USER_DEFINED_NAME + 4, 3),
+        ]));
+        let ack = host_ack::AckPacket::parse(&ack).unwrap();
+        assert!(ack.status().is_success());
+        let scd = ack.scd_as::<host_ack::ReadMemStacked>().unwrap();
+        assert_eq!(scd.data, b"Stacked");
+    }
+
+    #[test]
+    fn test_read_mem_stacked_invalid_address() {
+        let module = Module::spawn(Memory::new());
+
+        let ack = module.send_command(read_stacked(&[
+            (USER_DEFINED_NAME, 4),
+            (INVALID_ADDRESS, 4),
+        ]));
+        assert_eq!(
+            status(&ack),
+            host_ack::StatusKind::GenCp(host_ack::GenCpStatus::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn test_write_mem_stacked() {
+        let module = Module::spawn(Memory::new());
+
+        let ack = module.send_command(write_stacked(&[
+            (USER_DEFINED_NAME, b"Sta"),
+            (USER_DEFINED_NAME + 3, b"cked\0"),
+        ]));
+        let ack = host_ack::AckPacket::parse(&ack).unwrap();
+        assert!(ack.status().is_success());
+        let scd = ack.scd_as::<host_ack::WriteMemStacked>().unwrap();
+        assert_eq!(scd.lengths, &[3, 5]);
+        assert_eq!(module.user_defined_name(), "Stacked");
+    }
+
+    #[test]
+    fn test_write_mem_stacked_partial_write() {
+        let module = Module::spawn(Memory::new());
+
+        // The second entry is write protected, so the third entry must not be written.
+        let ack = module.send_command(write_stacked(&[
+            (USER_DEFINED_NAME, b"First\0"),
+            (SERIAL_NUMBER, b"Serial\0"),
+            (USER_DEFINED_NAME, b"Third\0"),
+        ]));
+        assert_eq!(
+            status(&ack),
+            host_ack::StatusKind::GenCp(host_ack::GenCpStatus::WriteProtect)
+        );
+        // Entries preceding the failed one stay written.
+        assert_eq!(module.user_defined_name(), "First");
+
+        let ack = module.send_command(write_stacked(&[
+            (USER_DEFINED_NAME, b"Second\0"),
+            (INVALID_ADDRESS, b"Invalid"),
+        ]));
+        assert_eq!(
+            status(&ack),
+            host_ack::StatusKind::GenCp(host_ack::GenCpStatus::InvalidAddress)
+        );
+        assert_eq!(module.user_defined_name(), "Second");
+    }
+
+    #[test]
+    fn test_stacked_commands_not_supported() {
+        let mut memory = Memory::new();
+        let mut capability = memory.read::<ABRM::DeviceCapability>().unwrap();
+        capability[1] &= !0b0010_0000;
+        memory.write::<ABRM::DeviceCapability>(capability).unwrap();
+        let module = Module::spawn(memory);
+
+        let not_implemented = host_ack::StatusKind::GenCp(host_ack::GenCpStatus::NotImplemented);
+        let ack = module.send_command(read_stacked(&[(USER_DEFINED_NAME, 4)]));
+        assert_eq!(status(&ack), not_implemented);
+        let ack = module.send_command(write_stacked(&[(USER_DEFINED_NAME, b"Name\0")]));
+        assert_eq!(status(&ack), not_implemented);
+        assert_eq!(module.user_defined_name(), "");
+
+        // Non-stacked commands are still available.
+        let ack = module.send_command(host_cmd::ReadMem::new(USER_DEFINED_NAME, 4));
+        assert!(host_ack::AckPacket::parse(&ack)
+            .unwrap()
+            .status()
+            .is_success());
+    }
+}
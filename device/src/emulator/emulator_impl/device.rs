@@ -2,7 +2,14 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::{convert::TryInto, sync::Arc, time};
+use std::{
+    convert::TryInto,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time,
+};
 
 use async_std::{
     channel::{self, Receiver, Sender},
@@ -15,6 +22,7 @@ use crate::u3v::DeviceInfo;
 
 use super::{
     fake_protocol::{FakeAckPacket, FakeReqPacket},
+    fault::FaultConfig,
     interface::Interface,
     memory::Memory,
 };
@@ -28,16 +36,21 @@ pub(super) struct Device {
     shutdown_tx: Option<oneshot::Sender<()>>,
     completion_rx: Option<oneshot::Receiver<()>>,
     device_info: DeviceInfo,
+    faults: FaultConfig,
+    /// Set when the device is disconnected by fault injection.
+    disconnected: Arc<AtomicBool>,
 }
 
 impl Device {
-    pub(super) fn new(memory: Memory, device_info: DeviceInfo) -> Self {
+    pub(super) fn new(memory: Memory, device_info: DeviceInfo, faults: FaultConfig) -> Self {
         Self {
             timestamp: Timestamp::new(),
             memory: Arc::new(Mutex::new(memory)),
             shutdown_tx: None,
             completion_rx: None,
             device_info,
+            faults,
+            disconnected: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -53,12 +66,13 @@ impl Device {
         self.completion_rx = Some(completion_rx);
 
         task::spawn(
-            Interface::new(self.memory.clone(), self.timestamp.clone()).run(
-                ack_tx,
-                req_rx,
-                shutdown_rx,
-                completion_tx,
-            ),
+            Interface::new(
+                self.memory.clone(),
+                self.timestamp.clone(),
+                self.faults.clone(),
+                self.disconnected.clone(),
+            )
+            .run(ack_tx, req_rx, shutdown_rx, completion_tx),
         );
 
         (req_tx, ack_rx)
@@ -74,6 +88,10 @@ impl Device {
         }
     }
 
+    pub(super) fn is_disconnected(&self) -> bool {
+        self.disconnected.load(Ordering::Relaxed)
+    }
+
     pub(super) fn device_info(&self) -> &DeviceInfo {
         &self.device_info
     }
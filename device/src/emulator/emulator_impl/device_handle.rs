@@ -21,6 +21,9 @@ use super::{
     },
 };
 
+/// Interval of polling an emulated device while it has no data to send.
+const POLLING_INTERVAL: Duration = Duration::from_micros(500);
+
 #[derive(Debug)]
 pub(crate) struct DeviceHandle {
     device_id: u32,
@@ -50,6 +53,7 @@ impl DeviceHandle {
                     return Ok(data.len());
                 }
                 RecvNak => {
+                    std::thread::sleep(POLLING_INTERVAL);
                     continue;
                 }
                 IfaceHalted => {
@@ -62,25 +66,16 @@ impl DeviceHandle {
         Err(LibUsbError::Timeout.into())
     }
 
-    pub(crate) fn write_bulk(&self, buf: &[u8], timeout: Duration) -> Result<usize> {
-        let start = Instant::now();
-
-        while timeout.checked_sub(start.elapsed()).is_some() {
-            let req = FakeReqPacket::new(self.iface_kind, FakeReqKind::Send(buf.to_vec()));
-            let ack = self.send_packet(req)?;
+    pub(crate) fn write_bulk(&self, buf: &[u8], _timeout: Duration) -> Result<usize> {
+        // An emulated device accepts data immediately, so timeout never occurs.
+        let req = FakeReqPacket::new(self.iface_kind, FakeReqKind::Send(buf.to_vec()));
+        let ack = self.send_packet(req)?;
 
-            match ack.kind {
-                SendAck => {
-                    return Ok(buf.len());
-                }
-                IfaceHalted => {
-                    return Err(LibUsbError::Pipe.into());
-                }
-                _ => unreachable!(),
-            }
+        match ack.kind {
+            SendAck => Ok(buf.len()),
+            IfaceHalted => Err(LibUsbError::Pipe.into()),
+            _ => unreachable!(),
         }
-
-        Err(LibUsbError::Timeout.into())
     }
 
     pub(crate) fn set_halt(&self) -> Result<()> {
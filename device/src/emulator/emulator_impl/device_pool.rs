@@ -35,8 +35,13 @@ impl DevicePool {
         Ok(ctx.device_info())
     }
 
+    /// Returns ids of devices connected to the host.
     pub(crate) fn device_ids(&self) -> Vec<u32> {
-        self.contexts.iter().map(|ctx| ctx.device_id).collect()
+        self.contexts
+            .iter()
+            .filter(|ctx| !ctx.device.is_disconnected())
+            .map(|ctx| ctx.device_id)
+            .collect()
     }
 
     pub(crate) fn with<F, R>(f: F) -> R
@@ -44,7 +49,7 @@ impl DevicePool {
         F: FnOnce(&mut DevicePool) -> R,
     {
         let mut pool = task::block_on(DEVICE_POOL.lock());
-        f(&mut *pool)
+        f(&mut pool)
     }
 
     pub(super) fn claim_interface(
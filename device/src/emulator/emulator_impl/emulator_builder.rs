@@ -2,6 +2,8 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::time::Duration;
+
 use rand::seq::SliceRandom;
 use semver::Version;
 use thiserror::Error;
@@ -11,6 +13,7 @@ use crate::u3v::{BusSpeed, DeviceInfo};
 use super::{
     device::Device,
     device_pool::DevicePool,
+    fault::{ControlFault, FaultConfig, FaultSchedule, StreamFault},
     memory::{Memory, ABRM, SBRM},
 };
 
@@ -20,6 +23,9 @@ use cameleon_impl::memory::prelude::*;
 pub enum BuilderError {
     #[error("invalid string: {0}")]
     InvalidString(String),
+
+    #[error("invalid fault: {0}")]
+    InvalidFault(String),
 }
 
 pub type BuilderResult<T> = std::result::Result<T, BuilderError>;
@@ -30,12 +36,12 @@ pub type BuilderResult<T> = std::result::Result<T, BuilderError>;
 /// An emulator is passed to the device pool and user can't control the emulator itself directly
 /// once build process is finished by calling [`EmulatorBuilder::build`].
 ///
-/// Emulators in the device pool can be found by [`crate::u3v::enumerate_devices`] and controlled via
-/// [`crate::u3v::Device`] in the same way as real device.
+/// Emulators in the device pool can be found by [`crate::emulator::enumerate_devices`] and controlled via
+/// [`crate::emulator::Device`] in the same way as real device.
 ///
 /// # Example
 /// ```rust
-/// use cameleon_device::u3v::{EmulatorBuilder, enumerate_devices};
+/// use cameleon_device::emulator::{EmulatorBuilder, enumerate_devices};
 ///
 /// // Build device with default configuration and pass it to the device pool.
 /// // Now the device pool has one device.
@@ -51,6 +57,7 @@ pub type BuilderResult<T> = std::result::Result<T, BuilderError>;
 /// ```
 pub struct EmulatorBuilder {
     memory: Memory,
+    faults: FaultConfig,
 }
 
 impl EmulatorBuilder {
@@ -67,18 +74,21 @@ impl EmulatorBuilder {
             .collect();
         memory.write::<ABRM::SerialNumber>(serial_number).unwrap();
 
-        Self { memory }
+        Self {
+            memory,
+            faults: FaultConfig::default(),
+        }
     }
 
     /// Build an emulator and pass it to the device pool. User can't control the emulator itself
     /// directly once call this method.
     ///
-    /// Emulators in the device pool can be found by [`crate::u3v::enumerate_devices`] and controlled via
-    /// [`crate::u3v::Device`] in the same way as real device.
+    /// Emulators in the device pool can be found by [`crate::emulator::enumerate_devices`] and controlled via
+    /// [`crate::emulator::Device`] in the same way as real device.
     ///
     /// # Example
     /// ```rust
-    /// use cameleon_device::u3v::EmulatorBuilder;
+    /// use cameleon_device::emulator::EmulatorBuilder;
     ///
     /// // Build device with default configuration and pass it to the device pool.
     /// // Now the device pool has one device.
@@ -91,7 +101,7 @@ impl EmulatorBuilder {
     /// ```
     pub fn build(self) {
         let device_info = self.build_device_info();
-        let device = Device::new(self.memory, device_info);
+        let device = Device::new(self.memory, device_info, self.faults);
         DevicePool::with(|pool| pool.pool_and_run(device));
     }
 
@@ -108,14 +118,14 @@ impl EmulatorBuilder {
     /// # Examples
     ///
     /// ```rust
-    /// use cameleon_device::u3v::EmulatorBuilder;
+    /// use cameleon_device::emulator::EmulatorBuilder;
     ///
     /// assert!(EmulatorBuilder::new().serial_number("CAM1984").is_ok());
     /// assert!(EmulatorBuilder::new().serial_number("カム1984年").is_err());
     /// ```
     pub fn serial_number(mut self, serial: &str) -> BuilderResult<Self> {
         self.memory
-            .write::<ABRM::UserDefinedName>(serial.into())
+            .write::<ABRM::SerialNumber>(serial.into())
             .map_err(|e| BuilderError::InvalidString(format! {"{}", e}))?;
         Ok(self)
     }
@@ -133,7 +143,7 @@ impl EmulatorBuilder {
     /// # Examples
     ///
     /// ```rust
-    /// use cameleon_device::u3v::EmulatorBuilder;
+    /// use cameleon_device::emulator::EmulatorBuilder;
     ///
     /// assert!(EmulatorBuilder::new().user_defined_name("user define name").is_ok());
     /// assert!(EmulatorBuilder::new().user_defined_name("使用者が定義した名前").is_err());
@@ -145,6 +155,158 @@ impl EmulatorBuilder {
         Ok(self)
     }
 
+    /// Set whether the device supports stacked commands (`ReadMemStacked` and
+    /// `WriteMemStacked`). The flag is flushed to the device capability register in ABRM segment
+    /// of the device memory.
+    ///
+    /// If the device doesn't support stacked commands, they are rejected with `NotImplemented`
+    /// status. Stacked commands are supported by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cameleon_device::emulator::EmulatorBuilder;
+    ///
+    /// EmulatorBuilder::new().stacked_commands(false).build();
+    /// ```
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn stacked_commands(mut self, is_supported: bool) -> Self {
+        // Stacked commands are indicated by the 13th bit of the device capability.
+        const STACKED_COMMANDS_BIT: u8 = 0b0010_0000;
+
+        let mut capability = self.memory.read::<ABRM::DeviceCapability>().unwrap();
+        if is_supported {
+            capability[1] |= STACKED_COMMANDS_BIT;
+        } else {
+            capability[1] &= !STACKED_COMMANDS_BIT;
+        }
+        self.memory
+            .write::<ABRM::DeviceCapability>(capability)
+            .unwrap();
+        self
+    }
+
+    /// Delay processing of each command sent to the control endpoint by `latency`.
+    ///
+    /// If `latency` exceeds the timeout of the host, the host fails to receive the acknowledge in
+    /// time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use cameleon_device::emulator::EmulatorBuilder;
+    ///
+    /// EmulatorBuilder::new().latency(Duration::from_millis(10)).build();
+    /// ```
+    #[must_use]
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.faults.latency = Some(latency);
+        self
+    }
+
+    /// Inject `fault` into commands sent to the control endpoint according to `schedule`.
+    ///
+    /// This method can be called multiple times. If several faults are due for a command, the
+    /// fault set first is injected.
+    ///
+    /// # Errors
+    /// If the timeout of [`ControlFault::Pending`] is larger than `u16::MAX` milliseconds, then
+    /// [`BuilderError::InvalidFault`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use cameleon_device::emulator::{ControlFault, EmulatorBuilder, FaultSchedule};
+    ///
+    /// // Return two pending acknowledges for every command, and drop the acknowledge of the
+    /// // 10th command.
+    /// let pending = ControlFault::Pending {
+    ///     count: 2,
+    ///     timeout: Duration::from_millis(5),
+    /// };
+    /// EmulatorBuilder::new()
+    ///     .control_fault(ControlFault::DropAck, FaultSchedule::Once(9))
+    ///     .unwrap()
+    ///     .control_fault(pending, FaultSchedule::Always)
+    ///     .unwrap()
+    ///     .build();
+    ///
+    /// let too_long = ControlFault::Pending {
+    ///     count: 1,
+    ///     timeout: Duration::from_secs(100),
+    /// };
+    /// assert!(EmulatorBuilder::new()
+    ///     .control_fault(too_long, FaultSchedule::Always)
+    ///     .is_err());
+    /// ```
+    pub fn control_fault(
+        mut self,
+        fault: ControlFault,
+        schedule: FaultSchedule,
+    ) -> BuilderResult<Self> {
+        if let ControlFault::Pending { timeout, .. } = fault {
+            if timeout.as_millis() > u128::from(u16::MAX) {
+                return Err(BuilderError::InvalidFault(format!(
+                    "pending timeout must be less than or equal to {} ms, but {} ms is given",
+                    u16::MAX,
+                    timeout.as_millis()
+                )));
+            }
+        }
+
+        self.faults.control.push((fault, schedule));
+        Ok(self)
+    }
+
+    /// Inject `fault` into packets sent from the stream endpoint according to `schedule`.
+    ///
+    /// This method can be called multiple times. If several faults are due for a packet, the fault
+    /// set first is injected.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use cameleon_device::emulator::{EmulatorBuilder, FaultSchedule, StreamFault};
+    ///
+    /// // Drop every 100th packet.
+    /// EmulatorBuilder::new()
+    ///     .stream_fault(StreamFault::DropPacket, FaultSchedule::Every(100))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn stream_fault(mut self, fault: StreamFault, schedule: FaultSchedule) -> Self {
+        self.faults.stream.push((fault, schedule));
+        self
+    }
+
+    /// Disconnect the device when `duration` elapses after [`EmulatorBuilder::build`] is called.
+    ///
+    /// Once disconnected, the device never responds and all requests to it fail with
+    /// [`crate::u3v::LibUsbError::NoDevice`]. The device is no longer found by
+    /// [`crate::emulator::enumerate_devices`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    ///
+    /// use cameleon_device::emulator::EmulatorBuilder;
+    ///
+    /// EmulatorBuilder::new()
+    ///     .disconnect_after(Duration::from_secs(1))
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn disconnect_after(mut self, duration: Duration) -> Self {
+        self.faults.disconnect_after = Some(duration);
+        self
+    }
+
     fn build_device_info(&self) -> DeviceInfo {
         use ABRM::{
             DeviceVersion, FamilyName, GenCpVersionMajor, GenCpVersionMinor, ManufacturerInfo,
@@ -178,7 +340,7 @@ impl EmulatorBuilder {
         let guid = if serial_len > 8 {
             format!("EMU-{}", &serial_number[serial_len - 8..])
         } else {
-            let pad: String = std::iter::repeat('0').take(8 - serial_len).collect();
+            let pad = "0".repeat(8 - serial_len);
             format!("EMU-{}{}", pad, serial_number)
         };
 
@@ -194,6 +356,7 @@ impl EmulatorBuilder {
             serial_number,
             user_defined_name,
             supported_speed,
+            bus_path: None,
         }
     }
 
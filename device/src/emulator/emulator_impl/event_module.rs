@@ -122,7 +122,7 @@ mod event_packet {
 
     use thiserror::Error;
 
-    use crate::u3v::protocol::util::WriteBytes;
+    use cameleon_impl::bytes_io::WriteBytes;
 
     #[derive(Debug, Error)]
     pub(super) enum ProtocolError {
@@ -156,11 +156,11 @@ mod event_packet {
 
         pub(super) fn serialize(&self, mut buf: impl Write) -> ProtocolResult<()> {
             // Serialize CCD.
-            buf.write_bytes(Self::PREFIX_MAGIC)?;
-            buf.write_bytes(Self::COMMAND_FLAG)?;
-            buf.write_bytes(Self::COMMAND_ID)?;
-            buf.write_bytes(self.scd.scd_len_unchecked())?;
-            buf.write_bytes(self.request_id)?;
+            buf.write_bytes_le(Self::PREFIX_MAGIC)?;
+            buf.write_bytes_le(Self::COMMAND_FLAG)?;
+            buf.write_bytes_le(Self::COMMAND_ID)?;
+            buf.write_bytes_le(self.scd.scd_len_unchecked())?;
+            buf.write_bytes_le(self.request_id)?;
 
             // Serialize SCD.
             self.scd.serialize(buf)?;
@@ -190,9 +190,9 @@ mod event_packet {
         }
 
         fn serialize(&self, mut buf: impl Write) -> ProtocolResult<()> {
-            buf.write_bytes(self.event_size)?;
-            buf.write_bytes(self.event_id)?;
-            buf.write_bytes(self.timestamp)?;
+            buf.write_bytes_le(self.event_size)?;
+            buf.write_bytes_le(self.event_id)?;
+            buf.write_bytes_le(self.timestamp)?;
             buf.write_all(self.data)?;
             Ok(())
         }
@@ -0,0 +1,205 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Byte offset of the request id in a serialized acknowledge packet.
+/// Prefix(4 bytes) + status(2 bytes) + scd kind(2 bytes) + scd length(2 bytes).
+const ACK_REQUEST_ID_OFFSET: usize = 10;
+
+/// A fault injected into the control endpoint of an emulator.
+///
+/// Faults are set by [`super::EmulatorBuilder::control_fault`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFault {
+    /// Return `count` pending acknowledges before the actual acknowledge.
+    /// The emulator spends `timeout` announced in each pending acknowledge before sending the
+    /// next one.
+    Pending {
+        /// The number of pending acknowledges.
+        count: u16,
+        /// The timeout announced in each pending acknowledge. Maximum value is `u16::MAX`
+        /// milliseconds.
+        timeout: Duration,
+    },
+
+    /// Process the command, but never send the acknowledge back.
+    DropAck,
+
+    /// Send the acknowledge with a broken prefix.
+    CorruptAck,
+
+    /// Send the acknowledge with a request id which doesn't match the command.
+    WrongRequestId,
+
+    /// Process the command, then stall the control endpoint instead of sending the acknowledge.
+    Halt,
+}
+
+/// A fault injected into the stream endpoint of an emulator.
+///
+/// Faults are set by [`super::EmulatorBuilder::stream_fault`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFault {
+    /// Drop the packet.
+    DropPacket,
+
+    /// Drop the packet and stall the stream endpoint.
+    Halt,
+}
+
+/// Specifies when a fault is injected.
+///
+/// Control commands and stream packets are counted separately from zero in the order the emulator
+/// handles them. Stream packets include leaders and trailers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultSchedule {
+    /// Inject the fault every time.
+    Always,
+
+    /// Inject the fault only at the n-th command or packet.
+    Once(u64),
+
+    /// Inject the fault at every n-th command or packet, i.e. n-1, 2n-1, 3n-1, ...
+    /// `Every(0)` never injects the fault.
+    Every(u64),
+}
+
+impl FaultSchedule {
+    fn is_due(self, count: u64) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Once(n) => count == n,
+            Self::Every(n) => n != 0 && count % n == n - 1,
+        }
+    }
+}
+
+/// All faults injected into an emulator.
+#[derive(Debug, Clone, Default)]
+pub(super) struct FaultConfig {
+    pub(super) latency: Option<Duration>,
+    pub(super) control: Vec<(ControlFault, FaultSchedule)>,
+    pub(super) stream: Vec<(StreamFault, FaultSchedule)>,
+    pub(super) disconnect_after: Option<Duration>,
+}
+
+/// Decides faults of control commands. Shared by all workers of the control module.
+#[derive(Debug, Clone)]
+pub(super) struct ControlFaults {
+    latency: Option<Duration>,
+    faults: Arc<Vec<(ControlFault, FaultSchedule)>>,
+    count: Arc<AtomicU64>,
+}
+
+impl ControlFaults {
+    pub(super) fn new(config: &FaultConfig) -> Self {
+        Self {
+            latency: config.latency,
+            faults: Arc::new(config.control.clone()),
+            count: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub(super) fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// Counts a command up and returns the fault injected into the command.
+    /// If several faults are due, the one set first is returned.
+    pub(super) fn next(&self) -> Option<ControlFault> {
+        let count = self.count.fetch_add(1, Ordering::Relaxed);
+        find_due(&self.faults, count)
+    }
+}
+
+/// Decides faults of stream packets.
+#[derive(Debug)]
+pub(super) struct StreamFaults {
+    faults: Vec<(StreamFault, FaultSchedule)>,
+    count: u64,
+}
+
+impl StreamFaults {
+    pub(super) fn new(config: &FaultConfig) -> Self {
+        Self {
+            faults: config.stream.clone(),
+            count: 0,
+        }
+    }
+
+    /// Counts a packet up and returns the fault injected into the packet.
+    /// If several faults are due, the one set first is returned.
+    pub(super) fn next(&mut self) -> Option<StreamFault> {
+        let count = self.count;
+        self.count = self.count.wrapping_add(1);
+        find_due(&self.faults, count)
+    }
+}
+
+/// Applies the fault to a serialized acknowledge packet.
+/// Returns `None` if the packet must not be sent.
+pub(super) fn apply_to_ack(fault: Option<ControlFault>, mut buf: Vec<u8>) -> Option<Vec<u8>> {
+    match fault {
+        Some(ControlFault::DropAck) | Some(ControlFault::Halt) => return None,
+        Some(ControlFault::CorruptAck) => {
+            if let Some(byte) = buf.first_mut() {
+                *byte ^= 0xff;
+            }
+        }
+        Some(ControlFault::WrongRequestId) => {
+            if let Some(id) = buf.get_mut(ACK_REQUEST_ID_OFFSET..ACK_REQUEST_ID_OFFSET + 2) {
+                let wrong_id = u16::from_le_bytes([id[0], id[1]]).wrapping_add(1);
+                id.copy_from_slice(&wrong_id.to_le_bytes());
+            }
+        }
+        Some(ControlFault::Pending { .. }) | None => {}
+    }
+
+    Some(buf)
+}
+
+fn find_due<T: Copy>(faults: &[(T, FaultSchedule)], count: u64) -> Option<T> {
+    faults
+        .iter()
+        .find(|(_, schedule)| schedule.is_due(count))
+        .map(|(fault, _)| *fault)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let due = |schedule: FaultSchedule| -> Vec<u64> {
+            (0..10).filter(|i| schedule.is_due(*i)).collect()
+        };
+        assert_eq!(due(FaultSchedule::Always).len(), 10);
+        assert_eq!(due(FaultSchedule::Once(3)), vec![3]);
+        assert_eq!(due(FaultSchedule::Every(3)), vec![2, 5, 8]);
+        assert!(due(FaultSchedule::Every(0)).is_empty());
+    }
+
+    #[test]
+    fn test_apply_to_ack() {
+        let ack: Vec<u8> = vec![0x55, 0x33, 0x56, 0x43, 0, 0, 0x01, 0x08, 0, 0, 0x05, 0x00];
+
+        assert_eq!(apply_to_ack(None, ack.clone()), Some(ack.clone()));
+        assert!(apply_to_ack(Some(ControlFault::DropAck), ack.clone()).is_none());
+        assert!(apply_to_ack(Some(ControlFault::Halt), ack.clone()).is_none());
+
+        let corrupted = apply_to_ack(Some(ControlFault::CorruptAck), ack.clone()).unwrap();
+        assert_ne!(corrupted[0], ack[0]);
+
+        let wrong_id = apply_to_ack(Some(ControlFault::WrongRequestId), ack).unwrap();
+        assert_eq!(&wrong_id[10..], &[0x06, 0x00]);
+    }
+}
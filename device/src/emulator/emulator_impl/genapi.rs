@@ -5,7 +5,7 @@
 use cameleon_impl::memory::{prelude::*, register_map};
 use const_format::formatcp;
 
-use super::memory::GENAPI_XML_ADDRESS;
+use super::memory::{ABRM, GENAPI_REG_ADDRESS};
 
 pub(super) const MODEL_NAME: &str = "CameleonU3VEmulator";
 pub(super) const VENDOR_NAME: &str = "CameleonProjectDevelopers";
@@ -25,8 +25,40 @@ pub(super) const PORT_NAME: &str = "Device";
 const PRODUCT_GUID: &str = "eaabe337-2c3b-4e0b-b9b9-e67b347c4da8";
 const VERSION_GUID: &str = "0d29949b-5cd9-4f08-93fb-eea24950de3f";
 
-#[register_map(base=GENAPI_XML_ADDRESS, endianness=LE)]
+pub(super) const WIDTH_MAX: u32 = 1280;
+pub(super) const HEIGHT_MAX: u32 = 960;
+const WIDTH_INC: u32 = 8;
+const HEIGHT_INC: u32 = 2;
+const DEFAULT_WIDTH: u32 = 640;
+const DEFAULT_HEIGHT: u32 = 480;
+pub(super) const DEFAULT_PAYLOAD_SIZE: u64 =
+    payload_size(DEFAULT_WIDTH, DEFAULT_HEIGHT, PIXEL_FORMAT_MONO8);
+
+pub(super) const ACQUISITION_MODE_CONTINUOUS: u32 = 0;
+pub(super) const ACQUISITION_MODE_SINGLE_FRAME: u32 = 1;
+
+pub(super) const TRIGGER_MODE_OFF: u32 = 0;
+pub(super) const TRIGGER_MODE_ON: u32 = 1;
+
+const PIXEL_FORMAT_MONO8: u32 = 0x0108_0001;
+const PIXEL_FORMAT_MONO16: u32 = 0x0110_0007;
+const PIXEL_FORMAT_RGB8: u32 = 0x0218_0014;
+
+/// Returns the size of an image in bytes. Bits per pixel is encoded in bits 16-23 of `PFNC` value.
+pub(super) const fn payload_size(width: u32, height: u32, pixel_format: u32) -> u64 {
+    let bits_per_pixel = (pixel_format >> 16) & 0xff;
+    width as u64 * height as u64 * bits_per_pixel as u64 / 8
+}
+
+#[register_map(base = GENAPI_REG_ADDRESS, endianness = LE)]
 pub(super) enum GenApiReg {
+    /// Transport layer parameters are locked while the register is set to 1.
+    #[register(len = 4, access = RW, ty = u32)]
+    TLParamsLocked = 0,
+
+    #[register(len = 4, access = RW, ty = u32)]
+    AcquisitionMode = ACQUISITION_MODE_CONTINUOUS,
+
     /// Start acquisition of images when the register is set to 1.
     #[register(len = 1, access = WO, ty = u8)]
     AcquisitionStart,
@@ -34,9 +66,36 @@ pub(super) enum GenApiReg {
     /// Stop the acquisition of images when the register is set to 1.
     #[register(len = 1, access = WO, ty = u8)]
     AcquisitionStop,
+
+    /// Exposure time in microseconds. The emulator doesn't use the value.
+    #[register(len = 8, access = RW, ty = f64)]
+    ExposureTime = 10_000.0,
+
+    /// Only `FrameStart` is available.
+    #[register(len = 4, access = RW, ty = u32)]
+    TriggerSelector = 0,
+
+    #[register(len = 4, access = RW, ty = u32)]
+    TriggerMode = TRIGGER_MODE_OFF,
+
+    /// Only `Software` is available.
+    #[register(len = 4, access = RW, ty = u32)]
+    TriggerSource = 0,
+
+    /// Generate a frame when the register is set to 1 and `TriggerMode` is `On`.
+    #[register(len = 1, access = WO, ty = u8)]
+    TriggerSoftware,
+
+    #[register(len = 4, access = RW, ty = u32)]
+    Width = DEFAULT_WIDTH,
+
+    #[register(len = 4, access = RW, ty = u32)]
+    Height = DEFAULT_HEIGHT,
+
+    #[register(len = 4, access = RW, ty = u32)]
+    PixelFormat = PIXEL_FORMAT_MONO8,
 }
 
-// TODO: Add node to this XML.
 pub(super) const GENAPI_XML: &str = formatcp!(
     r#"<?xml version="1.0" encoding="UTF-8"?>
 <RegisterDescription
@@ -59,7 +118,10 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     <Category Name="Root" NameSpace="Standard">
         <Description>Provides the Root of the GenICam features tree.</Description>
         <Visibility>Beginner</Visibility>
+        <pFeature>DeviceControl</pFeature>
+        <pFeature>ImageFormatControl</pFeature>
         <pFeature>AcquisitionControl</pFeature>
+        <pFeature>TransportLayerControl</pFeature>
     </Category>
 
     <Port Name="{PORT_NAME}" NameSpace="Standard">
@@ -67,12 +129,226 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Visibility>Invisible</Visibility>
     </Port>
 
+    <Category Name="DeviceControl" NameSpace="Standard">
+        <DisplayName>Device Control</DisplayName>
+        <pFeature>DeviceVendorName</pFeature>
+        <pFeature>DeviceModelName</pFeature>
+        <pFeature>DeviceFamilyName</pFeature>
+        <pFeature>DeviceVersion</pFeature>
+        <pFeature>DeviceManufacturerInfo</pFeature>
+        <pFeature>DeviceSerialNumber</pFeature>
+        <pFeature>DeviceUserID</pFeature>
+        <pFeature>TimestampLatch</pFeature>
+        <pFeature>TimestampLatchValue</pFeature>
+    </Category>
+
+    <StringReg Name="DeviceVendorName" NameSpace="Standard">
+        <Description>Name of the manufacturer of the device.</Description>
+        <DisplayName>Device Vendor Name</DisplayName>
+        <Address>{vendor_name_addr}</Address>
+        <Length>{vendor_name_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceModelName" NameSpace="Standard">
+        <Description>Model of the device.</Description>
+        <DisplayName>Device Model Name</DisplayName>
+        <Address>{model_name_addr}</Address>
+        <Length>{model_name_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceFamilyName" NameSpace="Standard">
+        <Description>Identifier of the product family of the device.</Description>
+        <DisplayName>Device Family Name</DisplayName>
+        <Address>{family_name_addr}</Address>
+        <Length>{family_name_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceVersion" NameSpace="Standard">
+        <Description>Version of the device.</Description>
+        <DisplayName>Device Version</DisplayName>
+        <Address>{device_version_addr}</Address>
+        <Length>{device_version_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceManufacturerInfo" NameSpace="Standard">
+        <Description>Manufacturer information about the device.</Description>
+        <DisplayName>Device Manufacturer Info</DisplayName>
+        <Address>{manufacturer_info_addr}</Address>
+        <Length>{manufacturer_info_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceSerialNumber" NameSpace="Standard">
+        <Description>Serial number of the device.</Description>
+        <DisplayName>Device Serial Number</DisplayName>
+        <Address>{serial_number_addr}</Address>
+        <Length>{serial_number_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceUserID" NameSpace="Standard">
+        <Description>User-programmable device identifier.</Description>
+        <DisplayName>Device User ID</DisplayName>
+        <Address>{user_defined_name_addr}</Address>
+        <Length>{user_defined_name_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <Command Name="TimestampLatch" NameSpace="Standard">
+        <Description>Latches the current timestamp counter into TimestampLatchValue.</Description>
+        <DisplayName>Timestamp Latch</DisplayName>
+        <pValue>TimestampLatchReg</pValue>
+        <CommandValue>1</CommandValue>
+    </Command>
+
+    <IntReg Name="TimestampLatchReg" NameSpace="Custom">
+        <Address>{timestamp_latch_addr}</Address>
+        <Length>{timestamp_latch_len}</Length>
+        <AccessMode>WO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="TimestampLatchValue" NameSpace="Standard">
+        <Description>Returns the latched value of the timestamp counter in nanoseconds.</Description>
+        <DisplayName>Timestamp Latch Value</DisplayName>
+        <Address>{timestamp_addr}</Address>
+        <Length>{timestamp_len}</Length>
+        <AccessMode>RO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Cachable>NoCache</Cachable>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Category Name="ImageFormatControl" NameSpace="Standard">
+        <DisplayName>Image Format Control</DisplayName>
+        <pFeature>WidthMax</pFeature>
+        <pFeature>HeightMax</pFeature>
+        <pFeature>Width</pFeature>
+        <pFeature>Height</pFeature>
+        <pFeature>PixelFormat</pFeature>
+    </Category>
+
+    <Integer Name="WidthMax" NameSpace="Standard">
+        <Description>Maximum width of the image in pixels.</Description>
+        <DisplayName>Width Max</DisplayName>
+        <Value>{WIDTH_MAX}</Value>
+    </Integer>
+
+    <Integer Name="HeightMax" NameSpace="Standard">
+        <Description>Maximum height of the image in pixels.</Description>
+        <DisplayName>Height Max</DisplayName>
+        <Value>{HEIGHT_MAX}</Value>
+    </Integer>
+
+    <Integer Name="Width" NameSpace="Standard">
+        <Description>Width of the image provided by the device in pixels.</Description>
+        <DisplayName>Width</DisplayName>
+        <pValue>WidthReg</pValue>
+        <Min>{WIDTH_INC}</Min>
+        <pMax>WidthMax</pMax>
+        <Inc>{WIDTH_INC}</Inc>
+        <Unit>px</Unit>
+    </Integer>
+
+    <IntReg Name="WidthReg" NameSpace="Custom">
+        <Address>{width_addr}</Address>
+        <Length>{width_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Integer Name="Height" NameSpace="Standard">
+        <Description>Height of the image provided by the device in pixels.</Description>
+        <DisplayName>Height</DisplayName>
+        <pValue>HeightReg</pValue>
+        <Min>{HEIGHT_INC}</Min>
+        <pMax>HeightMax</pMax>
+        <Inc>{HEIGHT_INC}</Inc>
+        <Unit>px</Unit>
+    </Integer>
+
+    <IntReg Name="HeightReg" NameSpace="Custom">
+        <Address>{height_addr}</Address>
+        <Length>{height_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Enumeration Name="PixelFormat" NameSpace="Standard">
+        <Description>Format of the pixels provided by the device.</Description>
+        <DisplayName>Pixel Format</DisplayName>
+        <EnumEntry Name="Mono8" NameSpace="Standard">
+            <Value>{PIXEL_FORMAT_MONO8}</Value>
+        </EnumEntry>
+        <EnumEntry Name="Mono16" NameSpace="Standard">
+            <Value>{PIXEL_FORMAT_MONO16}</Value>
+        </EnumEntry>
+        <EnumEntry Name="RGB8" NameSpace="Standard">
+            <Value>{PIXEL_FORMAT_RGB8}</Value>
+        </EnumEntry>
+        <pValue>PixelFormatReg</pValue>
+    </Enumeration>
+
+    <IntReg Name="PixelFormatReg" NameSpace="Custom">
+        <Address>{pixel_format_addr}</Address>
+        <Length>{pixel_format_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
     <Category Name="AcquisitionControl" NameSpace="Standard">
         <DisplayName>Acquisition Control</DisplayName>
+        <pFeature>AcquisitionMode</pFeature>
         <pFeature>AcquisitionStart</pFeature>
         <pFeature>AcquisitionStop</pFeature>
+        <pFeature>ExposureTime</pFeature>
+        <pFeature>TriggerSelector</pFeature>
+        <pFeature>TriggerMode</pFeature>
+        <pFeature>TriggerSource</pFeature>
+        <pFeature>TriggerSoftware</pFeature>
     </Category>
 
+    <Enumeration Name="AcquisitionMode" NameSpace="Standard">
+        <Description>Sets the acquisition mode of the device.</Description>
+        <DisplayName>Acquisition Mode</DisplayName>
+        <EnumEntry Name="Continuous" NameSpace="Standard">
+            <Value>{ACQUISITION_MODE_CONTINUOUS}</Value>
+        </EnumEntry>
+        <EnumEntry Name="SingleFrame" NameSpace="Standard">
+            <Value>{ACQUISITION_MODE_SINGLE_FRAME}</Value>
+        </EnumEntry>
+        <pValue>AcquisitionModeReg</pValue>
+    </Enumeration>
+
+    <IntReg Name="AcquisitionModeReg" NameSpace="Custom">
+        <Address>{acquisition_mode_addr}</Address>
+        <Length>{acquisition_mode_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
     <Command Name="AcquisitionStart" NameSpace="Standard">
         <ToolTip>Starts the acquisition of images.</ToolTip>
         <Description>This command starts the acquisition of images.</Description>
@@ -107,11 +383,166 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Endianess>LittleEndian</Endianess>
     </IntReg>
 
+    <FloatReg Name="ExposureTime" NameSpace="Standard">
+        <Description>Exposure time in microseconds. The emulator ignores the value.</Description>
+        <DisplayName>Exposure Time</DisplayName>
+        <Address>{exposure_time_addr}</Address>
+        <Length>{exposure_time_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+        <Unit>us</Unit>
+    </FloatReg>
+
+    <Enumeration Name="TriggerSelector" NameSpace="Standard">
+        <Description>Selects the type of trigger to configure.</Description>
+        <DisplayName>Trigger Selector</DisplayName>
+        <EnumEntry Name="FrameStart" NameSpace="Standard">
+            <Value>0</Value>
+        </EnumEntry>
+        <pValue>TriggerSelectorReg</pValue>
+    </Enumeration>
+
+    <IntReg Name="TriggerSelectorReg" NameSpace="Custom">
+        <Address>{trigger_selector_addr}</Address>
+        <Length>{trigger_selector_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Enumeration Name="TriggerMode" NameSpace="Standard">
+        <Description>Controls if the selected trigger is active.</Description>
+        <DisplayName>Trigger Mode</DisplayName>
+        <EnumEntry Name="Off" NameSpace="Standard">
+            <Value>{TRIGGER_MODE_OFF}</Value>
+        </EnumEntry>
+        <EnumEntry Name="On" NameSpace="Standard">
+            <Value>{TRIGGER_MODE_ON}</Value>
+        </EnumEntry>
+        <pValue>TriggerModeReg</pValue>
+    </Enumeration>
+
+    <IntReg Name="TriggerModeReg" NameSpace="Custom">
+        <Address>{trigger_mode_addr}</Address>
+        <Length>{trigger_mode_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Enumeration Name="TriggerSource" NameSpace="Standard">
+        <Description>Specifies the internal signal or physical input line to use as the trigger source.</Description>
+        <DisplayName>Trigger Source</DisplayName>
+        <EnumEntry Name="Software" NameSpace="Standard">
+            <Value>0</Value>
+        </EnumEntry>
+        <pValue>TriggerSourceReg</pValue>
+    </Enumeration>
+
+    <IntReg Name="TriggerSourceReg" NameSpace="Custom">
+        <Address>{trigger_source_addr}</Address>
+        <Length>{trigger_source_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Command Name="TriggerSoftware" NameSpace="Standard">
+        <Description>Generates an internal trigger.</Description>
+        <DisplayName>Trigger Software</DisplayName>
+        <pValue>TriggerSoftwareReg</pValue>
+        <CommandValue>1</CommandValue>
+    </Command>
+
+    <IntReg Name="TriggerSoftwareReg" NameSpace="Custom">
+        <Address>{trigger_software_addr}</Address>
+        <Length>{trigger_software_len}</Length>
+        <AccessMode>WO</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Category Name="TransportLayerControl" NameSpace="Standard">
+        <DisplayName>Transport Layer Control</DisplayName>
+        <pFeature>PayloadSize</pFeature>
+        <pFeature>TLParamsLocked</pFeature>
+    </Category>
+
+    <IntSwissKnife Name="PayloadSize" NameSpace="Standard">
+        <Description>Provides the number of bytes transferred for each image on the stream channel.</Description>
+        <DisplayName>Payload Size</DisplayName>
+        <pVariable Name="WIDTH">Width</pVariable>
+        <pVariable Name="HEIGHT">Height</pVariable>
+        <pVariable Name="PIXELFORMAT">PixelFormat</pVariable>
+        <Formula>WIDTH * HEIGHT * ((PIXELFORMAT &gt;&gt; 16) &amp; 0xFF) / 8</Formula>
+    </IntSwissKnife>
+
+    <Integer Name="TLParamsLocked" NameSpace="Standard">
+        <Description>Used by the transport layer to prevent critical features from changing during acquisition.</Description>
+        <DisplayName>TL Params Locked</DisplayName>
+        <Visibility>Invisible</Visibility>
+        <pValue>TLParamsLockedReg</pValue>
+        <Min>0</Min>
+        <Max>1</Max>
+    </Integer>
+
+    <IntReg Name="TLParamsLockedReg" NameSpace="Custom">
+        <Address>{tl_params_locked_addr}</Address>
+        <Length>{tl_params_locked_len}</Length>
+        <AccessMode>RW</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
 </RegisterDescription>"#,
+    vendor_name_addr = ABRM::ManufacturerName::ADDRESS,
+    vendor_name_len = ABRM::ManufacturerName::LENGTH,
+    model_name_addr = ABRM::ModelName::ADDRESS,
+    model_name_len = ABRM::ModelName::LENGTH,
+    family_name_addr = ABRM::FamilyName::ADDRESS,
+    family_name_len = ABRM::FamilyName::LENGTH,
+    device_version_addr = ABRM::DeviceVersion::ADDRESS,
+    device_version_len = ABRM::DeviceVersion::LENGTH,
+    manufacturer_info_addr = ABRM::ManufacturerInfo::ADDRESS,
+    manufacturer_info_len = ABRM::ManufacturerInfo::LENGTH,
+    serial_number_addr = ABRM::SerialNumber::ADDRESS,
+    serial_number_len = ABRM::SerialNumber::LENGTH,
+    user_defined_name_addr = ABRM::UserDefinedName::ADDRESS,
+    user_defined_name_len = ABRM::UserDefinedName::LENGTH,
+    timestamp_latch_addr = ABRM::TimestampLatch::ADDRESS,
+    timestamp_latch_len = ABRM::TimestampLatch::LENGTH,
+    timestamp_addr = ABRM::Timestamp::ADDRESS,
+    timestamp_len = ABRM::Timestamp::LENGTH,
+    width_addr = GenApiReg::Width::ADDRESS,
+    width_len = GenApiReg::Width::LENGTH,
+    height_addr = GenApiReg::Height::ADDRESS,
+    height_len = GenApiReg::Height::LENGTH,
+    pixel_format_addr = GenApiReg::PixelFormat::ADDRESS,
+    pixel_format_len = GenApiReg::PixelFormat::LENGTH,
+    acquisition_mode_addr = GenApiReg::AcquisitionMode::ADDRESS,
+    acquisition_mode_len = GenApiReg::AcquisitionMode::LENGTH,
     acquisition_start_addr = GenApiReg::AcquisitionStart::ADDRESS,
     acquisition_start_len = GenApiReg::AcquisitionStart::LENGTH,
     acquisition_start_access = GenApiReg::AcquisitionStart::ACCESS_RIGHT.as_str(),
     acquisition_stop_addr = GenApiReg::AcquisitionStop::ADDRESS,
     acquisition_stop_len = GenApiReg::AcquisitionStop::LENGTH,
     acquisition_stop_access = GenApiReg::AcquisitionStop::ACCESS_RIGHT.as_str(),
+    exposure_time_addr = GenApiReg::ExposureTime::ADDRESS,
+    exposure_time_len = GenApiReg::ExposureTime::LENGTH,
+    trigger_selector_addr = GenApiReg::TriggerSelector::ADDRESS,
+    trigger_selector_len = GenApiReg::TriggerSelector::LENGTH,
+    trigger_mode_addr = GenApiReg::TriggerMode::ADDRESS,
+    trigger_mode_len = GenApiReg::TriggerMode::LENGTH,
+    trigger_source_addr = GenApiReg::TriggerSource::ADDRESS,
+    trigger_source_len = GenApiReg::TriggerSource::LENGTH,
+    trigger_software_addr = GenApiReg::TriggerSoftware::ADDRESS,
+    trigger_software_len = GenApiReg::TriggerSoftware::LENGTH,
+    tl_params_locked_addr = GenApiReg::TLParamsLocked::ADDRESS,
+    tl_params_locked_len = GenApiReg::TLParamsLocked::LENGTH,
 );
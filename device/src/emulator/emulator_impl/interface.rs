@@ -2,21 +2,26 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use async_std::{
     channel::{self, Receiver, Sender, TrySendError},
+    future,
     prelude::*,
     sync::{Mutex, RwLock},
     task,
 };
-use futures::{channel::oneshot, select, FutureExt};
+use futures::{channel::oneshot, pin_mut, select, FutureExt};
 
 use super::{
     control_module::ControlModule,
     device::Timestamp,
     event_module::EventModule,
     fake_protocol::{FakeAckKind, FakeAckPacket, FakeReqKind, FakeReqPacket, IfaceKind},
+    fault::{ControlFaults, FaultConfig, StreamFaults},
     memory::Memory,
     shared_queue::SharedQueue,
     signal::{ControlSignal, EventSignal, InterfaceSignal, StreamSignal},
@@ -27,6 +32,8 @@ pub(super) struct Interface {
     iface_state: IfaceState,
     memory: Arc<Mutex<Memory>>,
     timestamp: Timestamp,
+    faults: FaultConfig,
+    disconnected: Arc<AtomicBool>,
 
     ctrl_queue: SharedQueue<Vec<u8>>,
     event_queue: SharedQueue<Vec<u8>>,
@@ -34,18 +41,27 @@ pub(super) struct Interface {
 }
 
 const SHARED_QUEUE_SIZE: usize = 32;
+/// A frame is split into many packets, so stream queue needs larger capacity than others.
+const STREAM_QUEUE_SIZE: usize = 256;
 const CHANNEL_CAPACITY: usize = 128;
 
 impl Interface {
-    pub(super) fn new(memory: Arc<Mutex<Memory>>, timestamp: Timestamp) -> Self {
+    pub(super) fn new(
+        memory: Arc<Mutex<Memory>>,
+        timestamp: Timestamp,
+        faults: FaultConfig,
+        disconnected: Arc<AtomicBool>,
+    ) -> Self {
         Self {
             iface_state: IfaceState::new(),
             memory,
             timestamp,
+            faults,
+            disconnected,
 
             ctrl_queue: SharedQueue::new(SHARED_QUEUE_SIZE),
             event_queue: SharedQueue::new(SHARED_QUEUE_SIZE),
-            stream_queue: SharedQueue::new(SHARED_QUEUE_SIZE),
+            stream_queue: SharedQueue::new(STREAM_QUEUE_SIZE),
         }
     }
 
@@ -74,6 +90,18 @@ impl Interface {
         let mut fake_req_rx = fake_req_rx.fuse();
         let mut shutdown = shutdown.fuse();
 
+        // Emulate disconnection of the device. The host side sees the device as gone once the
+        // interface stops.
+        let disconnect_after = self.faults.disconnect_after;
+        let disconnect = async move {
+            match disconnect_after {
+                Some(duration) => task::sleep(duration).await,
+                None => future::pending().await,
+            }
+        }
+        .fuse();
+        pin_mut!(disconnect);
+
         loop {
             select! {
                 packet = fake_req_rx.next().fuse() => {
@@ -97,6 +125,12 @@ impl Interface {
                 _ = shutdown => {
                     break;
                 }
+
+                _ = disconnect => {
+                    log::info!("inject fault: disconnect the device");
+                    self.disconnected.store(true, Ordering::Relaxed);
+                    break;
+                }
             }
         }
 
@@ -118,6 +152,7 @@ impl Interface {
             self.memory.clone(),
             self.timestamp.clone(),
             self.ctrl_queue.clone(),
+            ControlFaults::new(&self.faults),
         );
         task::spawn(control_module.run(signal_tx, ctrl_signal_rx));
 
@@ -137,8 +172,13 @@ impl Interface {
     fn spawn_stream_module(&self, signal_tx: Sender<InterfaceSignal>) -> Sender<StreamSignal> {
         let (stream_signal_tx, stream_signal_rx) = channel::bounded(CHANNEL_CAPACITY);
 
-        // Construct and spawn control module.
-        let stream_module = StreamModule::new(self.timestamp.clone(), self.stream_queue.clone());
+        // Construct and spawn stream module.
+        let stream_module = StreamModule::new(
+            self.timestamp.clone(),
+            self.stream_queue.clone(),
+            self.memory.clone(),
+            StreamFaults::new(&self.faults),
+        );
         task::spawn(stream_module.run(signal_tx, stream_signal_rx));
 
         stream_signal_tx
@@ -159,14 +199,14 @@ impl Interface {
             self.iface_state
                 .set_state(iface, IfaceStateKind::Ready)
                 .await;
-            send_ack(&ack_tx, iface, FakeAckKind::ClearHaltAck);
+            send_ack(ack_tx, iface, FakeAckKind::ClearHaltAck);
             return;
         }
 
         // Handle set halt request.
         if req_kind.is_set_halt() {
             self.set_halt(iface, signal_tx).await;
-            send_ack(&ack_tx, iface, FakeAckKind::SetHaltAck);
+            send_ack(ack_tx, iface, FakeAckKind::SetHaltAck);
             return;
         }
 
@@ -189,12 +229,12 @@ impl Interface {
                     Some(data) => FakeAckKind::RecvAck(data),
                     None => FakeAckKind::RecvNak,
                 };
-                send_ack(&ack_tx, iface, ack_kind);
+                send_ack(ack_tx, iface, ack_kind);
             }
 
             (IfaceKind::Control, FakeReqKind::Send(data)) => {
                 signal_tx.send_ctrl(ControlSignal::ReceiveData(data));
-                send_ack(&ack_tx, iface, FakeAckKind::SendAck);
+                send_ack(ack_tx, iface, FakeAckKind::SendAck);
             }
 
             (iface, req) => {
@@ -203,7 +243,7 @@ impl Interface {
                     iface,
                     req
                 );
-                send_ack(&ack_tx, iface, FakeAckKind::BrokenReq);
+                send_ack(ack_tx, iface, FakeAckKind::BrokenReq);
             }
         };
     }
@@ -268,7 +308,7 @@ pub(super) struct IfaceState {
 }
 
 impl IfaceState {
-    fn new() -> Self {
+    pub(super) fn new() -> Self {
         use IfaceStateKind::Ready;
         Self {
             ctrl_state: Arc::new(RwLock::new(Ready)),
@@ -4,13 +4,14 @@
 
 use cameleon_impl::memory::{memory, register_map, Register};
 
-use super::genapi;
+use super::genapi::{self, GenApiReg};
 
 const ABRM_ADDRESS: usize = 0;
 const SBRM_ADDRESS: usize = 0xffff;
 const SIRM_ADDRESS: usize = SBRM::base() + SBRM::size();
 const MANIFEST_TABLE_ADDRESS: usize = SIRM::base() + SIRM::size();
-pub(super) const GENAPI_XML_ADDRESS: usize = ManifestTable::base() + ManifestTable::size();
+pub(super) const GENAPI_REG_ADDRESS: usize = ManifestTable::base() + ManifestTable::size();
+const GENAPI_XML_ADDRESS: usize = GenApiReg::base() + GenApiReg::size();
 const GENAPI_XML_LENGTH: usize = genapi::GENAPI_XML.len();
 
 /// Offset | Value | Description.
@@ -24,12 +25,12 @@ const GENAPI_XML_LENGTH: usize = genapi::GENAPI_XML.len();
 ///     10 |     1 | Endianness Register is supported.
 ///     11 |     1 | Written Length Field is supported.
 ///     12 |     0 | Multi Event is currently NOT supported.
-///     13 |     0 | Stacked Commands is NOT supported.
+///     13 |     1 | Stacked Commands is supported.
 ///     14 |     1 | Device Software Interface Version is supported.
 ///  15-63 |     0 | Reserved. All remained bits are set to 0.
 const DEVICE_CAPABILITY: &[u8] = &[
     0b0000_1001,
-    0b0100_1111,
+    0b0110_1111,
     0b0000_0000,
     0b0000_0000,
     0b0000_0000,
@@ -75,6 +76,7 @@ pub(super) struct Memory {
     sbrm: SBRM,
     sirm: SIRM,
     manifest_table: ManifestTable,
+    genapi_reg: GenApiReg,
     genapi_xml: GenApiXml,
 }
 
@@ -135,7 +137,7 @@ pub(super) enum ABRM {
     TimestampLatch,
 
     #[register(len = 8, access = RO, ty = u64)]
-    TimestampIncrement = 1000, // Dummy value indicating device clock runs at 1MHZ.
+    TimestampIncrement = 1, // The device clock runs at 1GHz, i.e. timestamps are in ns.
 
     #[register(len = 4, access = NA, ty = Bytes)]
     AccessPrivilege,
@@ -206,7 +208,7 @@ pub(super) enum SIRM {
     Control = 0,
 
     #[register(len = 8, access = RO, ty = u64)]
-    RequiredPayloadSize = 15_151_104,
+    RequiredPayloadSize = genapi::DEFAULT_PAYLOAD_SIZE,
 
     #[register(len = 4, access = RO, ty = u32)]
     RequiredLeaderSize = 1024,
@@ -10,6 +10,7 @@ use cameleon_impl::memory::{prelude::*, MemoryObserver};
 use super::{
     control_module::Worker,
     control_protocol::{ack, cmd},
+    genapi::{self, GenApiReg},
     memory::{Memory, ABRM, SIRM, SIRM_ALIGNMENT},
     signal::{EventSignal, StreamSignal},
 };
@@ -292,6 +293,80 @@ define_handler_for_sirm!(
     MemoryEvent::MaximumTrailerSize
 );
 
+/// This macro defines handler for command registers which send `$signal` to
+/// [`super::stream_module::StreamModule`] when 1 is written.
+macro_rules! define_handler_for_stream_command {
+    ($handler_name:ident, $reg:path, $event:path, $signal:expr) => {
+        define_handler!($handler_name, $reg, $event);
+
+        impl $handler_name {
+            async fn handle_events(
+                worker: &Worker,
+                scd_kind: cmd::ScdKind,
+            ) -> Result<(), ack::ErrorAck> {
+                // Write any number other than 1 cause error.
+                if Self::read(&*worker.memory.lock().await, scd_kind)? != 1 {
+                    return Err(ack::ErrorAck::new(ack::GenCpStatus::GenericError, scd_kind));
+                }
+
+                worker.try_send_signal($signal);
+                Ok(())
+            }
+        }
+    };
+}
+
+define_handler_for_stream_command!(
+    AcquisitionStartHandler,
+    GenApiReg::AcquisitionStart,
+    MemoryEvent::AcquisitionStart,
+    StreamSignal::StartAcquisition
+);
+define_handler_for_stream_command!(
+    AcquisitionStopHandler,
+    GenApiReg::AcquisitionStop,
+    MemoryEvent::AcquisitionStop,
+    StreamSignal::StopAcquisition
+);
+define_handler_for_stream_command!(
+    TriggerSoftwareHandler,
+    GenApiReg::TriggerSoftware,
+    MemoryEvent::TriggerSoftware,
+    StreamSignal::TriggerSoftware
+);
+
+/// This macro defines handler for registers which affect the image size.
+///
+/// A handler defined by this macro keeps `SIRM::RequiredPayloadSize` consistent with the image
+/// format.
+macro_rules! define_handler_for_image_format {
+    ($handler_name:ident, $reg:path, $event:path) => {
+        define_handler!($handler_name, $reg, $event);
+
+        impl $handler_name {
+            async fn handle_events(
+                worker: &Worker,
+                scd_kind: cmd::ScdKind,
+            ) -> Result<(), ack::ErrorAck> {
+                let mut memory = worker.memory.lock().await;
+                let width = read_memory::<GenApiReg::Width>(&memory, scd_kind)?;
+                let height = read_memory::<GenApiReg::Height>(&memory, scd_kind)?;
+                let pixel_format = read_memory::<GenApiReg::PixelFormat>(&memory, scd_kind)?;
+                let payload_size = genapi::payload_size(width, height, pixel_format);
+                write_memory::<SIRM::RequiredPayloadSize>(payload_size, &mut memory, scd_kind)
+            }
+        }
+    };
+}
+
+define_handler_for_image_format!(WidthHandler, GenApiReg::Width, MemoryEvent::Width);
+define_handler_for_image_format!(HeightHandler, GenApiReg::Height, MemoryEvent::Height);
+define_handler_for_image_format!(
+    PixelFormatHandler,
+    GenApiReg::PixelFormat,
+    MemoryEvent::PixelFormat
+);
+
 enum MemoryEvent {
     TimestampLatch,
     SiControl,
@@ -300,13 +375,20 @@ enum MemoryEvent {
     PayloadFinalTransferSize1,
     PayloadFinalTransferSize2,
     MaximumTrailerSize,
+    AcquisitionStart,
+    AcquisitionStop,
+    TriggerSoftware,
+    Width,
+    Height,
+    PixelFormat,
 }
 
 impl MemoryEvent {
     async fn process(self, worker: &Worker, scd_kind: cmd::ScdKind) -> Result<(), ack::ErrorAck> {
         use MemoryEvent::{
-            MaximumLeaderSize, MaximumTrailerSize, PayloadFinalTransferSize1,
-            PayloadFinalTransferSize2, PayloadTransferSize, SiControl, TimestampLatch,
+            AcquisitionStart, AcquisitionStop, Height, MaximumLeaderSize, MaximumTrailerSize,
+            PayloadFinalTransferSize1, PayloadFinalTransferSize2, PayloadTransferSize, PixelFormat,
+            SiControl, TimestampLatch, TriggerSoftware, Width,
         };
         match self {
             TimestampLatch => TimestampLatchHandler::handle_events(worker, scd_kind).await,
@@ -322,6 +404,12 @@ impl MemoryEvent {
                 PayloadFinalTransferSize2Handler::handle_events(worker, scd_kind).await
             }
             MaximumTrailerSize => MaximumTrailerSizeHandler::handle_events(worker, scd_kind).await,
+            AcquisitionStart => AcquisitionStartHandler::handle_events(worker, scd_kind).await,
+            AcquisitionStop => AcquisitionStopHandler::handle_events(worker, scd_kind).await,
+            TriggerSoftware => TriggerSoftwareHandler::handle_events(worker, scd_kind).await,
+            Width => WidthHandler::handle_events(worker, scd_kind).await,
+            Height => HeightHandler::handle_events(worker, scd_kind).await,
+            PixelFormat => PixelFormatHandler::handle_events(worker, scd_kind).await,
         }
     }
 
@@ -333,6 +421,12 @@ impl MemoryEvent {
         PayloadFinalTransferSize1Handler::register(memory, sender);
         PayloadFinalTransferSize2Handler::register(memory, sender);
         MaximumTrailerSizeHandler::register(memory, sender);
+        AcquisitionStartHandler::register(memory, sender);
+        AcquisitionStopHandler::register(memory, sender);
+        TriggerSoftwareHandler::register(memory, sender);
+        WidthHandler::register(memory, sender);
+        HeightHandler::register(memory, sender);
+        PixelFormatHandler::register(memory, sender);
     }
 }
 
@@ -10,6 +10,7 @@ mod device_pool;
 mod emulator_builder;
 mod event_module;
 mod fake_protocol;
+mod fault;
 mod genapi;
 mod interface;
 mod memory;
@@ -19,6 +20,7 @@ mod signal;
 mod stream_module;
 
 pub use emulator_builder::*;
+pub use fault::{ControlFault, FaultSchedule, StreamFault};
 
 pub(super) use device_handle::*;
 pub(super) use device_pool::DevicePool;
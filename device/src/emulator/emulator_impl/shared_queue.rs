@@ -36,6 +36,14 @@ impl<T> SharedQueue<T> {
         }
     }
 
+    pub(super) fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.cap
+    }
+
     pub(super) fn clear(&self) {
         self.inner.lock().unwrap().clear()
     }
@@ -54,6 +54,15 @@ pub(super) enum StreamSignal {
     /// Signal to disable stream module.
     Disable(oneshot::Sender<()>),
 
+    /// Signal to start acquisition of images.
+    StartAcquisition,
+
+    /// Signal to stop acquisition of images.
+    StopAcquisition,
+
+    /// Signal to generate a frame while trigger mode is on.
+    TriggerSoftware,
+
     /// Signal to shutdown.
     Shutdown,
 }
@@ -2,40 +2,82 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::{sync::Arc, time::Duration};
+
 use async_std::{
     channel::{Receiver, Sender},
+    future,
     prelude::*,
+    sync::Mutex,
 };
 
+use cameleon_impl::memory::prelude::*;
+
 use super::{
     device::Timestamp,
+    fault::{StreamFault, StreamFaults},
+    genapi::{self, GenApiReg},
+    memory::{Memory, SIRM},
     shared_queue::SharedQueue,
     signal::{InterfaceSignal, StreamSignal},
+    IfaceKind,
 };
 
-// TODO: Implement stream module.
+/// Interval between frames while the device is acquiring images in free run.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
 pub(super) struct StreamModule {
-    _queue: SharedQueue<Vec<u8>>,
-    _timestamp: Timestamp,
+    queue: SharedQueue<Vec<u8>>,
+    timestamp: Timestamp,
+    memory: Arc<Mutex<Memory>>,
+    faults: StreamFaults,
 
     enabled: bool,
+    acquiring: bool,
+    next_block_id: u64,
 }
 
 impl StreamModule {
-    pub(super) fn new(timestamp: Timestamp, queue: SharedQueue<Vec<u8>>) -> Self {
+    pub(super) fn new(
+        timestamp: Timestamp,
+        queue: SharedQueue<Vec<u8>>,
+        memory: Arc<Mutex<Memory>>,
+        faults: StreamFaults,
+    ) -> Self {
         Self {
-            _timestamp: timestamp,
-            _queue: queue,
+            queue,
+            timestamp,
+            memory,
+            faults,
             enabled: false,
+            acquiring: false,
+            next_block_id: 0,
         }
     }
 
     pub(super) async fn run(
         mut self,
-        _signal_tx: Sender<InterfaceSignal>,
+        signal_tx: Sender<InterfaceSignal>,
         mut signal_rx: Receiver<StreamSignal>,
     ) {
-        while let Some(signal) = signal_rx.next().await {
+        loop {
+            let signal = if self.is_free_running().await {
+                match future::timeout(FRAME_INTERVAL, signal_rx.next()).await {
+                    Ok(signal) => signal,
+                    Err(_) => {
+                        self.send_frame(&signal_tx).await;
+                        continue;
+                    }
+                }
+            } else {
+                signal_rx.next().await
+            };
+
+            let signal = match signal {
+                Some(signal) => signal,
+                None => break,
+            };
+
             match signal {
                 StreamSignal::Enable => {
                     if self.enabled {
@@ -55,10 +97,218 @@ impl StreamModule {
                     }
                 }
 
+                StreamSignal::StartAcquisition => {
+                    self.acquiring = true;
+                    log::info! {"start acquisition"};
+                }
+
+                StreamSignal::StopAcquisition => {
+                    self.acquiring = false;
+                    log::info! {"stop acquisition"};
+                }
+
+                StreamSignal::TriggerSoftware => {
+                    if self.enabled && self.acquiring && self.is_trigger_mode_on().await {
+                        self.send_frame(&signal_tx).await;
+                    } else {
+                        log::warn! {"receive software trigger, but the device isn't waiting for a trigger"}
+                    }
+                }
+
                 StreamSignal::Shutdown => {
                     break;
                 }
             }
         }
     }
+
+    /// Returns `true` if the module should send frames without waiting for triggers.
+    async fn is_free_running(&self) -> bool {
+        self.enabled && self.acquiring && !self.is_trigger_mode_on().await
+    }
+
+    async fn is_trigger_mode_on(&self) -> bool {
+        let memory = self.memory.lock().await;
+        memory.read::<GenApiReg::TriggerMode>().unwrap() == genapi::TRIGGER_MODE_ON
+    }
+
+    /// Send leader, payload and trailer of a frame to the host.
+    ///
+    /// The frame is dropped if the queue doesn't have enough room for the whole frame.
+    async fn send_frame(&mut self, signal_tx: &Sender<InterfaceSignal>) {
+        let frame = self.build_frame().await;
+        let block_id = self.next_block_id;
+        self.next_block_id = self.next_block_id.wrapping_add(1);
+
+        if self.queue.capacity() - self.queue.len() < frame.len() {
+            log::warn!("stream queue is full, drop frame {}", block_id);
+            return;
+        }
+
+        for packet in frame {
+            match self.faults.next() {
+                Some(StreamFault::DropPacket) => {
+                    log::info!("inject fault: drop a packet of frame {}", block_id);
+                    continue;
+                }
+                Some(StreamFault::Halt) => {
+                    log::info!("inject fault: entering a halted state");
+                    if signal_tx
+                        .try_send(InterfaceSignal::Halt(IfaceKind::Stream))
+                        .is_err()
+                    {
+                        log::error!("Stream module -> Interface channel is full");
+                    }
+                    return;
+                }
+                None => {}
+            }
+
+            if !self.queue.enqueue(packet) {
+                log::warn!("stream queue is full, entering a halted state");
+                if signal_tx
+                    .try_send(InterfaceSignal::Halt(IfaceKind::Stream))
+                    .is_err()
+                {
+                    log::error!("Stream module -> Interface channel is full");
+                }
+                return;
+            }
+        }
+
+        let single_frame = {
+            let memory = self.memory.lock().await;
+            memory.read::<GenApiReg::AcquisitionMode>().unwrap()
+                == genapi::ACQUISITION_MODE_SINGLE_FRAME
+        };
+        if single_frame {
+            self.acquiring = false;
+        }
+    }
+
+    /// Build packets of a frame according to the current `SIRM` and image format settings.
+    async fn build_frame(&self) -> Vec<Vec<u8>> {
+        let timestamp = self.timestamp.as_nanos().await;
+        let block_id = self.next_block_id;
+
+        let memory = self.memory.lock().await;
+        let width = memory.read::<GenApiReg::Width>().unwrap();
+        let height = memory.read::<GenApiReg::Height>().unwrap();
+        let pixel_format = memory.read::<GenApiReg::PixelFormat>().unwrap();
+        let transfer_size = memory.read::<SIRM::PayloadTransferSize>().unwrap() as usize;
+        let transfer_count = memory.read::<SIRM::PayloadTransferCount>().unwrap() as usize;
+        let final_transfer1_size =
+            memory.read::<SIRM::PayloadFinalTransferSize1>().unwrap() as usize;
+        let final_transfer2_size =
+            memory.read::<SIRM::PayloadFinalTransferSize2>().unwrap() as usize;
+        drop(memory);
+
+        let image_size = genapi::payload_size(width, height, pixel_format) as usize;
+        let image = image(width, height, image_size, block_id);
+
+        let mut frame = Vec::with_capacity(transfer_count + 4);
+        frame.push(packet::leader(
+            block_id,
+            timestamp,
+            pixel_format,
+            width,
+            height,
+        ));
+
+        // Split the image into packets in the same way as the host expects.
+        let mut rest = image.as_slice();
+        let transfer_sizes = (0..transfer_count)
+            .map(|_| transfer_size)
+            .chain(std::iter::once(final_transfer1_size))
+            .chain(std::iter::once(final_transfer2_size));
+        for size in transfer_sizes {
+            if rest.is_empty() {
+                break;
+            }
+            if size == 0 {
+                continue;
+            }
+            let (chunk, remained) = rest.split_at(std::cmp::min(size, rest.len()));
+            frame.push(chunk.to_vec());
+            rest = remained;
+        }
+
+        let valid_payload_size = (image_size - rest.len()) as u64;
+        frame.push(packet::trailer(block_id, valid_payload_size, height));
+
+        frame
+    }
+}
+
+/// Generate a moving gradient image.
+fn image(width: u32, height: u32, image_size: usize, block_id: u64) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let bytes_per_pixel = image_size.checked_div(width * height).unwrap_or(0);
+
+    let mut image = Vec::with_capacity(image_size);
+    for y in 0..height {
+        for x in 0..width {
+            let value = (x + y + block_id as usize) as u8;
+            image.resize(image.len() + bytes_per_pixel, value);
+        }
+    }
+    image.resize(image_size, 0);
+    image
+}
+
+/// Stream packet serializer implementation.
+mod packet {
+    use cameleon_impl::bytes_io::WriteBytes;
+
+    const LEADER_MAGIC: u32 = 0x4C56_3355;
+    const TRAILER_MAGIC: u32 = 0x5456_3355;
+    const PAYLOAD_TYPE_IMAGE: u16 = 0x0001;
+    const PAYLOAD_STATUS_SUCCESS: u16 = 0x0000;
+
+    /// Generic leader(20 bytes) + Image specific leader(32 bytes).
+    const IMAGE_LEADER_SIZE: u16 = 52;
+    /// Generic trailer(28 bytes) + Image specific trailer(4 bytes).
+    const IMAGE_TRAILER_SIZE: u16 = 32;
+
+    pub(super) fn leader(
+        block_id: u64,
+        timestamp: u64,
+        pixel_format: u32,
+        width: u32,
+        height: u32,
+    ) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(IMAGE_LEADER_SIZE as usize);
+        buf.write_bytes_le(LEADER_MAGIC).unwrap();
+        buf.write_bytes_le(0_u16).unwrap();
+        buf.write_bytes_le(IMAGE_LEADER_SIZE).unwrap();
+        buf.write_bytes_le(block_id).unwrap();
+        buf.write_bytes_le(0_u16).unwrap();
+        buf.write_bytes_le(PAYLOAD_TYPE_IMAGE).unwrap();
+
+        buf.write_bytes_le(timestamp).unwrap();
+        buf.write_bytes_le(pixel_format).unwrap();
+        buf.write_bytes_le(width).unwrap();
+        buf.write_bytes_le(height).unwrap();
+        // Offset x and y.
+        buf.write_bytes_le(0_u32).unwrap();
+        buf.write_bytes_le(0_u32).unwrap();
+        // Padding x and reserved.
+        buf.write_bytes_le(0_u16).unwrap();
+        buf.write_bytes_le(0_u16).unwrap();
+        buf
+    }
+
+    pub(super) fn trailer(block_id: u64, valid_payload_size: u64, actual_height: u32) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(IMAGE_TRAILER_SIZE as usize);
+        buf.write_bytes_le(TRAILER_MAGIC).unwrap();
+        buf.write_bytes_le(0_u16).unwrap();
+        buf.write_bytes_le(IMAGE_TRAILER_SIZE).unwrap();
+        buf.write_bytes_le(block_id).unwrap();
+        buf.write_bytes_le(PAYLOAD_STATUS_SUCCESS).unwrap();
+        buf.write_bytes_le(0_u16).unwrap();
+        buf.write_bytes_le(valid_payload_size).unwrap();
+
+        buf.write_bytes_le(actual_height).unwrap();
+        buf
+    }
 }
@@ -2,16 +2,38 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+//! This module provides an emulator of `U3V` compatible devices.
+//!
+//! Emulators are built by [`EmulatorBuilder`] and can be found by [`enumerate_devices`]. An emulator
+//! behaves in the same way as a real device, so the host side can communicate with it via
+//! [`ControlChannel`] and [`ReceiveChannel`].
+//!
+//! # Examples
+//!
+//! ```rust
+//! use cameleon_device::emulator::{enumerate_devices, EmulatorBuilder};
+//!
+//! // Build an emulator and pass it to the device pool.
+//! EmulatorBuilder::new().serial_number("CAM1984").unwrap().build();
+//!
+//! let devices = enumerate_devices().unwrap();
+//! assert_eq!(devices.len(), 1);
+//! assert_eq!(devices[0].device_info.serial_number, "CAM1984");
+//! ```
+
 mod channel;
 mod device;
 mod emulator_impl;
 
 pub use channel::{ControlChannel, ReceiveChannel};
 pub use device::Device;
-pub use emulator_impl::{BuilderError, BuilderResult, EmulatorBuilder};
+pub use emulator_impl::{
+    BuilderError, BuilderResult, ControlFault, EmulatorBuilder, FaultSchedule, StreamFault,
+};
 
 use crate::u3v::Result;
 
+/// Enumerate all emulators in the device pool.
 pub fn enumerate_devices() -> Result<Vec<Device>> {
     let device_ids = emulator_impl::DevicePool::with(|pool| pool.device_ids());
     let mut devices = Vec::with_capacity(device_ids.len());
@@ -10,11 +10,11 @@
     clippy::cast_possible_truncation
 )]
 
-#[cfg(feature = "libusb")]
+#[cfg(any(feature = "libusb", feature = "emulator"))]
 pub mod u3v;
 
-//// TODO: finish implementation.
-//mod emulator;
+#[cfg(feature = "emulator")]
+pub mod emulator;
 
 mod pixel_format;
 
@@ -5,7 +5,7 @@
 use cameleon_impl::bytes_io::ReadBytes;
 use semver::Version;
 
-use crate::u3v::{BusSpeed, DeviceInfo, Error, Result};
+use crate::u3v::{BusPath, BusSpeed, DeviceInfo, Error, Result};
 
 use super::{
     channel::{ControlIfaceInfo, ReceiveIfaceInfo},
@@ -33,6 +33,24 @@ pub fn enumerate_devices() -> Result<Vec<Device>> {
         .collect())
 }
 
+/// Returns bus paths of all U3V devices connected to the host.
+///
+/// Unlike [`enumerate_devices`], devices aren't opened, so devices which are opened exclusively
+/// by another handle are also listed.
+pub fn enumerate_bus_paths() -> Result<Vec<BusPath>> {
+    let rusb_device_list = rusb::DeviceList::new()?;
+    Ok(rusb_device_list
+        .iter()
+        .filter_map(|dev| DeviceBuilder::new(dev).ok().flatten())
+        .filter_map(|builder| {
+            Some(BusPath {
+                bus_number: builder.device.bus_number(),
+                port_numbers: builder.device.port_numbers().ok()?,
+            })
+        })
+        .collect())
+}
+
 struct DeviceBuilder {
     device: RusbDevice,
     u3v_iad: Iad,
@@ -63,7 +81,13 @@ impl DeviceBuilder {
         // TODO: Log it when device is broken or invalid.
         let mut dev_channel = self.device.open()?;
         if dev_channel.active_configuration()? != self.config_desc.number() {
-            dev_channel.set_active_configuration(self.config_desc.number())?;
+            match dev_channel.set_active_configuration(self.config_desc.number()) {
+                Ok(()) => {}
+                // The device is opened and configured by another handle, but its descriptors
+                // can still be read. Keep it so that busy devices are still enumerated.
+                Err(rusb::Error::Busy) => {}
+                Err(e) => return Err(e.into()),
+            }
         }
 
         // Skip interfaces while control interface is appeared.
@@ -84,7 +108,11 @@ impl DeviceBuilder {
             .ok_or(Error::InvalidDevice)?;
         let device_info_desc = ctrl_iface_desc.extra().ok_or(Error::InvalidDevice)?;
         let device_info_desc = DeviceInfoDescriptor::from_bytes(device_info_desc)?;
-        let device_info = device_info_desc.interpret(&dev_channel)?;
+        let mut device_info = device_info_desc.interpret(&dev_channel)?;
+        device_info.bus_path = self.device.port_numbers().ok().map(|port_numbers| BusPath {
+            bus_number: self.device.bus_number(),
+            port_numbers,
+        });
 
         // Retrieve event and stream interface information if exists.
         let receive_ifaces = interfaces.filter_map(|iface| ReceiveIfaceInfo::new(&iface));
@@ -383,6 +411,7 @@ impl DeviceInfoDescriptor {
             serial_number,
             user_defined_name,
             supported_speed,
+            bus_path: None,
         })
     }
 }
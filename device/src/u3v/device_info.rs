@@ -7,7 +7,10 @@ use std::fmt;
 use semver::Version;
 
 /// Device information in class-specific device descriptor.
+///
+/// Fields may be added in the future, so `DeviceInfo` can't be constructed outside this crate.
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct DeviceInfo {
     /// GenCP version the device provides.
     pub gencp_version: Version,
@@ -45,6 +48,41 @@ pub struct DeviceInfo {
 
     /// Bus speed supported by the device.
     pub supported_speed: BusSpeed,
+
+    /// Physical location of the device on the USB bus.
+    /// `None` if the location isn't available, e.g. the device is emulated.
+    pub bus_path: Option<BusPath>,
+}
+
+/// Physical location of a USB device, i.e. the bus number and the chain of hub port numbers from
+/// the root hub to the device.
+///
+/// The location is stable as long as the device is plugged in the same port, even if the device is
+/// replugged or the host is rebooted.
+///
+/// `BusPath` is displayed in the same notation as Linux sysfs, e.g. `2-1.3` means the device is
+/// connected to port 3 of the hub which is connected to port 1 of the root hub of bus 2.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BusPath {
+    /// Number of the bus that the device is connected to.
+    pub bus_number: u8,
+
+    /// Port numbers from the root hub to the device.
+    pub port_numbers: Vec<u8>,
+}
+
+impl fmt::Display for BusPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-", self.bus_number)?;
+        for (i, port) in self.port_numbers.iter().enumerate() {
+            if i != 0 {
+                write!(f, ".")?;
+            }
+            write!(f, "{}", port)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// Bus speed supported by each USB device.
@@ -96,8 +134,34 @@ impl fmt::Display for DeviceInfo {
             writeln!(f, "User Defined Name: N/A")
         }?;
 
-        write!(f, "Supported Speed: {:?}", self.supported_speed)?;
+        writeln!(f, "Supported Speed: {:?}", self.supported_speed)?;
+
+        if let Some(bus_path) = &self.bus_path {
+            write!(f, "Bus Path: {}", bus_path)
+        } else {
+            write!(f, "Bus Path: N/A")
+        }?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_path_display() {
+        let path = BusPath {
+            bus_number: 2,
+            port_numbers: vec![1, 3],
+        };
+        assert_eq!(path.to_string(), "2-1.3");
+
+        let path = BusPath {
+            bus_number: 1,
+            port_numbers: vec![4],
+        };
+        assert_eq!(path.to_string(), "1-4");
+    }
+}
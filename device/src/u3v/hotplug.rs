@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
+
+use rusb::UsbContext;
+
+use crate::u3v::Result;
+
+/// Interval of handling libusb events. This determines how long it takes to stop the event loop.
+const EVENT_HANDLING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Notifies that a USB device is plugged in or out by using libusb hotplug.
+///
+/// The notification doesn't tell which device is changed, so call [`super::enumerate_devices`]
+/// to find it.
+pub struct HotplugNotifier {
+    rx: mpsc::Receiver<()>,
+    stop: Arc<AtomicBool>,
+}
+
+impl HotplugNotifier {
+    /// Starts watching USB devices.
+    ///
+    /// Returns `None` if libusb doesn't support hotplug on the platform.
+    pub fn new() -> Result<Option<Self>> {
+        if !rusb::has_hotplug() {
+            return Ok(None);
+        }
+
+        let ctx = rusb::Context::new()?;
+        let (tx, rx) = mpsc::channel();
+        let registration = ctx.register_callback(None, None, None, Box::new(Callback { tx }))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_loop = stop.clone();
+        std::thread::spawn(move || {
+            // Keep registration alive while the loop is running.
+            let _registration = registration;
+            while !stop_loop.load(Ordering::Relaxed) {
+                if let Err(e) = ctx.handle_events(Some(EVENT_HANDLING_INTERVAL)) {
+                    log::error!("failed to handle libusb hotplug events: {}", e);
+                    break;
+                }
+            }
+        });
+
+        Ok(Some(Self { rx, stop }))
+    }
+
+    /// Waits a hotplug event up to `timeout`, and returns `true` if any USB device is plugged in or
+    /// out.
+    ///
+    /// Events which have occurred until this method returns are coalesced into one notification.
+    #[must_use]
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let notified = self.rx.recv_timeout(timeout).is_ok();
+        while self.rx.try_recv().is_ok() {}
+        notified
+    }
+}
+
+impl Drop for HotplugNotifier {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Callback {
+    tx: mpsc::Sender<()>,
+}
+
+impl<T: UsbContext> rusb::Hotplug<T> for Callback {
+    fn device_arrived(&mut self, _device: rusb::Device<T>) {
+        self.tx.send(()).ok();
+    }
+
+    fn device_left(&mut self, _device: rusb::Device<T>) {
+        self.tx.send(()).ok();
+    }
+}
@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+#[cfg(feature = "libusb")]
 pub mod async_read;
 pub mod protocol;
 pub mod register_map;
@@ -12,15 +13,25 @@ pub mod prelude {
     use super::protocol;
 }
 
+#[cfg(feature = "libusb")]
 mod channel;
+#[cfg(feature = "libusb")]
 mod device;
+#[cfg(feature = "libusb")]
 mod device_builder;
 mod device_info;
+#[cfg(feature = "libusb")]
+mod hotplug;
 
+#[cfg(feature = "libusb")]
 pub use channel::{ControlChannel, ReceiveChannel};
+#[cfg(feature = "libusb")]
 pub use device::Device;
-pub use device_builder::enumerate_devices;
-pub use device_info::{BusSpeed, DeviceInfo};
+#[cfg(feature = "libusb")]
+pub use device_builder::{enumerate_bus_paths, enumerate_devices};
+pub use device_info::{BusPath, BusSpeed, DeviceInfo};
+#[cfg(feature = "libusb")]
+pub use hotplug::HotplugNotifier;
 
 use std::borrow::Cow;
 
@@ -76,6 +87,7 @@ pub enum LibUsbError {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(feature = "libusb")]
 impl From<rusb::Error> for Error {
     fn from(err: rusb::Error) -> Error {
         use LibUsbError::{
@@ -87,6 +87,9 @@ pub trait Device {
 
 #[derive(Debug, thiserror::Error)]
 pub enum GenApiError {
+    /// An error returned from [`Device`].
+    ///
+    /// The inner error can be downcast to the concrete error type of the device.
     #[error("device I/O error: {0}")]
     Device(Box<dyn std::error::Error>),
 
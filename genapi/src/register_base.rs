@@ -76,7 +76,7 @@ impl RegisterBase {
     ) -> GenApiResult<R> {
         let length = self.length(device, store, cx)?;
         let address = self.address(device, store, cx)?;
-        if let Some(cache) = cx.get_cache(nid, length, address) {
+        if let Some(cache) = cx.get_cache(nid, address, length) {
             f(cache)
         } else {
             let mut buf = vec![0; length as usize];
@@ -138,7 +138,7 @@ impl RegisterBase {
         Ok(())
     }
 
-    pub(super) fn address<T: ValueStore, U: CacheStore>(
+    pub fn address<T: ValueStore, U: CacheStore>(
         &self,
         device: &mut impl Device,
         store: &impl NodeStore,
@@ -151,7 +151,7 @@ impl RegisterBase {
         Ok(address)
     }
 
-    pub(super) fn length<T: ValueStore, U: CacheStore>(
+    pub fn length<T: ValueStore, U: CacheStore>(
         &self,
         device: &mut impl Device,
         store: &impl NodeStore,
@@ -180,3 +180,84 @@ impl RegisterBase {
             && !matches!(self.access_mode(), AccessMode::RO))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::GenApiBuilder, interface::IInteger};
+
+    use super::*;
+
+    /// Device which counts reads of its memory.
+    struct CountingDevice {
+        memory: Vec<u8>,
+        num_reads: usize,
+    }
+
+    impl Device for CountingDevice {
+        fn read_mem(
+            &mut self,
+            address: i64,
+            buf: &mut [u8],
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let start = address as usize;
+            buf.copy_from_slice(&self.memory[start..start + buf.len()]);
+            self.num_reads += 1;
+            Ok(())
+        }
+
+        fn write_mem(
+            &mut self,
+            address: i64,
+            data: &[u8],
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            let start = address as usize;
+            self.memory[start..start + data.len()].copy_from_slice(data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_read_from_cache() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+        <RegisterDescription
+            ModelName="Test"
+            VendorName="Cameleon"
+            StandardNameSpace="None"
+            SchemaMajorVersion="1"
+            SchemaMinorVersion="1"
+            SchemaSubMinorVersion="0"
+            MajorVersion="1"
+            MinorVersion="0"
+            SubMinorVersion="0"
+            ProductGuid="01234567-0123-0123-0123-0123456789ab"
+            VersionGuid="76543210-3210-3210-3210-ba9876543210"
+            xmlns="http://www.genicam.org/GenApi/Version_1_1">
+            <Port Name="Device" />
+            <IntReg Name="TestNode">
+                <Address>0x10</Address>
+                <Length>4</Length>
+                <AccessMode>RO</AccessMode>
+                <pPort>Device</pPort>
+                <Cachable>WriteAround</Cachable>
+                <Endianess>LittleEndian</Endianess>
+            </IntReg>
+        </RegisterDescription>
+        "#;
+        let builder: GenApiBuilder = GenApiBuilder::default();
+        let (_, store, mut cx) = builder.build(&xml).unwrap();
+        let mut device = CountingDevice {
+            memory: vec![0; 0x20],
+            num_reads: 0,
+        };
+        device.memory[0x10] = 42;
+
+        let nid = store.id_by_name("TestNode").unwrap();
+        let node = nid.expect_iinteger_kind(&store).unwrap();
+        assert_eq!(node.value(&mut device, &store, &mut cx).unwrap(), 42);
+        assert_eq!(node.value(&mut device, &store, &mut cx).unwrap(), 42);
+
+        // The second read is served from the cache keyed by the address and the length.
+        assert_eq!(device.num_reads, 1);
+        assert_eq!(cx.get_cache(nid, 0x10, 4), Some(&[42, 0, 0, 0][..]));
+    }
+}
@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Builds `cameleon-gentl` for tests which load the producer as a shared library.
+
+use std::{
+    env,
+    io::BufRead,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// Builds `cameleon-gentl` with `features` into `target_dir`, and returns the path to the built
+/// library.
+///
+/// Tests should pass a target directory of their own, e.g. under `CARGO_TARGET_TMPDIR`, so that
+/// the build doesn't contend with the running `cargo test`.
+///
+/// # Panics
+/// Panics if the build fails.
+pub fn build_producer(target_dir: &Path, features: &[&str]) -> PathBuf {
+    let workspace_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .ancestors()
+        .nth(2)
+        .unwrap();
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+
+    let output = Command::new(cargo)
+        .current_dir(workspace_dir)
+        .args([
+            "build",
+            "--quiet",
+            "-p",
+            "cameleon-gentl",
+            "--message-format",
+            "json-render-diagnostics",
+        ])
+        .arg("--features")
+        .arg(features.join(","))
+        .arg("--target-dir")
+        .arg(target_dir)
+        .stderr(Stdio::inherit())
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "failed to build cameleon-gentl");
+
+    // The path to the library is reported by the artifact message of the build.
+    let lib_name = format!(
+        "{}cameleon_gentl{}",
+        env::consts::DLL_PREFIX,
+        env::consts::DLL_SUFFIX
+    );
+    output
+        .stdout
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line.unwrap()).ok())
+        .filter(|msg| msg["reason"] == "compiler-artifact")
+        .filter_map(|msg| msg["filenames"].as_array().cloned())
+        .flatten()
+        .filter_map(|filename| filename.as_str().map(PathBuf::from))
+        .find(|path| path.file_name() == Some(lib_name.as_ref()))
+        .expect("cameleon-gentl isn't built as a shared library")
+}
@@ -0,0 +1,332 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Generates `GenTL.h` from the definitions in `src/ffi`.
+//!
+//! The header is written only to `OUT_DIR`, see `README.md` of this crate for how to install it
+//! next to the built library.
+
+use std::{
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use syn::{
+    parse::ParseStream, Attribute, Expr, Fields, FnArg, Ident, Item, ItemEnum, ItemFn, Lit, Pat,
+    Token, Type,
+};
+
+/// Files in the order their definitions appear in the header.
+const FFI_FILES: &[&str] = &[
+    "mod.rs",
+    "system.rs",
+    "interface.rs",
+    "device.rs",
+    "port.rs",
+    "stream.rs",
+    "event.rs",
+];
+
+const HEADER_NAME: &str = "GenTL.h";
+
+#[derive(Default)]
+struct Header {
+    typedefs: Vec<(String, String)>,
+    enums: Vec<Enum>,
+    structs: Vec<Struct>,
+    functions: Vec<Function>,
+}
+
+struct Enum {
+    name: String,
+    variants: Vec<(String, i64, Vec<String>)>,
+}
+
+struct Struct {
+    name: String,
+    fields: Vec<(String, String)>,
+}
+
+struct Function {
+    name: String,
+    args: Vec<(String, String)>,
+    docs: Vec<String>,
+}
+
+fn main() {
+    let ffi_dir = Path::new("src/ffi");
+    println!("cargo:rerun-if-changed={}", ffi_dir.display());
+
+    let mut header = Header::default();
+    for file in FFI_FILES {
+        let path = ffi_dir.join(file);
+        let src = fs::read_to_string(&path).unwrap();
+        let ast = syn::parse_file(&src)
+            .unwrap_or_else(|e| panic!("failed to parse {}: {}", path.display(), e));
+        header.collect(&ast.items);
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join(HEADER_NAME), header.render()).unwrap();
+}
+
+impl Header {
+    fn collect(&mut self, items: &[Item]) {
+        for item in items {
+            match item {
+                // Handle types.
+                Item::Type(ty) if matches!(*ty.ty, Type::Ptr(_)) => {
+                    let name = ty.ident.to_string();
+                    if !self.has_typedef(&name) {
+                        self.typedefs.push((name, c_type(&ty.ty)));
+                    }
+                }
+
+                Item::Struct(st) if has_repr(&st.attrs, "transparent") => {
+                    if let Fields::Unnamed(fields) = &st.fields {
+                        let field = fields.unnamed.first().unwrap();
+                        self.typedefs
+                            .push((st.ident.to_string(), c_type(&field.ty)));
+                    }
+                }
+
+                Item::Struct(st) if has_repr(&st.attrs, "C") => {
+                    let fields = st
+                        .fields
+                        .iter()
+                        .map(|f| (f.ident.as_ref().unwrap().to_string(), c_type(&f.ty)))
+                        .collect();
+                    self.structs.push(Struct {
+                        name: st.ident.to_string(),
+                        fields,
+                    });
+                }
+
+                Item::Macro(mac) if mac.mac.path.is_ident("newtype_enum") => {
+                    let item: ItemEnum = mac.mac.parse_body().unwrap();
+                    self.enums.push(Enum::from_item(&item));
+                }
+
+                Item::Macro(mac) if mac.mac.path.is_ident("gentl_api") => {
+                    let item = mac.mac.parse_body_with(parse_gentl_api).unwrap();
+                    self.functions.push(Function::from_item(&item));
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    fn has_typedef(&self, name: &str) -> bool {
+        self.typedefs.iter().any(|(n, _)| n == name)
+    }
+
+    fn render(&self) -> String {
+        let mut s = String::new();
+        s.push_str(
+            "/* This file is generated by the build script of cameleon-gentl. Do not edit. */\n\n",
+        );
+        s.push_str("#ifndef CAMELEON_GENTL_H_\n#define CAMELEON_GENTL_H_\n\n");
+        s.push_str("#include <stddef.h>\n#include <stdint.h>\n\n");
+        // Exported functions use the `system` ABI, which is `__stdcall` on 32-bit Windows.
+        s.push_str("#if defined(_WIN32)\n#  define GC_IMPORT_EXPORT __declspec(dllimport)\n");
+        s.push_str("#  define GC_CALLTYPE __stdcall\n");
+        s.push_str("#else\n#  define GC_IMPORT_EXPORT\n#  define GC_CALLTYPE\n#endif\n\n");
+        s.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+
+        for (name, ty) in &self.typedefs {
+            writeln!(s, "typedef {};", declaration(ty, name)).unwrap();
+        }
+        s.push('\n');
+
+        for e in &self.enums {
+            writeln!(s, "enum {}_LIST\n{{", e.name).unwrap();
+            for (variant, value, docs) in &e.variants {
+                render_docs(&mut s, docs, "    ");
+                writeln!(s, "    {} = {},", variant, value).unwrap();
+            }
+            writeln!(s, "}};\ntypedef int32_t {};\n", e.name).unwrap();
+        }
+
+        for st in &self.structs {
+            writeln!(s, "typedef struct {}\n{{", st.name).unwrap();
+            for (name, ty) in &st.fields {
+                writeln!(s, "    {};", declaration(ty, name)).unwrap();
+            }
+            writeln!(s, "}} {};\n", st.name).unwrap();
+        }
+
+        for f in &self.functions {
+            render_docs(&mut s, &f.docs, "");
+            let args: Vec<_> = f
+                .args
+                .iter()
+                .map(|(name, ty)| declaration(ty, name))
+                .collect();
+            let args = if args.is_empty() {
+                "void".to_string()
+            } else {
+                args.join(", ")
+            };
+            writeln!(
+                s,
+                "GC_IMPORT_EXPORT GC_ERROR GC_CALLTYPE {}({});",
+                f.name, args
+            )
+            .unwrap();
+        }
+
+        s.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n#endif /* CAMELEON_GENTL_H_ */\n");
+        s
+    }
+}
+
+impl Enum {
+    fn from_item(item: &ItemEnum) -> Self {
+        let variants = item
+            .variants
+            .iter()
+            .map(|v| {
+                let value = match &v.discriminant.as_ref().unwrap().1 {
+                    Expr::Lit(lit) => int_lit(&lit.lit),
+                    Expr::Unary(unary) => match &*unary.expr {
+                        Expr::Lit(lit) => -int_lit(&lit.lit),
+                        _ => panic!("unsupported discriminant of {}", v.ident),
+                    },
+                    _ => panic!("unsupported discriminant of {}", v.ident),
+                };
+                (v.ident.to_string(), value, docs(&v.attrs))
+            })
+            .collect();
+
+        Self {
+            name: item.ident.to_string(),
+            variants,
+        }
+    }
+}
+
+impl Function {
+    fn from_item(item: &ItemFn) -> Self {
+        let args = item
+            .sig
+            .inputs
+            .iter()
+            .map(|arg| match arg {
+                FnArg::Typed(arg) => match &*arg.pat {
+                    Pat::Ident(pat) => (
+                        pat.ident.to_string().trim_start_matches('_').to_string(),
+                        c_type(&arg.ty),
+                    ),
+                    _ => panic!("unsupported argument of {}", item.sig.ident),
+                },
+                FnArg::Receiver(_) => panic!("unsupported argument of {}", item.sig.ident),
+            })
+            .collect();
+
+        Self {
+            name: item.sig.ident.to_string(),
+            args,
+            docs: docs(&item.attrs),
+        }
+    }
+}
+
+/// Parses the body of `gentl_api!`, which is a function optionally preceded by `no_assert`.
+fn parse_gentl_api(input: ParseStream) -> syn::Result<ItemFn> {
+    if input.peek(Ident) && !input.peek(Token![pub]) {
+        input.parse::<Ident>()?;
+    }
+    input.parse()
+}
+
+fn c_type(ty: &Type) -> String {
+    match ty {
+        Type::Ptr(ptr) => {
+            let elem = c_type(&ptr.elem);
+            if ptr.mutability.is_some() {
+                format!("{} *", elem)
+            } else {
+                format!("const {} *", elem)
+            }
+            .replace("* *", "**")
+        }
+
+        Type::Path(path) => {
+            let ident = path.path.segments.last().unwrap().ident.to_string();
+            match ident.as_str() {
+                "c_void" => "void",
+                "c_char" => "char",
+                "size_t" | "usize" => "size_t",
+                "ptrdiff_t" | "isize" => "ptrdiff_t",
+                "u8" => "uint8_t",
+                "u16" => "uint16_t",
+                "u32" => "uint32_t",
+                "u64" => "uint64_t",
+                "i8" => "int8_t",
+                "i16" => "int16_t",
+                "i32" => "int32_t",
+                "i64" => "int64_t",
+                "f64" => "double",
+                other => return other.to_string(),
+            }
+            .to_string()
+        }
+
+        _ => panic!("unsupported type in ffi definitions"),
+    }
+}
+
+fn declaration(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{}{}", ty, name)
+    } else {
+        format!("{} {}", ty, name)
+    }
+}
+
+fn int_lit(lit: &Lit) -> i64 {
+    match lit {
+        Lit::Int(lit) => lit.base10_parse().unwrap(),
+        _ => panic!("unsupported literal in ffi definitions"),
+    }
+}
+
+fn has_repr(attrs: &[Attribute], repr: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path.is_ident("repr")
+            && matches!(attr.parse_args::<Ident>(), Ok(ident) if ident == repr)
+    })
+}
+
+fn docs(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(nv)) => match nv.lit {
+                Lit::Str(s) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn render_docs(s: &mut String, docs: &[String], indent: &str) {
+    if docs.is_empty() {
+        return;
+    }
+    writeln!(s, "{}/**", indent).unwrap();
+    for line in docs {
+        if line.is_empty() {
+            writeln!(s, "{} *", indent).unwrap();
+        } else {
+            writeln!(s, "{} * {}", indent, line.replace("*/", "* /")).unwrap();
+        }
+    }
+    writeln!(s, "{} */", indent).unwrap();
+}
@@ -2,15 +2,16 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::{convert::TryInto, ops::Deref, sync::Mutex};
+use std::{convert::TryInto, ffi::CStr, ops::Deref, sync::Mutex};
 
 use super::{
-    copy_info, imp, interface, GenTlError, GenTlResult, ModuleHandle, GC_ERROR, INFO_DATATYPE,
+    copy_info, event, imp, interface,
+    stream::{DataStreamModuleRef, DS_HANDLE},
+    CopyTo, GenTlError, GenTlResult, ModuleHandle, GC_ERROR, INFO_DATATYPE,
 };
 
 pub(super) type DEV_HANDLE = *mut libc::c_void;
 pub(super) type PORT_HANDLE = *mut libc::c_void;
-pub(super) type DS_HANDLE = *mut libc::c_void;
 
 #[derive(Clone, Copy)]
 pub(super) struct DeviceModuleRef<'a> {
@@ -144,6 +145,19 @@ newtype_enum! {
     }
 }
 
+newtype_enum! {
+    pub enum DEVICE_ACCESS_STATUS {
+        DEVICE_ACCESS_STATUS_UNKNOWN = 0,
+        DEVICE_ACCESS_STATUS_READWRITE = 1,
+        DEVICE_ACCESS_STATUS_READONLY = 2,
+        DEVICE_ACCESS_STATUS_NOACCESS = 3,
+        DEVICE_ACCESS_STATUS_BUSY = 4,
+        DEVICE_ACCESS_STATUS_OPEN_READWRITE = 5,
+        DEVICE_ACCESS_STATUS_OPEN_READONLY = 6,
+        DEVICE_ACCESS_STATUS_CUSTOM_ID = 1000,
+    }
+}
+
 impl TryInto<imp::device::DeviceAccessFlag> for DEVICE_ACCESS_FLAGS {
     type Error = GenTlError;
 
@@ -166,16 +180,19 @@ gentl_api! {
         // Close the device module.
         dev_handle.lock().unwrap().close()?;
 
+        // Release event handles registered to the device.
+        event::release_events(hDevice);
+
         // Drop remote device handle.
         // This seems weired but there is no function to close remote device in GenTL API.
         unsafe {
                 let mut remote_handle = ModuleHandle::from_raw_manually_drop(dev_handle.remote_handle)?;
-                std::mem::ManuallyDrop::drop(&mut remote_handle);
+                ModuleHandle::drop_raw(&mut remote_handle);
         }
 
         // Drop the device handle.
         unsafe {
-            std::mem::ManuallyDrop::drop(&mut handle)
+            ModuleHandle::drop_raw(&mut handle)
         }
 
         Ok(())
@@ -204,14 +221,27 @@ gentl_api! {
         sDataStreamID: *mut libc::c_char,
         piSize: *mut libc::size_t,
     ) -> GenTlResult<()> {
-        todo!()
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDevice)? };
+        let dev_handle = handle.device()?;
+
+        let dev_guard = dev_handle.lock().unwrap();
+        let id = dev_guard.data_stream_id(iIndex as usize)?;
+        id.copy_to(sDataStreamID, piSize)
     }
 }
 
 gentl_api! {
     pub fn DevGetNumDataStreams(hDevice: DEV_HANDLE, piNumDataStreams: *mut u32) -> GenTlResult<()>
     {
-        todo!()
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDevice)? };
+        let dev_handle = handle.device()?;
+
+        let num = dev_handle.lock().unwrap().num_data_streams()?;
+        unsafe {
+            *piNumDataStreams = num as u32;
+        }
+
+        Ok(())
     }
 }
 
@@ -234,7 +264,20 @@ gentl_api! {
         sDataStreamID: *const ::std::os::raw::c_char,
         phDataStream: *mut DS_HANDLE,
     ) -> GenTlResult<()> {
-        todo!()
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDevice)? };
+        let dev_handle = handle.device()?;
+
+        let dev_guard = dev_handle.lock().unwrap();
+        let id = unsafe { CStr::from_ptr(sDataStreamID) }.to_string_lossy();
+        let ds = dev_guard.data_stream(&id)?;
+
+        ds.lock().unwrap().open()?;
+        let ds_handle = Box::new(ModuleHandle::DataStream(DataStreamModuleRef::new(ds, hDevice)));
+        unsafe {
+            *phDataStream = ds_handle.into_raw();
+        }
+
+        Ok(())
     }
 }
 
@@ -0,0 +1,552 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use cameleon::u3v::DeviceEvent;
+
+use imp::event::{Event, EventData, EventType, NEW_BUFFER_DATA_SIZE};
+
+use super::{
+    copy_info, imp,
+    stream::{buffer_handle, BUFFER_HANDLE},
+    GenTlError, GenTlResult, ModuleHandle, GC_ERROR, INFO_DATATYPE,
+};
+
+pub(super) type EVENT_HANDLE = *mut libc::c_void;
+pub(super) type EVENTSRC_HANDLE = *mut libc::c_void;
+
+/// Infinite timeout to wait for an event.
+const GENTL_INFINITE: u64 = u64::MAX;
+
+#[derive(Clone)]
+pub(super) struct EventModuleRef {
+    inner: Arc<dyn Event>,
+}
+
+impl Deref for EventModuleRef {
+    type Target = dyn Event;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner.as_ref()
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Registered event handles keyed by the raw event source handle and the raw event type.
+    /// The registry owns the event handles so that they are released when the event is
+    /// unregistered or the event source is closed.
+    static ref REGISTERED_EVENTS: Mutex<HashMap<(usize, i32), usize>> = Mutex::new(HashMap::new());
+}
+
+/// Release all event handles registered to the event source.
+/// Must be called after the events are unregistered from the module.
+pub(super) fn release_events(hEventSrc: EVENTSRC_HANDLE) {
+    let mut registry = REGISTERED_EVENTS.lock().unwrap();
+    let keys: Vec<_> = registry
+        .keys()
+        .filter(|(src, _)| *src == hEventSrc as usize)
+        .copied()
+        .collect();
+    for key in keys {
+        // Ok to unwrap because the key is taken from the registry.
+        let hEvent = registry.remove(&key).unwrap() as EVENT_HANDLE;
+        unsafe { drop_event_handle(hEvent) };
+    }
+}
+
+unsafe fn drop_event_handle(hEvent: EVENT_HANDLE) {
+    if let Ok(mut handle) = ModuleHandle::from_raw_manually_drop(hEvent) {
+        ModuleHandle::drop_raw(&mut handle);
+    }
+}
+
+newtype_enum! {
+    pub enum EVENT_TYPE {
+        EVENT_ERROR = 0,
+        EVENT_NEW_BUFFER = 1,
+        EVENT_FEATURE_INVALIDATE = 2,
+        EVENT_FEATURE_CHANGE = 3,
+        EVENT_REMOTE_DEVICE = 4,
+        EVENT_MODULE = 5,
+        EVENT_CUSTOM_ID = 1000,
+    }
+}
+
+impl TryInto<EventType> for EVENT_TYPE {
+    type Error = GenTlError;
+
+    fn try_into(self) -> GenTlResult<EventType> {
+        match self {
+            EVENT_TYPE::EVENT_ERROR => Ok(EventType::Error),
+            EVENT_TYPE::EVENT_NEW_BUFFER => Ok(EventType::NewBuffer),
+            EVENT_TYPE::EVENT_FEATURE_INVALIDATE => Ok(EventType::FeatureInvalidate),
+            EVENT_TYPE::EVENT_FEATURE_CHANGE => Ok(EventType::FeatureChange),
+            EVENT_TYPE::EVENT_REMOTE_DEVICE => Ok(EventType::RemoteDevice),
+            EVENT_TYPE::EVENT_MODULE => Ok(EventType::Module),
+            _ => Err(GenTlError::InvalidParameter),
+        }
+    }
+}
+
+impl From<EventType> for EVENT_TYPE {
+    fn from(event_type: EventType) -> Self {
+        match event_type {
+            EventType::Error => EVENT_TYPE::EVENT_ERROR,
+            EventType::NewBuffer => EVENT_TYPE::EVENT_NEW_BUFFER,
+            EventType::FeatureInvalidate => EVENT_TYPE::EVENT_FEATURE_INVALIDATE,
+            EventType::FeatureChange => EVENT_TYPE::EVENT_FEATURE_CHANGE,
+            EventType::RemoteDevice => EVENT_TYPE::EVENT_REMOTE_DEVICE,
+            EventType::Module => EVENT_TYPE::EVENT_MODULE,
+        }
+    }
+}
+
+newtype_enum! {
+    pub enum EVENT_INFO_CMD {
+        EVENT_EVENT_TYPE = 0,
+        EVENT_NUM_IN_QUEUE = 1,
+        EVENT_NUM_FIRED = 2,
+        EVENT_SIZE_MAX = 3,
+        EVENT_INFO_DATA_SIZE_MAX = 4,
+        EVENT_INFO_CUSTOM_ID = 1000,
+    }
+}
+
+newtype_enum! {
+    pub enum EVENT_DATA_INFO_CMD {
+        EVENT_DATA_ID = 0,
+        EVENT_DATA_VALUE = 1,
+        EVENT_DATA_NUMID = 2,
+        EVENT_DATA_CUSTOM_ID = 1000,
+    }
+}
+
+/// Data delivered by the new buffer event.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct EVENT_NEW_BUFFER_DATA {
+    BufferHandle: BUFFER_HANDLE,
+    pUserPointer: *mut libc::c_void,
+}
+
+/// Write the event data in the layout described in [`EventData::size`].
+unsafe fn write_event_data(data: &EventData, dst: *mut libc::c_void) {
+    match data {
+        EventData::NewBuffer { id, user_ptr } => {
+            let new_buffer_data = EVENT_NEW_BUFFER_DATA {
+                BufferHandle: buffer_handle(*id),
+                pUserPointer: user_ptr.0,
+            };
+            dst.cast::<EVENT_NEW_BUFFER_DATA>()
+                .write_unaligned(new_buffer_data);
+        }
+
+        EventData::Error { code, message } => {
+            let dst = dst.cast::<u8>();
+            let code = code.to_ne_bytes();
+            std::ptr::copy_nonoverlapping(code.as_ptr(), dst, code.len());
+            let dst = dst.add(code.len());
+            std::ptr::copy_nonoverlapping(message.as_ptr(), dst, message.len());
+            dst.add(message.len()).write(0); // Null terminated.
+        }
+
+        EventData::RemoteDevice { packet } => {
+            std::ptr::copy_nonoverlapping(packet.as_ptr(), dst.cast::<u8>(), packet.len());
+        }
+    }
+}
+
+fn event_data_info(
+    event_type: EventType,
+    data: &[u8],
+    iInfoCmd: EVENT_DATA_INFO_CMD,
+    pOutBuffer: *mut libc::c_void,
+    piOutSize: *mut libc::size_t,
+) -> GenTlResult<INFO_DATATYPE> {
+    match event_type {
+        EventType::NewBuffer => {
+            if data.len() < NEW_BUFFER_DATA_SIZE {
+                return Err(GenTlError::InvalidParameter);
+            }
+            let new_buffer_data = unsafe {
+                data.as_ptr()
+                    .cast::<EVENT_NEW_BUFFER_DATA>()
+                    .read_unaligned()
+            };
+
+            match iInfoCmd {
+                EVENT_DATA_INFO_CMD::EVENT_DATA_ID => {
+                    copy_info(new_buffer_data.BufferHandle, pOutBuffer, piOutSize)
+                }
+                EVENT_DATA_INFO_CMD::EVENT_DATA_VALUE => {
+                    copy_info(new_buffer_data.pUserPointer, pOutBuffer, piOutSize)
+                }
+                EVENT_DATA_INFO_CMD::EVENT_DATA_NUMID => Err(GenTlError::NotAvailable),
+                _ => Err(GenTlError::InvalidParameter),
+            }
+        }
+
+        EventType::Error => {
+            let code_len = std::mem::size_of::<i32>();
+            if data.len() < code_len {
+                return Err(GenTlError::InvalidParameter);
+            }
+            let mut code = [0; 4];
+            code.copy_from_slice(&data[..code_len]);
+            let code = i32::from_ne_bytes(code);
+
+            let message = &data[code_len..];
+            let message_len = message
+                .iter()
+                .position(|c| *c == 0)
+                .unwrap_or(message.len());
+            let message = String::from_utf8_lossy(&message[..message_len]);
+
+            match iInfoCmd {
+                EVENT_DATA_INFO_CMD::EVENT_DATA_ID => copy_info(code, pOutBuffer, piOutSize),
+                EVENT_DATA_INFO_CMD::EVENT_DATA_VALUE => {
+                    copy_info(message.as_ref(), pOutBuffer, piOutSize)
+                }
+                EVENT_DATA_INFO_CMD::EVENT_DATA_NUMID => Err(GenTlError::NotAvailable),
+                _ => Err(GenTlError::InvalidParameter),
+            }
+        }
+
+        EventType::RemoteDevice => {
+            let events =
+                DeviceEvent::parse_packet(data).map_err(|_| GenTlError::InvalidParameter)?;
+            // Only the first event is reported because GenTL assumes that an event packet
+            // contains one event.
+            let event = events.first().ok_or(GenTlError::NoData)?;
+
+            match iInfoCmd {
+                // `EventID` of GenApi is a hexadecimal string.
+                EVENT_DATA_INFO_CMD::EVENT_DATA_ID => copy_info(
+                    format!("{:04X}", event.event_id).as_str(),
+                    pOutBuffer,
+                    piOutSize,
+                ),
+                EVENT_DATA_INFO_CMD::EVENT_DATA_VALUE => {
+                    copy_info(event.data.as_slice(), pOutBuffer, piOutSize)
+                }
+                EVENT_DATA_INFO_CMD::EVENT_DATA_NUMID => {
+                    copy_info(u64::from(event.event_id), pOutBuffer, piOutSize)
+                }
+                _ => Err(GenTlError::InvalidParameter),
+            }
+        }
+
+        _ => Err(GenTlError::NotImplemented),
+    }
+}
+
+gentl_api! {
+    pub fn GCRegisterEvent(
+        hEventSrc: EVENTSRC_HANDLE,
+        iEventID: EVENT_TYPE,
+        phEvent: *mut EVENT_HANDLE,
+    ) -> GenTlResult<()> {
+        if phEvent.is_null() {
+            return Err(GenTlError::InvalidParameter);
+        }
+        let event_type: EventType = iEventID.try_into()?;
+
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hEventSrc)? };
+        let event = match &**handle {
+            ModuleHandle::Device(dev) => dev.lock().unwrap().register_event(event_type)?,
+            ModuleHandle::DataStream(ds) => ds.lock().unwrap().register_event(event_type)?,
+            ModuleHandle::Event(..) => return Err(GenTlError::InvalidHandle),
+            _ => return Err(GenTlError::NotImplemented),
+        };
+
+        let event_handle = unsafe {
+            Box::new(ModuleHandle::Event(EventModuleRef { inner: event })).into_raw()
+        };
+        REGISTERED_EVENTS
+            .lock()
+            .unwrap()
+            .insert((hEventSrc as usize, iEventID.0), event_handle as usize);
+        unsafe {
+            *phEvent = event_handle;
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn GCUnregisterEvent(hEventSrc: EVENTSRC_HANDLE, iEventID: EVENT_TYPE) -> GenTlResult<()> {
+        let event_type: EventType = iEventID.try_into()?;
+
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hEventSrc)? };
+        match &**handle {
+            ModuleHandle::Device(dev) => dev.lock().unwrap().unregister_event(event_type)?,
+            ModuleHandle::DataStream(ds) => ds.lock().unwrap().unregister_event(event_type)?,
+            ModuleHandle::Event(..) => return Err(GenTlError::InvalidHandle),
+            _ => return Err(GenTlError::NotImplemented),
+        };
+
+        let hEvent = REGISTERED_EVENTS
+            .lock()
+            .unwrap()
+            .remove(&(hEventSrc as usize, iEventID.0));
+        if let Some(hEvent) = hEvent {
+            unsafe { drop_event_handle(hEvent as EVENT_HANDLE) };
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn EventGetData(
+        hEvent: EVENT_HANDLE,
+        pBuffer: *mut libc::c_void,
+        piSize: *mut libc::size_t,
+        iTimeout: u64,
+    ) -> GenTlResult<()> {
+        if pBuffer.is_null() || piSize.is_null() {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        // Clone the event so that the handle isn't accessed while waiting for the event.
+        let event = unsafe { ModuleHandle::from_raw_manually_drop(hEvent)? }.event()?;
+
+        let timeout = if iTimeout == GENTL_INFINITE {
+            None
+        } else {
+            Some(Duration::from_millis(iTimeout))
+        };
+        let data = event.get_data(timeout, unsafe { *piSize })?;
+
+        unsafe {
+            write_event_data(&data, pBuffer);
+            *piSize = data.size();
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn EventGetDataInfo(
+        hEvent: EVENT_HANDLE,
+        pInBuffer: *const libc::c_void,
+        iInSize: libc::size_t,
+        iInfoCmd: EVENT_DATA_INFO_CMD,
+        piType: *mut INFO_DATATYPE,
+        pOutBuffer: *mut libc::c_void,
+        piOutSize: *mut libc::size_t,
+    ) -> GenTlResult<()> {
+        if pInBuffer.is_null() {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        let event = unsafe { ModuleHandle::from_raw_manually_drop(hEvent)? }.event()?;
+        let data = unsafe { std::slice::from_raw_parts(pInBuffer.cast::<u8>(), iInSize) };
+        let info_data_type =
+            event_data_info(event.event_type(), data, iInfoCmd, pOutBuffer, piOutSize)?;
+
+        unsafe {
+            *piType = info_data_type;
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn EventGetInfo(
+        hEvent: EVENT_HANDLE,
+        iInfoCmd: EVENT_INFO_CMD,
+        piType: *mut INFO_DATATYPE,
+        pBuffer: *mut libc::c_void,
+        piSize: *mut libc::size_t,
+    ) -> GenTlResult<()> {
+        let event = unsafe { ModuleHandle::from_raw_manually_drop(hEvent)? }.event()?;
+
+        let info_data_type = match iInfoCmd {
+            EVENT_INFO_CMD::EVENT_EVENT_TYPE => {
+                copy_info(EVENT_TYPE::from(event.event_type()).0, pBuffer, piSize)
+            }
+            EVENT_INFO_CMD::EVENT_NUM_IN_QUEUE => copy_info(event.num_in_queue(), pBuffer, piSize),
+            EVENT_INFO_CMD::EVENT_NUM_FIRED => copy_info(event.num_fired(), pBuffer, piSize),
+            EVENT_INFO_CMD::EVENT_SIZE_MAX => copy_info(event.max_data_size(), pBuffer, piSize),
+            EVENT_INFO_CMD::EVENT_INFO_DATA_SIZE_MAX => {
+                copy_info(event.max_info_data_size(), pBuffer, piSize)
+            }
+            _ => Err(GenTlError::InvalidParameter),
+        }?;
+
+        unsafe {
+            *piType = info_data_type;
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn EventFlush(hEvent: EVENT_HANDLE) -> GenTlResult<()> {
+        let event = unsafe { ModuleHandle::from_raw_manually_drop(hEvent)? }.event()?;
+        event.flush();
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn EventKill(hEvent: EVENT_HANDLE) -> GenTlResult<()> {
+        let event = unsafe { ModuleHandle::from_raw_manually_drop(hEvent)? }.event()?;
+        event.kill();
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use std::{mem, ptr, thread};
+
+    use super::{
+        super::tests::{
+            EmulatedDevice, Lib, GC_ERR_ABORT, GC_ERR_INVALID_HANDLE, GC_ERR_INVALID_PARAMETER,
+            GC_ERR_NOT_AVAILABLE, GC_ERR_RESOURCE_IN_USE, GC_ERR_SUCCESS, GC_ERR_TIMEOUT,
+        },
+        *,
+    };
+
+    fn register_event(h_src: EVENTSRC_HANDLE, event_type: EVENT_TYPE) -> EVENT_HANDLE {
+        let mut h_event = ptr::null_mut();
+        assert_eq!(
+            GCRegisterEvent(h_src, event_type, &mut h_event).0,
+            GC_ERR_SUCCESS
+        );
+        assert!(!h_event.is_null());
+        h_event
+    }
+
+    fn event_info<T: Default>(h_event: EVENT_HANDLE, cmd: EVENT_INFO_CMD) -> T {
+        let mut value = T::default();
+        let mut info_type = INFO_DATATYPE::INFO_DATATYPE_UNKNOWN;
+        let mut size = mem::size_of::<T>();
+        assert_eq!(
+            EventGetInfo(
+                h_event,
+                cmd,
+                &mut info_type,
+                (&mut value as *mut T).cast(),
+                &mut size
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(size, mem::size_of::<T>());
+        value
+    }
+
+    fn get_data(h_event: EVENT_HANDLE, timeout: u64) -> i32 {
+        let mut buf = [0_u8; 1024];
+        let mut size = buf.len();
+        EventGetData(h_event, buf.as_mut_ptr().cast(), &mut size, timeout).0
+    }
+
+    #[test]
+    fn test_register_event() {
+        let _lib = Lib::init();
+        let dev = EmulatedDevice::open("FFIEV000");
+        let h_stream = dev.open_stream();
+
+        let mut h_event = ptr::null_mut();
+        assert_eq!(
+            GCRegisterEvent(h_stream, EVENT_TYPE::EVENT_CUSTOM_ID, &mut h_event).0,
+            GC_ERR_INVALID_PARAMETER
+        );
+        assert_eq!(
+            GCRegisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER, ptr::null_mut()).0,
+            GC_ERR_INVALID_PARAMETER
+        );
+        // The emulator doesn't implement `EIRM`.
+        assert_eq!(
+            GCRegisterEvent(dev.h_device, EVENT_TYPE::EVENT_REMOTE_DEVICE, &mut h_event).0,
+            GC_ERR_NOT_AVAILABLE
+        );
+
+        let h_event = register_event(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER);
+        let mut h_dup = ptr::null_mut();
+        assert_eq!(
+            GCRegisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER, &mut h_dup).0,
+            GC_ERR_RESOURCE_IN_USE
+        );
+
+        assert_eq!(
+            event_info::<i32>(h_event, EVENT_INFO_CMD::EVENT_EVENT_TYPE),
+            EVENT_TYPE::EVENT_NEW_BUFFER.0
+        );
+        assert_eq!(
+            event_info::<usize>(h_event, EVENT_INFO_CMD::EVENT_NUM_IN_QUEUE),
+            0
+        );
+        assert_eq!(
+            event_info::<u64>(h_event, EVENT_INFO_CMD::EVENT_NUM_FIRED),
+            0
+        );
+        assert_eq!(
+            event_info::<usize>(h_event, EVENT_INFO_CMD::EVENT_SIZE_MAX),
+            NEW_BUFFER_DATA_SIZE
+        );
+
+        assert_eq!(
+            GCUnregisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER).0,
+            GC_ERR_SUCCESS
+        );
+        // The event handle is released on unregistration.
+        assert_eq!(EventKill(h_event).0, GC_ERR_INVALID_HANDLE);
+
+        // The event can be registered again.
+        register_event(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER);
+        dev.close();
+    }
+
+    #[test]
+    fn test_get_data_and_kill() {
+        let _lib = Lib::init();
+        let dev = EmulatedDevice::open("FFIEV001");
+        let h_stream = dev.open_stream();
+
+        for event_type in &[EVENT_TYPE::EVENT_NEW_BUFFER, EVENT_TYPE::EVENT_ERROR] {
+            let h_event = register_event(h_stream, *event_type);
+
+            assert_eq!(get_data(h_event, 10), GC_ERR_TIMEOUT);
+            assert_eq!(
+                EventGetData(h_event, ptr::null_mut(), ptr::null_mut(), 10).0,
+                GC_ERR_INVALID_PARAMETER
+            );
+
+            // Kill before waiting aborts the next wait only.
+            assert_eq!(EventKill(h_event).0, GC_ERR_SUCCESS);
+            assert_eq!(get_data(h_event, GENTL_INFINITE), GC_ERR_ABORT);
+            assert_eq!(get_data(h_event, 10), GC_ERR_TIMEOUT);
+
+            // Kill wakes up a thread waiting infinitely.
+            let raw_event = h_event as usize;
+            let waiter = thread::spawn(move || get_data(raw_event as EVENT_HANDLE, GENTL_INFINITE));
+            thread::sleep(Duration::from_millis(50));
+            assert_eq!(EventKill(h_event).0, GC_ERR_SUCCESS);
+            assert_eq!(waiter.join().unwrap(), GC_ERR_ABORT);
+
+            assert_eq!(EventFlush(h_event).0, GC_ERR_SUCCESS);
+            assert_eq!(GCUnregisterEvent(h_stream, *event_type).0, GC_ERR_SUCCESS);
+        }
+
+        dev.close();
+    }
+}
@@ -49,7 +49,7 @@ pub(super) fn if_get_info(
             copy_info(iface_guard.interface_id(), pBuffer, piSize)
         }
 
-        INTERFACE_INFO_CMD::INTERFACE_INFO_DISPLAY_NAME => {
+        INTERFACE_INFO_CMD::INTERFACE_INFO_DISPLAYNAME => {
             copy_info(iface_guard.display_name(), pBuffer, piSize)
         }
 
@@ -73,7 +73,7 @@ newtype_enum! {
         INTERFACE_INFO_ID = 0,
 
         /// User readable name of the interface.
-        INTERFACE_INFO_DISPLAY_NAME = 1,
+        INTERFACE_INFO_DISPLAYNAME = 1,
 
         /// Transport layer technology that is supported.
         INTERFACE_INFO_TLTYPE = 2,
@@ -89,7 +89,7 @@ gentl_api! {
         iface_handle.lock().unwrap().close()?;
         // Drop its handle.
         unsafe {
-            std::mem::ManuallyDrop::drop(&mut handle);
+            ModuleHandle::drop_raw(&mut handle);
         }
 
         Ok(())
@@ -8,7 +8,7 @@ macro_rules! gentl_api {
     )
     => {
         #[no_mangle]
-        pub extern "C" fn $name($($arg: $ty),*) -> GC_ERROR {
+        pub extern "system" fn $name($($arg: $ty),*) -> GC_ERROR {
             #[inline(always)]
             fn inner($($arg: $ty),*) -> GenTlResult<()> {
                 crate::ffi::assert_lib_initialized()?;
@@ -27,7 +27,7 @@ macro_rules! gentl_api {
     )
     => {
         #[no_mangle]
-        pub extern "C" fn $name($($arg: $ty),*) -> GC_ERROR {
+        pub extern "system" fn $name($($arg: $ty),*) -> GC_ERROR {
             #[inline(always)]
             fn inner($($arg: $ty),*) -> GenTlResult<()> {
                 $body
@@ -6,17 +6,53 @@
 mod macros;
 
 pub mod device;
+pub mod event;
 pub mod interface;
 pub mod port;
+pub mod stream;
 pub mod system;
 
-use std::{cell::RefCell, mem::ManuallyDrop, sync::RwLock};
+#[cfg(test)]
+mod tests;
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    mem::ManuallyDrop,
+    sync::{Mutex, RwLock},
+};
 
 use crate::{imp, GenTlError, GenTlResult};
 
-#[repr(transparent)]
-#[derive(Clone, Copy, PartialEq, Eq)]
-pub struct GC_ERROR(i32);
+newtype_enum! {
+    pub enum GC_ERROR {
+        GC_ERR_SUCCESS = 0,
+        GC_ERR_ERROR = -1001,
+        GC_ERR_NOT_INITIALIZED = -1002,
+        GC_ERR_NOT_IMPLEMENTED = -1003,
+        GC_ERR_RESOURCE_IN_USE = -1004,
+        GC_ERR_ACCESS_DENIED = -1005,
+        GC_ERR_INVALID_HANDLE = -1006,
+        GC_ERR_INVALID_ID = -1007,
+        GC_ERR_NO_DATA = -1008,
+        GC_ERR_INVALID_PARAMETER = -1009,
+        GC_ERR_IO = -1010,
+        GC_ERR_TIMEOUT = -1011,
+        GC_ERR_ABORT = -1012,
+        GC_ERR_INVALID_BUFFER = -1013,
+        GC_ERR_NOT_AVAILABLE = -1014,
+        GC_ERR_INVALID_ADDRESS = -1015,
+        GC_ERR_BUFFER_TOO_SMALL = -1016,
+        GC_ERR_INVALID_INDEX = -1017,
+        GC_ERR_PARSING_CHUNK_DATA = -1018,
+        GC_ERR_INVALID_VALUE = -1019,
+        GC_ERR_RESOURCE_EXHAUSTED = -1020,
+        GC_ERR_OUT_OF_MEMORY = -1021,
+        GC_ERR_BUSY = -1022,
+        GC_ERR_AMBIGUOUS = -1023,
+        GC_ERR_CUSTOM_ID = -10000,
+    }
+}
 
 #[repr(transparent)]
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -50,38 +86,7 @@ impl From<bool> for bool8_t {
 
 impl From<&GenTlError> for GC_ERROR {
     fn from(val: &GenTlError) -> Self {
-        use GenTlError::{
-            Abort, AccessDenied, Ambiguous, BufferTooSmall, Busy, Error, InvalidAddress,
-            InvalidBuffer, InvalidHandle, InvalidId, InvalidIndex, InvalidParameter, InvalidValue,
-            Io, NoData, NotAvailable, NotImplemented, NotInitialized, OutOfMemory,
-            ParsingChunkData, ResourceExhausted, ResourceInUse, Timeout,
-        };
-        let code: i32 = match val {
-            Error(..) => -1001,
-            NotInitialized => -1002,
-            NotImplemented => -1003,
-            ResourceInUse => -1004,
-            AccessDenied => -1005,
-            InvalidHandle => -1006,
-            InvalidId(..) => -1007,
-            NoData => -1008,
-            InvalidParameter => -1009,
-            Io(..) => -1010,
-            Timeout => -1011,
-            Abort => -1012,
-            InvalidBuffer => -1013,
-            NotAvailable => -1014,
-            InvalidAddress => -1015,
-            BufferTooSmall => -1016,
-            InvalidIndex => -1017,
-            ParsingChunkData => -1018,
-            InvalidValue(..) => -1019,
-            ResourceExhausted => -1020,
-            OutOfMemory => -1021,
-            Busy => -1022,
-            Ambiguous => -1023,
-        };
-        GC_ERROR(code)
+        GC_ERROR(val.code())
     }
 }
 
@@ -94,7 +99,7 @@ impl From<GenTlError> for GC_ERROR {
 impl<T> From<GenTlResult<T>> for GC_ERROR {
     fn from(val: GenTlResult<T>) -> Self {
         match val {
-            Ok(..) => GC_ERROR(0),
+            Ok(..) => GC_ERROR::GC_ERR_SUCCESS,
             Err(e) => e.into(),
         }
     }
@@ -103,7 +108,7 @@ impl<T> From<GenTlResult<T>> for GC_ERROR {
 impl<T> From<&GenTlResult<T>> for GC_ERROR {
     fn from(val: &GenTlResult<T>) -> Self {
         match val {
-            Ok(..) => GC_ERROR(0),
+            Ok(..) => GC_ERROR::GC_ERR_SUCCESS,
             Err(e) => e.into(),
         }
     }
@@ -118,6 +123,8 @@ enum ModuleHandle<'a> {
     Interface(interface::InterfaceModuleRef<'a>),
     Device(device::DeviceModuleRef<'a>),
     RemoteDevice(device::RemoteDeviceRef<'a>),
+    DataStream(stream::DataStreamModuleRef<'a>),
+    Event(event::EventModuleRef),
 }
 
 impl<'a> ModuleHandle<'a> {
@@ -142,10 +149,31 @@ impl<'a> ModuleHandle<'a> {
         }
     }
 
+    fn data_stream(&self) -> GenTlResult<stream::DataStreamModuleRef<'a>> {
+        match self {
+            ModuleHandle::DataStream(ds) => Ok(*ds),
+            _ => Err(GenTlError::InvalidHandle),
+        }
+    }
+
+    fn event(&self) -> GenTlResult<event::EventModuleRef> {
+        match self {
+            ModuleHandle::Event(event) => Ok(event.clone()),
+            _ => Err(GenTlError::InvalidHandle),
+        }
+    }
+
+    /// Returns [`GenTlError::InvalidHandle`] if `raw_handle` isn't a live handle returned from
+    /// [`ModuleHandle::into_raw`], e.g. a handle already closed.
     unsafe fn from_raw_manually_drop(
         raw_handle: *mut libc::c_void,
     ) -> GenTlResult<ManuallyDrop<Box<ModuleHandle<'a>>>> {
-        if raw_handle.is_null() {
+        if raw_handle.is_null()
+            || !LIVE_HANDLES
+                .lock()
+                .unwrap()
+                .contains(&(raw_handle as usize))
+        {
             Err(GenTlError::InvalidHandle)
         } else {
             let handle = raw_handle.cast::<ModuleHandle>();
@@ -154,7 +182,16 @@ impl<'a> ModuleHandle<'a> {
     }
 
     unsafe fn into_raw(self: Box<Self>) -> *mut libc::c_void {
-        Box::into_raw(self).cast::<libc::c_void>()
+        let raw_handle = Box::into_raw(self).cast::<libc::c_void>();
+        LIVE_HANDLES.lock().unwrap().insert(raw_handle as usize);
+        raw_handle
+    }
+
+    /// Invalidates the raw handle and drops it.
+    unsafe fn drop_raw(handle: &mut ManuallyDrop<Box<ModuleHandle<'a>>>) {
+        let raw_handle = &***handle as *const ModuleHandle as usize;
+        LIVE_HANDLES.lock().unwrap().remove(&raw_handle);
+        ManuallyDrop::drop(handle);
     }
 }
 
@@ -180,6 +217,10 @@ newtype_enum! {
 
 lazy_static::lazy_static! {
     static ref IS_LIB_INITIALIZED: RwLock<bool> = RwLock::new(false);
+
+    /// Addresses of handles passed to the consumer and not closed yet.
+    /// Used to reject invalid handles, e.g. handles closed twice, instead of dereferencing them.
+    static ref LIVE_HANDLES: Mutex<HashSet<usize>> = Mutex::new(HashSet::new());
 }
 
 thread_local! {
@@ -407,6 +448,8 @@ impl_copy_to_for_numeric!(i32, INFO_DATATYPE::INFO_DATATYPE_INT32);
 impl_copy_to_for_numeric!(u32, INFO_DATATYPE::INFO_DATATYPE_UINT32);
 impl_copy_to_for_numeric!(i64, INFO_DATATYPE::INFO_DATATYPE_INT64);
 impl_copy_to_for_numeric!(u64, INFO_DATATYPE::INFO_DATATYPE_UINT64);
+impl_copy_to_for_numeric!(usize, INFO_DATATYPE::INFO_DATATYPE_SIZET);
+impl_copy_to_for_numeric!(*mut libc::c_void, INFO_DATATYPE::INFO_DATATYPE_PTR);
 
 fn assert_lib_initialized() -> GenTlResult<()> {
     if *IS_LIB_INITIALIZED.read().unwrap() {
@@ -442,13 +485,13 @@ gentl_api!(
 );
 
 gentl_api!(
-    pub fn CGCGetInfo(
-        _iInfoCmd: i32,
-        _piType: i32,
-        _pBuffer: *mut libc::c_void,
-        _piSize: *mut libc::size_t,
+    pub fn GCGetInfo(
+        iInfoCmd: system::TL_INFO_CMD,
+        piType: *mut INFO_DATATYPE,
+        pBuffer: *mut libc::c_void,
+        piSize: *mut libc::size_t,
     ) -> GenTlResult<()> {
-        Err(GenTlError::NotImplemented)
+        system::copy_system_info(iInfoCmd, piType, pBuffer, piSize)
     }
 );
 
@@ -466,7 +509,7 @@ gentl_api!(
             code
         } else {
             "No Error".copy_to(sErrorText, piSize)?;
-            Ok(()).into()
+            GC_ERROR::GC_ERR_SUCCESS
         };
 
         unsafe {
@@ -79,6 +79,15 @@ macro_rules! with_port {
                 let mut $port = handle.lock().unwrap();
                 $body
             }
+
+            ModuleHandle::DataStream(handle) => {
+                let mut $port = handle.lock().unwrap();
+                $body
+            }
+
+            ModuleHandle::Event(..) => {
+                return Err(GenTlError::InvalidHandle);
+            }
         }
     };
 }
@@ -92,61 +101,9 @@ pub struct PORT_REGISTER_STACK_ENTRY {
 }
 
 fn file_location_to_url(xml_info: &imp::port::XmlInfo, port_info: &imp::port::PortInfo) -> String {
-    use imp::port::XmlLocation;
-    match &xml_info.location {
-        XmlLocation::RegisterMap { address, size } => {
-            // The specificaton requires us to stringfy xml location as below when the
-            // xml is On the register,
-            //
-            // local:{filename}.{extension};{address};{length}[?SchemaVersion={major}.(minor).{subminor}]
-            //
-            // filename: {vendor}_{model}_{file_version}.
-            // extension: "zip" if compressed else "xml".
-            // address: Start address of the xml, must be expressed in hexademical
-            // without prefix.
-            // length: Byte length  of the xml, must be expressed in hexademical
-            // without prefix.
-            let extension = match xml_info.compressed {
-                cameleon::genapi::CompressionType::Uncompressed => "xml",
-                cameleon::genapi::CompressionType::Zip => "zip",
-            };
-            let schema_version = &xml_info.schema_version;
-
-            format!("local:{vendor}_{model}_{file_version}.{extension};{address:X};{size:X}?SchemaVersion={schema_major}.{schema_minor}.{schema_subminor}",
-                            vendor = port_info.vendor,
-                            model = port_info.model,
-                            file_version = xml_info.file_version,
-                            extension = extension,
-                            address = address,
-                            size = size,
-                            schema_major=schema_version.major,
-                            schema_minor = schema_version.minor,
-                            schema_subminor = schema_version.patch,
-                        )
-        }
-        XmlLocation::LocalFile(path) => {
-            // file:{filepath}[?SchemaVersion={major}.{minor}.{subminor}]
-            let schema_version = &xml_info.schema_version;
-            format!(
-                "file:{filepath}?SchemaVersion={schema_major}.{schema_minor}.{schema_subminor}",
-                filepath = path.to_string_lossy(),
-                schema_major = schema_version.major,
-                schema_minor = schema_version.minor,
-                schema_subminor = schema_version.patch,
-            )
-        }
-        XmlLocation::Url(url) => {
-            // {url}[?SchemaVersion={major}.{minor}.{subminor}]
-            let schema_version = &xml_info.schema_version;
-            format!(
-                "{url}?SchemaVersion={schema_major}.{schema_minor}.{schema_subminor}",
-                url = url.as_str(),
-                schema_major = schema_version.major,
-                schema_minor = schema_version.minor,
-                schema_subminor = schema_version.patch,
-            )
-        }
-    }
+    // local:{vendor}_{model}_{file_version}.{extension};{address};{length}[?SchemaVersion=x.x.x],
+    // file:{filepath}[?SchemaVersion=x.x.x] or {url}[?SchemaVersion=x.x.x].
+    xml_info.xml_url(port_info).to_string()
 }
 
 gentl_api! {
@@ -340,20 +297,10 @@ gentl_api! {
                 }
 
                 URL_INFO_CMD::URL_INFO_FILENAME => {
-                    use imp::port::XmlLocation;
-                    let file_name = match &info.location {
-                        XmlLocation::LocalFile(path) => {
-                            let err_msg = "local file name is invalid";
-                            let file_name =
-                                path.file_name().ok_or_else(|| GenTlError::Error(err_msg.into()))?;
-                            file_name
-                                .to_str()
-                                .ok_or_else(|| GenTlError::Error(err_msg.into()))?
-                        }
-
-                        _ => return Err(GenTlError::NotAvailable),
-                    };
-
+                    let url = info.xml_url(port.port_info()?);
+                    let file_name = url
+                        .file_name()
+                        .ok_or_else(|| GenTlError::Error("local file name is invalid".into()))?;
                     copy_info(file_name, pBuffer, piSize)
                 }
 
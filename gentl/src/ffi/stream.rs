@@ -0,0 +1,1160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{convert::TryInto, ops::Deref, sync::Mutex};
+
+use cameleon::payload::PayloadType;
+
+use imp::stream::{
+    AcquisitionStartFlag, AcquisitionStopFlag, BufferId, BufferMemory, DataStream, FlushOperation,
+    RawPtr,
+};
+
+use super::{
+    bool8_t, copy_info, device, event, imp, GenTlError, GenTlResult, ModuleHandle, GC_ERROR,
+    INFO_DATATYPE,
+};
+
+pub(super) type DS_HANDLE = *mut libc::c_void;
+pub(super) type BUFFER_HANDLE = *mut libc::c_void;
+
+/// Infinite number of frames to acquire.
+const GENTL_INFINITE: u64 = u64::MAX;
+
+#[derive(Clone, Copy)]
+pub(super) struct DataStreamModuleRef<'a> {
+    inner: &'a Mutex<dyn DataStream>,
+    parent_dev: device::DEV_HANDLE,
+}
+
+impl<'a> DataStreamModuleRef<'a> {
+    pub(super) fn new(inner: &'a Mutex<dyn DataStream>, parent_dev: device::DEV_HANDLE) -> Self {
+        Self { inner, parent_dev }
+    }
+}
+
+impl<'a> Deref for DataStreamModuleRef<'a> {
+    type Target = Mutex<dyn DataStream>;
+
+    fn deref(&self) -> &Self::Target {
+        self.inner
+    }
+}
+
+fn buffer_id(hBuffer: BUFFER_HANDLE) -> GenTlResult<BufferId> {
+    if hBuffer.is_null() {
+        Err(GenTlError::InvalidHandle)
+    } else {
+        Ok(BufferId::from_raw(hBuffer as usize))
+    }
+}
+
+pub(super) fn buffer_handle(id: BufferId) -> BUFFER_HANDLE {
+    id.as_raw() as BUFFER_HANDLE
+}
+
+newtype_enum! {
+    pub enum ACQ_START_FLAGS {
+        ACQ_START_FLAGS_DEFAULT = 0,
+        ACQ_START_FLAGS_CUSTOM_ID = 1000,
+    }
+}
+
+impl TryInto<AcquisitionStartFlag> for ACQ_START_FLAGS {
+    type Error = GenTlError;
+
+    fn try_into(self) -> GenTlResult<AcquisitionStartFlag> {
+        match self {
+            ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT => Ok(AcquisitionStartFlag::Default),
+            _ => Err(GenTlError::InvalidParameter),
+        }
+    }
+}
+
+newtype_enum! {
+    pub enum ACQ_STOP_FLAGS {
+        ACQ_STOP_FLAGS_DEFAULT = 0,
+        ACQ_STOP_FLAGS_KILL = 1,
+        ACQ_STOP_FLAGS_CUSTOM_ID = 1000,
+    }
+}
+
+impl TryInto<AcquisitionStopFlag> for ACQ_STOP_FLAGS {
+    type Error = GenTlError;
+
+    fn try_into(self) -> GenTlResult<AcquisitionStopFlag> {
+        match self {
+            ACQ_STOP_FLAGS::ACQ_STOP_FLAGS_DEFAULT => Ok(AcquisitionStopFlag::Default),
+            ACQ_STOP_FLAGS::ACQ_STOP_FLAGS_KILL => Ok(AcquisitionStopFlag::Kill),
+            _ => Err(GenTlError::InvalidParameter),
+        }
+    }
+}
+
+newtype_enum! {
+    pub enum ACQ_QUEUE_TYPE {
+        ACQ_QUEUE_INPUT_TO_OUTPUT = 0,
+        ACQ_QUEUE_OUTPUT_DISCARD = 1,
+        ACQ_QUEUE_ALL_TO_INPUT = 2,
+        ACQ_QUEUE_UNQUEUED_TO_INPUT = 3,
+        ACQ_QUEUE_ALL_DISCARD = 4,
+        ACQ_QUEUE_CUSTOM_ID = 1000,
+    }
+}
+
+impl TryInto<FlushOperation> for ACQ_QUEUE_TYPE {
+    type Error = GenTlError;
+
+    fn try_into(self) -> GenTlResult<FlushOperation> {
+        use FlushOperation::{
+            AllDiscard, AllToInput, InputToOutput, OutputDiscard, UnqueuedToInput,
+        };
+        match self {
+            ACQ_QUEUE_TYPE::ACQ_QUEUE_INPUT_TO_OUTPUT => Ok(InputToOutput),
+            ACQ_QUEUE_TYPE::ACQ_QUEUE_OUTPUT_DISCARD => Ok(OutputDiscard),
+            ACQ_QUEUE_TYPE::ACQ_QUEUE_ALL_TO_INPUT => Ok(AllToInput),
+            ACQ_QUEUE_TYPE::ACQ_QUEUE_UNQUEUED_TO_INPUT => Ok(UnqueuedToInput),
+            ACQ_QUEUE_TYPE::ACQ_QUEUE_ALL_DISCARD => Ok(AllDiscard),
+            _ => Err(GenTlError::InvalidParameter),
+        }
+    }
+}
+
+newtype_enum! {
+    pub enum STREAM_INFO_CMD {
+        STREAM_INFO_ID = 0,
+        STREAM_INFO_NUM_DELIVERED = 1,
+        STREAM_INFO_NUM_UNDERRUN = 2,
+        STREAM_INFO_NUM_ANNOUNCED = 3,
+        STREAM_INFO_NUM_QUEUED = 4,
+        STREAM_INFO_NUM_AWAIT_DELIVERY = 5,
+        STREAM_INFO_NUM_STARTED = 6,
+        STREAM_INFO_PAYLOAD_SIZE = 7,
+        STREAM_INFO_IS_GRABBING = 8,
+        STREAM_INFO_DEFINES_PAYLOADSIZE = 9,
+        STREAM_INFO_TLTYPE = 10,
+        STREAM_INFO_NUM_CHUNKS_MAX = 11,
+        STREAM_INFO_BUF_ANNOUNCE_MIN = 12,
+        STREAM_INFO_BUF_ALIGNMENT = 13,
+        STREAM_INFO_CUSTOM_ID = 1000,
+    }
+}
+
+newtype_enum! {
+    pub enum BUFFER_INFO_CMD {
+        BUFFER_INFO_BASE = 0,
+        BUFFER_INFO_SIZE = 1,
+        BUFFER_INFO_USER_PTR = 2,
+        BUFFER_INFO_TIMESTAMP = 3,
+        BUFFER_INFO_NEW_DATA = 4,
+        BUFFER_INFO_IS_QUEUED = 5,
+        BUFFER_INFO_IS_ACQUIRING = 6,
+        BUFFER_INFO_IS_INCOMPLETE = 7,
+        BUFFER_INFO_TLTYPE = 8,
+        BUFFER_INFO_SIZE_FILLED = 9,
+        BUFFER_INFO_WIDTH = 10,
+        BUFFER_INFO_HEIGHT = 11,
+        BUFFER_INFO_XOFFSET = 12,
+        BUFFER_INFO_YOFFSET = 13,
+        BUFFER_INFO_XPADDING = 14,
+        BUFFER_INFO_YPADDING = 15,
+        BUFFER_INFO_FRAMEID = 16,
+        BUFFER_INFO_IMAGEPRESENT = 17,
+        BUFFER_INFO_IMAGEOFFSET = 18,
+        BUFFER_INFO_PAYLOADTYPE = 19,
+        BUFFER_INFO_PIXELFORMAT = 20,
+        BUFFER_INFO_PIXELFORMAT_NAMESPACE = 21,
+        BUFFER_INFO_DELIVERED_IMAGEHEIGHT = 22,
+        BUFFER_INFO_DELIVERED_CHUNKPAYLOADSIZE = 23,
+        BUFFER_INFO_CHUNKLAYOUTID = 24,
+        BUFFER_INFO_FILENAME = 25,
+        BUFFER_INFO_PIXEL_ENDIANNESS = 26,
+        BUFFER_INFO_DATA_SIZE = 27,
+        BUFFER_INFO_TIMESTAMP_NS = 28,
+        BUFFER_INFO_DATA_LARGER_THAN_BUFFER = 29,
+        BUFFER_INFO_CONTAINS_CHUNKDATA = 30,
+        BUFFER_INFO_CUSTOM_ID = 1000,
+    }
+}
+
+newtype_enum! {
+    pub enum PAYLOADTYPE_INFO_IDS {
+        PAYLOAD_TYPE_UNKNOWN = 0,
+        PAYLOAD_TYPE_IMAGE = 1,
+        PAYLOAD_TYPE_RAW_DATA = 2,
+        PAYLOAD_TYPE_FILE = 3,
+        PAYLOAD_TYPE_CHUNK_DATA = 4,
+        PAYLOAD_TYPE_JPEG = 5,
+        PAYLOAD_TYPE_JPEG2000 = 6,
+        PAYLOAD_TYPE_H264 = 7,
+        PAYLOAD_TYPE_CHUNK_ONLY = 8,
+        PAYLOAD_TYPE_DEVICE_SPECIFIC = 9,
+        PAYLOAD_TYPE_MULTI_PART = 10,
+        PAYLOAD_TYPE_CUSTOM_ID = 1000,
+    }
+}
+
+newtype_enum! {
+    pub enum PIXELFORMAT_NAMESPACE_IDS {
+        PIXELFORMAT_NAMESPACE_UNKNOWN = 0,
+        PIXELFORMAT_NAMESPACE_GEV = 1,
+        PIXELFORMAT_NAMESPACE_IIDC = 2,
+        PIXELFORMAT_NAMESPACE_PFNC_16BIT = 3,
+        PIXELFORMAT_NAMESPACE_PFNC_32BIT = 4,
+        PIXELFORMAT_NAMESPACE_CUSTOM_ID = 1000,
+    }
+}
+
+newtype_enum! {
+    pub enum PIXELENDIANNESS_IDS {
+        PIXELENDIANNESS_UNKNOWN = 0,
+        PIXELENDIANNESS_LITTLE = 1,
+        PIXELENDIANNESS_BIG = 2,
+    }
+}
+
+newtype_enum! {
+    pub enum BUFFER_PART_INFO_CMD {
+        BUFFER_PART_INFO_BASE = 0,
+        BUFFER_PART_INFO_DATA_SIZE = 1,
+        BUFFER_PART_INFO_DATA_TYPE = 2,
+        BUFFER_PART_INFO_DATA_FORMAT = 3,
+        BUFFER_PART_INFO_DATA_FORMAT_NAMESPACE = 4,
+        BUFFER_PART_INFO_WIDTH = 5,
+        BUFFER_PART_INFO_HEIGHT = 6,
+        BUFFER_PART_INFO_XOFFSET = 7,
+        BUFFER_PART_INFO_YOFFSET = 8,
+        BUFFER_PART_INFO_XPADDING = 9,
+        BUFFER_PART_INFO_SOURCE_ID = 10,
+        BUFFER_PART_INFO_DELIVERED_IMAGEHEIGHT = 11,
+        BUFFER_PART_INFO_REGION_ID = 12,
+        BUFFER_PART_INFO_DATA_PURPOSE_ID = 13,
+        BUFFER_PART_INFO_CUSTOM_ID = 1000,
+    }
+}
+
+newtype_enum! {
+    pub enum PARTDATATYPE_IDS {
+        PART_DATATYPE_UNKNOWN = 0,
+        PART_DATATYPE_2D_IMAGE = 1,
+        PART_DATATYPE_2D_PLANE_BIPLANAR = 2,
+        PART_DATATYPE_2D_PLANE_TRIPLANAR = 3,
+        PART_DATATYPE_2D_PLANE_QUADPLANAR = 4,
+        PART_DATATYPE_3D_IMAGE = 5,
+        PART_DATATYPE_3D_PLANE_BIPLANAR = 6,
+        PART_DATATYPE_3D_PLANE_TRIPLANAR = 7,
+        PART_DATATYPE_3D_PLANE_QUADPLANAR = 8,
+        PART_DATATYPE_CONFIDENCE_MAP = 9,
+        PART_DATATYPE_CUSTOM_ID = 1000,
+    }
+}
+
+/// Location of a chunk in a buffer.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SINGLE_CHUNK_DATA {
+    ChunkID: u64,
+    ChunkOffset: libc::ptrdiff_t,
+    ChunkLength: libc::size_t,
+}
+
+fn payload_type_id(payload_type: PayloadType) -> PAYLOADTYPE_INFO_IDS {
+    match payload_type {
+        PayloadType::Image | PayloadType::ImageExtendedChunk => {
+            PAYLOADTYPE_INFO_IDS::PAYLOAD_TYPE_IMAGE
+        }
+        PayloadType::Chunk => PAYLOADTYPE_INFO_IDS::PAYLOAD_TYPE_CHUNK_DATA,
+    }
+}
+
+pub(super) fn ds_get_info(
+    ds: impl Deref<Target = Mutex<dyn DataStream>>,
+    iInfoCmd: STREAM_INFO_CMD,
+    piType: *mut INFO_DATATYPE,
+    pBuffer: *mut libc::c_void,
+    piSize: *mut libc::size_t,
+) -> GenTlResult<()> {
+    let ds_guard = ds.lock().unwrap();
+    let info_data_type = match iInfoCmd {
+        STREAM_INFO_CMD::STREAM_INFO_ID => copy_info(ds_guard.stream_id(), pBuffer, piSize),
+
+        STREAM_INFO_CMD::STREAM_INFO_TLTYPE => copy_info(ds_guard.tl_type(), pBuffer, piSize),
+
+        STREAM_INFO_CMD::STREAM_INFO_DEFINES_PAYLOADSIZE => {
+            // The payload size is defined by the remote device.
+            copy_info(bool8_t::false_(), pBuffer, piSize)
+        }
+
+        STREAM_INFO_CMD::STREAM_INFO_NUM_CHUNKS_MAX => Err(GenTlError::NotAvailable),
+
+        _ => {
+            let info = ds_guard.stream_info()?;
+            match iInfoCmd {
+                STREAM_INFO_CMD::STREAM_INFO_NUM_DELIVERED => {
+                    copy_info(info.num_delivered, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_NUM_UNDERRUN => {
+                    copy_info(info.num_underrun, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_NUM_ANNOUNCED => {
+                    copy_info(info.num_announced, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_NUM_QUEUED => {
+                    copy_info(info.num_queued, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_NUM_AWAIT_DELIVERY => {
+                    copy_info(info.num_await_delivery, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_NUM_STARTED => {
+                    copy_info(info.num_started, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_PAYLOAD_SIZE => {
+                    copy_info(info.payload_size, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_IS_GRABBING => {
+                    copy_info(bool8_t::from(info.is_grabbing), pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_BUF_ANNOUNCE_MIN => {
+                    copy_info(info.buf_announce_min, pBuffer, piSize)
+                }
+                STREAM_INFO_CMD::STREAM_INFO_BUF_ALIGNMENT => {
+                    copy_info(info.buf_alignment, pBuffer, piSize)
+                }
+                _ => Err(GenTlError::InvalidParameter),
+            }
+        }
+    }?;
+
+    unsafe {
+        *piType = info_data_type;
+    }
+
+    Ok(())
+}
+
+pub(super) fn ds_get_buffer_info(
+    ds: impl Deref<Target = Mutex<dyn DataStream>>,
+    id: BufferId,
+    iInfoCmd: BUFFER_INFO_CMD,
+    piType: *mut INFO_DATATYPE,
+    pBuffer: *mut libc::c_void,
+    piSize: *mut libc::size_t,
+) -> GenTlResult<()> {
+    let ds_guard = ds.lock().unwrap();
+    let info = ds_guard.buffer_info(id)?;
+    let frame = info.frame.as_ref();
+    let image = frame.and_then(|frame| frame.image.as_ref());
+
+    let info_data_type = match iInfoCmd {
+        BUFFER_INFO_CMD::BUFFER_INFO_BASE => copy_info(info.base.0, pBuffer, piSize),
+
+        BUFFER_INFO_CMD::BUFFER_INFO_SIZE => copy_info(info.size, pBuffer, piSize),
+
+        BUFFER_INFO_CMD::BUFFER_INFO_USER_PTR => copy_info(info.user_ptr.0, pBuffer, piSize),
+
+        BUFFER_INFO_CMD::BUFFER_INFO_NEW_DATA => {
+            copy_info(bool8_t::from(info.new_data), pBuffer, piSize)
+        }
+
+        BUFFER_INFO_CMD::BUFFER_INFO_IS_QUEUED => {
+            copy_info(bool8_t::from(info.is_queued), pBuffer, piSize)
+        }
+
+        BUFFER_INFO_CMD::BUFFER_INFO_IS_ACQUIRING => {
+            // Payloads are copied into a buffer at once, so no buffer is observed while being
+            // filled.
+            copy_info(bool8_t::false_(), pBuffer, piSize)
+        }
+
+        BUFFER_INFO_CMD::BUFFER_INFO_TLTYPE => copy_info(ds_guard.tl_type(), pBuffer, piSize),
+
+        BUFFER_INFO_CMD::BUFFER_INFO_XPADDING | BUFFER_INFO_CMD::BUFFER_INFO_YPADDING => {
+            copy_info(0_usize, pBuffer, piSize)
+        }
+
+        BUFFER_INFO_CMD::BUFFER_INFO_PIXEL_ENDIANNESS => copy_info(
+            PIXELENDIANNESS_IDS::PIXELENDIANNESS_LITTLE.0,
+            pBuffer,
+            piSize,
+        ),
+
+        BUFFER_INFO_CMD::BUFFER_INFO_TIMESTAMP
+        | BUFFER_INFO_CMD::BUFFER_INFO_IS_INCOMPLETE
+        | BUFFER_INFO_CMD::BUFFER_INFO_SIZE_FILLED
+        | BUFFER_INFO_CMD::BUFFER_INFO_FRAMEID
+        | BUFFER_INFO_CMD::BUFFER_INFO_IMAGEPRESENT
+        | BUFFER_INFO_CMD::BUFFER_INFO_PAYLOADTYPE
+        | BUFFER_INFO_CMD::BUFFER_INFO_DATA_SIZE
+        | BUFFER_INFO_CMD::BUFFER_INFO_TIMESTAMP_NS
+        | BUFFER_INFO_CMD::BUFFER_INFO_DATA_LARGER_THAN_BUFFER
+        | BUFFER_INFO_CMD::BUFFER_INFO_CONTAINS_CHUNKDATA
+        | BUFFER_INFO_CMD::BUFFER_INFO_DELIVERED_CHUNKPAYLOADSIZE
+        | BUFFER_INFO_CMD::BUFFER_INFO_CHUNKLAYOUTID => {
+            let frame = frame.ok_or(GenTlError::NotAvailable)?;
+            match iInfoCmd {
+                BUFFER_INFO_CMD::BUFFER_INFO_TIMESTAMP => {
+                    copy_info(frame.timestamp, pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_IS_INCOMPLETE => {
+                    copy_info(bool8_t::from(frame.is_incomplete()), pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_SIZE_FILLED
+                | BUFFER_INFO_CMD::BUFFER_INFO_DATA_SIZE => {
+                    copy_info(frame.size_filled, pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_FRAMEID => copy_info(frame.frame_id, pBuffer, piSize),
+                BUFFER_INFO_CMD::BUFFER_INFO_IMAGEPRESENT => {
+                    copy_info(bool8_t::from(frame.image.is_some()), pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_PAYLOADTYPE => copy_info(
+                    payload_type_id(frame.payload_type).0 as usize,
+                    pBuffer,
+                    piSize,
+                ),
+                BUFFER_INFO_CMD::BUFFER_INFO_TIMESTAMP_NS => copy_info(
+                    frame.timestamp_ns.ok_or(GenTlError::NotAvailable)?,
+                    pBuffer,
+                    piSize,
+                ),
+                BUFFER_INFO_CMD::BUFFER_INFO_DATA_LARGER_THAN_BUFFER => copy_info(
+                    bool8_t::from(frame.data_larger_than_buffer),
+                    pBuffer,
+                    piSize,
+                ),
+                BUFFER_INFO_CMD::BUFFER_INFO_CONTAINS_CHUNKDATA => {
+                    copy_info(bool8_t::from(frame.contains_chunk_data()), pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_DELIVERED_CHUNKPAYLOADSIZE => {
+                    if !frame.contains_chunk_data() {
+                        return Err(GenTlError::NotAvailable);
+                    }
+                    copy_info(frame.size_filled, pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_CHUNKLAYOUTID => copy_info(
+                    u64::from(frame.chunk_layout_id.ok_or(GenTlError::NotAvailable)?),
+                    pBuffer,
+                    piSize,
+                ),
+                _ => Err(GenTlError::InvalidParameter),
+            }
+        }
+
+        BUFFER_INFO_CMD::BUFFER_INFO_WIDTH
+        | BUFFER_INFO_CMD::BUFFER_INFO_HEIGHT
+        | BUFFER_INFO_CMD::BUFFER_INFO_XOFFSET
+        | BUFFER_INFO_CMD::BUFFER_INFO_YOFFSET
+        | BUFFER_INFO_CMD::BUFFER_INFO_IMAGEOFFSET
+        | BUFFER_INFO_CMD::BUFFER_INFO_PIXELFORMAT
+        | BUFFER_INFO_CMD::BUFFER_INFO_PIXELFORMAT_NAMESPACE
+        | BUFFER_INFO_CMD::BUFFER_INFO_DELIVERED_IMAGEHEIGHT => {
+            let image = image.ok_or(GenTlError::NotAvailable)?;
+            match iInfoCmd {
+                BUFFER_INFO_CMD::BUFFER_INFO_WIDTH => copy_info(image.width, pBuffer, piSize),
+                BUFFER_INFO_CMD::BUFFER_INFO_HEIGHT
+                | BUFFER_INFO_CMD::BUFFER_INFO_DELIVERED_IMAGEHEIGHT => {
+                    copy_info(image.height, pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_XOFFSET => copy_info(image.x_offset, pBuffer, piSize),
+                BUFFER_INFO_CMD::BUFFER_INFO_YOFFSET => copy_info(image.y_offset, pBuffer, piSize),
+                // An image always comes first in a U3V payload.
+                BUFFER_INFO_CMD::BUFFER_INFO_IMAGEOFFSET => copy_info(0_usize, pBuffer, piSize),
+                BUFFER_INFO_CMD::BUFFER_INFO_PIXELFORMAT => {
+                    copy_info(u64::from(u32::from(image.pixel_format)), pBuffer, piSize)
+                }
+                BUFFER_INFO_CMD::BUFFER_INFO_PIXELFORMAT_NAMESPACE => copy_info(
+                    PIXELFORMAT_NAMESPACE_IDS::PIXELFORMAT_NAMESPACE_PFNC_32BIT.0 as u64,
+                    pBuffer,
+                    piSize,
+                ),
+                _ => Err(GenTlError::InvalidParameter),
+            }
+        }
+
+        _ => Err(GenTlError::InvalidParameter),
+    }?;
+
+    unsafe {
+        *piType = info_data_type;
+    }
+
+    Ok(())
+}
+
+gentl_api! {
+    pub fn DSAnnounceBuffer(
+        hDataStream: DS_HANDLE,
+        pBuffer: *mut libc::c_void,
+        iSize: libc::size_t,
+        pPrivate: *mut libc::c_void,
+        phBuffer: *mut BUFFER_HANDLE,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        if pBuffer.is_null() || phBuffer.is_null() {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        let memory = BufferMemory::User {
+            ptr: RawPtr(pBuffer),
+            len: iSize,
+        };
+        let id = ds.lock().unwrap().announce_buffer(memory, RawPtr(pPrivate))?;
+        unsafe {
+            *phBuffer = buffer_handle(id);
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSAllocAndAnnounceBuffer(
+        hDataStream: DS_HANDLE,
+        iSize: libc::size_t,
+        pPrivate: *mut libc::c_void,
+        phBuffer: *mut BUFFER_HANDLE,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        if phBuffer.is_null() {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        let memory = BufferMemory::Allocated(vec![0; iSize].into_boxed_slice());
+        let id = ds.lock().unwrap().announce_buffer(memory, RawPtr(pPrivate))?;
+        unsafe {
+            *phBuffer = buffer_handle(id);
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSRevokeBuffer(
+        hDataStream: DS_HANDLE,
+        hBuffer: BUFFER_HANDLE,
+        ppBuffer: *mut *mut libc::c_void,
+        ppPrivate: *mut *mut libc::c_void,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let (memory, user_ptr) = ds.lock().unwrap().revoke_buffer(buffer_id(hBuffer)?)?;
+        let base = match memory {
+            BufferMemory::User { ptr, .. } => ptr,
+            // Memory allocated by the producer is freed here.
+            BufferMemory::Allocated(..) => RawPtr::null(),
+        };
+
+        unsafe {
+            if !ppBuffer.is_null() {
+                *ppBuffer = base.0;
+            }
+            if !ppPrivate.is_null() {
+                *ppPrivate = user_ptr.0;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSQueueBuffer(hDataStream: DS_HANDLE, hBuffer: BUFFER_HANDLE) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let mut ds_guard = ds.lock().unwrap();
+        ds_guard.queue_buffer(buffer_id(hBuffer)?)
+    }
+}
+
+gentl_api! {
+    pub fn DSFlushQueue(hDataStream: DS_HANDLE, iOperation: ACQ_QUEUE_TYPE) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let mut ds_guard = ds.lock().unwrap();
+        ds_guard.flush_queue(iOperation.try_into()?)
+    }
+}
+
+gentl_api! {
+    pub fn DSGetBufferID(
+        hDataStream: DS_HANDLE,
+        iIndex: u32,
+        phBuffer: *mut BUFFER_HANDLE,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let id = ds.lock().unwrap().buffer_id(iIndex as usize)?;
+        unsafe {
+            *phBuffer = buffer_handle(id);
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSStartAcquisition(
+        hDataStream: DS_HANDLE,
+        iStartFlags: ACQ_START_FLAGS,
+        iNumToAcquire: u64,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let num_to_acquire = if iNumToAcquire == GENTL_INFINITE {
+            None
+        } else {
+            Some(iNumToAcquire)
+        };
+        let mut ds_guard = ds.lock().unwrap();
+        ds_guard.start_acquisition(iStartFlags.try_into()?, num_to_acquire)
+    }
+}
+
+gentl_api! {
+    pub fn DSStopAcquisition(hDataStream: DS_HANDLE, iStopFlags: ACQ_STOP_FLAGS) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let mut ds_guard = ds.lock().unwrap();
+        ds_guard.stop_acquisition(iStopFlags.try_into()?)
+    }
+}
+
+gentl_api! {
+    pub fn DSGetInfo(
+        hDataStream: DS_HANDLE,
+        iInfoCmd: STREAM_INFO_CMD,
+        piType: *mut INFO_DATATYPE,
+        pBuffer: *mut libc::c_void,
+        piSize: *mut libc::size_t,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        ds_get_info(ds, iInfoCmd, piType, pBuffer, piSize)
+    }
+}
+
+gentl_api! {
+    pub fn DSGetBufferInfo(
+        hDataStream: DS_HANDLE,
+        hBuffer: BUFFER_HANDLE,
+        iInfoCmd: BUFFER_INFO_CMD,
+        piType: *mut INFO_DATATYPE,
+        pBuffer: *mut libc::c_void,
+        piSize: *mut libc::size_t,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        ds_get_buffer_info(ds, buffer_id(hBuffer)?, iInfoCmd, piType, pBuffer, piSize)
+    }
+}
+
+pub(super) fn ds_get_buffer_part_info(
+    ds: impl Deref<Target = Mutex<dyn DataStream>>,
+    id: BufferId,
+    part_index: usize,
+    iInfoCmd: BUFFER_PART_INFO_CMD,
+    piType: *mut INFO_DATATYPE,
+    pBuffer: *mut libc::c_void,
+    piSize: *mut libc::size_t,
+) -> GenTlResult<()> {
+    let info = ds.lock().unwrap().buffer_info(id)?;
+    let frame = info.frame.as_ref().ok_or(GenTlError::NotAvailable)?;
+    let parts = frame.parts();
+    let part = parts.get(part_index).ok_or(GenTlError::InvalidIndex)?;
+    let image = part.image;
+
+    let info_data_type = match iInfoCmd {
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_BASE => {
+            let base = unsafe { info.base.0.cast::<u8>().add(part.offset) };
+            copy_info(base.cast::<libc::c_void>(), pBuffer, piSize)
+        }
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_DATA_SIZE => copy_info(part.size, pBuffer, piSize),
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_DATA_TYPE => copy_info(
+            PARTDATATYPE_IDS::PART_DATATYPE_2D_IMAGE.0 as usize,
+            pBuffer,
+            piSize,
+        ),
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_DATA_FORMAT => {
+            copy_info(u64::from(u32::from(image.pixel_format)), pBuffer, piSize)
+        }
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_DATA_FORMAT_NAMESPACE => copy_info(
+            PIXELFORMAT_NAMESPACE_IDS::PIXELFORMAT_NAMESPACE_PFNC_32BIT.0 as u64,
+            pBuffer,
+            piSize,
+        ),
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_WIDTH => copy_info(image.width, pBuffer, piSize),
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_HEIGHT
+        | BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_DELIVERED_IMAGEHEIGHT => {
+            copy_info(image.height, pBuffer, piSize)
+        }
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_XOFFSET => {
+            copy_info(image.x_offset, pBuffer, piSize)
+        }
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_YOFFSET => {
+            copy_info(image.y_offset, pBuffer, piSize)
+        }
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_XPADDING => copy_info(0_usize, pBuffer, piSize),
+        // U3V devices have a single source and a single region.
+        BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_SOURCE_ID
+        | BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_REGION_ID
+        | BUFFER_PART_INFO_CMD::BUFFER_PART_INFO_DATA_PURPOSE_ID => {
+            copy_info(0_u64, pBuffer, piSize)
+        }
+        _ => Err(GenTlError::InvalidParameter),
+    }?;
+
+    unsafe {
+        *piType = info_data_type;
+    }
+
+    Ok(())
+}
+
+gentl_api! {
+    pub fn DSGetBufferChunkData(
+        hDataStream: DS_HANDLE,
+        hBuffer: BUFFER_HANDLE,
+        pChunkData: *mut SINGLE_CHUNK_DATA,
+        piNumChunks: *mut libc::size_t,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        if piNumChunks.is_null() {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        let info = ds.lock().unwrap().buffer_info(buffer_id(hBuffer)?)?;
+        let frame = info.frame.as_ref().ok_or(GenTlError::NoData)?;
+        if !frame.contains_chunk_data() {
+            return Err(GenTlError::NoData);
+        }
+        let chunks = frame.chunks.as_ref().ok_or(GenTlError::ParsingChunkData)?;
+
+        if !pChunkData.is_null() {
+            if unsafe { *piNumChunks } < chunks.len() {
+                return Err(GenTlError::BufferTooSmall);
+            }
+            for (i, chunk) in chunks.iter().enumerate() {
+                let chunk_data = SINGLE_CHUNK_DATA {
+                    ChunkID: u64::from(chunk.id),
+                    ChunkOffset: chunk.offset as libc::ptrdiff_t,
+                    ChunkLength: chunk.len,
+                };
+                unsafe {
+                    pChunkData.add(i).write(chunk_data);
+                }
+            }
+        }
+
+        unsafe {
+            *piNumChunks = chunks.len();
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSGetNumBufferParts(
+        hDataStream: DS_HANDLE,
+        hBuffer: BUFFER_HANDLE,
+        piNumParts: *mut u32,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        let info = ds.lock().unwrap().buffer_info(buffer_id(hBuffer)?)?;
+        let num_parts = info.frame.as_ref().map_or(0, |frame| frame.parts().len());
+        unsafe {
+            *piNumParts = num_parts as u32;
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSGetBufferPartInfo(
+        hDataStream: DS_HANDLE,
+        hBuffer: BUFFER_HANDLE,
+        iPartIndex: u32,
+        iInfoCmd: BUFFER_PART_INFO_CMD,
+        piType: *mut INFO_DATATYPE,
+        pBuffer: *mut libc::c_void,
+        piSize: *mut libc::size_t,
+    ) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        ds_get_buffer_part_info(
+            ds,
+            buffer_id(hBuffer)?,
+            iPartIndex as usize,
+            iInfoCmd,
+            piType,
+            pBuffer,
+            piSize,
+        )
+    }
+}
+
+gentl_api! {
+    pub fn DSGetParentDev(hDataStream: DS_HANDLE, phDevice: *mut device::DEV_HANDLE) -> GenTlResult<()> {
+        let handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        unsafe {
+            *phDevice = ds.parent_dev;
+        }
+
+        Ok(())
+    }
+}
+
+gentl_api! {
+    pub fn DSClose(hDataStream: DS_HANDLE) -> GenTlResult<()> {
+        let mut handle = unsafe { ModuleHandle::from_raw_manually_drop(hDataStream)? };
+        let ds = handle.data_stream()?;
+
+        // Close the data stream module.
+        ds.lock().unwrap().close()?;
+
+        // Release event handles registered to the data stream.
+        event::release_events(hDataStream);
+
+        // Drop the data stream handle.
+        unsafe {
+            ModuleHandle::drop_raw(&mut handle)
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use std::{mem, ptr};
+
+    use super::{
+        super::{
+            event::{EventGetData, GCRegisterEvent, GCUnregisterEvent, EVENT_HANDLE, EVENT_TYPE},
+            tests::{
+                EmulatedDevice, Lib, NewBufferData, GC_ERR_BUSY, GC_ERR_INVALID_HANDLE,
+                GC_ERR_INVALID_INDEX, GC_ERR_INVALID_PARAMETER, GC_ERR_NOT_INITIALIZED,
+                GC_ERR_RESOURCE_IN_USE, GC_ERR_SUCCESS,
+            },
+        },
+        *,
+    };
+
+    /// Queries a fixed size info of the data stream.
+    fn stream_info<T: Default>(h_stream: DS_HANDLE, cmd: STREAM_INFO_CMD) -> T {
+        let mut value = T::default();
+        let mut info_type = INFO_DATATYPE::INFO_DATATYPE_UNKNOWN;
+        let mut size = mem::size_of::<T>();
+        assert_eq!(
+            DSGetInfo(
+                h_stream,
+                cmd,
+                &mut info_type,
+                (&mut value as *mut T).cast(),
+                &mut size
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(size, mem::size_of::<T>());
+        value
+    }
+
+    /// Returns the number of buffers in the input pool and the output queue.
+    fn queue_lens(h_stream: DS_HANDLE) -> (usize, usize) {
+        (
+            stream_info(h_stream, STREAM_INFO_CMD::STREAM_INFO_NUM_QUEUED),
+            stream_info(h_stream, STREAM_INFO_CMD::STREAM_INFO_NUM_AWAIT_DELIVERY),
+        )
+    }
+
+    fn is_grabbing(h_stream: DS_HANDLE) -> bool {
+        stream_info::<u8>(h_stream, STREAM_INFO_CMD::STREAM_INFO_IS_GRABBING) != 0
+    }
+
+    fn alloc_buffers(h_stream: DS_HANDLE, num: usize) -> Vec<BUFFER_HANDLE> {
+        (0..num)
+            .map(|_| {
+                let mut h_buffer = ptr::null_mut();
+                assert_eq!(
+                    DSAllocAndAnnounceBuffer(h_stream, 64, ptr::null_mut(), &mut h_buffer).0,
+                    GC_ERR_SUCCESS
+                );
+                h_buffer
+            })
+            .collect()
+    }
+
+    fn next_buffer(h_event: EVENT_HANDLE) -> BUFFER_HANDLE {
+        let mut data = NewBufferData {
+            buffer: ptr::null_mut(),
+            user_pointer: ptr::null_mut(),
+        };
+        let mut size = mem::size_of::<NewBufferData>();
+        assert_eq!(
+            EventGetData(
+                h_event,
+                (&mut data as *mut NewBufferData).cast(),
+                &mut size,
+                0
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        data.buffer
+    }
+
+    #[test]
+    fn test_announce_buffer() {
+        let _lib = Lib::init();
+        let dev = EmulatedDevice::open("FFIDS000");
+        let h_stream = dev.open_stream();
+
+        let mut memory = vec![0_u8; 64];
+        let mut private = 0_u32;
+        let private_ptr: *mut libc::c_void = (&mut private as *mut u32).cast();
+        let mut h_user = ptr::null_mut();
+        assert_eq!(
+            DSAnnounceBuffer(
+                h_stream,
+                memory.as_mut_ptr().cast(),
+                memory.len(),
+                private_ptr,
+                &mut h_user
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        let h_alloc = alloc_buffers(h_stream, 1)[0];
+
+        // Empty or null buffers are rejected.
+        let mut h_invalid = ptr::null_mut();
+        assert_eq!(
+            DSAnnounceBuffer(
+                h_stream,
+                ptr::null_mut(),
+                64,
+                ptr::null_mut(),
+                &mut h_invalid
+            )
+            .0,
+            GC_ERR_INVALID_PARAMETER
+        );
+        assert_eq!(
+            DSAllocAndAnnounceBuffer(h_stream, 0, ptr::null_mut(), &mut h_invalid).0,
+            GC_ERR_INVALID_PARAMETER
+        );
+
+        let num_announced: usize =
+            stream_info(h_stream, STREAM_INFO_CMD::STREAM_INFO_NUM_ANNOUNCED);
+        assert_eq!(num_announced, 2);
+        for (i, &expected) in [h_user, h_alloc].iter().enumerate() {
+            let mut h_buffer = ptr::null_mut();
+            assert_eq!(
+                DSGetBufferID(h_stream, i as u32, &mut h_buffer).0,
+                GC_ERR_SUCCESS
+            );
+            assert_eq!(h_buffer, expected);
+        }
+        let mut h_buffer = ptr::null_mut();
+        assert_eq!(
+            DSGetBufferID(h_stream, 2, &mut h_buffer).0,
+            GC_ERR_INVALID_INDEX
+        );
+
+        // The memory of the user buffer is used as is.
+        let mut base: *mut libc::c_void = ptr::null_mut();
+        let mut info_type = INFO_DATATYPE::INFO_DATATYPE_UNKNOWN;
+        let mut size = mem::size_of::<*mut libc::c_void>();
+        assert_eq!(
+            DSGetBufferInfo(
+                h_stream,
+                h_user,
+                BUFFER_INFO_CMD::BUFFER_INFO_BASE,
+                &mut info_type,
+                (&mut base as *mut *mut libc::c_void).cast(),
+                &mut size
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(base, memory.as_mut_ptr().cast());
+
+        // Revoking returns the user memory and the private data.
+        let mut revoked_base = ptr::null_mut();
+        let mut revoked_private = ptr::null_mut();
+        assert_eq!(
+            DSRevokeBuffer(h_stream, h_user, &mut revoked_base, &mut revoked_private).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(revoked_base, memory.as_mut_ptr().cast());
+        assert_eq!(revoked_private, private_ptr);
+        assert_eq!(
+            DSRevokeBuffer(h_stream, h_user, ptr::null_mut(), ptr::null_mut()).0,
+            GC_ERR_INVALID_HANDLE
+        );
+        assert_eq!(
+            DSRevokeBuffer(h_stream, h_alloc, ptr::null_mut(), ptr::null_mut()).0,
+            GC_ERR_SUCCESS
+        );
+
+        assert_eq!(DSClose(h_stream).0, GC_ERR_SUCCESS);
+        dev.close();
+    }
+
+    #[test]
+    fn test_queue_and_flush() {
+        let _lib = Lib::init();
+        let dev = EmulatedDevice::open("FFIDS001");
+        let h_stream = dev.open_stream();
+        let h_buffers = alloc_buffers(h_stream, 3);
+        let mut h_event = ptr::null_mut();
+        assert_eq!(
+            GCRegisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER, &mut h_event).0,
+            GC_ERR_SUCCESS
+        );
+
+        for &h_buffer in &h_buffers {
+            assert_eq!(DSQueueBuffer(h_stream, h_buffer).0, GC_ERR_SUCCESS);
+        }
+        assert_eq!(queue_lens(h_stream), (3, 0));
+        assert_eq!(
+            DSQueueBuffer(h_stream, h_buffers[0]).0,
+            GC_ERR_RESOURCE_IN_USE
+        );
+        assert_eq!(
+            DSQueueBuffer(h_stream, ptr::null_mut()).0,
+            GC_ERR_INVALID_HANDLE
+        );
+
+        // Buffers moved to the output queue are delivered in the queued order.
+        assert_eq!(
+            DSFlushQueue(h_stream, ACQ_QUEUE_TYPE::ACQ_QUEUE_INPUT_TO_OUTPUT).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(queue_lens(h_stream), (0, 3));
+        // Buffers in the output queue can be neither queued nor revoked.
+        assert_eq!(DSQueueBuffer(h_stream, h_buffers[0]).0, GC_ERR_BUSY);
+        assert_eq!(
+            DSRevokeBuffer(h_stream, h_buffers[0], ptr::null_mut(), ptr::null_mut()).0,
+            GC_ERR_BUSY
+        );
+
+        assert_eq!(next_buffer(h_event), h_buffers[0]);
+        assert_eq!(queue_lens(h_stream), (0, 2));
+        // The delivered buffer is handed back to the input pool.
+        assert_eq!(DSQueueBuffer(h_stream, h_buffers[0]).0, GC_ERR_SUCCESS);
+        assert_eq!(queue_lens(h_stream), (1, 2));
+
+        assert_eq!(
+            DSFlushQueue(h_stream, ACQ_QUEUE_TYPE::ACQ_QUEUE_OUTPUT_DISCARD).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(queue_lens(h_stream), (1, 0));
+        assert_eq!(
+            DSFlushQueue(h_stream, ACQ_QUEUE_TYPE::ACQ_QUEUE_UNQUEUED_TO_INPUT).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(queue_lens(h_stream), (3, 0));
+        assert_eq!(
+            DSFlushQueue(h_stream, ACQ_QUEUE_TYPE::ACQ_QUEUE_ALL_DISCARD).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(queue_lens(h_stream), (0, 0));
+        assert_eq!(
+            DSFlushQueue(h_stream, ACQ_QUEUE_TYPE::ACQ_QUEUE_ALL_TO_INPUT).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(queue_lens(h_stream), (3, 0));
+        assert_eq!(
+            DSFlushQueue(h_stream, ACQ_QUEUE_TYPE(1000)).0,
+            GC_ERR_INVALID_PARAMETER
+        );
+
+        assert_eq!(
+            GCUnregisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(DSClose(h_stream).0, GC_ERR_SUCCESS);
+        dev.close();
+    }
+
+    #[test]
+    fn test_start_stop_acquisition() {
+        let _lib = Lib::init();
+        let dev = EmulatedDevice::open("FFIDS002");
+        let h_stream = dev.open_stream();
+        alloc_buffers(h_stream, 2);
+        assert!(!is_grabbing(h_stream));
+
+        assert_eq!(
+            DSStopAcquisition(h_stream, ACQ_STOP_FLAGS::ACQ_STOP_FLAGS_DEFAULT).0,
+            GC_ERR_NOT_INITIALIZED
+        );
+        assert_eq!(
+            DSStartAcquisition(h_stream, ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT, 0).0,
+            GC_ERR_INVALID_PARAMETER
+        );
+
+        for &stop_flag in &[
+            ACQ_STOP_FLAGS::ACQ_STOP_FLAGS_DEFAULT,
+            ACQ_STOP_FLAGS::ACQ_STOP_FLAGS_KILL,
+        ] {
+            assert_eq!(
+                DSStartAcquisition(
+                    h_stream,
+                    ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT,
+                    GENTL_INFINITE
+                )
+                .0,
+                GC_ERR_SUCCESS
+            );
+            assert!(is_grabbing(h_stream));
+            assert_eq!(
+                DSStartAcquisition(
+                    h_stream,
+                    ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT,
+                    GENTL_INFINITE
+                )
+                .0,
+                GC_ERR_RESOURCE_IN_USE
+            );
+
+            assert_eq!(DSStopAcquisition(h_stream, stop_flag).0, GC_ERR_SUCCESS);
+            assert!(!is_grabbing(h_stream));
+            assert_eq!(
+                DSStopAcquisition(h_stream, stop_flag).0,
+                GC_ERR_NOT_INITIALIZED
+            );
+        }
+
+        // Closing the data stream stops the acquisition.
+        assert_eq!(
+            DSStartAcquisition(h_stream, ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT, 1).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(DSClose(h_stream).0, GC_ERR_SUCCESS);
+        dev.close();
+    }
+}
@@ -41,7 +41,7 @@ gentl_api!(
         system_handle.lock().unwrap().close()?;
         // Drop its handle.
         unsafe {
-            std::mem::ManuallyDrop::drop(&mut handle);
+            ModuleHandle::drop_raw(&mut handle);
         }
         Ok(())
     }
@@ -56,58 +56,68 @@ gentl_api!(
         piSize: *mut libc::size_t,
     ) -> GenTlResult<()> {
         let handle = unsafe { ModuleHandle::from_raw_manually_drop(hSystem)? };
-        let system_handle = handle.system()?;
-        let handle_guard = system_handle.lock().unwrap();
-        let system_info = handle_guard.system_info();
+        handle.system()?;
 
-        let info_data_type = match iInfoCmd {
-            TL_INFO_CMD::TL_INFO_ID => copy_info(system_info.id.as_str(), pBuffer, piSize),
+        copy_system_info(iInfoCmd, piType, pBuffer, piSize)
+    }
+);
 
-            TL_INFO_CMD::TL_INFO_VENDOR => copy_info(system_info.vendor.as_str(), pBuffer, piSize),
+/// Copies the system module information. `GCGetInfo` shares this with `TLGetInfo` since it must
+/// work without opening the system module.
+pub(super) fn copy_system_info(
+    iInfoCmd: TL_INFO_CMD,
+    piType: *mut INFO_DATATYPE,
+    pBuffer: *mut libc::c_void,
+    piSize: *mut libc::size_t,
+) -> GenTlResult<()> {
+    let handle_guard = SYSTEM_MODULE.lock().unwrap();
+    let system_info = handle_guard.system_info();
 
-            TL_INFO_CMD::TL_INFO_MODEL => copy_info(system_info.model.as_str(), pBuffer, piSize),
+    let info_data_type = match iInfoCmd {
+        TL_INFO_CMD::TL_INFO_ID => copy_info(system_info.id.as_str(), pBuffer, piSize),
 
-            TL_INFO_CMD::TL_INFO_VERSION => {
-                copy_info(system_info.version.as_str(), pBuffer, piSize)
-            }
+        TL_INFO_CMD::TL_INFO_VENDOR => copy_info(system_info.vendor.as_str(), pBuffer, piSize),
 
-            TL_INFO_CMD::TL_INFO_TLTYPE => copy_info(system_info.tl_type.as_str(), pBuffer, piSize),
+        TL_INFO_CMD::TL_INFO_MODEL => copy_info(system_info.model.as_str(), pBuffer, piSize),
 
-            TL_INFO_CMD::TL_INFO_NAME => copy_info(
-                &*system_info.full_path.file_name().unwrap().to_string_lossy(),
-                pBuffer,
-                piSize,
-            ),
+        TL_INFO_CMD::TL_INFO_VERSION => copy_info(system_info.version.as_str(), pBuffer, piSize),
 
-            TL_INFO_CMD::TL_INFO_PATHNAME => {
-                copy_info(&*system_info.full_path.to_string_lossy(), pBuffer, piSize)
-            }
+        TL_INFO_CMD::TL_INFO_TLTYPE => copy_info(system_info.tl_type.as_str(), pBuffer, piSize),
 
-            TL_INFO_CMD::TL_INFO_DISPLAYNAME => {
-                copy_info(system_info.display_name.as_str(), pBuffer, piSize)
-            }
+        TL_INFO_CMD::TL_INFO_NAME => copy_info(
+            &*system_info.full_path.file_name().unwrap().to_string_lossy(),
+            pBuffer,
+            piSize,
+        ),
 
-            TL_INFO_CMD::TL_INFO_CHAR_ENCODING => {
-                copy_info(system_info.encoding.as_raw(), pBuffer, piSize)
-            }
+        TL_INFO_CMD::TL_INFO_PATHNAME => {
+            copy_info(&*system_info.full_path.to_string_lossy(), pBuffer, piSize)
+        }
 
-            TL_INFO_CMD::TL_INFO_GENTL_VER_MAJOR => {
-                copy_info(system_info.gentl_version_major, pBuffer, piSize)
-            }
+        TL_INFO_CMD::TL_INFO_DISPLAYNAME => {
+            copy_info(system_info.display_name.as_str(), pBuffer, piSize)
+        }
 
-            TL_INFO_CMD::TL_INFO_GENTL_VER_MINOR => {
-                copy_info(system_info.gentl_version_minor, pBuffer, piSize)
-            }
-            _ => return Err(GenTlError::InvalidParameter),
-        }?;
+        TL_INFO_CMD::TL_INFO_CHAR_ENCODING => {
+            copy_info(system_info.encoding.as_raw(), pBuffer, piSize)
+        }
 
-        unsafe {
-            *piType = info_data_type;
+        TL_INFO_CMD::TL_INFO_GENTL_VER_MAJOR => {
+            copy_info(system_info.gentl_version_major, pBuffer, piSize)
         }
 
-        Ok(())
+        TL_INFO_CMD::TL_INFO_GENTL_VER_MINOR => {
+            copy_info(system_info.gentl_version_minor, pBuffer, piSize)
+        }
+        _ => return Err(GenTlError::InvalidParameter),
+    }?;
+
+    unsafe {
+        *piType = info_data_type;
     }
-);
+
+    Ok(())
+}
 
 gentl_api! {
     pub fn TLGetInterfaceID(
@@ -198,17 +208,14 @@ gentl_api! {
     ) -> GenTlResult<()> {
         let handle = unsafe { ModuleHandle::from_raw_manually_drop(hSystem)? };
         let system_handle = handle.system()?;
-        let handle_guard = system_handle.lock().unwrap();
+        let mut handle_guard = system_handle.lock().unwrap();
 
-        if handle_guard.is_opened() {
-            unsafe {
-                *pbChanged = bool8_t::false_();
-            }
-
-            Ok(())
-        } else {
-            Err(GenTlError::NotInitialized)
+        let is_changed = handle_guard.update_interface_list()?.into();
+        unsafe {
+            *pbChanged = is_changed;
         }
+
+        Ok(())
     }
 }
 
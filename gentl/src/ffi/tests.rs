@@ -0,0 +1,817 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Conformance tests that drive the exported functions exactly as a C consumer does, i.e. only
+//! through raw pointers, handles and return codes.
+
+use std::{
+    ffi::{CStr, CString},
+    ptr,
+    sync::MutexGuard,
+};
+
+use super::{system::*, *};
+use crate::test_utils::lock_global_state;
+
+pub(super) const GC_ERR_SUCCESS: i32 = 0;
+pub(super) const GC_ERR_NOT_INITIALIZED: i32 = -1002;
+pub(super) const GC_ERR_RESOURCE_IN_USE: i32 = -1004;
+pub(super) const GC_ERR_INVALID_HANDLE: i32 = -1006;
+#[cfg(feature = "emulator")]
+pub(super) const GC_ERR_INVALID_PARAMETER: i32 = -1009;
+#[cfg(feature = "emulator")]
+pub(super) const GC_ERR_TIMEOUT: i32 = -1011;
+#[cfg(feature = "emulator")]
+pub(super) const GC_ERR_ABORT: i32 = -1012;
+#[cfg(feature = "emulator")]
+pub(super) const GC_ERR_NOT_AVAILABLE: i32 = -1014;
+pub(super) const GC_ERR_BUFFER_TOO_SMALL: i32 = -1016;
+#[cfg(feature = "emulator")]
+pub(super) const GC_ERR_INVALID_INDEX: i32 = -1017;
+#[cfg(feature = "emulator")]
+pub(super) const GC_ERR_BUSY: i32 = -1022;
+
+/// Serializes a test and initializes the library for it. The library is closed on drop.
+pub(super) struct Lib {
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Lib {
+    pub(super) fn init() -> Self {
+        let guard = lock_global_state();
+        assert_eq!(GCInitLib().0, GC_ERR_SUCCESS);
+        Self { _guard: guard }
+    }
+}
+
+impl Drop for Lib {
+    fn drop(&mut self) {
+        GCCloseLib();
+    }
+}
+
+/// Layout of `EVENT_NEW_BUFFER_DATA` seen from a C consumer.
+#[cfg(feature = "emulator")]
+#[repr(C)]
+pub(super) struct NewBufferData {
+    pub(super) buffer: stream::BUFFER_HANDLE,
+    pub(super) user_pointer: *mut libc::c_void,
+}
+
+fn last_error() -> (i32, String) {
+    let mut code = GC_ERROR(0);
+    let mut size = 0;
+    assert_eq!(
+        GCGetLastError(&mut code, ptr::null_mut(), &mut size).0,
+        GC_ERR_SUCCESS
+    );
+
+    let mut text = vec![0_u8; size];
+    assert_eq!(
+        GCGetLastError(&mut code, text.as_mut_ptr().cast(), &mut size).0,
+        GC_ERR_SUCCESS
+    );
+    (code.0, c_string(&text))
+}
+
+/// Queries a string with the two-call pattern, checking the size negotiation on the way.
+pub(super) fn query_string(
+    mut f: impl FnMut(*mut libc::c_char, *mut libc::size_t) -> GC_ERROR,
+) -> String {
+    let mut size = 0;
+    assert_eq!(f(ptr::null_mut(), &mut size).0, GC_ERR_SUCCESS);
+    assert!(size > 0);
+
+    let mut buf = vec![0_u8; size];
+    let mut too_small = size - 1;
+    assert_eq!(
+        f(buf.as_mut_ptr().cast(), &mut too_small).0,
+        GC_ERR_BUFFER_TOO_SMALL
+    );
+
+    assert_eq!(f(buf.as_mut_ptr().cast(), &mut size).0, GC_ERR_SUCCESS);
+    assert_eq!(buf.len(), size);
+    c_string(&buf)
+}
+
+fn c_string(buf: &[u8]) -> String {
+    CStr::from_bytes_with_nul(buf)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_owned()
+}
+
+/// An opened system module.
+///
+/// The module is closed on drop, so a failed test doesn't leave it opened for the following
+/// tests.
+pub(super) struct System(pub(super) TL_HANDLE);
+
+impl System {
+    fn open() -> Self {
+        let mut h_system = ptr::null_mut();
+        assert_eq!(TLOpen(&mut h_system).0, GC_ERR_SUCCESS);
+        assert!(!h_system.is_null());
+        Self(h_system)
+    }
+
+    /// Closes the module, checking it's closed successfully.
+    fn close(mut self) {
+        assert_eq!(TLClose(self.0).0, GC_ERR_SUCCESS);
+        self.0 = ptr::null_mut();
+    }
+}
+
+impl Drop for System {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            TLClose(self.0);
+        }
+    }
+}
+
+/// Handles of an emulated device opened through the emulator interface.
+///
+/// The handles are closed on drop, so a failed test doesn't leave the modules opened for the
+/// following tests.
+#[cfg(feature = "emulator")]
+pub(super) struct EmulatedDevice {
+    pub(super) system: System,
+    pub(super) h_iface: interface::IF_HANDLE,
+    pub(super) h_device: device::DEV_HANDLE,
+}
+
+#[cfg(feature = "emulator")]
+impl EmulatedDevice {
+    /// Builds an emulator with the serial number, then opens it exclusively.
+    ///
+    /// The device is looked up by its serial number because the emulator pool is shared by all
+    /// tests.
+    pub(super) fn open(serial: &str) -> Self {
+        use cameleon_device::emulator::EmulatorBuilder;
+
+        EmulatorBuilder::new()
+            .serial_number(serial)
+            .unwrap()
+            .build();
+        let mut dev = Self {
+            system: System::open(),
+            h_iface: ptr::null_mut(),
+            h_device: ptr::null_mut(),
+        };
+
+        // Enable the emulator interface through the system module port.
+        let enable = 1_u32.to_le_bytes();
+        let mut size = enable.len();
+        assert_eq!(
+            port::GCWritePort(
+                dev.system.0,
+                imp::system::EMULATOR_INTERFACE_ENABLE_ADDRESS,
+                enable.as_ptr().cast(),
+                &mut size
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        let mut changed = bool8_t::false_();
+        assert_eq!(
+            TLUpdateInterfaceList(dev.system.0, &mut changed, 0).0,
+            GC_ERR_SUCCESS
+        );
+
+        let iface_id = CString::new(imp::interface::EMULATOR_INTERFACE_ID).unwrap();
+        assert_eq!(
+            TLOpenInterface(dev.system.0, iface_id.as_ptr(), &mut dev.h_iface).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(
+            interface::IFUpdateDeviceList(dev.h_iface, &mut changed, 100).0,
+            GC_ERR_SUCCESS
+        );
+
+        let device_id = dev.find_device(serial).expect("the emulator must be found");
+        assert_eq!(
+            interface::IFOpenDevice(
+                dev.h_iface,
+                device_id.as_ptr(),
+                device::DEVICE_ACCESS_FLAGS::DEVICE_ACCESS_EXCLUSIVE,
+                &mut dev.h_device
+            )
+            .0,
+            GC_ERR_SUCCESS
+        );
+        dev
+    }
+
+    /// Closes the modules, checking each of them is closed successfully.
+    pub(super) fn close(mut self) {
+        assert_eq!(device::DevClose(self.h_device).0, GC_ERR_SUCCESS);
+        self.h_device = ptr::null_mut();
+        assert_eq!(interface::IFClose(self.h_iface).0, GC_ERR_SUCCESS);
+        self.h_iface = ptr::null_mut();
+        let system = std::mem::replace(&mut self.system, System(ptr::null_mut()));
+        system.close();
+    }
+
+    /// Opens the first data stream of the device.
+    pub(super) fn open_stream(&self) -> stream::DS_HANDLE {
+        let stream_id =
+            query_string(|buf, size| device::DevGetDataStreamID(self.h_device, 0, buf, size));
+        let stream_id = CString::new(stream_id).unwrap();
+        let mut h_stream = ptr::null_mut();
+        assert_eq!(
+            device::DevOpenDataStream(self.h_device, stream_id.as_ptr(), &mut h_stream).0,
+            GC_ERR_SUCCESS
+        );
+        h_stream
+    }
+
+    /// Returns the ID of the device with the serial number.
+    fn find_device(&self, serial: &str) -> Option<CString> {
+        let mut num_devices = 0;
+        assert_eq!(
+            interface::IFGetNumDevices(self.h_iface, &mut num_devices).0,
+            GC_ERR_SUCCESS
+        );
+
+        (0..num_devices).find_map(|i| {
+            let id = query_string(|buf, size| interface::IFGetDeviceID(self.h_iface, i, buf, size));
+            let id = CString::new(id).unwrap();
+            let mut info_type = INFO_DATATYPE::INFO_DATATYPE_UNKNOWN;
+            let device_serial = query_string(|buf, size| {
+                interface::IFGetDeviceInfo(
+                    self.h_iface,
+                    id.as_ptr(),
+                    device::DEVICE_INFO_CMD::DEVICE_INFO_SERIAL_NUMBER,
+                    &mut info_type,
+                    buf.cast(),
+                    size,
+                )
+            });
+            if device_serial == serial {
+                Some(id)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(feature = "emulator")]
+impl Drop for EmulatedDevice {
+    fn drop(&mut self) {
+        if !self.h_device.is_null() {
+            device::DevClose(self.h_device);
+        }
+        if !self.h_iface.is_null() {
+            interface::IFClose(self.h_iface);
+        }
+    }
+}
+
+#[test]
+fn test_lib_initialization() {
+    let _guard = lock_global_state();
+
+    let mut h_system = ptr::null_mut();
+    assert_eq!(TLOpen(&mut h_system).0, GC_ERR_NOT_INITIALIZED);
+    assert!(h_system.is_null());
+    assert_eq!(GCCloseLib().0, GC_ERR_NOT_INITIALIZED);
+
+    assert_eq!(GCInitLib().0, GC_ERR_SUCCESS);
+    assert_eq!(GCInitLib().0, GC_ERR_RESOURCE_IN_USE);
+    assert_eq!(last_error().0, GC_ERR_RESOURCE_IN_USE);
+
+    assert_eq!(GCCloseLib().0, GC_ERR_SUCCESS);
+    assert_eq!(GCCloseLib().0, GC_ERR_NOT_INITIALIZED);
+}
+
+#[test]
+fn test_last_error() {
+    let _lib = Lib::init();
+
+    assert_eq!(TLClose(ptr::null_mut()).0, GC_ERR_INVALID_HANDLE);
+    let (code, text) = last_error();
+    assert_eq!(code, GC_ERR_INVALID_HANDLE);
+    assert_eq!(text, GenTlError::InvalidHandle.to_string());
+
+    // An undersized buffer must be rejected instead of truncating the text.
+    let mut code = GC_ERROR(0);
+    let mut buf = [0_u8; 4];
+    let mut size = buf.len();
+    assert_eq!(
+        GCGetLastError(&mut code, buf.as_mut_ptr().cast(), &mut size).0,
+        GC_ERR_BUFFER_TOO_SMALL
+    );
+}
+
+#[test]
+fn test_invalid_handles() {
+    let _lib = Lib::init();
+    let garbage = 0xdead_beef_usize as *mut libc::c_void;
+
+    for &handle in &[ptr::null_mut(), garbage] {
+        let mut num = 0;
+        assert_eq!(TLClose(handle).0, GC_ERR_INVALID_HANDLE);
+        assert_eq!(
+            TLGetNumInterfaces(handle, &mut num).0,
+            GC_ERR_INVALID_HANDLE
+        );
+        assert_eq!(interface::IFClose(handle).0, GC_ERR_INVALID_HANDLE);
+        assert_eq!(device::DevClose(handle).0, GC_ERR_INVALID_HANDLE);
+        assert_eq!(stream::DSClose(handle).0, GC_ERR_INVALID_HANDLE);
+        assert_eq!(event::EventKill(handle).0, GC_ERR_INVALID_HANDLE);
+    }
+
+    // A handle of another module type is rejected as well.
+    let system = System::open();
+    let h_system = system.0;
+    assert_eq!(interface::IFClose(h_system).0, GC_ERR_INVALID_HANDLE);
+    assert_eq!(device::DevClose(h_system).0, GC_ERR_INVALID_HANDLE);
+    assert_eq!(stream::DSClose(h_system).0, GC_ERR_INVALID_HANDLE);
+
+    // The system module can't be opened twice.
+    let mut h_system2 = ptr::null_mut();
+    assert_eq!(TLOpen(&mut h_system2).0, GC_ERR_RESOURCE_IN_USE);
+
+    // Double close.
+    system.close();
+    assert_eq!(TLClose(h_system).0, GC_ERR_INVALID_HANDLE);
+    let mut num = 0;
+    assert_eq!(
+        TLGetNumInterfaces(h_system, &mut num).0,
+        GC_ERR_INVALID_HANDLE
+    );
+}
+
+#[test]
+fn test_string_info() {
+    let _lib = Lib::init();
+
+    // `GCGetInfo` doesn't require the system module to be opened.
+    let mut info_type = INFO_DATATYPE::INFO_DATATYPE_UNKNOWN;
+    let gc_id = query_string(|buf, size| {
+        GCGetInfo(TL_INFO_CMD::TL_INFO_ID, &mut info_type, buf.cast(), size)
+    });
+
+    let system = System::open();
+    let h_system = system.0;
+    let id = query_string(|buf, size| {
+        TLGetInfo(
+            h_system,
+            TL_INFO_CMD::TL_INFO_ID,
+            &mut info_type,
+            buf.cast(),
+            size,
+        )
+    });
+    assert!(!id.is_empty());
+    assert_eq!(id, gc_id);
+    assert!(info_type == INFO_DATATYPE::INFO_DATATYPE_STRING);
+
+    let mut changed = bool8_t::false_();
+    assert_eq!(
+        TLUpdateInterfaceList(h_system, &mut changed, 0).0,
+        GC_ERR_SUCCESS
+    );
+    let mut num = 0;
+    assert_eq!(TLGetNumInterfaces(h_system, &mut num).0, GC_ERR_SUCCESS);
+    assert!(num > 0);
+    for i in 0..num {
+        let iface_id = query_string(|buf, size| TLGetInterfaceID(h_system, i, buf, size));
+        assert!(!iface_id.is_empty());
+    }
+
+    system.close();
+}
+
+#[cfg(feature = "emulator")]
+#[test]
+fn test_emulated_device() {
+    use super::{device::*, event::*, interface::*, port::*, stream::*};
+
+    let _lib = Lib::init();
+    let dev = EmulatedDevice::open("FFIDEV00");
+    let (h_iface, h_device) = (dev.h_iface, dev.h_device);
+
+    // The device is listed by the interface.
+    let mut num_devices = 0;
+    assert_eq!(IFGetNumDevices(h_iface, &mut num_devices).0, GC_ERR_SUCCESS);
+    assert!(num_devices >= 1);
+    let mut parent_iface = ptr::null_mut();
+    assert_eq!(
+        DevGetParentIF(h_device, &mut parent_iface).0,
+        GC_ERR_SUCCESS
+    );
+    assert_eq!(parent_iface, h_iface);
+
+    // Access the remote device port.
+    let mut h_port = ptr::null_mut();
+    assert_eq!(DevGetPort(h_device, &mut h_port).0, GC_ERR_SUCCESS);
+    let mut buf = [0_u8; 4];
+    let mut size = buf.len();
+    assert_eq!(
+        GCReadPort(h_port, 0, buf.as_mut_ptr().cast(), &mut size).0,
+        GC_ERR_SUCCESS
+    );
+    assert_eq!(size, buf.len());
+    let mut info_type = INFO_DATATYPE::INFO_DATATYPE_UNKNOWN;
+    let url = query_string(|buf, size| {
+        GCGetPortURLInfo(
+            h_port,
+            0,
+            URL_INFO_CMD::URL_INFO_URL,
+            &mut info_type,
+            buf.cast(),
+            size,
+        )
+    });
+    assert!(url.starts_with("local:"), "{}", url);
+
+    // Open the data stream and announce buffers.
+    let stream_id = query_string(|buf, size| DevGetDataStreamID(h_device, 0, buf, size));
+    let stream_id = CString::new(stream_id).unwrap();
+    let mut h_stream = ptr::null_mut();
+    assert_eq!(
+        DevOpenDataStream(h_device, stream_id.as_ptr(), &mut h_stream).0,
+        GC_ERR_SUCCESS
+    );
+    let mut payload_size: usize = 0;
+    let mut size = std::mem::size_of::<usize>();
+    assert_eq!(
+        DSGetInfo(
+            h_stream,
+            STREAM_INFO_CMD::STREAM_INFO_PAYLOAD_SIZE,
+            &mut info_type,
+            (&mut payload_size as *mut usize).cast(),
+            &mut size,
+        )
+        .0,
+        GC_ERR_SUCCESS
+    );
+    assert!(payload_size > 0);
+
+    let mut h_buffers = [ptr::null_mut(); 2];
+    for h_buffer in &mut h_buffers {
+        assert_eq!(
+            DSAllocAndAnnounceBuffer(h_stream, payload_size, ptr::null_mut(), h_buffer).0,
+            GC_ERR_SUCCESS
+        );
+        assert_eq!(DSQueueBuffer(h_stream, *h_buffer).0, GC_ERR_SUCCESS);
+    }
+    // A buffer handle isn't a module handle.
+    assert_eq!(DSClose(h_buffers[0]).0, GC_ERR_INVALID_HANDLE);
+
+    let mut h_event = ptr::null_mut();
+    assert_eq!(
+        GCRegisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER, &mut h_event).0,
+        GC_ERR_SUCCESS
+    );
+    assert_eq!(
+        DSStartAcquisition(h_stream, ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT, u64::MAX).0,
+        GC_ERR_SUCCESS
+    );
+    assert_eq!(
+        DSStartAcquisition(h_stream, ACQ_START_FLAGS::ACQ_START_FLAGS_DEFAULT, u64::MAX).0,
+        GC_ERR_RESOURCE_IN_USE
+    );
+
+    // The remote device isn't started, so no buffer is delivered.
+    let mut data = NewBufferData {
+        buffer: ptr::null_mut(),
+        user_pointer: ptr::null_mut(),
+    };
+    let mut size = std::mem::size_of::<NewBufferData>();
+    assert_eq!(
+        EventGetData(
+            h_event,
+            (&mut data as *mut NewBufferData).cast(),
+            &mut size,
+            10
+        )
+        .0,
+        GC_ERR_TIMEOUT
+    );
+    assert_eq!(EventKill(h_event).0, GC_ERR_SUCCESS);
+    assert_eq!(
+        EventGetData(
+            h_event,
+            (&mut data as *mut NewBufferData).cast(),
+            &mut size,
+            1000
+        )
+        .0,
+        GC_ERR_ABORT
+    );
+
+    // Tear down.
+    assert_eq!(
+        DSStopAcquisition(h_stream, ACQ_STOP_FLAGS::ACQ_STOP_FLAGS_DEFAULT).0,
+        GC_ERR_SUCCESS
+    );
+    assert_eq!(
+        DSFlushQueue(h_stream, ACQ_QUEUE_TYPE::ACQ_QUEUE_ALL_DISCARD).0,
+        GC_ERR_SUCCESS
+    );
+    for &h_buffer in &h_buffers {
+        assert_eq!(
+            DSRevokeBuffer(h_stream, h_buffer, ptr::null_mut(), ptr::null_mut()).0,
+            GC_ERR_SUCCESS
+        );
+    }
+    assert_eq!(
+        GCUnregisterEvent(h_stream, EVENT_TYPE::EVENT_NEW_BUFFER).0,
+        GC_ERR_SUCCESS
+    );
+    assert_eq!(EventKill(h_event).0, GC_ERR_INVALID_HANDLE);
+
+    assert_eq!(DSClose(h_stream).0, GC_ERR_SUCCESS);
+    assert_eq!(DSClose(h_stream).0, GC_ERR_INVALID_HANDLE);
+    dev.close();
+    assert_eq!(DevClose(h_device).0, GC_ERR_INVALID_HANDLE);
+    assert_eq!(IFClose(h_iface).0, GC_ERR_INVALID_HANDLE);
+}
+
+const GENTL_H: &str = include_str!(concat!(env!("OUT_DIR"), "/GenTL.h"));
+
+/// Enumeration values defined in the GenTL standard.
+const STANDARD_ENUM_VALUES: &[(&str, i64)] = &[
+    // GC_ERROR
+    ("GC_ERR_SUCCESS", 0),
+    ("GC_ERR_ERROR", -1001),
+    ("GC_ERR_NOT_INITIALIZED", -1002),
+    ("GC_ERR_NOT_IMPLEMENTED", -1003),
+    ("GC_ERR_RESOURCE_IN_USE", -1004),
+    ("GC_ERR_ACCESS_DENIED", -1005),
+    ("GC_ERR_INVALID_HANDLE", -1006),
+    ("GC_ERR_INVALID_ID", -1007),
+    ("GC_ERR_NO_DATA", -1008),
+    ("GC_ERR_INVALID_PARAMETER", -1009),
+    ("GC_ERR_IO", -1010),
+    ("GC_ERR_TIMEOUT", -1011),
+    ("GC_ERR_ABORT", -1012),
+    ("GC_ERR_INVALID_BUFFER", -1013),
+    ("GC_ERR_NOT_AVAILABLE", -1014),
+    ("GC_ERR_INVALID_ADDRESS", -1015),
+    ("GC_ERR_BUFFER_TOO_SMALL", -1016),
+    ("GC_ERR_INVALID_INDEX", -1017),
+    ("GC_ERR_PARSING_CHUNK_DATA", -1018),
+    ("GC_ERR_INVALID_VALUE", -1019),
+    ("GC_ERR_RESOURCE_EXHAUSTED", -1020),
+    ("GC_ERR_OUT_OF_MEMORY", -1021),
+    ("GC_ERR_BUSY", -1022),
+    ("GC_ERR_AMBIGUOUS", -1023),
+    ("GC_ERR_CUSTOM_ID", -10000),
+    // INFO_DATATYPE
+    ("INFO_DATATYPE_UNKNOWN", 0),
+    ("INFO_DATATYPE_STRING", 1),
+    ("INFO_DATATYPE_STRINGLIST", 2),
+    ("INFO_DATATYPE_INT16", 3),
+    ("INFO_DATATYPE_UINT16", 4),
+    ("INFO_DATATYPE_INT32", 5),
+    ("INFO_DATATYPE_UINT32", 6),
+    ("INFO_DATATYPE_INT64", 7),
+    ("INFO_DATATYPE_UINT64", 8),
+    ("INFO_DATATYPE_FLOAT64", 9),
+    ("INFO_DATATYPE_PTR", 10),
+    ("INFO_DATATYPE_BOOL8", 11),
+    ("INFO_DATATYPE_SIZET", 12),
+    ("INFO_DATATYPE_BUFFER", 13),
+    ("INFO_DATATYPE_PTRDIFF", 14),
+    ("INFO_DATATYPE_CUSTOM_ID", 1000),
+    // TL_INFO_CMD
+    ("TL_INFO_ID", 0),
+    ("TL_INFO_VENDOR", 1),
+    ("TL_INFO_MODEL", 2),
+    ("TL_INFO_VERSION", 3),
+    ("TL_INFO_TLTYPE", 4),
+    ("TL_INFO_NAME", 5),
+    ("TL_INFO_PATHNAME", 6),
+    ("TL_INFO_DISPLAYNAME", 7),
+    ("TL_INFO_CHAR_ENCODING", 8),
+    ("TL_INFO_GENTL_VER_MAJOR", 9),
+    ("TL_INFO_GENTL_VER_MINOR", 10),
+    ("TL_INFO_CUSTOM_ID", 1000),
+    // INTERFACE_INFO_CMD
+    ("INTERFACE_INFO_ID", 0),
+    ("INTERFACE_INFO_DISPLAYNAME", 1),
+    ("INTERFACE_INFO_TLTYPE", 2),
+    ("INTERFACE_INFO_CUSTOM_ID", 1000),
+    // DEVICE_INFO_CMD
+    ("DEVICE_INFO_ID", 0),
+    ("DEVICE_INFO_VENDOR", 1),
+    ("DEVICE_INFO_MODEL", 2),
+    ("DEVICE_INFO_TLTYPE", 3),
+    ("DEVICE_INFO_DISPLAYNAME", 4),
+    ("DEVICE_INFO_ACCESS_STATUS", 5),
+    ("DEVICE_INFO_USER_DEFINED_NAME", 6),
+    ("DEVICE_INFO_SERIAL_NUMBER", 7),
+    ("DEVICE_INFO_VERSION", 8),
+    ("DEVICE_INFO_TIMESTAMP_FREQUENCY", 9),
+    ("DEVICE_INFO_CUSTOM_ID", 1000),
+    // DEVICE_ACCESS_FLAGS
+    ("DEVICE_ACCESS_UNKNOWN", 0),
+    ("DEVICE_ACCESS_NONE", 1),
+    ("DEVICE_ACCESS_READONLY", 2),
+    ("DEVICE_ACCESS_CONTROL", 3),
+    ("DEVICE_ACCESS_EXCLUSIVE", 4),
+    ("DEVICE_ACCESS_CUSTOM_ID", 1000),
+    // DEVICE_ACCESS_STATUS
+    ("DEVICE_ACCESS_STATUS_UNKNOWN", 0),
+    ("DEVICE_ACCESS_STATUS_READWRITE", 1),
+    ("DEVICE_ACCESS_STATUS_READONLY", 2),
+    ("DEVICE_ACCESS_STATUS_NOACCESS", 3),
+    ("DEVICE_ACCESS_STATUS_BUSY", 4),
+    ("DEVICE_ACCESS_STATUS_OPEN_READWRITE", 5),
+    ("DEVICE_ACCESS_STATUS_OPEN_READONLY", 6),
+    ("DEVICE_ACCESS_STATUS_CUSTOM_ID", 1000),
+    // PORT_INFO_CMD
+    ("PORT_INFO_ID", 0),
+    ("PORT_INFO_VENDOR", 1),
+    ("PORT_INFO_MODEL", 2),
+    ("PORT_INFO_TLTYPE", 3),
+    ("PORT_INFO_MODULE", 4),
+    ("PORT_INFO_LITTLE_ENDIAN", 5),
+    ("PORT_INFO_BIG_ENDIAN", 6),
+    ("PORT_INFO_ACCESS_READ", 7),
+    ("PORT_INFO_ACCESS_WRITE", 8),
+    ("PORT_INFO_ACCESS_NA", 9),
+    ("PORT_INFO_ACCESS_NI", 10),
+    ("PORT_INFO_VERSION", 11),
+    ("PORT_INFO_PORTNAME", 12),
+    ("PORT_INFO_CUSTOM_ID", 1000),
+    // URL_INFO_CMD
+    ("URL_INFO_URL", 0),
+    ("URL_INFO_SCHEMA_VER_MAJOR", 1),
+    ("URL_INFO_SCHEMA_VER_MINOR", 2),
+    ("URL_INFO_FILE_VER_MAJOR", 3),
+    ("URL_INFO_FILE_VER_MINOR", 4),
+    ("URL_INFO_FILE_VER_SUBMINOR", 5),
+    ("URL_INFO_FILE_SHA1_HASH", 6),
+    ("URL_INFO_FILE_REGISTER_ADDRESS", 7),
+    ("URL_INFO_FILE_SIZE", 8),
+    ("URL_INFO_SCHEME", 9),
+    ("URL_INFO_FILENAME", 10),
+    ("URL_INFO_CUSTOM_ID", 1000),
+    // URL_SCHEME_IDS
+    ("URL_SCHEME_LOCAL", 0),
+    ("URL_SCHEME_HTTP", 1),
+    ("URL_SCHEME_FILE", 2),
+    ("URL_SCHEME_CUSTOM_ID", 1000),
+    // ACQ_START_FLAGS
+    ("ACQ_START_FLAGS_DEFAULT", 0),
+    ("ACQ_START_FLAGS_CUSTOM_ID", 1000),
+    // ACQ_STOP_FLAGS
+    ("ACQ_STOP_FLAGS_DEFAULT", 0),
+    ("ACQ_STOP_FLAGS_KILL", 1),
+    ("ACQ_STOP_FLAGS_CUSTOM_ID", 1000),
+    // ACQ_QUEUE_TYPE
+    ("ACQ_QUEUE_INPUT_TO_OUTPUT", 0),
+    ("ACQ_QUEUE_OUTPUT_DISCARD", 1),
+    ("ACQ_QUEUE_ALL_TO_INPUT", 2),
+    ("ACQ_QUEUE_UNQUEUED_TO_INPUT", 3),
+    ("ACQ_QUEUE_ALL_DISCARD", 4),
+    ("ACQ_QUEUE_CUSTOM_ID", 1000),
+    // STREAM_INFO_CMD
+    ("STREAM_INFO_ID", 0),
+    ("STREAM_INFO_NUM_DELIVERED", 1),
+    ("STREAM_INFO_NUM_UNDERRUN", 2),
+    ("STREAM_INFO_NUM_ANNOUNCED", 3),
+    ("STREAM_INFO_NUM_QUEUED", 4),
+    ("STREAM_INFO_NUM_AWAIT_DELIVERY", 5),
+    ("STREAM_INFO_NUM_STARTED", 6),
+    ("STREAM_INFO_PAYLOAD_SIZE", 7),
+    ("STREAM_INFO_IS_GRABBING", 8),
+    ("STREAM_INFO_DEFINES_PAYLOADSIZE", 9),
+    ("STREAM_INFO_TLTYPE", 10),
+    ("STREAM_INFO_NUM_CHUNKS_MAX", 11),
+    ("STREAM_INFO_BUF_ANNOUNCE_MIN", 12),
+    ("STREAM_INFO_BUF_ALIGNMENT", 13),
+    ("STREAM_INFO_CUSTOM_ID", 1000),
+    // BUFFER_INFO_CMD
+    ("BUFFER_INFO_BASE", 0),
+    ("BUFFER_INFO_SIZE", 1),
+    ("BUFFER_INFO_USER_PTR", 2),
+    ("BUFFER_INFO_TIMESTAMP", 3),
+    ("BUFFER_INFO_NEW_DATA", 4),
+    ("BUFFER_INFO_IS_QUEUED", 5),
+    ("BUFFER_INFO_IS_ACQUIRING", 6),
+    ("BUFFER_INFO_IS_INCOMPLETE", 7),
+    ("BUFFER_INFO_TLTYPE", 8),
+    ("BUFFER_INFO_SIZE_FILLED", 9),
+    ("BUFFER_INFO_WIDTH", 10),
+    ("BUFFER_INFO_HEIGHT", 11),
+    ("BUFFER_INFO_XOFFSET", 12),
+    ("BUFFER_INFO_YOFFSET", 13),
+    ("BUFFER_INFO_XPADDING", 14),
+    ("BUFFER_INFO_YPADDING", 15),
+    ("BUFFER_INFO_FRAMEID", 16),
+    ("BUFFER_INFO_IMAGEPRESENT", 17),
+    ("BUFFER_INFO_IMAGEOFFSET", 18),
+    ("BUFFER_INFO_PAYLOADTYPE", 19),
+    ("BUFFER_INFO_PIXELFORMAT", 20),
+    ("BUFFER_INFO_PIXELFORMAT_NAMESPACE", 21),
+    ("BUFFER_INFO_DELIVERED_IMAGEHEIGHT", 22),
+    ("BUFFER_INFO_DELIVERED_CHUNKPAYLOADSIZE", 23),
+    ("BUFFER_INFO_CHUNKLAYOUTID", 24),
+    ("BUFFER_INFO_FILENAME", 25),
+    ("BUFFER_INFO_PIXEL_ENDIANNESS", 26),
+    ("BUFFER_INFO_DATA_SIZE", 27),
+    ("BUFFER_INFO_TIMESTAMP_NS", 28),
+    ("BUFFER_INFO_DATA_LARGER_THAN_BUFFER", 29),
+    ("BUFFER_INFO_CONTAINS_CHUNKDATA", 30),
+    ("BUFFER_INFO_CUSTOM_ID", 1000),
+    // PAYLOADTYPE_INFO_IDS
+    ("PAYLOAD_TYPE_UNKNOWN", 0),
+    ("PAYLOAD_TYPE_IMAGE", 1),
+    ("PAYLOAD_TYPE_RAW_DATA", 2),
+    ("PAYLOAD_TYPE_FILE", 3),
+    ("PAYLOAD_TYPE_CHUNK_DATA", 4),
+    ("PAYLOAD_TYPE_JPEG", 5),
+    ("PAYLOAD_TYPE_JPEG2000", 6),
+    ("PAYLOAD_TYPE_H264", 7),
+    ("PAYLOAD_TYPE_CHUNK_ONLY", 8),
+    ("PAYLOAD_TYPE_DEVICE_SPECIFIC", 9),
+    ("PAYLOAD_TYPE_MULTI_PART", 10),
+    ("PAYLOAD_TYPE_CUSTOM_ID", 1000),
+    // PIXELFORMAT_NAMESPACE_IDS
+    ("PIXELFORMAT_NAMESPACE_UNKNOWN", 0),
+    ("PIXELFORMAT_NAMESPACE_GEV", 1),
+    ("PIXELFORMAT_NAMESPACE_IIDC", 2),
+    ("PIXELFORMAT_NAMESPACE_PFNC_16BIT", 3),
+    ("PIXELFORMAT_NAMESPACE_PFNC_32BIT", 4),
+    ("PIXELFORMAT_NAMESPACE_CUSTOM_ID", 1000),
+    // PIXELENDIANNESS_IDS
+    ("PIXELENDIANNESS_UNKNOWN", 0),
+    ("PIXELENDIANNESS_LITTLE", 1),
+    ("PIXELENDIANNESS_BIG", 2),
+    // BUFFER_PART_INFO_CMD
+    ("BUFFER_PART_INFO_BASE", 0),
+    ("BUFFER_PART_INFO_DATA_SIZE", 1),
+    ("BUFFER_PART_INFO_DATA_TYPE", 2),
+    ("BUFFER_PART_INFO_DATA_FORMAT", 3),
+    ("BUFFER_PART_INFO_DATA_FORMAT_NAMESPACE", 4),
+    ("BUFFER_PART_INFO_WIDTH", 5),
+    ("BUFFER_PART_INFO_HEIGHT", 6),
+    ("BUFFER_PART_INFO_XOFFSET", 7),
+    ("BUFFER_PART_INFO_YOFFSET", 8),
+    ("BUFFER_PART_INFO_XPADDING", 9),
+    ("BUFFER_PART_INFO_SOURCE_ID", 10),
+    ("BUFFER_PART_INFO_DELIVERED_IMAGEHEIGHT", 11),
+    ("BUFFER_PART_INFO_REGION_ID", 12),
+    ("BUFFER_PART_INFO_DATA_PURPOSE_ID", 13),
+    ("BUFFER_PART_INFO_CUSTOM_ID", 1000),
+    // PARTDATATYPE_IDS
+    ("PART_DATATYPE_UNKNOWN", 0),
+    ("PART_DATATYPE_2D_IMAGE", 1),
+    ("PART_DATATYPE_2D_PLANE_BIPLANAR", 2),
+    ("PART_DATATYPE_2D_PLANE_TRIPLANAR", 3),
+    ("PART_DATATYPE_2D_PLANE_QUADPLANAR", 4),
+    ("PART_DATATYPE_3D_IMAGE", 5),
+    ("PART_DATATYPE_3D_PLANE_BIPLANAR", 6),
+    ("PART_DATATYPE_3D_PLANE_TRIPLANAR", 7),
+    ("PART_DATATYPE_3D_PLANE_QUADPLANAR", 8),
+    ("PART_DATATYPE_CONFIDENCE_MAP", 9),
+    ("PART_DATATYPE_CUSTOM_ID", 1000),
+    // EVENT_TYPE
+    ("EVENT_ERROR", 0),
+    ("EVENT_NEW_BUFFER", 1),
+    ("EVENT_FEATURE_INVALIDATE", 2),
+    ("EVENT_FEATURE_CHANGE", 3),
+    ("EVENT_REMOTE_DEVICE", 4),
+    ("EVENT_MODULE", 5),
+    ("EVENT_CUSTOM_ID", 1000),
+    // EVENT_INFO_CMD
+    ("EVENT_EVENT_TYPE", 0),
+    ("EVENT_NUM_IN_QUEUE", 1),
+    ("EVENT_NUM_FIRED", 2),
+    ("EVENT_SIZE_MAX", 3),
+    ("EVENT_INFO_DATA_SIZE_MAX", 4),
+    ("EVENT_INFO_CUSTOM_ID", 1000),
+    // EVENT_DATA_INFO_CMD
+    ("EVENT_DATA_ID", 0),
+    ("EVENT_DATA_VALUE", 1),
+    ("EVENT_DATA_NUMID", 2),
+    ("EVENT_DATA_CUSTOM_ID", 1000),
+];
+
+#[test]
+fn test_header_enum_values() {
+    let values = GENTL_H.lines().filter_map(|line| {
+        let (name, value) = line.trim().strip_suffix(',')?.split_once(" = ")?;
+        Some((name, value.parse::<i64>().unwrap()))
+    });
+
+    let mut num_values = 0;
+    for (name, value) in values {
+        let expected = STANDARD_ENUM_VALUES
+            .iter()
+            .find(|(standard_name, _)| *standard_name == name)
+            .unwrap_or_else(|| panic!("`{}` isn't a GenTL enumeration value", name));
+        assert_eq!(value, expected.1, "`{}` has a wrong value", name);
+        num_values += 1;
+    }
+    assert!(num_values > 0);
+}
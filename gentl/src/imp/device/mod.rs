@@ -2,13 +2,20 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::{convert::TryFrom, sync::Mutex};
+use std::{
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
 
 use crate::{GenTlError, GenTlResult};
 
 pub(crate) mod u3v;
 
-use crate::imp::port::{Port, TlType};
+use crate::imp::{
+    event::{Event, EventType},
+    port::{Port, TlType},
+    stream::DataStream,
+};
 
 mod u3v_genapi;
 
@@ -92,6 +99,7 @@ impl DeviceAccessStatus {
 
 pub(crate) trait Device: Port {
     /// Open the device and the remote device.
+    /// Returns [`GenTlError::AccessDenied`] if the device doesn't support `access_flag`.
     fn open(&mut self, access_flag: DeviceAccessFlag) -> GenTlResult<()>;
 
     /// close the device and the remote device.
@@ -103,6 +111,23 @@ pub(crate) trait Device: Port {
     /// Port of the remote device.
     fn remote_device(&self) -> GenTlResult<&Mutex<dyn Port>>;
 
+    /// Number of data streams of the device.
+    fn num_data_streams(&self) -> GenTlResult<usize>;
+
+    /// ID of the `index`-th data stream.
+    fn data_stream_id(&self, index: usize) -> GenTlResult<&str>;
+
+    /// Data stream module which has the ID.
+    fn data_stream(&self, stream_id: &str) -> GenTlResult<&Mutex<dyn DataStream>>;
+
+    /// Register an event of the device.
+    /// Returns [`GenTlError::ResourceInUse`] if the event is already registered.
+    fn register_event(&mut self, event_type: EventType) -> GenTlResult<Arc<dyn Event>>;
+
+    /// Unregister an event of the device.
+    /// Threads waiting for the event are aborted.
+    fn unregister_event(&mut self, event_type: EventType) -> GenTlResult<()>;
+
     /// Vendor name of the remote device.
     fn vendor_name(&self) -> GenTlResult<String>;
 
@@ -2,56 +2,115 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::{convert::TryFrom, sync::Mutex};
+use std::{
+    convert::TryFrom,
+    ops::Range,
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
 
 use cameleon::{
     genapi::{CompressionType, SharedDefaultGenApiCtxt},
-    u3v::{self, SharedControlHandle, StreamHandle},
+    u3v::{
+        self,
+        register_map::{Abrm, GenICamFileType, Sbrm},
+        EventHandle, SharedControlHandle,
+    },
+    ControlError, ControlResult, DeviceControl,
 };
 use cameleon_impl::memory::prelude::*;
 
 use crate::{
     imp::{
+        event::{Event, EventData, EventQueue, EventType},
         genapi_common,
         port::{Endianness, ModuleType, Port, PortAccess, PortInfo, TlType, XmlInfo, XmlLocation},
+        stream::{
+            u3v::{SharedStreamHandle, U3VDataStreamModule, STREAM_ID},
+            DataStream,
+        },
     },
     GenTlError, GenTlResult,
 };
 
-use super::{u3v_genapi as genapi, Device, DeviceAccessStatus};
+use super::{u3v_genapi as genapi, Device, DeviceAccessFlag, DeviceAccessStatus};
 use genapi::GenApiReg;
 
-type Camera = cameleon::Camera<SharedControlHandle, StreamHandle, SharedDefaultGenApiCtxt>;
+type Camera = cameleon::Camera<SharedControlHandle, SharedStreamHandle, SharedDefaultGenApiCtxt>;
+
+/// Opens the event handle of the device described by the device info.
+type EventHandleFinder = fn(&u3v::DeviceInfo) -> ControlResult<Option<EventHandle>>;
 
 pub(crate) fn enumerate_u3v_device() -> GenTlResult<Vec<U3VDeviceModule>> {
-    todo!()
+    // `rusb` panics if `libusb` fails to initialize, e.g. on a host without USB support. The panic
+    // must not unwind across the C ABI, so it's reported as an error instead.
+    let cameras = panic::catch_unwind(u3v::enumerate_cameras)
+        .map_err(|_| GenTlError::Error("failed to initialize libusb".into()))??;
+    cameras
+        .into_iter()
+        .map(|camera| U3VDeviceModule::new(camera.convert_into()))
+        .collect()
+}
+
+#[cfg(feature = "emulator")]
+pub(crate) fn enumerate_emulated_u3v_device() -> GenTlResult<Vec<U3VDeviceModule>> {
+    u3v::enumerate_emulated_cameras()?
+        .into_iter()
+        .map(|camera| U3VDeviceModule::new_emulated(camera.convert_into()))
+        .collect()
 }
 
 pub(crate) struct U3VDeviceModule {
-    vm: genapi::Memory,
+    /// Locked on reads too, because some registers reflect live values of the remote device.
+    vm: Mutex<genapi::Memory>,
     port_info: PortInfo,
     xml_infos: Vec<XmlInfo>,
 
     camera: Camera,
+    device_info: u3v::DeviceInfo,
     remote_device: Option<Box<Mutex<U3VRemoteDevice>>>,
 
+    /// The data stream module is boxed and never dropped until the device module is dropped so
+    /// that handles of the data stream stay valid.
+    data_stream: Box<Mutex<U3VDataStreamModule>>,
+
+    /// Pump of the remote device event, which exists while the event is registered.
+    event_pump: Option<EventPump>,
+    find_event_handle: EventHandleFinder,
+
     /// Current status of the device.  
     /// `DeviceAccessStatus` and `DeviceAccessStatusReg` in VM doesn't reflect this value while
     /// [`Interface::UpdateDeviceList`] is called as the GenTL specification describes.
     current_status: super::DeviceAccessStatus,
 }
 
-// TODO: Implement methods for stream and event channel.
 impl U3VDeviceModule {
     pub(crate) fn new(camera: Camera) -> GenTlResult<Self> {
+        Self::with_event_handle_finder(camera, u3v::find_event_handle)
+    }
+
+    #[cfg(feature = "emulator")]
+    pub(crate) fn new_emulated(camera: Camera) -> GenTlResult<Self> {
+        Self::with_event_handle_finder(camera, u3v::find_emulated_event_handle)
+    }
+
+    fn with_event_handle_finder(
+        camera: Camera,
+        find_event_handle: EventHandleFinder,
+    ) -> GenTlResult<Self> {
         let device_info = camera.ctrl.device_info();
 
         let port_info = PortInfo {
-            id: device_info.guid,
+            id: device_info.guid.clone(),
             vendor: genapi::VENDOR_NAME.into(),
             model: genapi::MODEL_NAME.into(),
             tl_type: genapi::DEVICE_TYPE,
-            module_type: ModuleType::Interface,
+            module_type: ModuleType::Device,
             endianness: Endianness::LE,
             access: PortAccess::RW,
             version: semver::Version::new(
@@ -73,21 +132,27 @@ impl U3VDeviceModule {
                 genapi_common::SCHEME_SUBMINOR_VERSION,
             ),
             file_version: semver::Version::new(
-                genapi_common::SCHEME_MAJOR_VERSION,
-                genapi_common::SCHEME_MINOR_VERSION,
-                genapi_common::SCHEME_SUBMINOR_VERSION,
+                genapi::XML_MAJOR_VERSION,
+                genapi::XML_MINOR_VERSION,
+                genapi::XML_SUBMINOR_VERSION,
             ),
             sha1_hash: None,
             compressed: CompressionType::Uncompressed,
         };
 
+        let data_stream = U3VDataStreamModule::new(camera.ctrl.clone(), camera.strm.clone());
+
         let mut dev = Self {
-            vm: genapi::Memory::new(),
+            vm: Mutex::new(genapi::Memory::new()),
             port_info,
             xml_infos: vec![xml_info],
 
             camera,
+            device_info,
             remote_device: None,
+            data_stream: Box::new(Mutex::new(data_stream)),
+            event_pump: None,
+            find_event_handle,
 
             current_status: super::DeviceAccessStatus::Unknown,
         };
@@ -97,7 +162,7 @@ impl U3VDeviceModule {
     }
 
     pub(crate) fn device_info(&self) -> &u3v::DeviceInfo {
-        todo!()
+        &self.device_info
     }
 
     /// Reflect current_status to `DeviceAccessStatusReg` in VM.
@@ -105,6 +170,8 @@ impl U3VDeviceModule {
     /// See GenTL specification for more details.
     pub(crate) fn reflect_status(&mut self) {
         self.vm
+            .get_mut()
+            .unwrap()
             .write::<GenApiReg::DeviceAccessStatus>(self.current_status as u32)
             .unwrap();
     }
@@ -114,7 +181,12 @@ impl U3VDeviceModule {
     /// calling [`U3VDeviceModule::access_status`].  
     /// See GenTL specification for more details.
     pub(crate) fn access_status(&self) -> DeviceAccessStatus {
-        let raw_value = self.vm.read::<GenApiReg::DeviceAccessStatus>().unwrap() as i32;
+        let raw_value = self
+            .vm
+            .lock()
+            .unwrap()
+            .read::<GenApiReg::DeviceAccessStatus>()
+            .unwrap() as i32;
         // Ok to unwrap because DeviceAccessStatus is RO register.
         DeviceAccessStatus::try_from(raw_value).unwrap()
     }
@@ -136,6 +208,17 @@ impl U3VDeviceModule {
         }
     }
 
+    /// Streaming and events need writes to the device, so they are denied while the device is
+    /// opened read only.
+    fn assert_writable(&self) -> GenTlResult<()> {
+        self.assert_open()?;
+        if self.current_status == DeviceAccessStatus::OpenReadOnly {
+            Err(GenTlError::AccessDenied)
+        } else {
+            Ok(())
+        }
+    }
+
     fn is_opened(&self) -> bool {
         let current_status: DeviceAccessStatus = self.current_status;
         current_status.is_opened()
@@ -147,10 +230,87 @@ impl U3VDeviceModule {
     }
 
     fn initialize_vm(&mut self) -> GenTlResult<()> {
-        todo!()
+        let display_name = self.display_name()?;
+        let vm = self.vm.get_mut().unwrap();
+        vm.write::<GenApiReg::DeviceID>(self.port_info.id.clone())?;
+        vm.write::<GenApiReg::DeviceVendorName>(self.device_info.vendor_name.clone())?;
+        vm.write::<GenApiReg::DeviceModelName>(self.device_info.model_name.clone())?;
+        vm.write::<GenApiReg::DeviceDisplayName>(display_name)?;
+        vm.write::<GenApiReg::DeviceSerialNumber>(self.device_info.serial_number.clone())?;
+        vm.write::<GenApiReg::DeviceTLVersionMajor>(self.device_info.u3v_version.major as u32)?;
+        vm.write::<GenApiReg::DeviceTLVersionMinor>(self.device_info.u3v_version.minor as u32)?;
+        vm.write::<GenApiReg::StreamSelector>(0)?;
+        vm.write::<GenApiReg::StreamSelectorMax>(0)?;
+        vm.write::<GenApiReg::StreamID>(STREAM_ID.into())?;
+        self.reflect_status();
+
+        Ok(())
+    }
+
+    /// Reads the values which can change while the device is opened from the remote device
+    /// if `range` overlaps their registers.
+    fn reflect_remote_values(
+        &self,
+        vm: &mut genapi::Memory,
+        range: &Range<usize>,
+    ) -> GenTlResult<()> {
+        if overlaps::<GenApiReg::DeviceUserID>(range) {
+            let user_id = self.current_user_defined_name()?.unwrap_or_default();
+            vm.write::<GenApiReg::DeviceUserID>(user_id)?;
+        }
+        if overlaps::<GenApiReg::DeviceLinkSpeed>(range) {
+            let speed = self
+                .opened_remote_device()?
+                .lock()
+                .unwrap()
+                .current_speed()?;
+            vm.write::<GenApiReg::DeviceLinkSpeed>(link_speed(speed))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the user defined name the device currently has, which can be changed through the
+    /// remote device while the device is opened.
+    fn current_user_defined_name(&self) -> GenTlResult<Option<String>> {
+        if self.is_opened() {
+            self.opened_remote_device()?
+                .lock()
+                .unwrap()
+                .user_defined_name()
+        } else {
+            Ok(self.device_info.user_defined_name.clone())
+        }
+    }
+
+    /// Returns the remote device of the opened device.
+    fn opened_remote_device(&self) -> GenTlResult<&Mutex<U3VRemoteDevice>> {
+        self.assert_open()?;
+
+        // Ok to unwrap because the remote device is always set while the device is opened.
+        Ok(self.remote_device.as_ref().unwrap())
     }
 }
 
+/// Returns `true` if `range` overlaps the register `R`.
+fn overlaps<R: Register>(range: &Range<usize>) -> bool {
+    let reg = R::range();
+    reg.start < range.end && range.start < reg.end
+}
+
+/// Returns the signaling rate of the bus in bytes per second.
+fn link_speed(speed: u3v::BusSpeed) -> u64 {
+    const MEGA: u64 = 1_000_000;
+    let bits_per_sec = match speed {
+        u3v::BusSpeed::LowSpeed => 3 * MEGA / 2,
+        u3v::BusSpeed::FullSpeed => 12 * MEGA,
+        u3v::BusSpeed::HighSpeed => 480 * MEGA,
+        u3v::BusSpeed::SuperSpeed => 5000 * MEGA,
+        u3v::BusSpeed::SuperSpeedPlus => 10000 * MEGA,
+    };
+    bits_per_sec / 8
+}
+
 impl Drop for U3VDeviceModule {
     fn drop(&mut self) {
         self.close().ok();
@@ -163,8 +323,11 @@ impl Port for U3VDeviceModule {
 
         let address = address as usize;
         let len = buf.len();
+        let range = address..address + len;
 
-        let data = self.vm.read_raw(address..address + len)?;
+        let mut vm = self.vm.lock().unwrap();
+        self.reflect_remote_values(&mut vm, &range)?;
+        let data = vm.read_raw(range)?;
         buf.copy_from_slice(data);
 
         Ok(len)
@@ -173,7 +336,10 @@ impl Port for U3VDeviceModule {
     fn write(&mut self, address: u64, data: &[u8]) -> GenTlResult<usize> {
         self.assert_open()?;
 
-        self.vm.write_raw(address as usize, data)?;
+        self.vm
+            .get_mut()
+            .unwrap()
+            .write_raw(address as usize, data)?;
         self.handle_events();
 
         Ok(data.len())
@@ -193,12 +359,63 @@ impl Port for U3VDeviceModule {
 }
 
 impl Device for U3VDeviceModule {
-    fn open(&mut self, access_flag: super::DeviceAccessFlag) -> GenTlResult<()> {
-        todo!()
+    fn open(&mut self, access_flag: DeviceAccessFlag) -> GenTlResult<()> {
+        if self.is_opened() {
+            return Err(GenTlError::ResourceInUse);
+        }
+
+        // A U3V device is claimed by a single host, so other hosts can't be given read access
+        // while this host controls the device.
+        let (access, status) = match access_flag {
+            DeviceAccessFlag::ReadOnly => (PortAccess::RO, DeviceAccessStatus::OpenReadOnly),
+            DeviceAccessFlag::Exclusive => (PortAccess::RW, DeviceAccessStatus::OpenReadWrite),
+            DeviceAccessFlag::Control => return Err(GenTlError::AccessDenied),
+        };
+
+        if let Err(err) = self.camera.open() {
+            self.current_status = match &err {
+                cameleon::CameleonError::ControlError(cameleon::ControlError::Busy) => {
+                    DeviceAccessStatus::Busy
+                }
+                _ => DeviceAccessStatus::NoAccess,
+            };
+            return Err(err.into());
+        }
+
+        match U3VRemoteDevice::new(self.camera.ctrl.clone(), access) {
+            Ok(remote_device) => {
+                self.remote_device = Some(Box::new(Mutex::new(remote_device)));
+                self.current_status = status;
+                Ok(())
+            }
+            Err(err) => {
+                self.camera.close().ok();
+                self.current_status = DeviceAccessStatus::NoAccess;
+                Err(err)
+            }
+        }
     }
 
     fn close(&mut self) -> GenTlResult<()> {
-        todo!()
+        if !self.is_opened() {
+            return Ok(());
+        }
+
+        let ds_res = self.data_stream.lock().unwrap().close();
+        // Events are unregistered when the module is closed.
+        self.unregister_event(EventType::RemoteDevice).ok();
+        self.remote_device = None;
+        match self.camera.close() {
+            Ok(()) => {
+                self.current_status = DeviceAccessStatus::ReadWrite;
+                ds_res
+            }
+            Err(err) => {
+                // The device can't be closed gracefully only if it's not reachable.
+                self.current_status = DeviceAccessStatus::NoAccess;
+                Err(err.into())
+            }
+        }
     }
 
     fn device_id(&self) -> &str {
@@ -211,12 +428,70 @@ impl Device for U3VDeviceModule {
         Ok(self.remote_device.as_ref().unwrap().as_ref())
     }
 
+    fn num_data_streams(&self) -> GenTlResult<usize> {
+        self.assert_open()?;
+
+        Ok(1)
+    }
+
+    fn data_stream_id(&self, index: usize) -> GenTlResult<&str> {
+        self.assert_open()?;
+
+        if index == 0 {
+            Ok(STREAM_ID)
+        } else {
+            Err(GenTlError::InvalidIndex)
+        }
+    }
+
+    fn data_stream(&self, stream_id: &str) -> GenTlResult<&Mutex<dyn DataStream>> {
+        self.assert_writable()?;
+
+        if stream_id == STREAM_ID {
+            Ok(self.data_stream.as_ref())
+        } else {
+            Err(GenTlError::InvalidId(stream_id.into()))
+        }
+    }
+
+    fn register_event(&mut self, event_type: EventType) -> GenTlResult<Arc<dyn Event>> {
+        self.assert_writable()?;
+
+        match event_type {
+            EventType::RemoteDevice => {
+                if self.event_pump.is_some() {
+                    return Err(GenTlError::ResourceInUse);
+                }
+                let handle =
+                    (self.find_event_handle)(&self.device_info)?.ok_or(GenTlError::NotAvailable)?;
+                let pump = EventPump::start(handle, self.camera.ctrl.clone())?;
+                let event = pump.event.clone();
+                self.event_pump = Some(pump);
+                Ok(event)
+            }
+
+            _ => Err(GenTlError::NotImplemented),
+        }
+    }
+
+    fn unregister_event(&mut self, event_type: EventType) -> GenTlResult<()> {
+        match event_type {
+            EventType::RemoteDevice => self
+                .event_pump
+                .take()
+                .ok_or(GenTlError::NotInitialized)?
+                .stop(),
+
+            _ => Err(GenTlError::NotImplemented),
+        }
+    }
+
     fn vendor_name(&self) -> GenTlResult<String> {
-        todo!()
+        Ok(self.device_info.vendor_name.clone())
     }
 
     fn model_name(&self) -> GenTlResult<String> {
-        todo!()
+        Ok(self.device_info.model_name.clone())
     }
 
     fn display_name(&self) -> GenTlResult<String> {
@@ -231,11 +506,12 @@ impl Device for U3VDeviceModule {
     }
 
     fn device_access_status(&self) -> DeviceAccessStatus {
-        todo!()
+        self.current_status
     }
 
     fn user_defined_name(&self) -> GenTlResult<String> {
-        todo!()
+        self.current_user_defined_name()?
+            .ok_or(GenTlError::NotAvailable)
     }
 
     fn serial_number(&self) -> GenTlResult<String> {
@@ -243,44 +519,405 @@ impl Device for U3VDeviceModule {
     }
 
     fn device_version(&self) -> GenTlResult<String> {
-        todo!()
+        Ok(self.device_info.device_version.clone())
     }
 
     fn timespamp_frequency(&self) -> GenTlResult<u64> {
-        todo!()
+        let remote_device = self.opened_remote_device()?.lock().unwrap();
+        // The increment is the tick period of the timestamp counter in ns.
+        let increment = remote_device.timestamp_increment()?;
+        if increment == 0 {
+            return Err(GenTlError::InvalidValue(
+                "device reports zero timestamp increment".into(),
+            ));
+        }
+
+        Ok(1_000_000_000 / increment)
+    }
+}
+
+/// Receives event packets from the device and pushes them to the remote device event queue in a
+/// background thread.
+struct EventPump {
+    event: Arc<EventQueue>,
+    is_running: Arc<AtomicBool>,
+    worker: thread::JoinHandle<(EventHandle, SharedControlHandle)>,
+}
+
+impl EventPump {
+    fn start(mut handle: EventHandle, mut ctrl: SharedControlHandle) -> GenTlResult<Self> {
+        handle.open()?;
+        handle.enable(&mut ctrl).map_err(|err| match err {
+            // The device doesn't have the event interface.
+            ControlError::InvalidDevice(..) => GenTlError::NotAvailable,
+            _ => err.into(),
+        })?;
+
+        let event = Arc::new(EventQueue::new(
+            EventType::RemoteDevice,
+            handle.maximum_packet_size(),
+        ));
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let worker = {
+            let event = event.clone();
+            let is_running = is_running.clone();
+            thread::spawn(move || {
+                while is_running.load(Ordering::Relaxed) {
+                    match handle.recv_packet(EVENT_POLLING_INTERVAL) {
+                        Ok(packet) => event.push(EventData::RemoteDevice {
+                            packet: packet.to_vec(),
+                        }),
+                        Err(ControlError::Disconnected) => {
+                            event.push(EventData::error(&ControlError::Disconnected.into()));
+                            break;
+                        }
+                        // Timeout or a broken packet, just retry.
+                        Err(_) => {}
+                    }
+                }
+                (handle, ctrl)
+            })
+        };
+
+        Ok(Self {
+            event,
+            is_running,
+            worker,
+        })
+    }
+
+    /// Stop the pump and disable the event interface of the device.
+    fn stop(self) -> GenTlResult<()> {
+        self.is_running.store(false, Ordering::Relaxed);
+        self.event.kill();
+        let (mut handle, mut ctrl) = self
+            .worker
+            .join()
+            .map_err(|_| GenTlError::Error("event pump panicked".into()))?;
+
+        let disable_res = handle.disable(&mut ctrl);
+        handle.close()?;
+        disable_res?;
+        Ok(())
     }
 }
 
-pub(crate) struct U3VRemoteDevice {}
+/// Interval to check whether the event pump is requested to stop.
+const EVENT_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The remote device port, which accesses the registers of the device itself.
+pub(crate) struct U3VRemoteDevice {
+    handle: SharedControlHandle,
+    abrm: Abrm,
+    sbrm: Sbrm,
+    port_info: PortInfo,
+    xml_infos: Vec<XmlInfo>,
+}
 
 impl U3VRemoteDevice {
-    fn new(handle: u3v::ControlHandle) -> GenTlResult<Self> {
-        todo!()
+    fn new(mut handle: SharedControlHandle, access: PortAccess) -> GenTlResult<Self> {
+        let abrm = Abrm::new(&mut handle)?;
+        let sbrm = abrm.sbrm(&mut handle)?;
+        let port_info = Self::port_info(&mut handle, &abrm, access)?;
+        let xml_infos = Self::xml_infos(&mut handle, &abrm)?;
+
+        Ok(Self {
+            handle,
+            abrm,
+            sbrm,
+            port_info,
+            xml_infos,
+        })
+    }
+
+    fn port_info(
+        handle: &mut SharedControlHandle,
+        abrm: &Abrm,
+        access: PortAccess,
+    ) -> GenTlResult<PortInfo> {
+        let device_info = handle.device_info();
+
+        Ok(PortInfo {
+            id: device_info.guid,
+            vendor: abrm.manufacturer_name(handle)?,
+            model: abrm.model_name(handle)?,
+            tl_type: TlType::USB3Vision,
+            module_type: ModuleType::RemoteDevice,
+            endianness: Endianness::LE,
+            access,
+            version: abrm.gencp_version(handle)?,
+            port_name: REMOTE_DEVICE_PORT_NAME.into(),
+        })
+    }
+
+    fn xml_infos(handle: &mut SharedControlHandle, abrm: &Abrm) -> GenTlResult<Vec<XmlInfo>> {
+        let table = abrm.manifest_table(handle)?;
+
+        let mut xml_infos = vec![];
+        for ent in table.entries(handle)? {
+            let file_info = ent.file_info(handle)?;
+            if file_info.file_type()? != GenICamFileType::DeviceXml {
+                continue;
+            }
+
+            let size = usize::try_from(ent.file_size(handle)?).map_err(|_| {
+                GenTlError::InvalidValue("the size of the GenApi XML is too large".into())
+            })?;
+            xml_infos.push(XmlInfo {
+                location: XmlLocation::RegisterMap {
+                    address: ent.file_address(handle)?,
+                    size,
+                },
+                schema_version: file_info.schema_version(),
+                file_version: ent.genicam_file_version(handle)?,
+                sha1_hash: ent.sha1_hash(handle)?,
+                compressed: file_info.compression_type()?,
+            });
+        }
+
+        // The newest XML comes first so that consumers which only see the first URL use it.
+        xml_infos.sort_by(|a, b| b.file_version.cmp(&a.file_version));
+        Ok(xml_infos)
     }
 
-    fn port_info(handle: &u3v::ControlHandle) -> GenTlResult<PortInfo> {
-        todo!()
+    fn timestamp_increment(&self) -> GenTlResult<u64> {
+        Ok(self.abrm.timestamp_increment(&mut self.handle.clone())?)
     }
 
-    fn xml_infos(handle: &u3v::ControlHandle) -> GenTlResult<Vec<XmlInfo>> {
-        todo!()
+    fn user_defined_name(&self) -> GenTlResult<Option<String>> {
+        Ok(self.abrm.user_defined_name(&mut self.handle.clone())?)
+    }
+
+    /// Bus speed negotiated with the host.
+    fn current_speed(&self) -> GenTlResult<u3v::BusSpeed> {
+        Ok(self.sbrm.current_speed(&mut self.handle.clone())?)
     }
 }
 
 impl Port for U3VRemoteDevice {
     fn read(&self, address: u64, buf: &mut [u8]) -> GenTlResult<usize> {
-        todo!()
+        if !self.port_info.access.is_readable() {
+            return Err(GenTlError::AccessDenied);
+        }
+
+        // `SharedControlHandle` is a reference counted handle, so cloning it is cheap.
+        self.handle.clone().read(address, buf)?;
+        Ok(buf.len())
     }
 
     fn write(&mut self, address: u64, data: &[u8]) -> GenTlResult<usize> {
-        todo!()
+        if !self.port_info.access.is_writable() {
+            return Err(GenTlError::AccessDenied);
+        }
+
+        self.handle.write(address, data)?;
+        Ok(data.len())
     }
 
     fn port_info(&self) -> GenTlResult<&PortInfo> {
-        todo!()
+        Ok(&self.port_info)
     }
 
     fn xml_infos(&self) -> GenTlResult<&[XmlInfo]> {
-        todo!()
+        Ok(&self.xml_infos)
+    }
+}
+
+/// Port name of the remote device defined by GenTL SFNC.
+const REMOTE_DEVICE_PORT_NAME: &str = "Device";
+
+#[cfg(all(test, feature = "emulator"))]
+mod tests {
+    use cameleon::u3v::CameraFilter;
+    use cameleon_device::emulator::EmulatorBuilder;
+
+    use super::*;
+
+    /// Builds an emulator with the serial number, and returns the device module of it.
+    fn device(serial: &str, user_defined_name: Option<&str>) -> U3VDeviceModule {
+        let mut builder = EmulatorBuilder::new().serial_number(serial).unwrap();
+        if let Some(name) = user_defined_name {
+            builder = builder.user_defined_name(name).unwrap();
+        }
+        builder.build();
+
+        let filter = CameraFilter::new().serial_number(serial);
+        let camera = u3v::enumerate_emulated_cameras_with(&filter)
+            .unwrap()
+            .pop()
+            .unwrap();
+        U3VDeviceModule::new_emulated(camera.convert_into()).unwrap()
+    }
+
+    #[test]
+    fn test_open_close() {
+        let mut dev = device("GTLDEV00", None);
+        assert_eq!(dev.device_access_status(), DeviceAccessStatus::Unknown);
+        assert!(matches!(
+            dev.remote_device(),
+            Err(GenTlError::NotInitialized)
+        ));
+
+        dev.open(DeviceAccessFlag::Exclusive).unwrap();
+        assert_eq!(
+            dev.device_access_status(),
+            DeviceAccessStatus::OpenReadWrite
+        );
+        assert!(matches!(
+            dev.open(DeviceAccessFlag::Exclusive),
+            Err(GenTlError::ResourceInUse)
+        ));
+        assert_eq!(dev.num_data_streams().unwrap(), 1);
+        assert_eq!(dev.data_stream_id(0).unwrap(), STREAM_ID);
+        assert!(matches!(
+            dev.data_stream_id(1),
+            Err(GenTlError::InvalidIndex)
+        ));
+        assert!(dev.data_stream(STREAM_ID).is_ok());
+
+        // The remote device port reads the ABRM of the device.
+        let remote_device = dev.remote_device().unwrap();
+        let mut buf = [0; 4];
+        remote_device.lock().unwrap().read(0, &mut buf).unwrap();
+        {
+            let remote_device = remote_device.lock().unwrap();
+            let port_info = remote_device.port_info().unwrap();
+            assert!(matches!(port_info.access, PortAccess::RW));
+            assert!(matches!(port_info.module_type, ModuleType::RemoteDevice));
+        }
+
+        dev.close().unwrap();
+        assert_eq!(dev.device_access_status(), DeviceAccessStatus::ReadWrite);
+        assert!(matches!(
+            dev.read(0, &mut buf),
+            Err(GenTlError::NotInitialized)
+        ));
+        // Closing a closed device is a no-op.
+        dev.close().unwrap();
+
+        // The device can be opened again.
+        dev.open(DeviceAccessFlag::Exclusive).unwrap();
+        dev.close().unwrap();
+    }
+
+    #[test]
+    fn test_access_flags() {
+        let mut dev = device("GTLDEV01", None);
+
+        // Other hosts can't share a U3V device.
+        assert!(matches!(
+            dev.open(DeviceAccessFlag::Control),
+            Err(GenTlError::AccessDenied)
+        ));
+        assert!(!dev.is_opened());
+
+        dev.open(DeviceAccessFlag::ReadOnly).unwrap();
+        assert_eq!(dev.device_access_status(), DeviceAccessStatus::OpenReadOnly);
+        {
+            let mut remote_device = dev.remote_device().unwrap().lock().unwrap();
+            assert!(matches!(
+                remote_device.port_info().unwrap().access,
+                PortAccess::RO
+            ));
+            let mut buf = [0; 4];
+            remote_device.read(0, &mut buf).unwrap();
+            assert!(matches!(
+                remote_device.write(0, &buf),
+                Err(GenTlError::AccessDenied)
+            ));
+        }
+        // Streaming and events write to the device.
+        assert!(matches!(
+            dev.data_stream(STREAM_ID),
+            Err(GenTlError::AccessDenied)
+        ));
+        assert!(matches!(
+            dev.register_event(EventType::RemoteDevice),
+            Err(GenTlError::AccessDenied)
+        ));
+        dev.close().unwrap();
+
+        dev.open(DeviceAccessFlag::Exclusive).unwrap();
+        let mut remote_device = dev.remote_device().unwrap().lock().unwrap();
+        let mut buf = [0; 4];
+        remote_device.read(0, &mut buf).unwrap();
+        assert!(matches!(
+            remote_device.port_info().unwrap().access,
+            PortAccess::RW
+        ));
+    }
+
+    #[test]
+    fn test_device_info() {
+        let mut dev = device("GTLDEV02", Some("Left"));
+        let info = dev.device_info().clone();
+
+        assert_eq!(dev.device_id(), info.guid);
+        assert_eq!(dev.vendor_name().unwrap(), info.vendor_name);
+        assert_eq!(dev.model_name().unwrap(), info.model_name);
+        assert_eq!(dev.serial_number().unwrap(), "GTLDEV02");
+        assert_eq!(dev.user_defined_name().unwrap(), "Left");
+        assert_eq!(dev.device_version().unwrap(), info.device_version);
+        assert_eq!(
+            dev.display_name().unwrap(),
+            format!("{} {} ({})", info.vendor_name, info.model_name, info.guid)
+        );
+        assert!(matches!(dev.tl_type(), TlType::USB3Vision));
+
+        // The timestamp frequency is read from the remote device.
+        assert!(matches!(
+            dev.timespamp_frequency(),
+            Err(GenTlError::NotInitialized)
+        ));
+        dev.open(DeviceAccessFlag::Exclusive).unwrap();
+        assert!(dev.timespamp_frequency().unwrap() > 0);
+
+        // The module port exposes the same information through the VM.
+        assert_eq!(
+            dev.vm
+                .lock()
+                .unwrap()
+                .read::<GenApiReg::DeviceSerialNumber>()
+                .unwrap(),
+            "GTLDEV02"
+        );
+        dev.close().unwrap();
+    }
+
+    #[test]
+    fn test_live_values() {
+        let mut dev = device("GTLDEV03", Some("Left"));
+        dev.open(DeviceAccessFlag::Exclusive).unwrap();
+
+        let read_reg = |dev: &U3VDeviceModule, range: Range<usize>| {
+            let mut buf = vec![0; range.len()];
+            dev.read(range.start as u64, &mut buf).unwrap();
+            buf
+        };
+
+        let user_id = read_reg(&dev, GenApiReg::DeviceUserID::range());
+        assert!(user_id.starts_with(b"Left\0"));
+
+        // The name changed through the control handle is visible without reopening the device.
+        let mut ctrl = dev.camera.ctrl.clone();
+        let abrm = Abrm::new(&mut ctrl).unwrap();
+        abrm.set_user_defined_name(&mut ctrl, "Right").unwrap();
+        let user_id = read_reg(&dev, GenApiReg::DeviceUserID::range());
+        assert!(user_id.starts_with(b"Right\0"));
+        assert_eq!(dev.user_defined_name().unwrap(), "Right");
+
+        // The link speed is the speed negotiated with the host.
+        let speed = abrm
+            .sbrm(&mut ctrl)
+            .unwrap()
+            .current_speed(&mut ctrl)
+            .unwrap();
+        let link_speed_reg = read_reg(&dev, GenApiReg::DeviceLinkSpeed::range());
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&link_speed_reg);
+        assert_eq!(u64::from_le_bytes(bytes), link_speed(speed));
+
+        dev.close().unwrap();
     }
 }
@@ -6,8 +6,9 @@ use cameleon_impl::memory::{memory, prelude::*, register_map};
 use const_format::formatcp;
 
 use GenApiReg::{
-    DeviceAccessStatus, DeviceID, DeviceModelName, DeviceVendorName, StreamID, StreamSelector,
-    StreamSelectorMax,
+    DeviceAccessStatus, DeviceDisplayName, DeviceID, DeviceLinkSpeed, DeviceModelName,
+    DeviceSerialNumber, DeviceTLVersionMajor, DeviceTLVersionMinor, DeviceUserID, DeviceVendorName,
+    StreamID, StreamSelector, StreamSelectorMax,
 };
 
 use crate::imp::{
@@ -39,6 +40,30 @@ pub(super) enum GenApiReg {
     #[register(len = 4, access = RO, ty = u32)]
     DeviceAccessStatus,
 
+    /// User readable name of the remote device.
+    #[register(len = 256, access = RO, ty = String)]
+    DeviceDisplayName,
+
+    /// Serial number of the remote device.
+    #[register(len = 64, access = RO, ty = String)]
+    DeviceSerialNumber,
+
+    /// User-programmable device identifier of the remote device.
+    #[register(len = 64, access = RO, ty = String)]
+    DeviceUserID,
+
+    /// Major version number of the U3V specification the remote device complies with.
+    #[register(len = 4, access = RO, ty = u32)]
+    DeviceTLVersionMajor,
+
+    /// Minor version number of the U3V specification the remote device complies with.
+    #[register(len = 4, access = RO, ty = u32)]
+    DeviceTLVersionMinor,
+
+    /// Speed negotiated on the link to the remote device in bytes per second.
+    #[register(len = 8, access = RO, ty = u64)]
+    DeviceLinkSpeed,
+
     /// Selector for the different stream channels.
     #[register(len = 4, access = RW, ty = u32)]
     StreamSelector,
@@ -52,7 +77,7 @@ pub(super) enum GenApiReg {
     StreamID,
 }
 
-#[register_map(base=0, endianness=LE)]
+#[register_map(base=GENAPI_XML_ADDRESS, endianness=LE)]
 pub(super) enum GenApiXml {
     #[register(len = GENAPI_XML_LENGTH, access = RO, ty = String)]
     Xml = GENAPI_XML,
@@ -113,8 +138,15 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pFeature>DeviceID</pFeature>
         <pFeature>DeviceVendorName</pFeature>
         <pFeature>DeviceModelName</pFeature>
+        <pFeature>DeviceDisplayName</pFeature>
+        <pFeature>DeviceSerialNumber</pFeature>
+        <pFeature>DeviceUserID</pFeature>
         <pFeature>DeviceType</pFeature>
+        <pFeature>DeviceTLVersionMajor</pFeature>
+        <pFeature>DeviceTLVersionMinor</pFeature>
         <pFeature>DeviceAccessStatus</pFeature>
+        <pFeature>DeviceEndianessMechanism</pFeature>
+        <pFeature>DeviceLinkSpeed</pFeature>
     </Category>
 
     <StringReg Name="DeviceID" NameSpace="Standard">
@@ -144,6 +176,33 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pPort>{PORT_NAME}</pPort>
     </StringReg>
 
+    <StringReg Name="DeviceDisplayName" NameSpace="Standard">
+        <Description>User readable name of the device.</Description>
+        <Visibility>Beginner</Visibility>
+        <Address>{device_display_name_addr}</Address>
+        <Length>{device_display_name_len}</Length>
+        <AccessMode>{device_display_name_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceSerialNumber" NameSpace="Standard">
+        <Description>Serial number of the remote device.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_serial_number_addr}</Address>
+        <Length>{device_serial_number_len}</Length>
+        <AccessMode>{device_serial_number_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceUserID" NameSpace="Standard">
+        <Description>User-programmable device identifier of the remote device.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_user_id_addr}</Address>
+        <Length>{device_user_id_len}</Length>
+        <AccessMode>{device_user_id_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
     <Enumeration Name="DeviceType" NameSpace="Standard">
         <Description>Transport layer type of the device.</Description>
         <Visibility>Expert</Visibility>
@@ -154,6 +213,26 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Value>0</Value>
     </Enumeration>
 
+    <IntReg Name="DeviceTLVersionMajor" NameSpace="Standard">
+        <Description>Major version number of the transport layer specification the remote device complies with.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_tl_version_major_addr}</Address>
+        <Length>{device_tl_version_major_len}</Length>
+        <AccessMode>{device_tl_version_major_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="DeviceTLVersionMinor" NameSpace="Standard">
+        <Description>Minor version number of the transport layer specification the remote device complies with.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_tl_version_minor_addr}</Address>
+        <Length>{device_tl_version_minor_len}</Length>
+        <AccessMode>{device_tl_version_minor_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
     <Enumeration Name="DeviceAccessStatus" NameSpace="Standard">
         <Description>Gives the device's access status at the moment of the last execution of the DeviceUpdateList command.</Description>
         <Visibility>Expert</Visibility>
@@ -204,6 +283,31 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Endianess>LittleEndian</Endianess>
     </IntReg>
 
+    <Enumeration Name="DeviceEndianessMechanism" NameSpace="Standard">
+        <Description>Identifies the endianness handling mode.</Description>
+        <Visibility>Guru</Visibility>
+        <EnumEntry Name="Legacy" NameSpace="Standard">
+            <Description>Handling the device endianness according to GenICam Schema 1.0.</Description>
+            <Value>0</Value>
+        </EnumEntry>
+        <EnumEntry Name="Standard" NameSpace="Standard">
+            <Description>Handling the device endianness according to GenICam Schema 1.1 and later.</Description>
+            <Value>1</Value>
+        </EnumEntry>
+        <Value>1</Value>
+    </Enumeration>
+
+    <IntReg Name="DeviceLinkSpeed" NameSpace="Standard">
+        <Description>Indicates the speed of transmission negotiated on the link in bytes per second.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_link_speed_addr}</Address>
+        <Length>{device_link_speed_len}</Length>
+        <AccessMode>{device_link_speed_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Unit>Bps</Unit>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
     <Category Name="StreamEnumeration" NameSpace="Standard">
         <Description>Category that contains all Stream Enumeration features of the Device module.</Description>
         <Visibility>Beginner</Visibility>
@@ -257,7 +361,22 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     device_model_name_addr = DeviceModelName::ADDRESS,
     device_model_name_len = DeviceModelName::LENGTH,
     device_model_name_access = DeviceModelName::ACCESS_RIGHT.as_str(),
+    device_display_name_addr = DeviceDisplayName::ADDRESS,
+    device_display_name_len = DeviceDisplayName::LENGTH,
+    device_display_name_access = DeviceDisplayName::ACCESS_RIGHT.as_str(),
+    device_serial_number_addr = DeviceSerialNumber::ADDRESS,
+    device_serial_number_len = DeviceSerialNumber::LENGTH,
+    device_serial_number_access = DeviceSerialNumber::ACCESS_RIGHT.as_str(),
+    device_user_id_addr = DeviceUserID::ADDRESS,
+    device_user_id_len = DeviceUserID::LENGTH,
+    device_user_id_access = DeviceUserID::ACCESS_RIGHT.as_str(),
     device_type = DEVICE_TYPE.as_str(),
+    device_tl_version_major_addr = DeviceTLVersionMajor::ADDRESS,
+    device_tl_version_major_len = DeviceTLVersionMajor::LENGTH,
+    device_tl_version_major_access = DeviceTLVersionMajor::ACCESS_RIGHT.as_str(),
+    device_tl_version_minor_addr = DeviceTLVersionMinor::ADDRESS,
+    device_tl_version_minor_len = DeviceTLVersionMinor::LENGTH,
+    device_tl_version_minor_access = DeviceTLVersionMinor::ACCESS_RIGHT.as_str(),
     device_access_status_unknown_str = super::DeviceAccessStatus::Unknown.as_str(),
     device_access_status_unknown_int = super::DeviceAccessStatus::Unknown as i32,
     device_access_status_readwrite_str = super::DeviceAccessStatus::ReadWrite.as_str(),
@@ -275,6 +394,9 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     device_access_status_addr = DeviceAccessStatus::ADDRESS,
     device_access_status_len = DeviceAccessStatus::LENGTH,
     device_access_status_access = DeviceAccessStatus::ACCESS_RIGHT.as_str(),
+    device_link_speed_addr = DeviceLinkSpeed::ADDRESS,
+    device_link_speed_len = DeviceLinkSpeed::LENGTH,
+    device_link_speed_access = DeviceLinkSpeed::ACCESS_RIGHT.as_str(),
     stream_selector_addr = StreamSelector::ADDRESS,
     stream_selector_len = StreamSelector::LENGTH,
     stream_selector_access = StreamSelector::ACCESS_RIGHT.as_str(),
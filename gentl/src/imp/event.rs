@@ -0,0 +1,313 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{
+    collections::VecDeque,
+    sync::{Condvar, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{GenTlError, GenTlResult};
+
+use super::stream::{BufferId, RawPtr};
+
+/// Type of events defined in GenTL specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum EventType {
+    Error,
+    NewBuffer,
+    FeatureInvalidate,
+    FeatureChange,
+    RemoteDevice,
+    Module,
+}
+
+/// Data of an event delivered to the consumer.
+pub(crate) enum EventData {
+    /// An asynchronous error occurred in the module.
+    Error { code: i32, message: String },
+
+    /// A buffer is filled and moved to the output queue.
+    NewBuffer { id: BufferId, user_ptr: RawPtr },
+
+    /// Raw event packet sent from the remote device.
+    RemoteDevice { packet: Vec<u8> },
+}
+
+impl EventData {
+    /// Build error event data. The message is truncated to fit in [`MAX_ERROR_MESSAGE_LEN`]
+    /// bytes including the null terminator.
+    pub(crate) fn error(err: &GenTlError) -> Self {
+        let mut message = format!("{}", err);
+        let mut len = message.len().min(MAX_ERROR_MESSAGE_LEN - 1);
+        while !message.is_char_boundary(len) {
+            len -= 1;
+        }
+        message.truncate(len);
+
+        Self::Error {
+            code: err.code(),
+            message,
+        }
+    }
+
+    /// Size of the data in bytes when it is copied to the consumer's buffer.
+    pub(crate) fn size(&self) -> usize {
+        match self {
+            Self::Error { message, .. } => {
+                // Error code followed by the null terminated message.
+                std::mem::size_of::<i32>() + message.len() + 1
+            }
+            Self::NewBuffer { .. } => NEW_BUFFER_DATA_SIZE,
+            Self::RemoteDevice { packet } => packet.len(),
+        }
+    }
+}
+
+/// Size of `EVENT_NEW_BUFFER_DATA` which consists of a buffer handle and a user pointer.
+pub(crate) const NEW_BUFFER_DATA_SIZE: usize = std::mem::size_of::<*mut libc::c_void>() * 2;
+
+/// Maximum length of error messages of error events.
+pub(crate) const MAX_ERROR_MESSAGE_LEN: usize = 256;
+
+pub(crate) trait Event: Send + Sync {
+    /// Type of the event.
+    fn event_type(&self) -> EventType;
+
+    /// Pop the oldest data from the event queue.
+    /// Block until the data arrives, or `timeout` elapses if it's `Some`.
+    ///
+    /// The data is left in the queue and [`GenTlError::BufferTooSmall`] is returned if the data
+    /// doesn't fit in `capacity` bytes.
+    fn get_data(&self, timeout: Option<Duration>, capacity: usize) -> GenTlResult<EventData>;
+
+    /// Abort a wait in [`Event::get_data`].
+    /// If no thread waits, the next call to [`Event::get_data`] is aborted.
+    fn kill(&self);
+
+    /// Discard all data in the event queue.
+    fn flush(&self);
+
+    /// Number of data in the event queue.
+    fn num_in_queue(&self) -> usize;
+
+    /// Number of events fired since the event is registered.
+    fn num_fired(&self) -> u64;
+
+    /// Maximum size of the data in bytes.
+    fn max_data_size(&self) -> usize;
+
+    /// Maximum size of the data info in bytes.
+    fn max_info_data_size(&self) -> usize {
+        self.max_data_size()
+    }
+}
+
+/// An event queue which is filled by the producer.
+pub(crate) struct EventQueue {
+    event_type: EventType,
+    max_data_size: usize,
+    state: Mutex<QueueState>,
+    cond: Condvar,
+}
+
+struct QueueState {
+    queue: VecDeque<EventData>,
+    num_fired: u64,
+    num_kill: usize,
+}
+
+impl EventQueue {
+    pub(crate) fn new(event_type: EventType, max_data_size: usize) -> Self {
+        Self {
+            event_type,
+            max_data_size,
+            state: Mutex::new(QueueState {
+                queue: VecDeque::new(),
+                num_fired: 0,
+                num_kill: 0,
+            }),
+            cond: Condvar::new(),
+        }
+    }
+
+    /// Push the data to the queue and wake up a waiting thread.
+    pub(crate) fn push(&self, data: EventData) {
+        let mut state = self.state.lock().unwrap();
+        state.queue.push_back(data);
+        state.num_fired += 1;
+        self.cond.notify_one();
+    }
+}
+
+impl Event for EventQueue {
+    fn event_type(&self) -> EventType {
+        self.event_type
+    }
+
+    fn get_data(&self, timeout: Option<Duration>, capacity: usize) -> GenTlResult<EventData> {
+        let state = self.state.lock().unwrap();
+        let mut state = wait_until(&self.cond, state, timeout, |state| {
+            if state.num_kill > 0 {
+                state.num_kill -= 1;
+                return Some(Err(GenTlError::Abort));
+            }
+            let size = state.queue.front()?.size();
+            if size > capacity {
+                Some(Err(GenTlError::BufferTooSmall))
+            } else {
+                Some(Ok(()))
+            }
+        })?;
+
+        // Ok to unwrap because the queue is checked to be non-empty above.
+        Ok(state.queue.pop_front().unwrap())
+    }
+
+    fn kill(&self) {
+        self.state.lock().unwrap().num_kill += 1;
+        self.cond.notify_all();
+    }
+
+    fn flush(&self) {
+        self.state.lock().unwrap().queue.clear();
+    }
+
+    fn num_in_queue(&self) -> usize {
+        self.state.lock().unwrap().queue.len()
+    }
+
+    fn num_fired(&self) -> u64 {
+        self.state.lock().unwrap().num_fired
+    }
+
+    fn max_data_size(&self) -> usize {
+        self.max_data_size
+    }
+}
+
+/// Block until `check` returns `Some`, then returns the guard or the error returned by `check`.
+/// [`GenTlError::Timeout`] is returned if `timeout` elapses.
+pub(crate) fn wait_until<'a, T>(
+    cond: &Condvar,
+    mut guard: MutexGuard<'a, T>,
+    timeout: Option<Duration>,
+    mut check: impl FnMut(&mut T) -> Option<GenTlResult<()>>,
+) -> GenTlResult<MutexGuard<'a, T>> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+        if let Some(res) = check(&mut guard) {
+            return res.map(|_| guard);
+        }
+
+        guard = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(GenTlError::Timeout);
+                }
+                cond.wait_timeout(guard, deadline - now).unwrap().0
+            }
+            None => cond.wait(guard).unwrap(),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use super::*;
+
+    fn remote_device(packet: &[u8]) -> EventData {
+        EventData::RemoteDevice {
+            packet: packet.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_push_and_get_data() {
+        let queue = EventQueue::new(EventType::RemoteDevice, 16);
+        queue.push(remote_device(&[1, 2]));
+        queue.push(remote_device(&[3]));
+        assert_eq!(queue.num_in_queue(), 2);
+        assert_eq!(queue.num_fired(), 2);
+
+        for expected in &[vec![1, 2], vec![3]] {
+            match queue.get_data(Some(Duration::from_millis(0)), 16).unwrap() {
+                EventData::RemoteDevice { packet } => assert_eq!(&packet, expected),
+                _ => panic!("unexpected event data"),
+            }
+        }
+        assert_eq!(queue.num_in_queue(), 0);
+        assert_eq!(queue.num_fired(), 2);
+    }
+
+    #[test]
+    fn test_get_data_timeout() {
+        let queue = EventQueue::new(EventType::RemoteDevice, 16);
+        assert!(matches!(
+            queue.get_data(Some(Duration::from_millis(10)), 16),
+            Err(GenTlError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        let queue = EventQueue::new(EventType::RemoteDevice, 16);
+        queue.push(remote_device(&[1, 2, 3, 4]));
+
+        assert!(matches!(
+            queue.get_data(None, 3),
+            Err(GenTlError::BufferTooSmall)
+        ));
+        // The data is left in the queue.
+        assert_eq!(queue.num_in_queue(), 1);
+        assert!(queue.get_data(None, 4).is_ok());
+    }
+
+    #[test]
+    fn test_kill() {
+        let queue = Arc::new(EventQueue::new(EventType::RemoteDevice, 16));
+
+        // Kill without a waiting thread aborts the next wait only.
+        queue.kill();
+        assert!(matches!(queue.get_data(None, 16), Err(GenTlError::Abort)));
+        assert!(matches!(
+            queue.get_data(Some(Duration::from_millis(0)), 16),
+            Err(GenTlError::Timeout)
+        ));
+
+        // Kill wakes up a waiting thread.
+        let waiter = {
+            let queue = queue.clone();
+            thread::spawn(move || matches!(queue.get_data(None, 16), Err(GenTlError::Abort)))
+        };
+        thread::sleep(Duration::from_millis(10));
+        queue.kill();
+        assert!(waiter.join().unwrap());
+    }
+
+    #[test]
+    fn test_flush() {
+        let queue = EventQueue::new(EventType::RemoteDevice, 16);
+        queue.push(remote_device(&[1]));
+        queue.push(remote_device(&[2]));
+
+        queue.flush();
+        assert_eq!(queue.num_in_queue(), 0);
+        assert_eq!(queue.num_fired(), 2);
+    }
+
+    #[test]
+    fn test_error_message_truncated() {
+        let data = EventData::error(&GenTlError::InvalidId("é".repeat(MAX_ERROR_MESSAGE_LEN)));
+        match &data {
+            EventData::Error { message, .. } => assert!(message.len() < MAX_ERROR_MESSAGE_LEN),
+            _ => panic!("unexpected event data"),
+        }
+        assert!(data.size() <= std::mem::size_of::<i32>() + MAX_ERROR_MESSAGE_LEN);
+    }
+}
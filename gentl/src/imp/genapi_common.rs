@@ -8,3 +8,7 @@ pub(super) const SCHEME_SUBMINOR_VERSION: u64 = 0;
 
 pub(super) const GENTL_VERSION_MAJOR: u32 = 1;
 pub(super) const GENTL_VERSION_MINOR: u32 = 6;
+
+pub(super) const GENTL_SFNC_VERSION_MAJOR: u32 = 1;
+pub(super) const GENTL_SFNC_VERSION_MINOR: u32 = 1;
+pub(super) const GENTL_SFNC_VERSION_SUBMINOR: u32 = 0;
@@ -14,6 +14,9 @@ pub(crate) mod u3v;
 
 mod u3v_genapi;
 
+#[cfg(all(test, feature = "emulator"))]
+pub(crate) use u3v_genapi::EMULATOR_INTERFACE_ID;
+
 pub(crate) trait Interface: Port {
     fn open(&mut self) -> GenTlResult<()>;
 
@@ -8,6 +8,8 @@ use std::{
 };
 
 use cameleon::genapi::CompressionType;
+#[cfg(feature = "emulator")]
+use cameleon_device::emulator::EmulatorBuilder;
 use cameleon_impl::memory::{prelude::*, MemoryObserver};
 
 use crate::{
@@ -22,23 +24,58 @@ use crate::{
     GenTlError, GenTlResult,
 };
 
+#[cfg(feature = "emulator")]
+use crate::imp::device::u3v::enumerate_emulated_u3v_device;
+
 use super::{u3v_genapi as genapi, Interface};
 use genapi::GenApiReg;
 
+/// Enumerates devices connected to the interface.
+type DeviceEnumerator = fn() -> GenTlResult<Vec<U3VDeviceModule>>;
+
 #[allow(clippy::vec_box)]
 pub(crate) struct U3VInterfaceModule {
     vm: genapi::Memory,
     port_info: PortInfo,
     xml_infos: Vec<XmlInfo>,
     is_opened: bool,
+    display_name: &'static str,
+    enumerate_devices: DeviceEnumerator,
     devices: Vec<Box<Mutex<U3VDeviceModule>>>,
     event_queue: Arc<Mutex<VecDeque<MemoryEvent>>>,
 }
 
 impl U3VInterfaceModule {
     pub(crate) fn new() -> Self {
+        Self::with_devices(
+            genapi::INTERFACE_ID,
+            "U3V Interface Module",
+            enumerate_u3v_device,
+        )
+    }
+
+    /// Builds `num_cameras` emulated cameras and returns the interface to which they are
+    /// connected.
+    #[cfg(feature = "emulator")]
+    pub(crate) fn new_emulated(num_cameras: usize) -> Self {
+        for _ in 0..num_cameras {
+            EmulatorBuilder::new().build();
+        }
+
+        Self::with_devices(
+            genapi::EMULATOR_INTERFACE_ID,
+            "U3V Emulator Interface Module",
+            enumerate_emulated_u3v_device,
+        )
+    }
+
+    fn with_devices(
+        interface_id: &str,
+        display_name: &'static str,
+        enumerate_devices: DeviceEnumerator,
+    ) -> Self {
         let port_info = PortInfo {
-            id: genapi::INTERFACE_ID.into(),
+            id: interface_id.into(),
             vendor: genapi::VENDOR_NAME.into(),
             model: genapi::MODEL_NAME.into(),
             tl_type: genapi::INTERFACE_TYPE,
@@ -77,6 +114,8 @@ impl U3VInterfaceModule {
             port_info,
             xml_infos: vec![xml_info],
             is_opened: false,
+            display_name,
+            enumerate_devices,
 
             devices: vec![],
             event_queue: Arc::new(Mutex::new(VecDeque::new())),
@@ -95,7 +134,7 @@ impl U3VInterfaceModule {
         }
 
         // Enumerate devices connected to the interface.
-        let found_devices = enumerate_u3v_device()?
+        let found_devices = (self.enumerate_devices)()?
             .into_iter()
             .map(|dev| Box::new(Mutex::new(dev)));
 
@@ -147,7 +186,7 @@ impl U3VInterfaceModule {
 
     fn find_device_by_id(&self, id: &str) -> GenTlResult<Option<&Mutex<U3VDeviceModule>>> {
         for dev in &self.devices {
-            if dev.lock().unwrap().port_info()?.id == id {
+            if dev.lock().unwrap().device_id() == id {
                 return Ok(Some(dev.as_ref()));
             }
         }
@@ -156,6 +195,12 @@ impl U3VInterfaceModule {
     }
 
     fn initialize_vm(&mut self) {
+        self.vm
+            .write::<GenApiReg::InterfaceID>(self.port_info.id.clone())
+            .unwrap();
+        self.vm
+            .write::<GenApiReg::InterfaceDisplayName>(self.display_name.into())
+            .unwrap();
         self.vm.write::<GenApiReg::DeviceSelectorMax>(0).unwrap();
         self.vm.write::<GenApiReg::DeviceSelector>(0).unwrap();
 
@@ -200,7 +245,7 @@ impl U3VInterfaceModule {
         let device_info = device.device_info();
 
         self.vm
-            .write::<GenApiReg::DeviceID>(device.port_info()?.id.clone())?;
+            .write::<GenApiReg::DeviceID>(device.device_id().into())?;
 
         self.vm
             .write::<GenApiReg::DeviceVendorName>(device_info.vendor_name.clone())
@@ -213,6 +258,19 @@ impl U3VInterfaceModule {
         self.vm
             .write::<GenApiReg::DeviceAccessStatus>(status as u32)?;
 
+        self.vm
+            .write::<GenApiReg::DeviceSerialNumber>(device_info.serial_number.clone())?;
+
+        // The name is read from the device while it's opened, because it can be changed through
+        // the remote device.
+        let user_id = device.user_defined_name().unwrap_or_default();
+        self.vm.write::<GenApiReg::DeviceUserID>(user_id)?;
+
+        self.vm
+            .write::<GenApiReg::DeviceTLVersionMajor>(device_info.u3v_version.major as u32)?;
+        self.vm
+            .write::<GenApiReg::DeviceTLVersionMinor>(device_info.u3v_version.minor as u32)?;
+
         Ok(())
     }
 }
@@ -297,11 +355,11 @@ impl Interface for U3VInterfaceModule {
     }
 
     fn interface_id(&self) -> &str {
-        genapi::INTERFACE_ID
+        &self.port_info.id
     }
 
     fn display_name(&self) -> &str {
-        "U3V Interface Module"
+        self.display_name
     }
 
     fn tl_type(&self) -> TlType {
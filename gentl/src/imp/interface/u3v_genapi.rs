@@ -16,7 +16,8 @@ use crate::imp::{
 
 use GenApiReg::{
     DeviceAccessStatus, DeviceID, DeviceModelName, DeviceSelector, DeviceSelectorMax,
-    DeviceUpdateList, DeviceVendorName,
+    DeviceSerialNumber, DeviceTLVersionMajor, DeviceTLVersionMinor, DeviceUpdateList, DeviceUserID,
+    DeviceVendorName, InterfaceDisplayName, InterfaceID,
 };
 
 #[memory]
@@ -27,6 +28,14 @@ pub(super) struct Memory {
 
 #[register_map(base=0, endianness=LE)]
 pub(super) enum GenApiReg {
+    /// GenTL Producer wide unique identifier of the interface.
+    #[register(len = 64, access = RO, ty = String)]
+    InterfaceID,
+
+    /// User readable name of the interface.
+    #[register(len = 128, access = RO, ty = String)]
+    InterfaceDisplayName,
+
     /// Updates the internal list of the devices when non zero value is wrritten to this
     /// register.
     #[register(len = 4, access = WO, ty = u32)]
@@ -55,6 +64,22 @@ pub(super) enum GenApiReg {
     /// Gives the device's access status at the moment of the last execution of the DeviceUpdateList command.
     #[register(len = 4, access = RO, ty = u32)]
     DeviceAccessStatus,
+
+    /// Serial number of the selected device.
+    #[register(len = 64, access = RO, ty = String)]
+    DeviceSerialNumber,
+
+    /// User-programmable device identifier of the selected device.
+    #[register(len = 64, access = RO, ty = String)]
+    DeviceUserID,
+
+    /// Major version number of the U3V specification the selected device complies with.
+    #[register(len = 4, access = RO, ty = u32)]
+    DeviceTLVersionMajor,
+
+    /// Minor version number of the U3V specification the selected device complies with.
+    #[register(len = 4, access = RO, ty = u32)]
+    DeviceTLVersionMinor,
 }
 
 #[register_map(base=GENAPI_XML_ADDRESS, endianness=LE)]
@@ -68,6 +93,8 @@ pub(super) const VENDOR_NAME: &str = "CameleonProjectDevelopers";
 pub(super) const TOOL_TIP: &str = "GenTL U3V Interface Module";
 
 pub(super) const INTERFACE_ID: &str = PRODUCT_GUID;
+#[cfg(feature = "emulator")]
+pub(crate) const EMULATOR_INTERFACE_ID: &str = "b0a5b3c1-6a4e-4f0d-9a39-5e3c1f9d2c47";
 pub(super) const INTERFACE_TYPE: port::TlType = port::TlType::USB3Vision;
 pub(super) const PORT_NAME: &str = "InterfacePort";
 
@@ -116,16 +143,29 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Description>Category that contains all Interface Information features of the Interface module.</Description>
         <Visibility>Beginner</Visibility>
         <pFeature>InterfaceID</pFeature>
+        <pFeature>InterfaceDisplayName</pFeature>
         <pFeature>InterfaceType</pFeature>
         <pFeature>InterfaceTLVersionMajor</pFeature>
         <pFeature>InterfaceTLVersionMinor</pFeature>
     </Category>
 
-    <String Name="InterfaceID" NameSpace="Standard">
+    <StringReg Name="InterfaceID" NameSpace="Standard">
         <Description>GenTL Producer wide unique identifier of the selected interface.</Description>
         <Visibility>Expert</Visibility>
-        <Value>{INTERFACE_ID}</Value>
-    </String>
+        <Address>{interface_id_addr}</Address>
+        <Length>{interface_id_len}</Length>
+        <AccessMode>{interface_id_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="InterfaceDisplayName" NameSpace="Standard">
+        <Description>User readable name of the interface.</Description>
+        <Visibility>Beginner</Visibility>
+        <Address>{interface_display_name_addr}</Address>
+        <Length>{interface_display_name_len}</Length>
+        <AccessMode>{interface_display_name_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
 
     <Enumeration Name="InterfaceType" NameSpace="Standard">
         <Description>Transport layer type of the interface.</Description>
@@ -162,6 +202,8 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pFeature>DeviceVendorName</pFeature>
         <pFeature>DeviceModelName</pFeature>
         <pFeature>DeviceAccessStatus</pFeature>
+        <pFeature>DeviceSerialNumber</pFeature>
+        <pFeature>DeviceUserID</pFeature>
         <pFeature>DeviceTLVersionMajor</pFeature>
         <pFeature>DeviceTLVersionMinor</pFeature>
     </Category>
@@ -286,23 +328,51 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Endianess>LittleEndian</Endianess>
     </IntReg>
 
-    <Integer Name="DeviceTLVersionMajor" NameSpace="Standard">
+    <StringReg Name="DeviceSerialNumber" NameSpace="Standard">
+        <Description>Serial number of the selected remote device.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_serial_number_addr}</Address>
+        <Length>{device_serial_number_len}</Length>
+        <AccessMode>{device_serial_number_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <StringReg Name="DeviceUserID" NameSpace="Standard">
+        <Description>User-programmable device identifier of the selected remote device.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{device_user_id_addr}</Address>
+        <Length>{device_user_id_len}</Length>
+        <AccessMode>{device_user_id_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <IntReg Name="DeviceTLVersionMajor" NameSpace="Standard">
         <Description>Major version number of the transport layer specification the remote device complies with.</Description>
         <Visibility>Expert</Visibility>
-        <Value>{GENTL_VERSION_MAJOR}</Value>
-        <Min>{GENTL_VERSION_MAJOR}</Min>
-        <Max>{GENTL_VERSION_MAJOR}</Max>
-    </Integer>
+        <Address>{device_tl_version_major_addr}</Address>
+        <Length>{device_tl_version_major_len}</Length>
+        <AccessMode>{device_tl_version_major_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
 
-    <Integer Name="DeviceTLVersionMinor" NameSpace="Standard">
+    <IntReg Name="DeviceTLVersionMinor" NameSpace="Standard">
         <Description>Minor version number of the transport layer specification the remote device complies with.</Description>
         <Visibility>Expert</Visibility>
-        <Value>{GENTL_VERSION_MINOR}</Value>
-        <Min>{GENTL_VERSION_MINOR}</Min>
-        <Max>{GENTL_VERSION_MINOR}</Max>
-    </Integer>
+        <Address>{device_tl_version_minor_addr}</Address>
+        <Length>{device_tl_version_minor_len}</Length>
+        <AccessMode>{device_tl_version_minor_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
 </RegisterDescription>"#,
     interface_type = INTERFACE_TYPE.as_str(),
+    interface_id_addr = InterfaceID::ADDRESS,
+    interface_id_len = InterfaceID::LENGTH,
+    interface_id_access = InterfaceID::ACCESS_RIGHT.as_str(),
+    interface_display_name_addr = InterfaceDisplayName::ADDRESS,
+    interface_display_name_len = InterfaceDisplayName::LENGTH,
+    interface_display_name_access = InterfaceDisplayName::ACCESS_RIGHT.as_str(),
     device_update_list_addr = DeviceUpdateList::ADDRESS,
     device_update_list_len = DeviceUpdateList::LENGTH,
     device_update_list_access = DeviceUpdateList::ACCESS_RIGHT.as_str(),
@@ -338,4 +408,16 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     device_access_status_addr = DeviceAccessStatus::ADDRESS,
     device_access_status_len = DeviceAccessStatus::LENGTH,
     device_access_status_access = DeviceAccessStatus::ACCESS_RIGHT.as_str(),
+    device_serial_number_addr = DeviceSerialNumber::ADDRESS,
+    device_serial_number_len = DeviceSerialNumber::LENGTH,
+    device_serial_number_access = DeviceSerialNumber::ACCESS_RIGHT.as_str(),
+    device_user_id_addr = DeviceUserID::ADDRESS,
+    device_user_id_len = DeviceUserID::LENGTH,
+    device_user_id_access = DeviceUserID::ACCESS_RIGHT.as_str(),
+    device_tl_version_major_addr = DeviceTLVersionMajor::ADDRESS,
+    device_tl_version_major_len = DeviceTLVersionMajor::LENGTH,
+    device_tl_version_major_access = DeviceTLVersionMajor::ACCESS_RIGHT.as_str(),
+    device_tl_version_minor_addr = DeviceTLVersionMinor::ADDRESS,
+    device_tl_version_minor_len = DeviceTLVersionMinor::LENGTH,
+    device_tl_version_minor_access = DeviceTLVersionMinor::ACCESS_RIGHT.as_str(),
 );
@@ -3,13 +3,15 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 pub(super) mod device;
+pub(super) mod event;
 pub(super) mod interface;
 pub(super) mod port;
+pub(super) mod stream;
 pub(super) mod system;
 
 mod genapi_common;
 
-use cameleon::ControlError;
+use cameleon::{CameleonError, ControlError, ProtocolStatus, StreamError};
 use cameleon_impl::memory::MemoryError;
 
 use super::GenTlError;
@@ -27,7 +29,8 @@ impl From<MemoryError> for GenTlError {
 impl From<ControlError> for GenTlError {
     fn from(err: ControlError) -> Self {
         use GenTlError::{
-            BufferTooSmall, InvalidValue, Io, NotInitialized, ResourceInUse, Timeout,
+            AccessDenied, BufferTooSmall, InvalidAddress, InvalidParameter, InvalidValue, Io,
+            NotImplemented, NotInitialized, ResourceInUse, Timeout,
         };
 
         match err {
@@ -39,6 +42,39 @@ impl From<ControlError> for GenTlError {
             ControlError::InvalidData(..) => InvalidValue(format!("{}", err).into()),
             ControlError::Timeout => Timeout,
             ControlError::BufferTooSmall => BufferTooSmall,
+            ControlError::ProtocolStatus { status, .. } => match status {
+                ProtocolStatus::NotImplemented => NotImplemented,
+                ProtocolStatus::InvalidParameter => InvalidParameter,
+                ProtocolStatus::InvalidAddress | ProtocolStatus::BadAlignment => InvalidAddress,
+                ProtocolStatus::WriteProtect | ProtocolStatus::AccessDenied => AccessDenied,
+                ProtocolStatus::Busy => ResourceInUse,
+                ProtocolStatus::Timeout => Timeout,
+                _ => Io(err.into()),
+            },
+        }
+    }
+}
+
+impl From<StreamError> for GenTlError {
+    fn from(err: StreamError) -> Self {
+        use GenTlError::{BufferTooSmall, Error, Io, ResourceInUse, Timeout};
+
+        match err {
+            StreamError::Disconnected | StreamError::Io(..) => Io(err.into()),
+            StreamError::Timeout => Timeout,
+            StreamError::BufferTooSmall => BufferTooSmall,
+            StreamError::InStreaming => ResourceInUse,
+            _ => Error(format!("{}", err)),
+        }
+    }
+}
+
+impl From<CameleonError> for GenTlError {
+    fn from(err: CameleonError) -> Self {
+        match err {
+            CameleonError::ControlError(err) => err.into(),
+            CameleonError::StreamError(err) => err.into(),
+            _ => Self::Error(format!("{}", err)),
         }
     }
 }
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use cameleon::genapi::CompressionType;
+use cameleon::genapi::{self, CompressionType, XmlUrl};
 use semver::Version;
 
 use crate::GenTlResult;
@@ -192,6 +192,39 @@ pub(crate) struct XmlInfo {
     pub(crate) compressed: CompressionType,
 }
 
+impl XmlInfo {
+    /// URL of the XML file in the form reported by `GCGetPortURLInfo`.
+    ///
+    /// The URL doesn't include the SHA1 hash because the `GenTL` specification doesn't define it as
+    /// a part of URLs, consumers query it with `URL_INFO_FILE_SHA1_HASH` instead.
+    pub(crate) fn xml_url(&self, port_info: &PortInfo) -> XmlUrl {
+        let location = match &self.location {
+            XmlLocation::RegisterMap { address, size } => {
+                let extension = match self.compressed {
+                    CompressionType::Uncompressed => "xml",
+                    CompressionType::Zip => "zip",
+                };
+                genapi::XmlLocation::Local {
+                    file_name: format!(
+                        "{}_{}_{}.{}",
+                        port_info.vendor, port_info.model, self.file_version, extension
+                    ),
+                    address: *address,
+                    size: *size,
+                }
+            }
+            XmlLocation::LocalFile(path) => genapi::XmlLocation::File(path.clone()),
+            XmlLocation::Url(url) => genapi::XmlLocation::Web(url.to_string()),
+        };
+
+        XmlUrl {
+            location,
+            schema_version: Some(self.schema_version.clone()),
+            sha1_hash: None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) enum XmlLocation {
     RegisterMap { address: u64, size: usize },
@@ -0,0 +1,309 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cameleon::payload::{ChunkEntry, ImageInfo, PayloadType};
+
+use std::sync::Arc;
+
+use crate::{
+    imp::{
+        event::{Event, EventType},
+        port::{Port, TlType},
+    },
+    GenTlResult,
+};
+
+pub(crate) mod u3v;
+
+mod u3v_genapi;
+
+/// Identifies a buffer announced to a data stream.
+/// IDs are never reused in a data stream, so that a stale handle is detected as invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct BufferId(usize);
+
+impl BufferId {
+    pub(crate) fn from_raw(raw: usize) -> Self {
+        Self(raw)
+    }
+
+    pub(crate) fn as_raw(self) -> usize {
+        self.0
+    }
+}
+
+/// A pointer passed from the consumer, which is opaque to the producer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RawPtr(pub(crate) *mut libc::c_void);
+
+// The producer never dereferences the pointer except for the memory of announced buffers, which
+// the consumer must keep valid until the buffer is revoked.
+unsafe impl Send for RawPtr {}
+
+impl RawPtr {
+    pub(crate) fn null() -> Self {
+        Self(std::ptr::null_mut())
+    }
+}
+
+/// Memory of a buffer.
+pub(crate) enum BufferMemory {
+    /// Memory announced by the consumer.
+    /// The consumer must keep the memory valid until the buffer is revoked.
+    User { ptr: RawPtr, len: usize },
+
+    /// Memory allocated by the producer.
+    Allocated(Box<[u8]>),
+}
+
+impl BufferMemory {
+    pub(crate) fn base(&self) -> RawPtr {
+        match self {
+            Self::User { ptr, .. } => *ptr,
+            Self::Allocated(buf) => RawPtr(buf.as_ptr() as *mut libc::c_void),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::User { len, .. } => *len,
+            Self::Allocated(buf) => buf.len(),
+        }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        match self {
+            Self::User { ptr, len } => unsafe {
+                // Safety: The GenTL specification requires the consumer to keep the memory valid
+                // and not to touch it while the buffer is announced and queued.
+                std::slice::from_raw_parts_mut(ptr.0.cast::<u8>(), *len)
+            },
+            Self::Allocated(buf) => buf,
+        }
+    }
+}
+
+/// Flags for [`DataStream::start_acquisition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AcquisitionStartFlag {
+    /// Default behavior.
+    Default,
+}
+
+/// Flags for [`DataStream::stop_acquisition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum AcquisitionStopFlag {
+    /// Stop the acquisition after the buffer being filled is completed.
+    Default,
+
+    /// Stop the acquisition immediately and discard the buffer being filled.
+    Kill,
+}
+
+/// Operations of [`DataStream::flush_queue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FlushOperation {
+    /// Moves all buffers in the input pool to the output queue.
+    InputToOutput,
+
+    /// Discards all buffers in the output queue.
+    OutputDiscard,
+
+    /// Moves all announced buffers to the input pool.
+    AllToInput,
+
+    /// Moves all announced buffers which are neither in the input pool nor the output queue to
+    /// the input pool.
+    UnqueuedToInput,
+
+    /// Discards all buffers in the input pool and the output queue.
+    AllDiscard,
+}
+
+/// Statistics and properties of a data stream.
+#[derive(Clone, Debug)]
+pub(crate) struct StreamInfo {
+    /// Number of frames delivered to the output queue since the acquisition started.
+    pub(crate) num_delivered: u64,
+
+    /// Number of frames lost because the input pool was empty since the acquisition started.
+    pub(crate) num_underrun: u64,
+
+    /// Number of announced buffers.
+    pub(crate) num_announced: usize,
+
+    /// Number of buffers in the input pool.
+    pub(crate) num_queued: usize,
+
+    /// Number of buffers in the output queue.
+    pub(crate) num_await_delivery: usize,
+
+    /// Number of frames started in the acquisition engine since the acquisition started.
+    pub(crate) num_started: u64,
+
+    /// Size of the expected payload in bytes.
+    pub(crate) payload_size: usize,
+
+    /// Whether the acquisition engine is running.
+    pub(crate) is_grabbing: bool,
+
+    /// Minimum number of buffers to announce.
+    pub(crate) buf_announce_min: usize,
+
+    /// Alignment of buffers in bytes.
+    pub(crate) buf_alignment: usize,
+}
+
+/// Information of a buffer.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferInfo {
+    /// Base address of the buffer memory.
+    pub(crate) base: RawPtr,
+
+    /// Size of the buffer memory in bytes.
+    pub(crate) size: usize,
+
+    /// Private data pointer passed when the buffer is announced.
+    pub(crate) user_ptr: RawPtr,
+
+    /// Whether the buffer is in the input pool.
+    pub(crate) is_queued: bool,
+
+    /// Whether the buffer contains new data since it was last queued.
+    pub(crate) new_data: bool,
+
+    /// Information of the frame the buffer was last filled with.
+    pub(crate) frame: Option<FrameInfo>,
+}
+
+/// Information of a frame filled into a buffer.
+#[derive(Clone, Debug)]
+pub(crate) struct FrameInfo {
+    /// Block ID of the frame.
+    pub(crate) frame_id: u64,
+
+    /// Timestamp of the frame in device ticks.
+    pub(crate) timestamp: u64,
+
+    /// Timestamp of the frame in ns, if the tick frequency of the device is known.
+    pub(crate) timestamp_ns: Option<u64>,
+
+    /// Number of bytes filled into the buffer.
+    pub(crate) size_filled: usize,
+
+    /// Whether the payload didn't fit into the buffer.
+    pub(crate) data_larger_than_buffer: bool,
+
+    /// Type of the payload.
+    pub(crate) payload_type: PayloadType,
+
+    /// Image information if the payload contains an image.
+    pub(crate) image: Option<ImageInfo>,
+
+    /// Chunk layout ID reported by the device if the payload contains chunks.
+    pub(crate) chunk_layout_id: Option<u32>,
+
+    /// Chunks in the buffer.
+    /// `None` if the buffer doesn't hold the whole payload or the chunk data is broken.
+    pub(crate) chunks: Option<Vec<ChunkEntry>>,
+}
+
+impl FrameInfo {
+    /// Whether the buffer doesn't contain the whole payload.
+    pub(crate) fn is_incomplete(&self) -> bool {
+        self.data_larger_than_buffer
+    }
+
+    /// Whether the payload contains chunk data.
+    pub(crate) fn contains_chunk_data(&self) -> bool {
+        self.payload_type != PayloadType::Image
+    }
+
+    /// Parts of the buffer.
+    /// A U3V payload has at most one part, which is the image at the beginning of the payload.
+    pub(crate) fn parts(&self) -> Vec<BufferPart<'_>> {
+        self.image
+            .iter()
+            .map(|image| BufferPart {
+                offset: 0,
+                size: image.image_size.min(self.size_filled),
+                image,
+            })
+            .collect()
+    }
+}
+
+/// A part of a buffer.
+#[derive(Clone, Debug)]
+pub(crate) struct BufferPart<'a> {
+    /// Offset of the part from the base address of the buffer.
+    pub(crate) offset: usize,
+
+    /// Size of the part in bytes.
+    pub(crate) size: usize,
+
+    /// Image information of the part.
+    pub(crate) image: &'a ImageInfo,
+}
+
+pub(crate) trait DataStream: Port {
+    /// Open the data stream.
+    fn open(&mut self) -> GenTlResult<()>;
+
+    /// Close the data stream.
+    /// The acquisition is stopped and all buffers are revoked.
+    fn close(&mut self) -> GenTlResult<()>;
+
+    /// Returns `true` if the data stream is opened.
+    fn is_opened(&self) -> bool;
+
+    /// ID of the data stream.
+    fn stream_id(&self) -> &str;
+
+    /// Transport layer type of the data stream.
+    fn tl_type(&self) -> TlType;
+
+    /// Announce a buffer with its memory and private data pointer.
+    fn announce_buffer(&mut self, memory: BufferMemory, user_ptr: RawPtr) -> GenTlResult<BufferId>;
+
+    /// Revoke an announced buffer, and returns the memory and the private data pointer.
+    /// The buffer must be neither in the input pool nor the output queue.
+    fn revoke_buffer(&mut self, id: BufferId) -> GenTlResult<(BufferMemory, RawPtr)>;
+
+    /// Queue an announced buffer to the input pool.
+    /// Returns [`GenTlError::Busy`] if the buffer is in the output queue.
+    fn queue_buffer(&mut self, id: BufferId) -> GenTlResult<()>;
+
+    /// Move or discard buffers in the input pool and the output queue.
+    fn flush_queue(&mut self, operation: FlushOperation) -> GenTlResult<()>;
+
+    /// ID of the `index`-th announced buffer.
+    fn buffer_id(&self, index: usize) -> GenTlResult<BufferId>;
+
+    /// Start the acquisition engine.
+    /// The engine stops delivering frames after `num_to_acquire` frames are delivered if it's
+    /// `Some`.
+    fn start_acquisition(
+        &mut self,
+        flag: AcquisitionStartFlag,
+        num_to_acquire: Option<u64>,
+    ) -> GenTlResult<()>;
+
+    /// Stop the acquisition engine.
+    fn stop_acquisition(&mut self, flag: AcquisitionStopFlag) -> GenTlResult<()>;
+
+    /// Statistics and properties of the data stream.
+    fn stream_info(&self) -> GenTlResult<StreamInfo>;
+
+    /// Information of an announced buffer.
+    fn buffer_info(&self, id: BufferId) -> GenTlResult<BufferInfo>;
+
+    /// Register an event of the data stream.
+    /// Returns [`crate::GenTlError::ResourceInUse`] if the event is already registered.
+    fn register_event(&mut self, event_type: EventType) -> GenTlResult<Arc<dyn Event>>;
+
+    /// Unregister an event of the data stream.
+    /// Threads waiting for the event are aborted.
+    fn unregister_event(&mut self, event_type: EventType) -> GenTlResult<()>;
+}
@@ -0,0 +1,786 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::{
+    collections::VecDeque,
+    convert::TryInto,
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+use cameleon::{
+    genapi::CompressionType,
+    payload::{channel, Payload, PayloadReceiver, PayloadSender},
+    u3v::{register_map::Abrm, SharedControlHandle, StreamHandle},
+    DeviceControl, PayloadStream, StreamError, StreamResult,
+};
+use cameleon_impl::memory::prelude::*;
+
+use crate::{
+    imp::{
+        event::{
+            wait_until, Event, EventData, EventQueue, EventType, MAX_ERROR_MESSAGE_LEN,
+            NEW_BUFFER_DATA_SIZE,
+        },
+        genapi_common,
+        port::{Endianness, ModuleType, Port, PortAccess, PortInfo, TlType, XmlInfo, XmlLocation},
+    },
+    GenTlError, GenTlResult,
+};
+
+use super::{
+    u3v_genapi as genapi, AcquisitionStartFlag, AcquisitionStopFlag, BufferId, BufferInfo,
+    BufferMemory, DataStream, FlushOperation, FrameInfo, RawPtr, StreamInfo,
+};
+use genapi::GenApiReg;
+
+/// ID of the data stream. U3V devices have only one streaming channel.
+pub(crate) const STREAM_ID: &str = "Stream0";
+
+/// Capacity of the channel between the streaming loop and the acquisition engine.
+const PAYLOAD_CHANNEL_CAP: usize = 16;
+
+/// A reference counted [`StreamHandle`] shared by the device module and the data stream module.
+#[derive(Clone)]
+pub(crate) struct SharedStreamHandle(Arc<Mutex<StreamHandle>>);
+
+impl From<StreamHandle> for SharedStreamHandle {
+    fn from(strm: StreamHandle) -> Self {
+        Self(Arc::new(Mutex::new(strm)))
+    }
+}
+
+impl PayloadStream for SharedStreamHandle {
+    fn open(&mut self) -> StreamResult<()> {
+        self.0.lock().unwrap().open()
+    }
+
+    fn close(&mut self) -> StreamResult<()> {
+        self.0.lock().unwrap().close()
+    }
+
+    fn start_streaming_loop(
+        &mut self,
+        sender: PayloadSender,
+        ctrl: &mut dyn DeviceControl,
+    ) -> StreamResult<()> {
+        self.0.lock().unwrap().start_streaming_loop(sender, ctrl)
+    }
+
+    fn stop_streaming_loop(&mut self) -> StreamResult<()> {
+        self.0.lock().unwrap().stop_streaming_loop()
+    }
+
+    fn is_loop_running(&self) -> bool {
+        self.0.lock().unwrap().is_loop_running()
+    }
+}
+
+pub(crate) struct U3VDataStreamModule {
+    port_info: PortInfo,
+    xml_infos: Vec<XmlInfo>,
+
+    ctrl: SharedControlHandle,
+    strm: SharedStreamHandle,
+
+    /// State shared with the acquisition engine.
+    shared: Arc<Shared>,
+
+    /// The acquisition engine which fills queued buffers with received payloads.
+    engine: Option<thread::JoinHandle<()>>,
+
+    /// ID of the buffer announced next.
+    next_buffer_id: usize,
+
+    /// Tick period of the device timestamp counter in ns.
+    timestamp_increment: Option<u64>,
+
+    new_buffer_event: Option<Arc<NewBufferEvent>>,
+
+    is_opened: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+
+    /// Notified every time a buffer is pushed to the output queue.
+    output_cond: Condvar,
+
+    /// Registered error event, which is fired when the acquisition engine fails to receive a
+    /// payload.
+    error_event: Mutex<Option<Arc<EventQueue>>>,
+}
+
+struct State {
+    vm: genapi::Memory,
+
+    /// Announced buffers in the announced order.
+    buffers: Vec<Buffer>,
+
+    /// Input pool.
+    input: VecDeque<BufferId>,
+
+    /// Output queue.
+    output: VecDeque<BufferId>,
+
+    is_grabbing: bool,
+
+    /// Number of frames to deliver until the acquisition engine stops grabbing.
+    remaining: Option<u64>,
+
+    num_started: u64,
+    num_delivered: u64,
+    num_underrun: u64,
+
+    /// Number of new buffer events fired since the event is registered.
+    num_new_buffer_fired: u64,
+
+    /// Number of pending requests to abort a wait for a new buffer event.
+    num_new_buffer_kill: usize,
+}
+
+struct Buffer {
+    id: BufferId,
+    memory: BufferMemory,
+    user_ptr: RawPtr,
+    new_data: bool,
+    frame: Option<FrameInfo>,
+}
+
+impl U3VDataStreamModule {
+    pub(crate) fn new(ctrl: SharedControlHandle, strm: SharedStreamHandle) -> Self {
+        let port_info = PortInfo {
+            id: STREAM_ID.into(),
+            vendor: genapi::VENDOR_NAME.into(),
+            model: genapi::MODEL_NAME.into(),
+            tl_type: genapi::STREAM_TYPE,
+            module_type: ModuleType::DataStream,
+            endianness: Endianness::LE,
+            access: PortAccess::RW,
+            version: semver::Version::new(
+                genapi::XML_MAJOR_VERSION,
+                genapi::XML_MINOR_VERSION,
+                genapi::XML_SUBMINOR_VERSION,
+            ),
+            port_name: genapi::PORT_NAME.into(),
+        };
+
+        let xml_info = XmlInfo {
+            location: XmlLocation::RegisterMap {
+                address: genapi::GENAPI_XML_ADDRESS as u64,
+                size: genapi::GENAPI_XML_LENGTH,
+            },
+            schema_version: semver::Version::new(
+                genapi_common::SCHEME_MAJOR_VERSION,
+                genapi_common::SCHEME_MINOR_VERSION,
+                genapi_common::SCHEME_SUBMINOR_VERSION,
+            ),
+            file_version: semver::Version::new(
+                genapi::XML_MAJOR_VERSION,
+                genapi::XML_MINOR_VERSION,
+                genapi::XML_SUBMINOR_VERSION,
+            ),
+            sha1_hash: None,
+            compressed: CompressionType::Uncompressed,
+        };
+
+        let mut vm = genapi::Memory::new();
+        // Ok to unwrap because the values always fit in the registers.
+        vm.write::<GenApiReg::StreamID>(STREAM_ID.into()).unwrap();
+        vm.write::<GenApiReg::StreamAnnounceBufferMinimum>(BUF_ANNOUNCE_MIN as u64)
+            .unwrap();
+        vm.write::<GenApiReg::StreamBufferAlignment>(BUF_ALIGNMENT as u64)
+            .unwrap();
+
+        let state = State {
+            vm,
+            buffers: vec![],
+            input: VecDeque::new(),
+            output: VecDeque::new(),
+            is_grabbing: false,
+            remaining: None,
+            num_started: 0,
+            num_delivered: 0,
+            num_underrun: 0,
+            num_new_buffer_fired: 0,
+            num_new_buffer_kill: 0,
+        };
+
+        Self {
+            port_info,
+            xml_infos: vec![xml_info],
+
+            ctrl,
+            strm,
+
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                output_cond: Condvar::new(),
+                error_event: Mutex::new(None),
+            }),
+            engine: None,
+            next_buffer_id: 1,
+            timestamp_increment: None,
+            new_buffer_event: None,
+            is_opened: false,
+        }
+    }
+
+    fn assert_open(&self) -> GenTlResult<()> {
+        if self.is_opened {
+            Ok(())
+        } else {
+            Err(GenTlError::NotInitialized)
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.shared.state.lock().unwrap()
+    }
+}
+
+impl Drop for U3VDataStreamModule {
+    fn drop(&mut self) {
+        self.close().ok();
+    }
+}
+
+impl Port for U3VDataStreamModule {
+    fn read(&self, address: u64, buf: &mut [u8]) -> GenTlResult<usize> {
+        self.assert_open()?;
+
+        let mut state = self.state();
+        state.reflect_counters();
+
+        let address = address as usize;
+        let len = buf.len();
+        let data = state.vm.read_raw(address..address + len)?;
+        buf.copy_from_slice(data);
+
+        Ok(len)
+    }
+
+    fn write(&mut self, address: u64, data: &[u8]) -> GenTlResult<usize> {
+        self.assert_open()?;
+
+        self.state().vm.write_raw(address as usize, data)?;
+        Ok(data.len())
+    }
+
+    fn port_info(&self) -> GenTlResult<&PortInfo> {
+        self.assert_open()?;
+
+        Ok(&self.port_info)
+    }
+
+    fn xml_infos(&self) -> GenTlResult<&[XmlInfo]> {
+        self.assert_open()?;
+
+        Ok(&self.xml_infos)
+    }
+}
+
+impl DataStream for U3VDataStreamModule {
+    fn open(&mut self) -> GenTlResult<()> {
+        if self.is_opened {
+            return Err(GenTlError::ResourceInUse);
+        }
+
+        let mut ctrl = self.ctrl.clone();
+        self.timestamp_increment = Abrm::new(&mut ctrl)
+            .and_then(|abrm| abrm.timestamp_increment(&mut ctrl))
+            .ok()
+            .filter(|increment| *increment != 0);
+        self.is_opened = true;
+
+        Ok(())
+    }
+
+    fn close(&mut self) -> GenTlResult<()> {
+        if !self.is_opened {
+            return Ok(());
+        }
+
+        let res = if self.engine.is_some() {
+            self.stop_acquisition(AcquisitionStopFlag::Kill)
+        } else {
+            Ok(())
+        };
+
+        let mut state = self.state();
+        state.input.clear();
+        state.output.clear();
+        state.buffers.clear();
+        drop(state);
+
+        // Events are unregistered when the module is closed.
+        for event_type in [EventType::NewBuffer, EventType::Error] {
+            self.unregister_event(event_type).ok();
+        }
+
+        self.is_opened = false;
+        res
+    }
+
+    fn is_opened(&self) -> bool {
+        self.is_opened
+    }
+
+    fn stream_id(&self) -> &str {
+        &self.port_info.id
+    }
+
+    fn tl_type(&self) -> TlType {
+        genapi::STREAM_TYPE
+    }
+
+    fn announce_buffer(&mut self, memory: BufferMemory, user_ptr: RawPtr) -> GenTlResult<BufferId> {
+        self.assert_open()?;
+        if memory.len() == 0 {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        let id = BufferId::from_raw(self.next_buffer_id);
+        self.next_buffer_id += 1;
+
+        self.state().buffers.push(Buffer {
+            id,
+            memory,
+            user_ptr,
+            new_data: false,
+            frame: None,
+        });
+
+        Ok(id)
+    }
+
+    fn revoke_buffer(&mut self, id: BufferId) -> GenTlResult<(BufferMemory, RawPtr)> {
+        self.assert_open()?;
+
+        let mut state = self.state();
+        let index = state.buffer_index(id)?;
+        if state.input.contains(&id) || state.output.contains(&id) {
+            return Err(GenTlError::Busy);
+        }
+
+        let buffer = state.buffers.remove(index);
+        Ok((buffer.memory, buffer.user_ptr))
+    }
+
+    fn queue_buffer(&mut self, id: BufferId) -> GenTlResult<()> {
+        self.assert_open()?;
+
+        let mut state = self.state();
+        let index = state.buffer_index(id)?;
+        if state.input.contains(&id) {
+            return Err(GenTlError::ResourceInUse);
+        }
+        // A delivered buffer can be queued again only after it's taken from the output queue.
+        if state.output.contains(&id) {
+            return Err(GenTlError::Busy);
+        }
+
+        state.buffers[index].new_data = false;
+        state.input.push_back(id);
+
+        Ok(())
+    }
+
+    fn flush_queue(&mut self, operation: FlushOperation) -> GenTlResult<()> {
+        self.assert_open()?;
+
+        let mut state = self.state();
+        match operation {
+            FlushOperation::InputToOutput => {
+                let input: Vec<_> = state.input.drain(..).collect();
+                state.output.extend(input);
+                self.shared.output_cond.notify_all();
+            }
+            FlushOperation::OutputDiscard => state.output.clear(),
+            FlushOperation::AllToInput => {
+                state.output.clear();
+                state.input = state.buffers.iter().map(|buf| buf.id).collect();
+            }
+            FlushOperation::UnqueuedToInput => {
+                let unqueued: Vec<_> = state
+                    .buffers
+                    .iter()
+                    .map(|buf| buf.id)
+                    .filter(|id| !state.input.contains(id) && !state.output.contains(id))
+                    .collect();
+                state.input.extend(unqueued);
+            }
+            FlushOperation::AllDiscard => {
+                state.input.clear();
+                state.output.clear();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn buffer_id(&self, index: usize) -> GenTlResult<BufferId> {
+        self.assert_open()?;
+
+        self.state()
+            .buffers
+            .get(index)
+            .map(|buf| buf.id)
+            .ok_or(GenTlError::InvalidIndex)
+    }
+
+    fn start_acquisition(
+        &mut self,
+        _flag: AcquisitionStartFlag,
+        num_to_acquire: Option<u64>,
+    ) -> GenTlResult<()> {
+        self.assert_open()?;
+        if self.engine.is_some() || self.strm.is_loop_running() {
+            return Err(GenTlError::ResourceInUse);
+        }
+        if num_to_acquire == Some(0) {
+            return Err(GenTlError::InvalidParameter);
+        }
+
+        let (sender, receiver) = channel(PAYLOAD_CHANNEL_CAP, PAYLOAD_CHANNEL_CAP);
+        self.ctrl.enable_streaming()?;
+        if let Err(err) = self.strm.start_streaming_loop(sender, &mut self.ctrl) {
+            self.ctrl.disable_streaming().ok();
+            return Err(err.into());
+        }
+
+        let mut state = self.state();
+        state.is_grabbing = true;
+        state.remaining = num_to_acquire;
+        state.num_started = 0;
+        state.num_delivered = 0;
+        state.num_underrun = 0;
+        drop(state);
+
+        let shared = self.shared.clone();
+        let timestamp_increment = self.timestamp_increment;
+        self.engine = Some(thread::spawn(move || {
+            run_engine(&shared, &receiver, timestamp_increment);
+        }));
+
+        Ok(())
+    }
+
+    fn stop_acquisition(&mut self, flag: AcquisitionStopFlag) -> GenTlResult<()> {
+        self.assert_open()?;
+        let engine = self.engine.take().ok_or(GenTlError::NotInitialized)?;
+
+        // Payloads completed before the streaming loop stops are still delivered unless the
+        // acquisition is killed.
+        if flag == AcquisitionStopFlag::Kill {
+            self.state().is_grabbing = false;
+        }
+
+        // The engine exits when the streaming loop drops the sender of the channel.
+        let strm_res = self.strm.stop_streaming_loop();
+        engine.join().ok();
+        let ctrl_res = self.ctrl.disable_streaming();
+
+        self.state().is_grabbing = false;
+        strm_res?;
+        ctrl_res?;
+        Ok(())
+    }
+
+    fn stream_info(&self) -> GenTlResult<StreamInfo> {
+        self.assert_open()?;
+
+        // The transfer sizes in `SIRM` are configured only while streaming is enabled, so the
+        // payload size is derived from the current requirement of the device instead.
+        let mut ctrl = self.ctrl.clone();
+        let payload_size = Abrm::new(&mut ctrl)
+            .and_then(|abrm| abrm.sbrm(&mut ctrl))
+            .and_then(|sbrm| sbrm.sirm(&mut ctrl))?
+            .ok_or(GenTlError::NotAvailable)?
+            .required_payload_size(&mut ctrl)?;
+        let payload_size = payload_size
+            .try_into()
+            .map_err(|_| GenTlError::NotAvailable)?;
+        let state = self.state();
+        Ok(StreamInfo {
+            num_delivered: state.num_delivered,
+            num_underrun: state.num_underrun,
+            num_announced: state.buffers.len(),
+            num_queued: state.input.len(),
+            num_await_delivery: state.output.len(),
+            num_started: state.num_started,
+            payload_size,
+            is_grabbing: state.is_grabbing,
+            buf_announce_min: BUF_ANNOUNCE_MIN,
+            buf_alignment: BUF_ALIGNMENT,
+        })
+    }
+
+    fn buffer_info(&self, id: BufferId) -> GenTlResult<BufferInfo> {
+        self.assert_open()?;
+
+        let state = self.state();
+        let buffer = &state.buffers[state.buffer_index(id)?];
+        Ok(BufferInfo {
+            base: buffer.memory.base(),
+            size: buffer.memory.len(),
+            user_ptr: buffer.user_ptr,
+            is_queued: state.input.contains(&id),
+            new_data: buffer.new_data,
+            frame: buffer.frame.clone(),
+        })
+    }
+
+    fn register_event(&mut self, event_type: EventType) -> GenTlResult<Arc<dyn Event>> {
+        self.assert_open()?;
+
+        match event_type {
+            EventType::NewBuffer => {
+                if self.new_buffer_event.is_some() {
+                    return Err(GenTlError::ResourceInUse);
+                }
+                let mut state = self.state();
+                state.num_new_buffer_fired = 0;
+                state.num_new_buffer_kill = 0;
+                drop(state);
+
+                let event = Arc::new(NewBufferEvent {
+                    shared: self.shared.clone(),
+                });
+                self.new_buffer_event = Some(event.clone());
+                Ok(event)
+            }
+
+            EventType::Error => {
+                let mut error_event = self.shared.error_event.lock().unwrap();
+                if error_event.is_some() {
+                    return Err(GenTlError::ResourceInUse);
+                }
+                let event = Arc::new(EventQueue::new(
+                    EventType::Error,
+                    std::mem::size_of::<i32>() + MAX_ERROR_MESSAGE_LEN,
+                ));
+                *error_event = Some(event.clone());
+                Ok(event)
+            }
+
+            _ => Err(GenTlError::NotImplemented),
+        }
+    }
+
+    fn unregister_event(&mut self, event_type: EventType) -> GenTlResult<()> {
+        match event_type {
+            EventType::NewBuffer => self
+                .new_buffer_event
+                .take()
+                .ok_or(GenTlError::NotInitialized)?
+                .kill(),
+
+            EventType::Error => self
+                .shared
+                .error_event
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or(GenTlError::NotInitialized)?
+                .kill(),
+
+            _ => return Err(GenTlError::NotImplemented),
+        }
+
+        Ok(())
+    }
+}
+
+/// New buffer event of the data stream.
+/// The event queue is the output queue of the data stream itself.
+struct NewBufferEvent {
+    shared: Arc<Shared>,
+}
+
+impl Event for NewBufferEvent {
+    fn event_type(&self) -> EventType {
+        EventType::NewBuffer
+    }
+
+    fn get_data(&self, timeout: Option<Duration>, capacity: usize) -> GenTlResult<EventData> {
+        if capacity < NEW_BUFFER_DATA_SIZE {
+            return Err(GenTlError::BufferTooSmall);
+        }
+
+        let state = self.shared.state.lock().unwrap();
+        let mut state = wait_until(&self.shared.output_cond, state, timeout, |state| {
+            if state.num_new_buffer_kill > 0 {
+                state.num_new_buffer_kill -= 1;
+                Some(Err(GenTlError::Abort))
+            } else if state.output.is_empty() {
+                None
+            } else {
+                Some(Ok(()))
+            }
+        })?;
+
+        // Ok to unwrap because the output queue is checked to be non-empty above.
+        let id = state.output.pop_front().unwrap();
+        let user_ptr = state.buffers[state.buffer_index(id)?].user_ptr;
+        Ok(EventData::NewBuffer { id, user_ptr })
+    }
+
+    fn kill(&self) {
+        self.shared.state.lock().unwrap().num_new_buffer_kill += 1;
+        self.shared.output_cond.notify_all();
+    }
+
+    fn flush(&self) {
+        self.shared.state.lock().unwrap().output.clear();
+    }
+
+    fn num_in_queue(&self) -> usize {
+        self.shared.state.lock().unwrap().output.len()
+    }
+
+    fn num_fired(&self) -> u64 {
+        self.shared.state.lock().unwrap().num_new_buffer_fired
+    }
+
+    fn max_data_size(&self) -> usize {
+        NEW_BUFFER_DATA_SIZE
+    }
+}
+
+impl State {
+    fn buffer_index(&self, id: BufferId) -> GenTlResult<usize> {
+        self.buffers
+            .iter()
+            .position(|buf| buf.id == id)
+            .ok_or(GenTlError::InvalidHandle)
+    }
+
+    /// Reflect the counters to the registers in VM.
+    fn reflect_counters(&mut self) {
+        let announced = self.buffers.len() as u64;
+        let input = self.input.len() as u64;
+        let output = self.output.len() as u64;
+        let (started, delivered, lost) = (self.num_started, self.num_delivered, self.num_underrun);
+        let is_grabbing = u32::from(self.is_grabbing);
+
+        // Ok to unwrap because all the registers are u64 or u32 registers.
+        let vm = &mut self.vm;
+        vm.write::<GenApiReg::StreamAnnouncedBufferCount>(announced)
+            .unwrap();
+        vm.write::<GenApiReg::StreamInputBufferCount>(input)
+            .unwrap();
+        vm.write::<GenApiReg::StreamOutputBufferCount>(output)
+            .unwrap();
+        vm.write::<GenApiReg::StreamStartedFrameCount>(started)
+            .unwrap();
+        vm.write::<GenApiReg::StreamDeliveredFrameCount>(delivered)
+            .unwrap();
+        vm.write::<GenApiReg::StreamLostFrameCount>(lost).unwrap();
+        vm.write::<GenApiReg::StreamIsGrabbing>(is_grabbing)
+            .unwrap();
+    }
+
+    /// Fill the oldest buffer in the input pool with the payload, then move it to the output
+    /// queue.
+    fn deliver(&mut self, payload: &Payload, timestamp_increment: Option<u64>) -> bool {
+        if !self.is_grabbing {
+            return false;
+        }
+        self.num_started += 1;
+
+        let id = match self.input.pop_front() {
+            Some(id) => id,
+            None => {
+                self.num_underrun += 1;
+                return false;
+            }
+        };
+        // Ok to unwrap because only announced buffers are queued.
+        let index = self.buffer_index(id).unwrap();
+        let buffer = &mut self.buffers[index];
+
+        let data = payload.payload();
+        let dst = buffer.memory.as_mut_slice();
+        let size_filled = data.len().min(dst.len());
+        dst[..size_filled].copy_from_slice(&data[..size_filled]);
+
+        let timestamp: u64 = payload
+            .timestamp()
+            .as_nanos()
+            .try_into()
+            .unwrap_or(u64::MAX);
+        buffer.new_data = true;
+        buffer.frame = Some(FrameInfo {
+            frame_id: payload.id(),
+            timestamp,
+            timestamp_ns: timestamp_increment.map(|inc| timestamp.saturating_mul(inc)),
+            size_filled,
+            data_larger_than_buffer: size_filled < data.len(),
+            payload_type: payload.payload_type(),
+            image: payload.image_info().cloned(),
+            chunk_layout_id: payload.chunk_layout_id(),
+            chunks: if size_filled < data.len() {
+                None
+            } else {
+                payload.chunks().ok()
+            },
+        });
+
+        self.output.push_back(id);
+        self.num_delivered += 1;
+        self.num_new_buffer_fired += 1;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+            if *remaining == 0 {
+                self.is_grabbing = false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Receive payloads from the streaming loop and deliver them to the output queue until the
+/// streaming loop stops.
+fn run_engine(shared: &Shared, receiver: &PayloadReceiver, timestamp_increment: Option<u64>) {
+    loop {
+        match async_std::task::block_on(receiver.recv()) {
+            Ok(payload) => {
+                let delivered = shared
+                    .state
+                    .lock()
+                    .unwrap()
+                    .deliver(&payload, timestamp_increment);
+                if delivered {
+                    shared.output_cond.notify_all();
+                }
+                receiver.send_back(payload);
+            }
+
+            // The channel is closed by the streaming loop.
+            Err(StreamError::ReceiveError(..)) => break,
+
+            // A frame is started, but failed to be transferred.
+            Err(err) => {
+                let mut state = shared.state.lock().unwrap();
+                if state.is_grabbing {
+                    state.num_started += 1;
+                }
+                drop(state);
+
+                if let Some(event) = &*shared.error_event.lock().unwrap() {
+                    event.push(EventData::error(&err.into()));
+                }
+            }
+        }
+    }
+}
+
+/// Minimum number of buffers to announce.
+const BUF_ANNOUNCE_MIN: usize = 1;
+
+/// Buffers don't need to be aligned.
+const BUF_ALIGNMENT: usize = 1;
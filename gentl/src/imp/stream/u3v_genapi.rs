@@ -0,0 +1,319 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cameleon_impl::memory::{memory, prelude::*, register_map};
+use const_format::formatcp;
+
+use GenApiReg::{
+    StreamAnnounceBufferMinimum, StreamAnnouncedBufferCount, StreamBufferAlignment,
+    StreamDeliveredFrameCount, StreamID, StreamInputBufferCount, StreamIsGrabbing,
+    StreamLostFrameCount, StreamOutputBufferCount, StreamStartedFrameCount,
+};
+
+use crate::imp::{
+    genapi_common::{SCHEME_MAJOR_VERSION, SCHEME_MINOR_VERSION, SCHEME_SUBMINOR_VERSION},
+    port,
+};
+
+#[memory]
+pub(super) struct Memory {
+    genapi_reg: GenApiReg,
+    genapi_xml: GenApiXml,
+}
+
+#[register_map(base=0, endianness=LE)]
+pub(super) enum GenApiReg {
+    /// Device unique ID for the data stream.
+    #[register(len = 64, access = RO, ty = String)]
+    StreamID,
+
+    /// Number of announced buffers.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamAnnouncedBufferCount,
+
+    /// Minimal number of buffers to announce to enable selected acquisition mode.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamAnnounceBufferMinimum,
+
+    /// Alignment size in bytes of the buffers passed to the data stream.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamBufferAlignment,
+
+    /// Number of buffers in the input pool.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamInputBufferCount,
+
+    /// Number of buffers in the output queue.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamOutputBufferCount,
+
+    /// Number of frames started in the acquisition engine.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamStartedFrameCount,
+
+    /// Number of delivered frames since last acquisition start.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamDeliveredFrameCount,
+
+    /// Number of lost frames due to queue underrun.
+    #[register(len = 8, access = RO, ty = u64)]
+    StreamLostFrameCount,
+
+    /// Flag indicating whether the acquisition engine is started or not.
+    #[register(len = 4, access = RO, ty = u32)]
+    StreamIsGrabbing,
+}
+
+#[register_map(base=GENAPI_XML_ADDRESS, endianness=LE)]
+pub(super) enum GenApiXml {
+    #[register(len = GENAPI_XML_LENGTH, access = RO, ty = String)]
+    Xml = GENAPI_XML,
+}
+
+pub(super) const MODEL_NAME: &str = "CameleonGenTLU3VDataStreamModule";
+pub(super) const VENDOR_NAME: &str = "CameleonProjectDevelopers";
+pub(super) const TOOL_TIP: &str = "GenTL U3V Data Stream Module";
+
+pub(super) const STREAM_TYPE: port::TlType = port::TlType::USB3Vision;
+pub(super) const PORT_NAME: &str = "StreamPort";
+
+const PRODUCT_GUID: &str = "6b1f3a1e-5c1a-4c8e-9a43-2d7c0f3e8b52";
+const VERSION_GUID: &str = "e2a4d9c7-0b6f-4f0e-8d15-7a3c9e6b1f28";
+
+pub(super) const XML_MAJOR_VERSION: u64 = 1;
+pub(super) const XML_MINOR_VERSION: u64 = 0;
+pub(super) const XML_SUBMINOR_VERSION: u64 = 0;
+
+pub(super) const GENAPI_XML_ADDRESS: usize = GenApiReg::base() + GenApiReg::size();
+pub(super) const GENAPI_XML_LENGTH: usize = GENAPI_XML.len();
+
+const GENAPI_XML: &str = formatcp!(
+    r#"<?xml version="1.0" encoding="UTF-8"?>
+<RegisterDescription
+ModelName="{MODEL_NAME}"
+VendorName="{VENDOR_NAME}"
+StandardNameSpace="None"
+SchemaMajorVersion="{SCHEME_MAJOR_VERSION}"
+SchemaMinorVersion="{SCHEME_MINOR_VERSION}"
+SchemaSubMinorVersion="{SCHEME_SUBMINOR_VERSION}"
+MajorVersion="{XML_MAJOR_VERSION}"
+MinorVersion="{XML_MINOR_VERSION}"
+SubMinorVersion="{XML_SUBMINOR_VERSION}"
+ToolTip="{TOOL_TIP}"
+ProductGuid="{PRODUCT_GUID}"
+VersionGuid="{VERSION_GUID}"
+xmlns="http://www.genicam.org/GenApi/Version_1_1"
+xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"
+xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam.org/GenApi/GenApiSchema_Version_1_1.xsd">
+
+    <Category Name="Root" NameSpace="Standard">
+        <Description>Provides the Root of the GenICam features tree.</Description>
+        <Visibility>Beginner</Visibility>
+        <pFeature>StreamInformation</pFeature>
+        <pFeature>BufferHandlingControl</pFeature>
+        <pFeature>StreamDiagnostics</pFeature>
+    </Category>
+
+    <Port Name="{PORT_NAME}" NameSpace="Standard">
+        <Description>The GenICam port through which the Data Stream module is accessed.</Description>
+        <Visibility>Invisible</Visibility>
+    </Port>
+
+    <Category Name="StreamInformation" NameSpace="Standard">
+        <Description>Category that contains all Stream Information features of the Data Stream module.</Description>
+        <Visibility>Beginner</Visibility>
+
+        <pFeature>StreamID</pFeature>
+        <pFeature>StreamType</pFeature>
+    </Category>
+
+    <StringReg Name="StreamID" NameSpace="Standard">
+        <Description>Device unique ID for the data stream.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{stream_id_addr}</Address>
+        <Length>{stream_id_len}</Length>
+        <AccessMode>{stream_id_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <Enumeration Name="StreamType" NameSpace="Standard">
+        <Description>Stream type of the device.</Description>
+        <Visibility>Expert</Visibility>
+        <EnumEntry Name="{stream_type}" NameSpace="Standard">
+            <Description>USB3 Vision</Description>
+            <Value>0</Value>
+        </EnumEntry>
+        <Value>0</Value>
+    </Enumeration>
+
+    <Category Name="BufferHandlingControl" NameSpace="Standard">
+        <Description>Category that contains the Buffer Handling features of the Data Stream module.</Description>
+        <Visibility>Beginner</Visibility>
+
+        <pFeature>StreamBufferHandlingMode</pFeature>
+        <pFeature>StreamAnnouncedBufferCount</pFeature>
+        <pFeature>StreamAnnounceBufferMinimum</pFeature>
+        <pFeature>StreamInputBufferCount</pFeature>
+        <pFeature>StreamOutputBufferCount</pFeature>
+        <pFeature>StreamBufferAlignment</pFeature>
+    </Category>
+
+    <Enumeration Name="StreamBufferHandlingMode" NameSpace="Standard">
+        <Description>Selects the buffer handling mode of the data stream.</Description>
+        <Visibility>Beginner</Visibility>
+        <EnumEntry Name="OldestFirst" NameSpace="Standard">
+            <Description>The application always gets the buffer with the oldest image in the output buffer queue.</Description>
+            <Value>0</Value>
+        </EnumEntry>
+        <Value>0</Value>
+    </Enumeration>
+
+    <IntReg Name="StreamAnnouncedBufferCount" NameSpace="Standard">
+        <Description>Number of announced buffers.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{announced_addr}</Address>
+        <Length>{announced_len}</Length>
+        <AccessMode>{announced_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="StreamAnnounceBufferMinimum" NameSpace="Standard">
+        <Description>Minimal number of buffers to announce to enable selected buffer handling mode.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{announce_min_addr}</Address>
+        <Length>{announce_min_len}</Length>
+        <AccessMode>{announce_min_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="StreamBufferAlignment" NameSpace="Standard">
+        <Description>Alignment size in bytes of the buffers passed to the data stream.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{alignment_addr}</Address>
+        <Length>{alignment_len}</Length>
+        <AccessMode>{alignment_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="StreamInputBufferCount" NameSpace="Standard">
+        <Description>Number of buffers in the input pool.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{input_addr}</Address>
+        <Length>{input_len}</Length>
+        <AccessMode>{input_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="StreamOutputBufferCount" NameSpace="Standard">
+        <Description>Number of buffers in the output queue.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{output_addr}</Address>
+        <Length>{output_len}</Length>
+        <AccessMode>{output_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Category Name="StreamDiagnostics" NameSpace="Standard">
+        <Description>Category that contains the Diagnostics features of the Data Stream module.</Description>
+        <Visibility>Expert</Visibility>
+
+        <pFeature>StreamStartedFrameCount</pFeature>
+        <pFeature>StreamDeliveredFrameCount</pFeature>
+        <pFeature>StreamLostFrameCount</pFeature>
+        <pFeature>StreamIsGrabbing</pFeature>
+    </Category>
+
+    <IntReg Name="StreamStartedFrameCount" NameSpace="Standard">
+        <Description>Number of frames started in the acquisition engine.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{started_addr}</Address>
+        <Length>{started_len}</Length>
+        <AccessMode>{started_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="StreamDeliveredFrameCount" NameSpace="Standard">
+        <Description>Number of delivered frames since last acquisition start.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{delivered_addr}</Address>
+        <Length>{delivered_len}</Length>
+        <AccessMode>{delivered_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <IntReg Name="StreamLostFrameCount" NameSpace="Standard">
+        <Description>Number of lost frames due to queue underrun.</Description>
+        <Visibility>Expert</Visibility>
+        <Address>{lost_addr}</Address>
+        <Length>{lost_len}</Length>
+        <AccessMode>{lost_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Sign>Unsigned</Sign>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+    <Boolean Name="StreamIsGrabbing" NameSpace="Standard">
+        <Description>Flag indicating whether the acquisition engine is started or not.</Description>
+        <Visibility>Expert</Visibility>
+        <pValue>StreamIsGrabbingReg</pValue>
+        <OnValue>1</OnValue>
+        <OffValue>0</OffValue>
+    </Boolean>
+
+    <IntReg Name="StreamIsGrabbingReg" NameSpace="Custom">
+        <Visibility>Invisible</Visibility>
+        <Address>{is_grabbing_addr}</Address>
+        <Length>{is_grabbing_len}</Length>
+        <AccessMode>{is_grabbing_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>
+
+</RegisterDescription>"#,
+    stream_id_addr = StreamID::ADDRESS,
+    stream_id_len = StreamID::LENGTH,
+    stream_id_access = StreamID::ACCESS_RIGHT.as_str(),
+    stream_type = STREAM_TYPE.as_str(),
+    announced_addr = StreamAnnouncedBufferCount::ADDRESS,
+    announced_len = StreamAnnouncedBufferCount::LENGTH,
+    announced_access = StreamAnnouncedBufferCount::ACCESS_RIGHT.as_str(),
+    announce_min_addr = StreamAnnounceBufferMinimum::ADDRESS,
+    announce_min_len = StreamAnnounceBufferMinimum::LENGTH,
+    announce_min_access = StreamAnnounceBufferMinimum::ACCESS_RIGHT.as_str(),
+    alignment_addr = StreamBufferAlignment::ADDRESS,
+    alignment_len = StreamBufferAlignment::LENGTH,
+    alignment_access = StreamBufferAlignment::ACCESS_RIGHT.as_str(),
+    input_addr = StreamInputBufferCount::ADDRESS,
+    input_len = StreamInputBufferCount::LENGTH,
+    input_access = StreamInputBufferCount::ACCESS_RIGHT.as_str(),
+    output_addr = StreamOutputBufferCount::ADDRESS,
+    output_len = StreamOutputBufferCount::LENGTH,
+    output_access = StreamOutputBufferCount::ACCESS_RIGHT.as_str(),
+    started_addr = StreamStartedFrameCount::ADDRESS,
+    started_len = StreamStartedFrameCount::LENGTH,
+    started_access = StreamStartedFrameCount::ACCESS_RIGHT.as_str(),
+    delivered_addr = StreamDeliveredFrameCount::ADDRESS,
+    delivered_len = StreamDeliveredFrameCount::LENGTH,
+    delivered_access = StreamDeliveredFrameCount::ACCESS_RIGHT.as_str(),
+    lost_addr = StreamLostFrameCount::ADDRESS,
+    lost_len = StreamLostFrameCount::LENGTH,
+    lost_access = StreamLostFrameCount::ACCESS_RIGHT.as_str(),
+    is_grabbing_addr = StreamIsGrabbing::ADDRESS,
+    is_grabbing_len = StreamIsGrabbing::LENGTH,
+    is_grabbing_access = StreamIsGrabbing::ACCESS_RIGHT.as_str(),
+);
@@ -7,6 +7,7 @@ use const_format::formatcp;
 
 use crate::imp::{
     genapi_common::{
+        GENTL_SFNC_VERSION_MAJOR, GENTL_SFNC_VERSION_MINOR, GENTL_SFNC_VERSION_SUBMINOR,
         GENTL_VERSION_MAJOR, GENTL_VERSION_MINOR, SCHEME_MAJOR_VERSION, SCHEME_MINOR_VERSION,
         SCHEME_SUBMINOR_VERSION,
     },
@@ -15,8 +16,8 @@ use crate::imp::{
 
 use GenApiReg::{
     GevInterfaceDefaultGateway, GevInterfaceDefaultIPAddress, GevInterfaceDefaultSubnetMask,
-    GevInterfaceMACAddress, InterfaceID, InterfaceSelector, InterfaceSelectorMax,
-    InterfaceUpdateList, TlPath,
+    GevInterfaceMACAddress, InterfaceDisplayName, InterfaceID, InterfaceSelector,
+    InterfaceSelectorMax, InterfaceUpdateList, TlFileName, TlPath,
 };
 
 #[memory]
@@ -31,9 +32,13 @@ pub(super) enum GenApiReg {
     #[register(len = 1024, access = RO, ty = String)]
     TlPath,
 
+    /// File name of the GenTL producer including extension.
+    #[register(len = 256, access = RO, ty = String)]
+    TlFileName,
+
     /// Updates the internal list of the interfaces when non zero value is wrritten to this
     /// register.
-    #[register(len = 4, access = RO, ty = u32)]
+    #[register(len = 4, access = WO, ty = u32)]
     InterfaceUpdateList,
 
     /// Selector for the different GenTL Producer interfaces.
@@ -48,6 +53,10 @@ pub(super) enum GenApiReg {
     #[register(len = 64, access = RO, ty = String)]
     InterfaceID,
 
+    /// User readable name of the selected interface.
+    #[register(len = 128, access = RO, ty = String)]
+    InterfaceDisplayName,
+
     /// 48-bit MAC address of the selected interface.
     #[register(len = 8, access = RO, ty = BitField<u64, LSB = 0, MSB = 47>)]
     GevInterfaceMACAddress,
@@ -63,6 +72,12 @@ pub(super) enum GenApiReg {
     /// Gateway of the selected interface.
     #[register(len = 4, access = RO, ty = u32)]
     GevInterfaceDefaultGateway,
+
+    /// Adds the emulator interface at the next update of the interface list when non zero value
+    /// is written to this register.
+    /// The register is ignored and hidden from the XML if the `emulator` feature is disabled.
+    #[register(len = 4, access = RW, ty = u32)]
+    EmulatorInterfaceEnable,
 }
 
 #[register_map(base=GENAPI_XML_ADDRESS, endianness=LE)]
@@ -89,7 +104,7 @@ pub(super) const TL_TYPE: port::TlType = port::TlType::Mixed;
 const PRODUCT_GUID: &str = "C09F0257-3F5C-41C2-B34F-FE67CB108370";
 const VERSION_GUID: &str = "10F7AF60-A1B0-4AE4-8785-F214C22DAA9D";
 
-const GENAPI_XML: &str = formatcp!(
+pub(super) const GENAPI_XML: &str = formatcp!(
     r#"<?xml version="1.0" encoding="UTF-8"?>
 <RegisterDescription
 ModelName="{MODEL_NAME}"
@@ -130,9 +145,14 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pFeature>TLModelName</pFeature>
         <pFeature>TLVersion</pFeature>
         <pFeature>TLPath</pFeature>
+        <pFeature>TLFileName</pFeature>
+        <pFeature>TLDisplayName</pFeature>
         <pFeature>TLType</pFeature>
         <pFeature>GenTLVersionMajor</pFeature>
         <pFeature>GenTLVersionMinor</pFeature>
+        <pFeature>GenTLSFNCVersionMajor</pFeature>
+        <pFeature>GenTLSFNCVersionMinor</pFeature>
+        <pFeature>GenTLSFNCVersionSubMinor</pFeature>
     </Category>
 
     <String Name="TLID" NameSpace="Standard">
@@ -173,6 +193,23 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pPort>{PORT_NAME}</pPort>
     </StringReg>
 
+    <StringReg Name="TLFileName" NameSpace="Standard">
+        <Description>Filename including extension of the GenTL Producer.</Description>
+        <Visibility>Beginner</Visibility>
+
+        <Address>{tl_file_name_addr}</Address>
+        <Length>{tl_file_name_len}</Length>
+        <AccessMode>{tl_file_name_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
+    <String Name="TLDisplayName" NameSpace="Standard">
+        <Description>User readable name of the GenTL Producer.</Description>
+        <Visibility>Beginner</Visibility>
+
+        <Value>{TOOL_TIP}</Value>
+    </String>
+
     <Enumeration Name="TLType" NameSpace="Standard">
         <Description>Transport layer type of the GenTL Producer implementation.</Description>
         <Visibility>Expert</Visibility>
@@ -202,9 +239,25 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     <Integer Name="GenTLSFNCVersionMajor" NameSpace="Standard">
         <Description>Major version number of the GenTL Standard Features Naming Convention that was used to create the GenTL Producer`s XML.</Description>
         <Visibility>Expert</Visibility>
-        <Value>1</Value>
-        <Min>1</Min>
-        <Max>1</Max>
+        <Value>{GENTL_SFNC_VERSION_MAJOR}</Value>
+        <Min>{GENTL_SFNC_VERSION_MAJOR}</Min>
+        <Max>{GENTL_SFNC_VERSION_MAJOR}</Max>
+    </Integer>
+
+    <Integer Name="GenTLSFNCVersionMinor" NameSpace="Standard">
+        <Description>Minor version number of the GenTL Standard Features Naming Convention that was used to create the GenTL Producer`s XML.</Description>
+        <Visibility>Expert</Visibility>
+        <Value>{GENTL_SFNC_VERSION_MINOR}</Value>
+        <Min>{GENTL_SFNC_VERSION_MINOR}</Min>
+        <Max>{GENTL_SFNC_VERSION_MINOR}</Max>
+    </Integer>
+
+    <Integer Name="GenTLSFNCVersionSubMinor" NameSpace="Standard">
+        <Description>Sub minor version number of the GenTL Standard Features Naming Convention that was used to create the GenTL Producer`s XML.</Description>
+        <Visibility>Expert</Visibility>
+        <Value>{GENTL_SFNC_VERSION_SUBMINOR}</Value>
+        <Min>{GENTL_SFNC_VERSION_SUBMINOR}</Min>
+        <Max>{GENTL_SFNC_VERSION_SUBMINOR}</Max>
     </Integer>
 
     <Category Name="InterfaceEnumeration" NameSpace="Standard">
@@ -214,10 +267,12 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pFeature>InterfaceUpdateList</pFeature>
         <pFeature>InterfaceSelector</pFeature>
         <pFeature>InterfaceID</pFeature>
+        <pFeature>InterfaceDisplayName</pFeature>
         <pFeature>GevInterfaceMACAddress</pFeature>
         <pFeature>GevInterfaceDefaultIPAddress</pFeature>
         <pFeature>GevInterfaceDefaultSubnetMask</pFeature>
         <pFeature>GevInterfaceDefaultGateway</pFeature>
+        {EMULATOR_INTERFACE_ENABLE_FEATURE}
     </Category>
 
     <Command Name="InterfaceUpdateList" NameSpace="Standard">
@@ -271,6 +326,15 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <pPort>{PORT_NAME}</pPort>
     </StringReg>
 
+    <StringReg Name="InterfaceDisplayName" NameSpace="Standard">
+        <Description>User readable name of the selected interface.</Description>
+        <Visibility>Beginner</Visibility>
+        <Address>{interface_display_name_addr}</Address>
+        <Length>{interface_display_name_len}</Length>
+        <AccessMode>{interface_display_name_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+    </StringReg>
+
     <MaskedIntReg Name="GevInterfaceMACAddress" NameSpace="Standard">
         <Description>48-bit MAC address of the selected interface.</Description>
         <Visibility>Expert</Visibility>
@@ -316,11 +380,16 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
         <Endianess>LittleEndian</Endianess>
         <Representation>IPV4Address</Representation>
     </IntReg>
+
+{EMULATOR_INTERFACE_ENABLE_NODES}
 </RegisterDescription>"#,
     tl_type = TL_TYPE.as_str(),
     tl_path_addr = TlPath::ADDRESS,
     tl_path_length = TlPath::LENGTH,
     tl_path_access = TlPath::ACCESS_RIGHT.as_str(),
+    tl_file_name_addr = TlFileName::ADDRESS,
+    tl_file_name_len = TlFileName::LENGTH,
+    tl_file_name_access = TlFileName::ACCESS_RIGHT.as_str(),
     update_list_addr = InterfaceUpdateList::ADDRESS,
     update_list_len = InterfaceUpdateList::LENGTH,
     update_list_access = InterfaceUpdateList::ACCESS_RIGHT.as_str(),
@@ -333,6 +402,9 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     interface_id_addr = InterfaceID::ADDRESS,
     interface_id_len = InterfaceID::LENGTH,
     interface_id_access = InterfaceID::ACCESS_RIGHT.as_str(),
+    interface_display_name_addr = InterfaceDisplayName::ADDRESS,
+    interface_display_name_len = InterfaceDisplayName::LENGTH,
+    interface_display_name_access = InterfaceDisplayName::ACCESS_RIGHT.as_str(),
     mac_address_addr = GevInterfaceMACAddress::ADDRESS,
     mac_address_len = GevInterfaceMACAddress::LENGTH,
     mac_address_access = GevInterfaceMACAddress::ACCESS_RIGHT.as_str(),
@@ -348,3 +420,34 @@ xsi:schemaLocation="http://www.genicam.org/GenApi/Version_1_1 http://www.genicam
     default_gateway_len = GevInterfaceDefaultGateway::LENGTH,
     default_gateway_access = GevInterfaceDefaultGateway::ACCESS_RIGHT.as_str(),
 );
+
+/// Nodes of `EmulatorInterfaceEnable`, which are omitted if the `emulator` feature is disabled.
+#[cfg(feature = "emulator")]
+const EMULATOR_INTERFACE_ENABLE_FEATURE: &str = "<pFeature>EmulatorInterfaceEnable</pFeature>";
+#[cfg(not(feature = "emulator"))]
+const EMULATOR_INTERFACE_ENABLE_FEATURE: &str = "";
+
+#[cfg(feature = "emulator")]
+const EMULATOR_INTERFACE_ENABLE_NODES: &str = formatcp!(
+    r#"    <Boolean Name="EmulatorInterfaceEnable" NameSpace="Custom">
+        <Description>Adds the interface of emulated U3V cameras at the next update of the interface list. The interface can't be removed once it's added.</Description>
+        <Visibility>Expert</Visibility>
+        <pValue>EmulatorInterfaceEnableReg</pValue>
+        <OnValue>1</OnValue>
+        <OffValue>0</OffValue>
+    </Boolean>
+
+    <IntReg Name="EmulatorInterfaceEnableReg" NameSpace="Custom">
+        <Visibility>Invisible</Visibility>
+        <Address>{emulator_interface_enable_addr}</Address>
+        <Length>{emulator_interface_enable_len}</Length>
+        <AccessMode>{emulator_interface_enable_access}</AccessMode>
+        <pPort>{PORT_NAME}</pPort>
+        <Endianess>LittleEndian</Endianess>
+    </IntReg>"#,
+    emulator_interface_enable_addr = GenApiReg::EmulatorInterfaceEnable::ADDRESS,
+    emulator_interface_enable_len = GenApiReg::EmulatorInterfaceEnable::LENGTH,
+    emulator_interface_enable_access = GenApiReg::EmulatorInterfaceEnable::ACCESS_RIGHT.as_str(),
+);
+#[cfg(not(feature = "emulator"))]
+const EMULATOR_INTERFACE_ENABLE_NODES: &str = "";
@@ -26,8 +26,16 @@ use super::{
 
 mod genapi;
 
-const NUM_INTERFACE: usize = 1;
+/// Environment variable to specify the number of emulated cameras.
+/// The emulator interface is added to the system module if the number is larger than zero.
+#[cfg(feature = "emulator")]
+const EMULATOR_ENV_VAR: &str = "CAMELEON_GENTL_EMULATOR";
 
+#[cfg(all(test, feature = "emulator"))]
+pub(crate) const EMULATOR_INTERFACE_ENABLE_ADDRESS: u64 =
+    genapi::GenApiReg::EmulatorInterfaceEnable::ADDRESS as u64;
+
+#[allow(clippy::vec_box)]
 pub(crate) struct SystemModule {
     vm: genapi::Memory,
     port_info: PortInfo,
@@ -35,7 +43,10 @@ pub(crate) struct SystemModule {
     system_info: SystemInfo,
     is_opened: bool,
 
-    interfaces: [Box<Mutex<dyn Interface + Send>>; NUM_INTERFACE],
+    /// Interfaces are boxed and never removed so that handles of the interfaces stay valid.
+    interfaces: Vec<Box<Mutex<dyn Interface + Send>>>,
+    #[cfg(feature = "emulator")]
+    has_emulator_interface: bool,
     event_queue: Arc<Mutex<VecDeque<MemoryEvent>>>,
 }
 
@@ -102,11 +113,25 @@ impl SystemModule {
             system_info,
             is_opened: false,
 
-            interfaces: [Box::new(Mutex::new(U3VInterfaceModule::new()))],
+            interfaces: vec![Box::new(Mutex::new(U3VInterfaceModule::new()))],
+            #[cfg(feature = "emulator")]
+            has_emulator_interface: false,
             event_queue: Arc::new(Mutex::new(VecDeque::new())),
         };
 
         system_module.initialize_vm().unwrap();
+
+        #[cfg(feature = "emulator")]
+        {
+            let num_cameras = std::env::var(EMULATOR_ENV_VAR)
+                .ok()
+                .and_then(|var| var.parse::<usize>().ok())
+                .unwrap_or(0);
+            if num_cameras > 0 {
+                system_module.add_emulator_interface(num_cameras).unwrap();
+            }
+        }
+
         system_module
     }
 
@@ -124,6 +149,7 @@ impl SystemModule {
         for iface in &mut self.interfaces() {
             let _res = iface.lock().unwrap().close();
         }
+        self.is_opened = false;
 
         Ok(())
     }
@@ -145,6 +171,30 @@ impl SystemModule {
         &self.system_info
     }
 
+    /// Update the interface list, and returns `true` if the list is changed.
+    ///
+    /// The emulator interface is added if `EmulatorInterfaceEnable` is set. Other interfaces are
+    /// never added nor removed.
+    ///
+    /// `EmulatorInterfaceEnable` is ignored if the `emulator` feature is disabled.
+    pub(crate) fn update_interface_list(&mut self) -> GenTlResult<bool> {
+        self.assert_open()?;
+
+        #[cfg(feature = "emulator")]
+        {
+            use genapi::GenApiReg;
+
+            if !self.has_emulator_interface
+                && self.vm.read::<GenApiReg::EmulatorInterfaceEnable>()? != 0
+            {
+                self.add_emulator_interface(1)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
     fn assert_open(&self) -> GenTlResult<()> {
         if self.is_opened {
             Ok(())
@@ -156,17 +206,23 @@ impl SystemModule {
     fn initialize_vm(&mut self) -> GenTlResult<()> {
         use genapi::GenApiReg;
 
-        let full_path = Self::full_path()
+        let full_path = Self::full_path();
+        let file_name = full_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let full_path = full_path
             .into_os_string()
             .into_string()
             .map_err(|e| GenTlError::Error(format!("{:?}", e)))?;
         self.vm.write::<GenApiReg::TlPath>(full_path)?;
+        self.vm.write::<GenApiReg::TlFileName>(file_name)?;
 
         // Initialize registers related to interface.
         self.vm.write::<GenApiReg::InterfaceSelector>(0)?;
         self.handle_interface_selector_change()?;
         self.vm
-            .write::<GenApiReg::InterfaceSelectorMax>(NUM_INTERFACE as u32 - 1)?;
+            .write::<GenApiReg::InterfaceSelectorMax>(self.interfaces.len() as u32 - 1)?;
 
         // Register observers that trigger events in response to memory write.
         self.register_observers();
@@ -174,6 +230,23 @@ impl SystemModule {
         Ok(())
     }
 
+    #[cfg(feature = "emulator")]
+    fn add_emulator_interface(&mut self, num_cameras: usize) -> GenTlResult<()> {
+        use genapi::GenApiReg;
+
+        self.interfaces
+            .push(Box::new(Mutex::new(U3VInterfaceModule::new_emulated(
+                num_cameras,
+            ))));
+        self.has_emulator_interface = true;
+
+        self.vm.write::<GenApiReg::EmulatorInterfaceEnable>(1)?;
+        self.vm
+            .write::<GenApiReg::InterfaceSelectorMax>(self.interfaces.len() as u32 - 1)?;
+
+        Ok(())
+    }
+
     fn full_path() -> std::path::PathBuf {
         let path = Path::new("../").join(file!());
         std::fs::canonicalize(path).unwrap()
@@ -197,8 +270,9 @@ impl SystemModule {
             let event = self.event_queue.lock().unwrap().pop_front();
 
             match event {
-                // We don't implement dynamic update of interfaces.
-                Some(MemoryEvent::InterfaceUpdateList) => {}
+                Some(MemoryEvent::InterfaceUpdateList) => {
+                    self.update_interface_list()?;
+                }
                 Some(MemoryEvent::InterfaceSelector) => self.handle_interface_selector_change()?,
                 None => break,
             }
@@ -222,6 +296,8 @@ impl SystemModule {
         let interface_id = interface.interface_id();
         self.vm
             .write::<GenApiReg::InterfaceID>(interface_id.into())?;
+        self.vm
+            .write::<GenApiReg::InterfaceDisplayName>(interface.display_name().into())?;
 
         macro_rules! byte_array_to_int {
             ($array:expr, $array_size:literal, $result_ty: ty) => {{
@@ -369,5 +445,58 @@ mod tests {
             &system_module.vm.read::<GenApiReg::InterfaceID>().unwrap(),
             u3v_interface.lock().unwrap().interface_id()
         );
+        assert_eq!(
+            &system_module
+                .vm
+                .read::<GenApiReg::InterfaceDisplayName>()
+                .unwrap(),
+            u3v_interface.lock().unwrap().display_name()
+        );
+        assert!(!system_module
+            .vm
+            .read::<GenApiReg::TlFileName>()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(feature = "emulator")]
+    #[test]
+    fn test_add_emulator_interface() {
+        // The emulator interface adds a camera to the global emulator pool.
+        let _guard = crate::test_utils::lock_global_state();
+        let mut system_module = SystemModule::new();
+        system_module.open().unwrap();
+        assert!(genapi::GENAPI_XML.contains("EmulatorInterfaceEnable"));
+        let num_interfaces = system_module.interfaces().count();
+
+        let enable = GenApiReg::EmulatorInterfaceEnable::ADDRESS as u64;
+        system_module.write(enable, &1_u32.to_le_bytes()).unwrap();
+        assert_eq!(system_module.interfaces().count(), num_interfaces);
+
+        assert!(system_module.update_interface_list().unwrap());
+        assert_eq!(system_module.interfaces().count(), num_interfaces + 1);
+        assert!(!system_module.update_interface_list().unwrap());
+
+        let emulator_interface = system_module.interfaces().last().unwrap();
+        let mut emulator_interface = emulator_interface.lock().unwrap();
+        emulator_interface.open().unwrap();
+        assert!(emulator_interface
+            .update_device_list(std::time::Duration::from_millis(0))
+            .unwrap());
+        assert!(!emulator_interface.devices().is_empty());
+    }
+
+    #[cfg(not(feature = "emulator"))]
+    #[test]
+    fn test_emulator_interface_unavailable() {
+        let mut system_module = SystemModule::new();
+        system_module.open().unwrap();
+        assert!(!genapi::GENAPI_XML.contains("EmulatorInterfaceEnable"));
+
+        // The register is ignored without the emulator feature.
+        let enable = GenApiReg::EmulatorInterfaceEnable::ADDRESS as u64;
+        system_module.write(enable, &1_u32.to_le_bytes()).unwrap();
+        assert!(!system_module.update_interface_list().unwrap());
+        assert_eq!(system_module.interfaces().count(), 1);
     }
 }
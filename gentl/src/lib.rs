@@ -19,6 +19,9 @@ pub mod ffi;
 #[allow(unused)]
 mod imp;
 
+#[cfg(test)]
+mod test_utils;
+
 use thiserror::Error;
 
 /// Errors defined in GenTL specification.
@@ -123,4 +126,41 @@ pub(crate) enum GenTlError {
     Ambiguous,
 }
 
+impl GenTlError {
+    /// Error code defined in GenTL specification.
+    pub(crate) fn code(&self) -> i32 {
+        use GenTlError::{
+            Abort, AccessDenied, Ambiguous, BufferTooSmall, Busy, Error, InvalidAddress,
+            InvalidBuffer, InvalidHandle, InvalidId, InvalidIndex, InvalidParameter, InvalidValue,
+            Io, NoData, NotAvailable, NotImplemented, NotInitialized, OutOfMemory,
+            ParsingChunkData, ResourceExhausted, ResourceInUse, Timeout,
+        };
+        match self {
+            Error(..) => -1001,
+            NotInitialized => -1002,
+            NotImplemented => -1003,
+            ResourceInUse => -1004,
+            AccessDenied => -1005,
+            InvalidHandle => -1006,
+            InvalidId(..) => -1007,
+            NoData => -1008,
+            InvalidParameter => -1009,
+            Io(..) => -1010,
+            Timeout => -1011,
+            Abort => -1012,
+            InvalidBuffer => -1013,
+            NotAvailable => -1014,
+            InvalidAddress => -1015,
+            BufferTooSmall => -1016,
+            InvalidIndex => -1017,
+            ParsingChunkData => -1018,
+            InvalidValue(..) => -1019,
+            ResourceExhausted => -1020,
+            OutOfMemory => -1021,
+            Busy => -1022,
+            Ambiguous => -1023,
+        }
+    }
+}
+
 pub(crate) type GenTlResult<T> = std::result::Result<T, GenTlError>;
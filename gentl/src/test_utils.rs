@@ -0,0 +1,20 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Helpers shared by unit tests.
+
+use std::sync::{Mutex, MutexGuard};
+
+lazy_static::lazy_static! {
+    /// The library state and the emulator pool are global, so tests which change them must not
+    /// run concurrently.
+    static ref GLOBAL_STATE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Serializes tests which change the library state or the emulator pool.
+///
+/// The lock is recovered even if a test holding it panicked.
+pub(crate) fn lock_global_state() -> MutexGuard<'static, ()> {
+    GLOBAL_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,129 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Checks the symbols exported by the built library against `GenTL.h` and the GenTL standard.
+
+use std::{
+    os::raw::c_void,
+    path::{Path, PathBuf},
+};
+
+use libloading::Library;
+
+const GENTL_H: &str = include_str!(concat!(env!("OUT_DIR"), "/GenTL.h"));
+
+/// Functions defined in the GenTL standard up to version 1.6.
+const STANDARD_FUNCTIONS: &[&str] = &[
+    // 1.0
+    "GCGetInfo",
+    "GCGetLastError",
+    "GCInitLib",
+    "GCCloseLib",
+    "GCReadPort",
+    "GCWritePort",
+    "GCGetPortURL",
+    "GCGetPortInfo",
+    "GCRegisterEvent",
+    "GCUnregisterEvent",
+    "EventGetData",
+    "EventGetDataInfo",
+    "EventGetInfo",
+    "EventFlush",
+    "EventKill",
+    "TLOpen",
+    "TLClose",
+    "TLGetInfo",
+    "TLGetNumInterfaces",
+    "TLGetInterfaceID",
+    "TLGetInterfaceInfo",
+    "TLOpenInterface",
+    "TLUpdateInterfaceList",
+    "IFClose",
+    "IFGetInfo",
+    "IFGetNumDevices",
+    "IFGetDeviceID",
+    "IFUpdateDeviceList",
+    "IFGetDeviceInfo",
+    "IFOpenDevice",
+    "DevGetPort",
+    "DevGetNumDataStreams",
+    "DevGetDataStreamID",
+    "DevOpenDataStream",
+    "DevGetInfo",
+    "DevClose",
+    "DSAnnounceBuffer",
+    "DSAllocAndAnnounceBuffer",
+    "DSFlushQueue",
+    "DSStartAcquisition",
+    "DSStopAcquisition",
+    "DSGetInfo",
+    "DSGetBufferID",
+    "DSClose",
+    "DSRevokeBuffer",
+    "DSQueueBuffer",
+    "DSGetBufferInfo",
+    // 1.1
+    "GCGetNumPortURLs",
+    "GCGetPortURLInfo",
+    "GCReadPortStacked",
+    "GCWritePortStacked",
+    // 1.3
+    "DSGetBufferChunkData",
+    // 1.4
+    "IFGetParentTL",
+    "DevGetParentIF",
+    "DSGetParentDev",
+    // 1.5
+    "DSGetNumBufferParts",
+    "DSGetBufferPartInfo",
+    // 1.6
+    "DSGetBufferInfoStacked",
+    "DSGetBufferPartInfoStacked",
+    "DSGetNumFlows",
+    "DSGetFlowInfo",
+    "DSGetNumBufferSegments",
+    "DSGetBufferSegmentInfo",
+    "DSAnnounceCompositeBuffer",
+];
+
+/// Number of functions in [`STANDARD_FUNCTIONS`] up to GenTL 1.5, which must all be exported.
+const NUM_REQUIRED_FUNCTIONS: usize = 56;
+
+/// Builds `cameleon-gentl`, and returns the path to the built library.
+fn build_library() -> PathBuf {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("gentl-exports");
+    cameleon_gentl_build_helper::build_producer(&target_dir, &[])
+}
+
+fn is_exported(lib: &Library, name: &str) -> bool {
+    unsafe { lib.get::<*const c_void>(name.as_bytes()).is_ok() }
+}
+
+#[test]
+fn test_exported_functions() {
+    let lib = unsafe { Library::new(build_library()).unwrap() };
+
+    let declared: Vec<_> = GENTL_H
+        .lines()
+        .filter_map(|line| line.strip_prefix("GC_IMPORT_EXPORT GC_ERROR GC_CALLTYPE "))
+        .map(|decl| &decl[..decl.find('(').unwrap()])
+        .collect();
+    assert!(!declared.is_empty());
+
+    for name in &declared {
+        assert!(
+            STANDARD_FUNCTIONS.contains(name),
+            "`{}` isn't a GenTL function",
+            name
+        );
+        assert!(
+            is_exported(&lib, name),
+            "`{}` is declared but isn't exported",
+            name
+        );
+    }
+    for name in &STANDARD_FUNCTIONS[..NUM_REQUIRED_FUNCTIONS] {
+        assert!(is_exported(&lib, name), "`{}` isn't exported", name);
+    }
+}